@@ -1,20 +1,161 @@
-use chrono::Duration;
+use crate::error::Result;
+use chrono::{Duration, NaiveDateTime};
+use rayon::prelude::*;
+use serde::{Serialize, Serializer};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+use crate::config::{Config, PairRule};
+use crate::duration::parse_duration;
+use crate::expr::Expr;
+use crate::locale::Locale;
 use crate::parser::LogMatch;
+use crate::style::{self, DurationColors};
+use crate::tags::Condition;
+
+/// Correlated runs with fewer matches than this are paired on the calling thread, since
+/// spreading them over threads would cost more than it saves
+const MIN_SHARDED_MATCHES: usize = 50_000;
 
-#[derive(Debug)]
+/// The time from one match to a later one
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Interval {
     pub from_pattern: String,
     pub to_pattern: String,
+    /// Timestamp of the starting match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<NaiveDateTime>,
+    /// Timestamp of the ending match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<NaiveDateTime>,
+    /// Serialized as whole milliseconds (`duration_ms`)
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
     pub duration: Duration,
+    /// Source (file or host) of the starting match, when analyzing merged inputs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_source: Option<String>,
+    /// Source (file or host) of the ending match, when analyzing merged inputs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_source: Option<String>,
+    /// Named captures of the starting match
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub from_fields: BTreeMap<String, String>,
+    /// Named captures of the ending match
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub to_fields: BTreeMap<String, String>,
+    /// Correlation key shared by both endpoints, when correlation is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation: Option<String>,
+    /// Value of the `--group-by` capture on the starting match, or else on the ending one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Values of the configured `derived` expressions that could be computed
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub derived: BTreeMap<String, f64>,
+    /// Tags of the configured `tags` rules whose condition holds, in rule order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Line numbers of the starting and ending matches in their inputs, when the parser
+    /// records source lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_line: Option<usize>,
+    /// The starting and ending lines as read, when the parser keeps raw lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_raw_line: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_raw_line: Option<String>,
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_i64(duration.num_milliseconds())
 }
 
 impl Interval {
     pub fn format(&self) -> String {
-        let duration_str = self.format_duration();
-        format!("{} :::: {} ::::> {}", 
+        self.format_with_locale(None)
+    }
+    
+    /// The human output line, with the duration and derived values in `locale`'s conventions
+    pub fn format_with_locale(&self, locale: Option<Locale>) -> String {
+        self.format_styled(locale, None)
+    }
+    
+    /// Like `format_with_locale`, with the duration in the color of its threshold in `colors`
+    pub fn format_styled(&self, locale: Option<Locale>, colors: Option<DurationColors>) -> String {
+        let duration_str = match locale {
+            Some(locale) => locale.duration(&self.duration),
+            None => self.format_duration(),
+        };
+        let duration_str = style::paint(colors, self.duration.num_milliseconds() as f64, &duration_str);
+        let mut line = format!("{} :::: {} ::::> {}", 
             self.from_pattern, 
             duration_str, 
-            self.to_pattern)
+            self.to_pattern);
+        
+        if let Some(key) = &self.correlation {
+            line.push_str(&format!("  id={}", key));
+        }
+        if let Some(group) = &self.group {
+            line.push_str(&format!("  group={}", group));
+        }
+        for (name, value) in &self.derived {
+            let value = match locale {
+                Some(locale) => locale.number(*value, 3),
+                None => format_number(*value),
+            };
+            line.push_str(&format!("  {}={}", name, value));
+        }
+        if !self.tags.is_empty() {
+            line.push_str(&format!("  tags={}", self.tags.join(",")));
+        }
+        
+        if let Some(label) = self.source_label() {
+            line.push_str(&format!("  [{}]", label));
+            if self.is_cross_source() {
+                line.push_str(" (cross-source)");
+            }
+        }
+        
+        line
+    }
+    
+    /// True when the line numbers of the endpoints were recorded (`--include-source`)
+    pub fn has_source_lines(&self) -> bool {
+        self.from_line.is_some() || self.to_line.is_some()
+    }
+    
+    /// Named capture `name` of the starting match, or else of the ending match
+    pub fn capture(&self, name: &str) -> Option<&str> {
+        self.from_fields.get(name).or_else(|| self.to_fields.get(name)).map(String::as_str)
+    }
+    
+    /// Label the interval with its value of the capture `name` (`--group-by`), so statistics
+    /// are computed per value
+    pub fn group_by(&mut self, name: &str) {
+        self.group = self.capture(name).map(str::to_string);
+    }
+    
+    /// True when the interval carries at least one of `tags`
+    pub fn has_any_tag(&self, tags: &[String]) -> bool {
+        self.tags.iter().any(|tag| tags.contains(tag))
+    }
+    
+    /// True when the two endpoints come from different sources, which usually
+    /// means matches from unrelated files were paired by mistake
+    pub fn is_cross_source(&self) -> bool {
+        matches!((&self.from_source, &self.to_source), (Some(from), Some(to)) if from != to)
+    }
+    
+    /// "source" or "from_source -> to_source" for display, if sources are known
+    pub fn source_label(&self) -> Option<String> {
+        match (&self.from_source, &self.to_source) {
+            (Some(from), Some(to)) if from != to => Some(format!("{} -> {}", from, to)),
+            (Some(source), _) | (None, Some(source)) => Some(source.clone()),
+            (None, None) => None,
+        }
     }
     
     pub fn format_duration(&self) -> String {
@@ -22,10 +163,264 @@ impl Interval {
     }
 }
 
+/// Turns matches into intervals, either between consecutive matches or between configured
+/// start/end pairs
 pub struct Analyzer;
 
+/// Pairs each match with the previous match of the same correlation key as matches arrive.
+///
+/// Without correlation every match has the same (empty) key, which is plain consecutive
+/// pairing. The last match of every key is kept, since a request may always log again.
+#[derive(Debug, Clone, Default)]
+pub struct ConsecutiveTracker {
+    last: HashMap<Option<String>, LogMatch>,
+}
+
+impl ConsecutiveTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Feed the next match; returns the interval it completes, if any
+    pub fn push(&mut self, log_match: &LogMatch) -> Option<Interval> {
+        let from = self.last.insert(log_match.correlation.clone(), log_match.clone())?;
+        Some(Analyzer::interval_between(&from, log_match))
+    }
+}
+
+/// Drops the warm-up intervals of a run as intervals arrive: the first `skip_iterations` of
+/// every transition, and those starting within `skip_duration` of the first match.
+#[derive(Debug, Clone, Default)]
+pub struct Warmup {
+    iterations: usize,
+    duration: Option<Duration>,
+    /// Timestamp of the first match
+    origin: Option<NaiveDateTime>,
+    /// Intervals seen so far per transition
+    seen: HashMap<(String, String), usize>,
+}
+
+impl Warmup {
+    pub fn new(config: &Config) -> Self {
+        Warmup {
+            iterations: config.skip_iterations.unwrap_or(0),
+            // Durations are checked when the configuration is validated
+            duration: config.skip_duration.as_deref().and_then(|d| parse_duration(d).ok()),
+            ..Default::default()
+        }
+    }
+    
+    /// Note a match; the warm-up period counts from the first one
+    pub fn observe(&mut self, timestamp: NaiveDateTime) {
+        self.origin.get_or_insert(timestamp);
+    }
+    
+    /// Whether an interval is past the warm-up and kept
+    pub fn keep(&mut self, interval: &Interval) -> bool {
+        let in_window = match (self.duration, self.origin, interval.start) {
+            (Some(duration), Some(origin), Some(start)) => start < origin + duration,
+            _ => false,
+        };
+        if self.iterations == 0 {
+            return !in_window;
+        }
+        let seen = self
+            .seen
+            .entry((interval.from_pattern.clone(), interval.to_pattern.clone()))
+            .or_insert(0);
+        *seen += 1;
+        *seen > self.iterations && !in_window
+    }
+}
+
+/// The derived values, tags, and minimum durations of a configuration, compiled once so
+/// intervals arriving one at a time (`--stream`, `--follow`) don't parse them again
+#[derive(Debug, Clone, Default)]
+pub struct IntervalRules {
+    derived: Vec<(String, Expr)>,
+    tags: Vec<(String, Condition)>,
+    min_duration: Option<Duration>,
+    /// Per-transition minimums, which take precedence over `min_duration`; the last rule of
+    /// a transition wins
+    min_durations: Vec<(String, String, Duration)>,
+}
+
+impl IntervalRules {
+    pub fn new(config: &Config) -> Result<Self> {
+        Ok(IntervalRules {
+            derived: Self::derived(config),
+            tags: Self::tags(config),
+            ..Self::min_durations(config)?
+        })
+    }
+    
+    fn derived(config: &Config) -> Vec<(String, Expr)> {
+        // Expressions are checked when the configuration is validated
+        let derived_values = config.derived_values().unwrap_or_default();
+        let mut names: Vec<&str> = Vec::new();
+        let mut exprs = Vec::new();
+        for derived in &derived_values {
+            if let Ok(expr) = Expr::parse(&derived.expr, &names) {
+                exprs.push((derived.name.clone(), expr));
+                names.push(&derived.name);
+            }
+        }
+        exprs
+    }
+    
+    fn tags(config: &Config) -> Vec<(String, Condition)> {
+        let derived_values = config.derived_values().unwrap_or_default();
+        let names: Vec<&str> = derived_values.iter().map(|derived| derived.name.as_str()).collect();
+        // Conditions are checked when the configuration is validated
+        config
+            .tags
+            .iter()
+            .filter_map(|rule| Some((rule.tag.clone(), Condition::parse(&rule.condition, &names).ok()?)))
+            .collect()
+    }
+    
+    /// Only the minimum durations
+    fn min_durations(config: &Config) -> Result<Self> {
+        let min_duration = config.min_duration.as_deref().map(parse_duration).transpose()?;
+        let mut min_durations = Vec::new();
+        for rule in &config.transitions {
+            if let Some(min) = &rule.min_duration {
+                min_durations.push((rule.from.clone(), rule.to.clone(), parse_duration(min)?));
+            }
+        }
+        Ok(IntervalRules { min_duration, min_durations, ..Default::default() })
+    }
+    
+    /// Compute the derived values of each interval
+    pub fn derive_values(&self, intervals: &mut [Interval]) {
+        for interval in intervals {
+            for (name, expr) in &self.derived {
+                if let Some(value) = expr.eval(interval) {
+                    interval.derived.insert(name.clone(), value);
+                }
+            }
+        }
+    }
+    
+    /// Add the tag of each rule whose condition holds; derived values must already be computed
+    pub fn tag(&self, intervals: &mut [Interval]) {
+        for interval in intervals {
+            for (tag, condition) in &self.tags {
+                if condition.matches(interval) && !interval.tags.iter().any(|t| t == tag) {
+                    interval.tags.push(tag.clone());
+                }
+            }
+        }
+    }
+    
+    /// Whether an interval lasts at least its transition's minimum duration, or the global one
+    pub fn long_enough(&self, interval: &Interval) -> bool {
+        let min = self
+            .min_durations
+            .iter()
+            .rev()
+            .find(|(from, to, _)| *from == interval.from_pattern && *to == interval.to_pattern)
+            .map(|(_, _, min)| min)
+            .or(self.min_duration.as_ref());
+        min.is_none_or(|min| interval.duration >= *min)
+    }
+}
+
+/// Pairs start matches with their end matches as matches arrive (pair mode).
+///
+/// Each end closes the oldest open start of its pair with the same correlation key, so
+/// overlapping runs of the same pair are measured first-in, first-out; matches of other
+/// patterns are skipped.
+#[derive(Debug, Clone)]
+pub struct PairTracker {
+    pairs: Vec<PairRule>,
+    /// Open starts per pair and correlation key, oldest first
+    open: Vec<HashMap<Option<String>, VecDeque<LogMatch>>>,
+}
+
+impl PairTracker {
+    pub fn new(pairs: &[PairRule]) -> Self {
+        PairTracker {
+            pairs: pairs.to_vec(),
+            open: vec![HashMap::new(); pairs.len()],
+        }
+    }
+    
+    /// Feed the next match; returns the intervals it completes
+    pub fn push(&mut self, log_match: &LogMatch) -> Vec<Interval> {
+        let mut completed = Vec::new();
+        
+        for (pair, open) in self.pairs.iter().zip(&mut self.open) {
+            if log_match.pattern == pair.end {
+                if let Some(starts) = open.get_mut(&log_match.correlation) {
+                    if let Some(start) = starts.pop_front() {
+                        completed.push(Analyzer::interval_between(&start, log_match));
+                    }
+                    // Forget finished requests so a long run doesn't keep every key
+                    if starts.is_empty() {
+                        open.remove(&log_match.correlation);
+                    }
+                }
+            } else if log_match.pattern == pair.start {
+                open.entry(log_match.correlation.clone()).or_default().push_back(log_match.clone());
+            }
+        }
+        
+        completed
+    }
+}
+
 impl Analyzer {
-    /// Analyze log matches and find intervals between consecutive pattern matches
+    /// Analyze matches the way the configuration asks for: start/end pairs when `pairs`
+    /// are configured, consecutive matches otherwise, without the warm-up intervals
+    ///
+    /// Many correlated matches are paired on rayon's thread pool, each correlation key on one
+    /// thread (see `analyze_sharded`).
+    pub fn analyze_with(matches: Vec<LogMatch>, config: &Config) -> Vec<Interval> {
+        let mut warmup = Warmup::new(config);
+        if let Some(first) = matches.iter().map(|m| m.timestamp).min() {
+            warmup.observe(first);
+        }
+        let threads = rayon::current_num_threads();
+        let mut intervals = if threads > 1
+            && matches.len() >= MIN_SHARDED_MATCHES
+            && matches.iter().any(|m| m.correlation.is_some())
+        {
+            Self::analyze_sharded(&matches, &config.pairs, threads)
+        } else if config.pairs.is_empty() {
+            Self::analyze(matches)
+        } else {
+            Self::analyze_pairs(&matches, &config.pairs)
+        };
+        intervals.retain(|interval| warmup.keep(interval));
+        Self::derive_values(&mut intervals, config);
+        Self::tag_intervals(&mut intervals, config);
+        intervals
+    }
+    
+    /// Compute the configured `derived` values of each interval, in order, so later
+    /// expressions can use earlier results; values that can't be computed are left out
+    pub fn derive_values(intervals: &mut [Interval], config: &Config) {
+        IntervalRules { derived: IntervalRules::derived(config), ..Default::default() }.derive_values(intervals);
+    }
+    
+    /// Add the tag of each configured `tags` rule whose condition holds; derived values
+    /// must already be computed, as conditions can refer to them
+    pub fn tag_intervals(intervals: &mut [Interval], config: &Config) {
+        IntervalRules { tags: IntervalRules::tags(config), ..Default::default() }.tag(intervals);
+    }
+    
+    /// Measure each start pattern to its matching end pattern, in order of completion
+    pub fn analyze_pairs(matches: &[LogMatch], pairs: &[PairRule]) -> Vec<Interval> {
+        let mut tracker = PairTracker::new(pairs);
+        matches.iter().flat_map(|log_match| tracker.push(log_match)).collect()
+    }
+    
+    /// Analyze log matches and find intervals between consecutive pattern matches.
+    ///
+    /// Matches with a correlation key are only paired with the previous match of the
+    /// same key (matches without one are paired among themselves), in order of the
+    /// later match.
     pub fn analyze(matches: Vec<LogMatch>) -> Vec<Interval> {
         let mut intervals = Vec::new();
         
@@ -34,26 +429,147 @@ impl Analyzer {
         }
         
         // Find intervals between consecutive matches
-        for i in 0..matches.len() - 1 {
-            let from = &matches[i];
-            let to = &matches[i + 1];
-            
-            // Calculate duration
-            let duration = to.timestamp.signed_duration_since(from.timestamp);
-            
-            intervals.push(Interval {
-                from_pattern: from.pattern.clone(),
-                to_pattern: to.pattern.clone(),
-                duration,
-            });
+        if matches.iter().all(|m| m.correlation.is_none()) {
+            for i in 0..matches.len() - 1 {
+                intervals.push(Self::interval_between(&matches[i], &matches[i + 1]));
+            }
+            return intervals;
+        }
+        
+        // Index of the previous match of each key
+        let mut last: HashMap<Option<&str>, usize> = HashMap::new();
+        for (i, log_match) in matches.iter().enumerate() {
+            if let Some(previous) = last.insert(log_match.correlation.as_deref(), i) {
+                intervals.push(Self::interval_between(&matches[previous], log_match));
+            }
         }
         
         intervals
     }
+    
+    /// The intervals of `analyze` (or of `analyze_pairs` with `pairs`), with the matches split
+    /// into `shards` by correlation key and each shard paired on a thread of rayon's pool.
+    ///
+    /// Every match of a key lands in the same shard, in log order, so each key pairs exactly
+    /// as in a single pass; the intervals are then put back in the order of the matches that
+    /// complete them.
+    pub fn analyze_sharded(matches: &[LogMatch], pairs: &[PairRule], shards: usize) -> Vec<Interval> {
+        let shards = shards.max(1);
+        let mut shard_matches: Vec<Vec<usize>> = vec![Vec::new(); shards];
+        for (i, log_match) in matches.iter().enumerate() {
+            let mut hasher = DefaultHasher::new();
+            log_match.correlation.hash(&mut hasher);
+            shard_matches[(hasher.finish() % shards as u64) as usize].push(i);
+        }
+        
+        let mut completed: Vec<(usize, Interval)> = shard_matches
+            .into_par_iter()
+            .flat_map_iter(|indexes| {
+                let mut completed = Vec::new();
+                if pairs.is_empty() {
+                    let mut last: HashMap<Option<&str>, usize> = HashMap::new();
+                    for i in indexes {
+                        if let Some(previous) = last.insert(matches[i].correlation.as_deref(), i) {
+                            completed.push((i, Self::interval_between(&matches[previous], &matches[i])));
+                        }
+                    }
+                } else {
+                    let mut tracker = PairTracker::new(pairs);
+                    for i in indexes {
+                        completed.extend(tracker.push(&matches[i]).into_iter().map(|interval| (i, interval)));
+                    }
+                }
+                completed
+            })
+            .collect();
+        // Stable, so the intervals one match completes keep the order of the pairs
+        completed.par_sort_by_key(|&(i, _)| i);
+        completed.into_iter().map(|(_, interval)| interval).collect()
+    }
+    
+    /// Intervals between consecutive matches (see `analyze`) as the matches arrive, e.g. from
+    /// `LogParser::matches`, keeping only the last match of each correlation key in memory;
+    /// the first error ends the intervals
+    pub fn analyze_stream<I, E>(matches: I) -> impl Iterator<Item = std::result::Result<Interval, E>>
+    where
+        I: IntoIterator<Item = std::result::Result<LogMatch, E>>,
+    {
+        let mut tracker = ConsecutiveTracker::new();
+        matches.into_iter().filter_map(move |log_match| match log_match {
+            Ok(log_match) => tracker.push(&log_match).map(Ok),
+            Err(e) => Some(Err(e)),
+        })
+    }
+    
+    /// The interval from one match to the next, e.g. as matches arrive in follow mode
+    pub fn interval_between(from: &LogMatch, to: &LogMatch) -> Interval {
+        // Calculate duration
+        let duration = to.timestamp.signed_duration_since(from.timestamp);
+        
+        Interval {
+            from_pattern: from.pattern.clone(),
+            to_pattern: to.pattern.clone(),
+            start: Some(from.timestamp),
+            end: Some(to.timestamp),
+            duration,
+            from_source: from.source.clone(),
+            to_source: to.source.clone(),
+            from_fields: from.fields.clone(),
+            to_fields: to.fields.clone(),
+            correlation: from.correlation.clone(),
+            group: None,
+            derived: BTreeMap::new(),
+            tags: Vec::new(),
+            from_line: from.line_number,
+            to_line: to.line_number,
+            from_raw_line: from.line.clone(),
+            to_raw_line: to.line.clone(),
+        }
+    }
+    
+    /// Drop intervals shorter than the global `min_duration` or their transition's own minimum
+    pub fn apply_min_duration(intervals: Vec<Interval>, config: &Config) -> Result<Vec<Interval>> {
+        let rules = IntervalRules::min_durations(config)?;
+        Ok(intervals.into_iter().filter(|interval| rules.long_enough(interval)).collect())
+    }
+    
+    /// Intervals longer than the global `fail_if_over` or their transition's own limit,
+    /// each with the limit it exceeded
+    pub fn threshold_violations<'a>(intervals: &'a [Interval], config: &Config) -> Result<Vec<(&'a Interval, Duration)>> {
+        let global = config.fail_if_over.as_deref().map(parse_duration).transpose()?;
+        
+        let mut per_pair = HashMap::new();
+        for rule in &config.transitions {
+            if let Some(limit) = &rule.fail_if_over {
+                per_pair.insert((rule.from.as_str(), rule.to.as_str()), parse_duration(limit)?);
+            }
+        }
+        
+        Ok(intervals
+            .iter()
+            .filter_map(|interval| {
+                let limit = per_pair
+                    .get(&(interval.from_pattern.as_str(), interval.to_pattern.as_str()))
+                    .or(global.as_ref())?;
+                (interval.duration > *limit).then_some((interval, *limit))
+            })
+            .collect())
+    }
+}
+
+/// Format a derived value with at most three decimals and no trailing zeros
+pub fn format_number(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
 }
 
 /// Format duration in a human-readable way
-fn format_duration(duration: &Duration) -> String {
+pub fn format_duration(duration: &Duration) -> String {
     let total_seconds = duration.num_seconds();
     let is_negative = total_seconds < 0;
     let abs_seconds = total_seconds.abs();
@@ -76,9 +592,14 @@ fn format_duration(duration: &Duration) -> String {
     }
 }
 
+#[cfg(test)]
+#[path = "fixtures.rs"]
+pub(crate) mod fixtures;
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
 
     #[test]
     fn test_format_duration() {
@@ -94,4 +615,244 @@ mod tests {
         let duration = Duration::milliseconds(500);
         assert_eq!(format_duration(&duration), "500ms");
     }
+
+    #[test]
+    fn test_cross_source_intervals() {
+        use crate::parser::LogMatch;
+        use chrono::NaiveDateTime;
+
+        let ts = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let matches = vec![
+            LogMatch { pattern: "A".to_string(), timestamp: ts("2025-11-13 10:00:00"), source: Some("a.log".to_string()), ..Default::default() },
+            LogMatch { pattern: "B".to_string(), timestamp: ts("2025-11-13 10:00:01"), source: Some("a.log".to_string()), ..Default::default() },
+            LogMatch { pattern: "A".to_string(), timestamp: ts("2025-11-13 10:00:02"), source: Some("b.log".to_string()), ..Default::default() },
+        ];
+
+        let intervals = Analyzer::analyze(matches);
+        assert!(!intervals[0].is_cross_source());
+        assert_eq!(intervals[0].source_label().as_deref(), Some("a.log"));
+        assert!(intervals[1].is_cross_source());
+        assert_eq!(intervals[1].source_label().as_deref(), Some("a.log -> b.log"));
+    }
+
+    #[test]
+    fn test_apply_min_duration() {
+        use crate::config::TransitionRule;
+
+        let interval = |from: &str, to: &str, ms: i64| Interval::new(from, to, Duration::milliseconds(ms));
+        let intervals = vec![
+            interval("A", "B", 10),
+            interval("A", "B", 80),
+            interval("B", "C", 10),
+            interval("B", "C", 300),
+        ];
+
+        let config = Config {
+            min_duration: Some("50ms".to_string()),
+            transitions: vec![TransitionRule {
+                from: "B".to_string(),
+                to: "C".to_string(),
+                min_duration: Some("5ms".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let kept = Analyzer::apply_min_duration(intervals, &config).unwrap();
+        let kept_ms: Vec<i64> = kept.iter().map(|i| i.duration.num_milliseconds()).collect();
+        assert_eq!(kept_ms, vec![80, 10, 300]);
+
+        let rules = IntervalRules::new(&config).unwrap();
+        assert!(!rules.long_enough(&interval("A", "B", 10)));
+        assert!(rules.long_enough(&interval("B", "C", 10)));
+
+        let invalid = Config { min_duration: Some("soon".to_string()), ..Default::default() };
+        assert!(IntervalRules::new(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_warmup_is_skipped() {
+        let ts = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let matches: Vec<LogMatch> = (0..8)
+            .map(|i| LogMatch {
+                pattern: if i % 2 == 0 { "start" } else { "end" }.to_string(),
+                timestamp: ts("2025-11-13 10:00:00") + Duration::seconds(i * 10),
+                ..Default::default()
+            })
+            .collect();
+        let transitions = |intervals: Vec<Interval>| -> Vec<String> {
+            intervals.iter().map(|i| format!("{}@{}", i.from_pattern, i.start.unwrap().format("%M:%S"))).collect()
+        };
+
+        let config = Config { skip_iterations: Some(2), ..Default::default() };
+        assert_eq!(
+            transitions(Analyzer::analyze_with(matches.clone(), &config)),
+            vec!["start@00:40", "end@00:50", "start@01:00"],
+            "the first two iterations of each transition are dropped"
+        );
+
+        let config = Config { skip_duration: Some("30s".to_string()), ..Default::default() };
+        assert_eq!(
+            transitions(Analyzer::analyze_with(matches, &config)),
+            vec!["end@00:30", "start@00:40", "end@00:50", "start@01:00"]
+        );
+    }
+
+    #[test]
+    fn test_threshold_violations() {
+        use crate::config::TransitionRule;
+
+        let interval = |from: &str, to: &str, ms: i64| Interval::new(from, to, Duration::milliseconds(ms));
+        let intervals = vec![
+            interval("A", "B", 100),
+            interval("A", "B", 101),
+            interval("B", "C", 101),
+            interval("B", "C", 600),
+        ];
+
+        let config = Config {
+            fail_if_over: Some("100ms".to_string()),
+            transitions: vec![TransitionRule {
+                from: "B".to_string(),
+                to: "C".to_string(),
+                fail_if_over: Some("500ms".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let over: Vec<(i64, i64)> = Analyzer::threshold_violations(&intervals, &config)
+            .unwrap()
+            .iter()
+            .map(|(i, limit)| (i.duration.num_milliseconds(), limit.num_milliseconds()))
+            .collect();
+        assert_eq!(over, vec![(101, 100), (600, 500)]);
+        assert!(Analyzer::threshold_violations(&intervals, &Config::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pair_mode_skips_interleaved_matches() {
+        use chrono::NaiveDateTime;
+
+        let ts = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let log_match = |pattern: &str, time: &str| LogMatch { pattern: pattern.to_string(), timestamp: ts(time), ..Default::default() };
+        let matches = vec![
+            log_match("begin", "2025-11-13 10:00:00"),
+            log_match("cache miss", "2025-11-13 10:00:01"),
+            log_match("begin", "2025-11-13 10:00:02"),
+            log_match("done", "2025-11-13 10:00:05"),
+            log_match("done", "2025-11-13 10:00:06"),
+            log_match("done", "2025-11-13 10:00:07"),
+        ];
+        let pairs = [PairRule { start: "begin".to_string(), end: "done".to_string() }];
+
+        let intervals = Analyzer::analyze_pairs(&matches, &pairs);
+        let seconds: Vec<i64> = intervals.iter().map(|i| i.duration.num_seconds()).collect();
+        assert_eq!(seconds, vec![5, 4]);
+        assert!(intervals.iter().all(|i| i.from_pattern == "begin" && i.to_pattern == "done"));
+    }
+
+    #[test]
+    fn test_streamed_analysis_matches_collected_analysis() {
+        use chrono::NaiveDateTime;
+
+        let ts = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let log_match = |pattern: &str, time: &str, key: Option<&str>| LogMatch {
+            pattern: pattern.to_string(),
+            timestamp: ts(time),
+            correlation: key.map(str::to_string),
+            ..Default::default()
+        };
+        let matches = vec![
+            log_match("start", "2025-11-13 10:00:00", Some("a")),
+            log_match("start", "2025-11-13 10:00:01", Some("b")),
+            log_match("end", "2025-11-13 10:00:03", Some("a")),
+            log_match("end", "2025-11-13 10:00:07", Some("b")),
+        ];
+
+        let streamed: Vec<Interval> = Analyzer::analyze_stream(matches.clone().into_iter().map(Ok::<_, ()>))
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        let seconds = |intervals: &[Interval]| intervals.iter().map(|i| i.duration.num_seconds()).collect::<Vec<_>>();
+        assert_eq!(seconds(&streamed), vec![3, 6]);
+        assert_eq!(seconds(&streamed), seconds(&Analyzer::analyze(matches)));
+
+        let failing = vec![Ok(log_match("start", "2025-11-13 10:00:00", None)), Err("unreadable")];
+        assert_eq!(Analyzer::analyze_stream(failing).next().map(|i| i.is_err()), Some(true));
+    }
+    #[test]
+    fn test_correlated_matches_pair_by_key() {
+        use chrono::NaiveDateTime;
+
+        let ts = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let log_match = |pattern: &str, time: &str, key: &str| LogMatch {
+            pattern: pattern.to_string(),
+            timestamp: ts(time),
+            correlation: Some(key.to_string()),
+            ..Default::default()
+        };
+        let matches = vec![
+            log_match("begin", "2025-11-13 10:00:00", "r1"),
+            log_match("begin", "2025-11-13 10:00:01", "r2"),
+            log_match("done", "2025-11-13 10:00:03", "r2"),
+            log_match("done", "2025-11-13 10:00:07", "r1"),
+        ];
+
+        let intervals = Analyzer::analyze(matches.clone());
+        let summary: Vec<(i64, Option<&str>)> =
+            intervals.iter().map(|i| (i.duration.num_seconds(), i.correlation.as_deref())).collect();
+        assert_eq!(summary, vec![(2, Some("r2")), (7, Some("r1"))]);
+
+        let pairs = [PairRule { start: "begin".to_string(), end: "done".to_string() }];
+        let intervals = Analyzer::analyze_pairs(&matches, &pairs);
+        let summary: Vec<(i64, Option<&str>)> =
+            intervals.iter().map(|i| (i.duration.num_seconds(), i.correlation.as_deref())).collect();
+        assert_eq!(summary, vec![(2, Some("r2")), (7, Some("r1"))]);
+
+        let mut tracker = ConsecutiveTracker::new();
+        let streamed: Vec<Interval> = matches.iter().filter_map(|m| tracker.push(m)).collect();
+        assert_eq!(streamed.len(), 2);
+        assert_eq!(streamed[0].format(), "begin :::: 2s 0ms ::::> done  id=r2");
+    }
+
+    #[test]
+    fn test_sharded_analysis_matches_single_pass() {
+        let origin = NaiveDateTime::parse_from_str("2025-11-13 10:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        // Interleaved requests, some without a key, with a begin that two pairs start from
+        let matches: Vec<LogMatch> = (0..600_i64)
+            .map(|i| LogMatch {
+                pattern: ["begin", "query", "done"][(i % 7 % 3) as usize].to_string(),
+                timestamp: origin + Duration::milliseconds(i * 10 + i % 13),
+                correlation: (i % 11 != 0).then(|| format!("r{}", i % 17)),
+                ..Default::default()
+            })
+            .collect();
+        let summary = |intervals: Vec<Interval>| -> Vec<String> {
+            intervals.iter().map(|i| format!("{} at {:?}", i.format(), i.start)).collect()
+        };
+
+        let single = summary(Analyzer::analyze(matches.clone()));
+        assert!(single.len() > 500);
+        for shards in [1, 4, 32] {
+            assert_eq!(summary(Analyzer::analyze_sharded(&matches, &[], shards)), single, "{} shards", shards);
+        }
+
+        let pairs = [
+            PairRule { start: "begin".to_string(), end: "done".to_string() },
+            PairRule { start: "begin".to_string(), end: "query".to_string() },
+        ];
+        let single = summary(Analyzer::analyze_pairs(&matches, &pairs));
+        assert!(!single.is_empty());
+        assert_eq!(summary(Analyzer::analyze_sharded(&matches, &pairs, 4)), single);
+    }
+
+    #[test]
+    fn test_interval_serializes_duration_in_milliseconds() {
+        let interval = Interval::new("A", "B", Duration::milliseconds(1500));
+
+        assert_eq!(
+            serde_json::to_string(&interval).unwrap(),
+            r#"{"from_pattern":"A","to_pattern":"B","duration_ms":1500}"#
+        );
+    }
 }
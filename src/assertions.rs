@@ -0,0 +1,293 @@
+/// Expected-timing rules evaluated against an analysis (`assert --rules rules.yaml`)
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use log_time_analyzer::analyzer::Interval;
+use log_time_analyzer::duration::parse_duration;
+use log_time_analyzer::stats::{self, human_ms, TransitionStats};
+
+/// A rules file: a list of expectations per transition
+#[derive(Debug, Deserialize)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+}
+
+/// Expectations for one (from, to) transition
+#[derive(Debug, Deserialize)]
+pub struct Rule {
+    /// The transition as `[from_pattern, to_pattern]`
+    pub pair: [String; 2],
+
+    /// Checks such as `p95 < 200ms` or `count >= 10` (a single string may hold
+    /// several checks separated by commas)
+    pub expect: Vec<String>,
+}
+
+/// Statistic a check is made against
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Metric {
+    Count,
+    Min,
+    Max,
+    Mean,
+    Stddev,
+    Percentile(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+/// A parsed check: `<metric> <op> <value>`, with durations in milliseconds
+#[derive(Debug, Clone, PartialEq)]
+struct Check {
+    text: String,
+    metric: Metric,
+    comparison: Comparison,
+    value: f64,
+}
+
+/// Result of evaluating one check
+#[derive(Debug)]
+pub struct CheckResult {
+    pub from_pattern: String,
+    pub to_pattern: String,
+    pub check: String,
+    /// The measured value, rendered for display (`None` when the transition never occurred)
+    pub actual: Option<String>,
+    pub passed: bool,
+}
+
+impl RuleSet {
+    /// Load and validate a rules file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = fs::read_to_string(path.as_ref())
+            .with_context(|| format!("Failed to read rules file: {:?}", path.as_ref()))?;
+
+        let rules: RuleSet = serde_yaml::from_str(&contents).context("Failed to parse YAML rules")?;
+
+        for rule in &rules.rules {
+            rule.checks()?;
+        }
+
+        Ok(rules)
+    }
+
+    /// Evaluate every check of every rule against the analyzed intervals
+    pub fn evaluate(&self, intervals: &[Interval]) -> Result<Vec<CheckResult>> {
+        let all_stats = stats::compute(intervals, None);
+        let mut results = Vec::new();
+
+        for rule in &self.rules {
+            let [from, to] = &rule.pair;
+            let transition = all_stats
+                .iter()
+                .find(|s| &s.from_pattern == from && &s.to_pattern == to);
+
+            for check in rule.checks()? {
+                let (actual, passed) = match (transition, check.metric) {
+                    (None, Metric::Count) => (Some("0".to_string()), check.comparison.holds(0.0, check.value)),
+                    // Timing checks on a transition that never occurred cannot pass
+                    (None, _) => (None, false),
+                    (Some(transition), metric) => {
+                        let actual = metric.measure(transition);
+                        (Some(metric.render(actual)), check.comparison.holds(actual, check.value))
+                    }
+                };
+
+                results.push(CheckResult {
+                    from_pattern: from.clone(),
+                    to_pattern: to.clone(),
+                    check: check.text,
+                    actual,
+                    passed,
+                });
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+impl Rule {
+    fn checks(&self) -> Result<Vec<Check>> {
+        self.expect
+            .iter()
+            .flat_map(|text| text.split(','))
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(|text| {
+                parse_check(text).with_context(|| {
+                    format!("Invalid check '{}' for {} -> {}", text, self.pair[0], self.pair[1])
+                })
+            })
+            .collect()
+    }
+}
+
+impl Metric {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "count" => Metric::Count,
+            "min" => Metric::Min,
+            "max" => Metric::Max,
+            "mean" | "avg" => Metric::Mean,
+            "median" => Metric::Percentile(50.0),
+            "stddev" => Metric::Stddev,
+            _ => {
+                let p: f64 = name
+                    .strip_prefix('p')
+                    .and_then(|p| p.parse().ok())
+                    .filter(|p| (0.0..=100.0).contains(p))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unknown metric '{}'. Use count, min, max, mean, median, stddev, or p<0-100> (e.g. p95)",
+                            name
+                        )
+                    })?;
+                Metric::Percentile(p)
+            }
+        })
+    }
+
+    fn measure(self, stats: &TransitionStats) -> f64 {
+        match self {
+            Metric::Count => stats.count as f64,
+            Metric::Min => stats.min_ms as f64,
+            Metric::Max => stats.max_ms as f64,
+            Metric::Mean => stats.mean_ms,
+            Metric::Stddev => stats.stddev_ms,
+            Metric::Percentile(p) => stats.percentile(p),
+        }
+    }
+
+    fn render(self, value: f64) -> String {
+        match self {
+            Metric::Count => value.to_string(),
+            _ => human_ms(value),
+        }
+    }
+}
+
+impl Comparison {
+    fn holds(self, actual: f64, expected: f64) -> bool {
+        match self {
+            Comparison::Less => actual < expected,
+            Comparison::LessOrEqual => actual <= expected,
+            Comparison::Greater => actual > expected,
+            Comparison::GreaterOrEqual => actual >= expected,
+            Comparison::Equal => actual == expected,
+            Comparison::NotEqual => actual != expected,
+        }
+    }
+}
+
+/// Parse `<metric> <op> <value>`, e.g. `p95 < 200ms` or `count >= 10`
+fn parse_check(text: &str) -> Result<Check> {
+    // Two-character operators first so `<=` isn't read as `<`
+    const OPERATORS: [(&str, Comparison); 6] = [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    let (position, operator, comparison) = OPERATORS
+        .iter()
+        .filter_map(|&(op, comparison)| text.find(op).map(|pos| (pos, op, comparison)))
+        .min_by_key(|&(pos, op, _)| (pos, std::cmp::Reverse(op.len())))
+        .ok_or_else(|| anyhow::anyhow!("Expected a comparison such as 'p95 < 200ms'"))?;
+
+    let metric = Metric::parse(text[..position].trim())?;
+    let value_text = text[position + operator.len()..].trim();
+
+    let value = if metric == Metric::Count {
+        value_text
+            .parse::<usize>()
+            .map_err(|_| anyhow::anyhow!("Count must be a whole number, got '{}'", value_text))? as f64
+    } else {
+        parse_duration(value_text)?.num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0
+    };
+
+    Ok(Check {
+        text: text.to_string(),
+        metric,
+        comparison,
+        value,
+    })
+}
+
+/// Render check results as a pass/fail report
+pub fn format_report(results: &[CheckResult]) -> String {
+    let mut output = String::new();
+
+    for result in results {
+        output.push_str(&format!(
+            "{}  {} -> {}: {} (actual: {})\n",
+            if result.passed { "PASS" } else { "FAIL" },
+            result.from_pattern,
+            result.to_pattern,
+            result.check,
+            result.actual.as_deref().unwrap_or("no intervals")
+        ));
+    }
+
+    let failed = results.iter().filter(|r| !r.passed).count();
+    output.push_str(&format!(
+        "\n{} checks, {} passed, {} failed",
+        results.len(),
+        results.len() - failed,
+        failed
+    ));
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+    use chrono::Duration;
+
+    #[test]
+    fn test_parse_check() {
+        let check = parse_check("p95 < 200ms").unwrap();
+        assert_eq!(check.metric, Metric::Percentile(95.0));
+        assert_eq!(check.comparison, Comparison::Less);
+        assert_eq!(check.value, 200.0);
+
+        let check = parse_check("count>=10").unwrap();
+        assert_eq!(check.metric, Metric::Count);
+        assert_eq!(check.comparison, Comparison::GreaterOrEqual);
+        assert_eq!(check.value, 10.0);
+
+        assert!(parse_check("p95 200ms").is_err());
+        assert!(parse_check("p101 < 1s").is_err());
+        assert!(parse_check("count >= 1.5").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_rules() {
+        let intervals: Vec<Interval> = (1..=10)
+            .map(|i| Interval::new("A", "B", Duration::milliseconds(i * 10)))
+            .collect();
+
+        let rules: RuleSet = serde_yaml::from_str(
+            "rules:\n  - pair: [A, B]\n    expect: ['p95 < 200ms, count >= 10', 'max < 50ms']\n  - pair: [B, C]\n    expect: ['count == 0', 'mean < 1s']\n",
+        )
+        .unwrap();
+
+        let passed: Vec<bool> = rules.evaluate(&intervals).unwrap().iter().map(|r| r.passed).collect();
+        assert_eq!(passed, vec![true, true, false, true, false]);
+    }
+}
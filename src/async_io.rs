@@ -0,0 +1,237 @@
+/// Tokio-based variants of the parser entry points (`async` feature), so network
+/// inputs can share one runtime instead of blocking a thread per source
+use std::io::BufRead;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::decompress::{open_log_file, Compression};
+use crate::error::{LogLineError, Result};
+use crate::parser::{LogMatch, LogParser, DETECTION_SAMPLE_LINES};
+
+/// Bytes of decompressed log buffered between the decoding thread and the parser
+const DECOMPRESSED_BUFFER_BYTES: usize = 64 * 1024;
+
+impl LogParser {
+    /// Async counterpart of `parse_reader`: read lines from any async reader
+    /// (socket, HTTP body, child process) and return all matches in order
+    pub async fn parse_async_reader<R: AsyncBufRead + Unpin>(&mut self, reader: R) -> Result<Vec<LogMatch>> {
+        let mut matches = Vec::new();
+        self.start_input();
+
+        let mut lines = reader.lines();
+        let mut sample = Vec::new();
+        if self.needs_detection_sample() {
+            while sample.len() < DETECTION_SAMPLE_LINES {
+                match next_line(&mut lines).await? {
+                    Some(line) => sample.push(line),
+                    None => break,
+                }
+            }
+            self.rank_formats(&sample);
+        }
+
+        for line in sample {
+            self.feed_line(&line, &mut matches)?;
+        }
+        while let Some(line) = next_line(&mut lines).await? {
+            if self.should_stop() {
+                break;
+            }
+            self.feed_line(&line, &mut matches)?;
+        }
+        self.finish_input(&mut matches)?;
+
+        Ok(matches)
+    }
+
+    /// Async counterpart of `parse_file`, for plain and gzip/zstd/bzip2-compressed logs.
+    ///
+    /// Plain files are read with tokio's file I/O. The decoders are synchronous, so a
+    /// compressed file is decompressed on tokio's blocking thread pool and streamed to the
+    /// parser through an in-memory pipe.
+    pub async fn parse_file_async<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<LogMatch>> {
+        let path = path.as_ref();
+        self.start_file(path)?;
+        let file = tokio::fs::File::open(path)
+            .await
+            .map_err(|e| LogLineError::io(format!("Failed to open log file: {:?}", path), e))?;
+        let mut reader = BufReader::new(file);
+        let header = reader
+            .fill_buf()
+            .await
+            .map_err(|e| LogLineError::io(format!("Failed to read log file: {:?}", path), e))?;
+        if Compression::detect(header) == Compression::None {
+            return self.parse_async_reader(reader).await;
+        }
+        drop(reader);
+
+        let (mut writer, decompressed) = tokio::io::duplex(DECOMPRESSED_BUFFER_BYTES);
+        let runtime = tokio::runtime::Handle::current();
+        let owned_path = path.to_path_buf();
+        let decoder = tokio::task::spawn_blocking(move || -> Result<()> {
+            let mut input = open_log_file(&owned_path)?;
+            loop {
+                let chunk = input
+                    .fill_buf()
+                    .map_err(|e| LogLineError::io(format!("Failed to read log file: {:?}", owned_path), e))?;
+                if chunk.is_empty() {
+                    return Ok(());
+                }
+                let len = chunk.len();
+                // The parser stopped reading, e.g. on an error or a stop request
+                if runtime.block_on(writer.write_all(chunk)).is_err() {
+                    return Ok(());
+                }
+                input.consume(len);
+            }
+        });
+
+        let matches = self.parse_async_reader(BufReader::new(decompressed)).await;
+        // A decoding error ends the pipe early, so it is reported instead of the partial matches
+        decoder
+            .await
+            .map_err(|e| LogLineError::io(format!("Failed to decompress log file: {:?}", path), std::io::Error::other(e)))??;
+        matches
+    }
+
+    /// Async counterpart of `follow`: report each match as soon as its line is read.
+    ///
+    /// With `poll`, reaching the end of the input waits that long (without blocking the
+    /// runtime) and tries again, like `tail -f` on a file; without it (a socket or a child's
+    /// output) the end of the input ends the call. Formats are ranked as in `follow`.
+    pub async fn follow_async<R: AsyncBufRead + Unpin>(
+        &mut self,
+        mut reader: R,
+        poll: Option<Duration>,
+        mut on_match: impl FnMut(LogMatch),
+    ) -> Result<()> {
+        self.start_input();
+        let mut sample = self.needs_detection_sample().then(Vec::new);
+        let mut pending = String::new();
+        let mut found = Vec::new();
+
+        loop {
+            if self.should_stop() {
+                return Ok(());
+            }
+            let read = reader
+                .read_line(&mut pending)
+                .await
+                .map_err(|e| LogLineError::io("Failed to read line from log", e))?;
+            let finished = read == 0 && poll.is_none();
+
+            if (read > 0 && pending.ends_with('\n')) || (finished && !pending.is_empty()) {
+                let line = pending.trim_end_matches(['\n', '\r']).to_string();
+                pending.clear();
+
+                match sample.as_mut() {
+                    Some(lines) => {
+                        let has_candidate = poll.is_none() && self.has_timestamp_candidate(&line);
+                        lines.push(line);
+                        if has_candidate || lines.len() >= DETECTION_SAMPLE_LINES {
+                            let lines = sample.take().unwrap_or_default();
+                            self.feed_sample(lines, &mut on_match)?;
+                        }
+                    }
+                    None => {
+                        self.feed_line(&line, &mut found)?;
+                        found.drain(..).for_each(&mut on_match);
+                    }
+                }
+                continue;
+            }
+
+            if read > 0 {
+                // Partial line: the writer hasn't finished it yet
+                continue;
+            }
+
+            // Caught up with the writer; an entry it wrote in one go is complete by now
+            if let Some(lines) = sample.take() {
+                self.feed_sample(lines, &mut on_match)?;
+            }
+            self.finish_input(&mut found)?;
+            found.drain(..).for_each(&mut on_match);
+            match poll {
+                Some(interval) => tokio::time::sleep(interval).await,
+                None => return Ok(()),
+            }
+        }
+    }
+}
+
+async fn next_line<R: AsyncBufRead + Unpin>(lines: &mut tokio::io::Lines<R>) -> Result<Option<String>> {
+    lines
+        .next_line()
+        .await
+        .map_err(|e| LogLineError::io("Failed to read line from log", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use std::io::Write;
+
+    const LOG: &str = "\
+2025-11-13 10:00:00.100 start
+2025-11-13 10:00:01.350 end
+";
+
+    fn config() -> Config {
+        Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap()
+    }
+
+    fn stamps(matches: &[LogMatch]) -> Vec<chrono::NaiveDateTime> {
+        matches.iter().map(|m| m.timestamp).collect()
+    }
+
+    #[tokio::test]
+    async fn test_parse_async_reader_matches_sync_parser() {
+        let mut sync_parser = LogParser::new(&config()).unwrap();
+        let expected = sync_parser.parse_reader(std::io::Cursor::new(LOG)).unwrap();
+
+        let mut parser = LogParser::new(&config()).unwrap();
+        let matches = parser.parse_async_reader(LOG.as_bytes()).await.unwrap();
+
+        assert_eq!(stamps(&matches), stamps(&expected));
+        assert_eq!(parser.stats().lines_read, 2);
+    }
+
+    #[tokio::test]
+    async fn test_parse_file_async_decompresses() {
+        let dir = std::env::temp_dir().join(format!("ll-async-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log.gz");
+        let mut encoder = flate2::write::GzEncoder::new(std::fs::File::create(&path).unwrap(), flate2::Compression::default());
+        // Enough lines to fill the pipe between the decoding thread and the parser many times
+        for _ in 0..5000 {
+            encoder.write_all(LOG.as_bytes()).unwrap();
+        }
+        encoder.finish().unwrap();
+
+        let expected = LogParser::new(&config()).unwrap().parse_file(&path).unwrap();
+        let mut parser = LogParser::new(&config()).unwrap();
+        let matches = parser.parse_file_async(&path).await.unwrap();
+        assert_eq!(matches.len(), 10_000);
+        assert_eq!(stamps(&matches), stamps(&expected));
+
+        // A gzip stream cut short fails instead of yielding the lines before the cut
+        let compressed = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &compressed[..compressed.len() / 2]).unwrap();
+        assert!(LogParser::new(&config()).unwrap().parse_file_async(&path).await.is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_follow_async_reports_matches_as_read() {
+        let mut parser = LogParser::new(&config()).unwrap();
+        let mut patterns = Vec::new();
+        parser
+            .follow_async(format!("{}2025-11-13 10:00:02.000 start", LOG).as_bytes(), None, |m| patterns.push(m.pattern))
+            .await
+            .unwrap();
+        assert_eq!(patterns, ["start", "end", "start"], "the last line is complete at the end of the input");
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log_time_analyzer::analyzer::Analyzer;
+use log_time_analyzer::config::{Config, MessagePattern};
+use log_time_analyzer::output::{OutputFormat, OutputFormatter};
+use log_time_analyzer::parser::LogParser;
+
+/// A batch manifest describing many independent analysis jobs
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    /// Maximum number of jobs to run at once (defaults to the number of CPUs)
+    #[serde(default)]
+    pub parallelism: Option<usize>,
+
+    /// Jobs to run
+    pub jobs: Vec<Job>,
+}
+
+/// A single (log file, config, output) job in a batch manifest
+#[derive(Debug, Deserialize, Clone)]
+pub struct Job {
+    /// Name shown in the summary (defaults to the log file path)
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Log file to analyze
+    pub log_file: PathBuf,
+
+    /// YAML configuration file for this job (optional when patterns are given)
+    #[serde(default)]
+    pub config: Option<PathBuf>,
+
+    /// Message patterns, as regexes or `{ name, regex }` entries (override the config file, or
+    /// enable auto-detection without one)
+    #[serde(default)]
+    pub patterns: Vec<MessagePattern>,
+
+    /// File the formatted results are written to
+    pub output: PathBuf,
+
+    /// Output format for this job
+    #[serde(default = "default_format")]
+    pub format: String,
+}
+
+fn default_format() -> String {
+    "json".to_string()
+}
+
+/// Outcome of a single batch job
+#[derive(Debug)]
+pub struct JobResult {
+    pub name: String,
+    pub elapsed: Duration,
+    pub outcome: Result<JobStats, String>,
+}
+
+/// Counts reported for a successful job
+#[derive(Debug)]
+pub struct JobStats {
+    pub matches: usize,
+    pub intervals: usize,
+}
+
+impl Manifest {
+    /// Load a manifest from a YAML file, resolving job paths relative to the manifest
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest file: {:?}", path))?;
+
+        let mut manifest: Manifest = serde_yaml::from_str(&contents)
+            .context("Failed to parse YAML manifest")?;
+
+        if manifest.jobs.is_empty() {
+            anyhow::bail!("Manifest must contain at least one job");
+        }
+
+        let base = path.parent().unwrap_or_else(|| Path::new("."));
+        for job in &mut manifest.jobs {
+            job.log_file = resolve(base, &job.log_file);
+            job.output = resolve(base, &job.output);
+            job.config = job.config.as_ref().map(|c| resolve(base, c));
+        }
+
+        Ok(manifest)
+    }
+}
+
+fn resolve(base: &Path, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(path)
+    }
+}
+
+/// Run every job in the manifest, at most `parallelism` at a time, in manifest order
+pub fn run(manifest: &Manifest, parallelism: Option<usize>) -> Vec<JobResult> {
+    let workers = parallelism
+        .or(manifest.parallelism)
+        .unwrap_or_else(|| thread::available_parallelism().map(|n| n.get()).unwrap_or(1))
+        .clamp(1, manifest.jobs.len().max(1));
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<JobResult>>> =
+        Mutex::new((0..manifest.jobs.len()).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::SeqCst);
+                let Some(job) = manifest.jobs.get(idx) else {
+                    break;
+                };
+
+                let result = run_job(job);
+                results.lock().unwrap()[idx] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn run_job(job: &Job) -> JobResult {
+    let name = job
+        .name
+        .clone()
+        .unwrap_or_else(|| job.log_file.display().to_string());
+
+    let start = Instant::now();
+    let outcome = analyze_job(job).map_err(|e| format!("{:#}", e));
+
+    JobResult {
+        name,
+        elapsed: start.elapsed(),
+        outcome,
+    }
+}
+
+fn analyze_job(job: &Job) -> Result<JobStats> {
+    let output_format = job
+        .format
+        .parse::<OutputFormat>()
+        .map_err(|_| anyhow::anyhow!("Invalid output format '{}'", job.format))?;
+
+    let patterns = if job.patterns.is_empty() {
+        None
+    } else {
+        Some(job.patterns.clone())
+    };
+
+    let config = Config::from_file_with_overrides(job.config.as_deref(), None, None, patterns)
+        .context("Failed to load configuration")?;
+
+    let mut parser = LogParser::new(&config).context("Failed to create log parser")?;
+    let matches = parser
+        .parse_file(&job.log_file)
+        .context("Failed to parse log file")?;
+    let match_count = matches.len();
+
+    let intervals = Analyzer::analyze_with(matches, &config);
+    let output = OutputFormatter::format_intervals(&intervals, output_format);
+
+    fs::write(&job.output, output)
+        .with_context(|| format!("Failed to write output file: {:?}", job.output))?;
+
+    Ok(JobStats {
+        matches: match_count,
+        intervals: intervals.len(),
+    })
+}
+
+/// Render the consolidated summary table for a finished batch
+pub fn format_summary(results: &[JobResult], elapsed: Duration) -> String {
+    let failed = results.iter().filter(|r| r.outcome.is_err()).count();
+
+    let mut output = format!(
+        "Batch summary: {} jobs, {} succeeded, {} failed ({:.2}s)\n\n",
+        results.len(),
+        results.len() - failed,
+        failed,
+        elapsed.as_secs_f64()
+    );
+
+    let max_name = results
+        .iter()
+        .map(|r| r.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(3); // "Job" header length
+
+    output.push_str(&format!(
+        "| {:<width$} | {:<6} | {:>7} | {:>9} | {:>8} |\n",
+        "Job",
+        "Status",
+        "Matches",
+        "Intervals",
+        "Time",
+        width = max_name
+    ));
+    output.push_str(&format!(
+        "|{:-<width$}|--------|---------|-----------|----------|\n",
+        "-",
+        width = max_name + 2
+    ));
+
+    for result in results {
+        let (status, matches, intervals) = match &result.outcome {
+            Ok(stats) => ("ok", stats.matches.to_string(), stats.intervals.to_string()),
+            Err(_) => ("FAILED", "-".to_string(), "-".to_string()),
+        };
+
+        output.push_str(&format!(
+            "| {:<width$} | {:<6} | {:>7} | {:>9} | {:>7.2}s |\n",
+            result.name,
+            status,
+            matches,
+            intervals,
+            result.elapsed.as_secs_f64(),
+            width = max_name
+        ));
+    }
+
+    let errors: Vec<&JobResult> = results.iter().filter(|r| r.outcome.is_err()).collect();
+    if !errors.is_empty() {
+        output.push_str("\nFailures:\n");
+        for result in errors {
+            if let Err(e) = &result.outcome {
+                output.push_str(&format!("  {}: {}\n", result.name, e));
+            }
+        }
+    }
+
+    output.trim_end().to_string()
+}
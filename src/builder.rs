@@ -0,0 +1,295 @@
+/// Builder-style construction of `Config` and `LogParser` for programmatic use
+use crate::config::{Config, MessagePattern, TransitionRule};
+use crate::error::{LogLineError, Result};
+use crate::parser::LogParser;
+
+/// Builds a validated `Config` without touching its fields directly.
+///
+/// The timestamp is auto-detected unless `timestamp` is called (or
+/// `auto_detect(false)` is set explicitly, which then requires it).
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        ConfigBuilder {
+            config: Config {
+                is_auto_detect: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a message pattern (patterns are matched in the order they are added); a regex, or
+    /// `MessagePattern::named` for one shown under a name
+    pub fn pattern(mut self, pattern: impl Into<MessagePattern>) -> Self {
+        self.config.message_patterns.push(pattern.into());
+        self
+    }
+
+    /// Add several message patterns
+    pub fn patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<MessagePattern>,
+    {
+        self.config.message_patterns.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Use this timestamp regex and chrono format instead of auto-detection
+    pub fn timestamp(mut self, regex: impl Into<String>, format: impl Into<String>) -> Self {
+        self.config.timestamp_regex = regex.into();
+        self.config.timestamp_format = format.into();
+        self.config.is_auto_detect = false;
+        self
+    }
+
+    /// Turn timestamp auto-detection on or off
+    pub fn auto_detect(mut self, enabled: bool) -> Self {
+        self.config.is_auto_detect = enabled;
+        self
+    }
+
+    /// Built-in format to try first when auto-detecting (name or unique part of it)
+    pub fn prefer_format(mut self, name: impl Into<String>) -> Self {
+        self.config.prefer_format = Some(name.into());
+        self
+    }
+
+    /// Skip lines matching this regex
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.config.exclude_patterns.push(pattern.into());
+        self
+    }
+
+    /// Skip lines longer than this many bytes
+    pub fn max_line_length(mut self, bytes: usize) -> Self {
+        self.config.max_line_length = Some(bytes);
+        self
+    }
+
+    /// Only accept timestamps at the start of the line
+    pub fn anchor_timestamp(mut self, enabled: bool) -> Self {
+        self.config.anchor_timestamp = enabled;
+        self
+    }
+
+    /// Remove ANSI color codes before matching
+    pub fn strip_ansi(mut self, enabled: bool) -> Self {
+        self.config.strip_ansi = enabled;
+        self
+    }
+
+    /// Keep trying every built-in format on every line when auto-detecting
+    pub fn mixed_timestamp_formats(mut self, enabled: bool) -> Self {
+        self.config.mixed_timestamp_formats = enabled;
+        self
+    }
+
+    /// Join lines without a timestamp onto the entry before them, e.g. stack traces
+    pub fn multiline(mut self, enabled: bool) -> Self {
+        self.config.multiline = enabled;
+        self
+    }
+
+    /// Join the lines matching this regex onto the entry before them (turns on `multiline`)
+    pub fn continuation(mut self, regex: impl Into<String>) -> Self {
+        self.config.continuation_regex = Some(regex.into());
+        self
+    }
+
+    /// Find events embedded in each line with this regex (`label` and `timestamp` groups) and
+    /// parse their timestamps with this chrono format
+    pub fn embedded_events(mut self, regex: impl Into<String>, format: impl Into<String>) -> Self {
+        self.config.embedded_event_regex = Some(regex.into());
+        self.config.embedded_timestamp_format = Some(format.into());
+        self
+    }
+
+    /// Drop intervals shorter than this duration, e.g. "50ms"
+    pub fn min_duration(mut self, duration: impl Into<String>) -> Self {
+        self.config.min_duration = Some(duration.into());
+        self
+    }
+
+    /// Drop intervals of one transition shorter than this duration
+    pub fn transition_min_duration(
+        mut self,
+        from: impl Into<String>,
+        to: impl Into<String>,
+        duration: impl Into<String>,
+    ) -> Self {
+        self.config.transitions.push(TransitionRule {
+            from: from.into(),
+            to: to.into(),
+            min_duration: Some(duration.into()),
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Fail the run when any interval is longer than this duration, e.g. "2s"
+    pub fn fail_if_over(mut self, duration: impl Into<String>) -> Self {
+        self.config.fail_if_over = Some(duration.into());
+        self
+    }
+
+    /// Fail the run when an interval of one transition is longer than this duration
+    pub fn transition_fail_if_over(
+        mut self,
+        from: impl Into<String>,
+        to: impl Into<String>,
+        duration: impl Into<String>,
+    ) -> Self {
+        self.config.transitions.push(TransitionRule {
+            from: from.into(),
+            to: to.into(),
+            fail_if_over: Some(duration.into()),
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Expect intervals of one transition to take at most this duration (checked by `sla`)
+    pub fn transition_expected_max(
+        mut self,
+        from: impl Into<String>,
+        to: impl Into<String>,
+        duration: impl Into<String>,
+    ) -> Self {
+        self.config.transitions.push(TransitionRule {
+            from: from.into(),
+            to: to.into(),
+            expected_max: Some(duration.into()),
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Validate and return the configuration
+    pub fn build(self) -> Result<Config> {
+        if self.config.is_auto_detect
+            && (!self.config.timestamp_regex.is_empty() || !self.config.timestamp_format.is_empty())
+        {
+            return Err(LogLineError::config(
+                "A timestamp regex and format were given but auto-detection is enabled",
+            ));
+        }
+
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+/// Builds a `LogParser` directly, with the same options as `ConfigBuilder`
+#[derive(Debug, Clone, Default)]
+pub struct ParserBuilder {
+    config: ConfigBuilder,
+}
+
+impl ParserBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from an existing configuration builder
+    pub fn from_config(config: ConfigBuilder) -> Self {
+        ParserBuilder { config }
+    }
+
+    /// Adjust the underlying configuration, e.g. `.configure(|c| c.min_duration("50ms"))`
+    pub fn configure(mut self, f: impl FnOnce(ConfigBuilder) -> ConfigBuilder) -> Self {
+        self.config = f(self.config);
+        self
+    }
+
+    pub fn pattern(self, pattern: impl Into<MessagePattern>) -> Self {
+        self.configure(|c| c.pattern(pattern))
+    }
+
+    pub fn timestamp(self, regex: impl Into<String>, format: impl Into<String>) -> Self {
+        self.configure(|c| c.timestamp(regex, format))
+    }
+
+    pub fn auto_detect(self, enabled: bool) -> Self {
+        self.configure(|c| c.auto_detect(enabled))
+    }
+
+    pub fn exclude(self, pattern: impl Into<String>) -> Self {
+        self.configure(|c| c.exclude(pattern))
+    }
+
+    pub fn strip_ansi(self, enabled: bool) -> Self {
+        self.configure(|c| c.strip_ansi(enabled))
+    }
+
+    /// Validate the configuration and compile the parser
+    pub fn build(self) -> Result<LogParser> {
+        LogParser::new(&self.config.build()?)
+    }
+}
+
+impl Config {
+    /// Start building a configuration programmatically
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+}
+
+impl LogParser {
+    /// Start building a parser programmatically
+    pub fn builder() -> ParserBuilder {
+        ParserBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_config_builder_modes() {
+        let config = Config::builder().pattern("start").pattern("end").build().unwrap();
+        assert!(config.is_auto_detect);
+
+        let config = Config::builder()
+            .patterns(["start", "end"])
+            .timestamp(r"^(\S+ \S+)", "%Y-%m-%d %H:%M:%S")
+            .build()
+            .unwrap();
+        assert!(!config.is_auto_detect);
+
+        assert!(Config::builder().pattern("only one").build().is_err());
+        assert!(Config::builder().patterns(["a", "b"]).auto_detect(false).build().is_err());
+        assert!(Config::builder().patterns(["a", "b"]).min_duration("soon").build().is_err());
+    }
+
+    #[test]
+    fn test_parser_builder() {
+        let mut parser = LogParser::builder()
+            .pattern("start")
+            .pattern("end")
+            .exclude("DEBUG")
+            .build()
+            .unwrap();
+        let log = "\
+2025-11-13 10:00:00 start
+2025-11-13 10:00:01 DEBUG end
+2025-11-13 10:00:02 end
+";
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!((matches[1].timestamp - matches[0].timestamp).num_seconds(), 2);
+    }
+}
@@ -0,0 +1,87 @@
+/// Machine-readable description of what this build supports (`--capabilities`), for wrapper
+/// tools that adapt to the installed version.
+///
+/// The document is versioned by `capabilities_version`: fields may be added within a version,
+/// but are only renamed, removed, or changed in meaning with a new version.
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::config::InputFormat;
+use crate::decompress::Compression;
+use crate::output::OutputFormat;
+use crate::schema::SCHEMA_VERSION;
+use crate::timestamp_formats::{get_builtin_formats, TimestampFormat};
+
+/// Version of the capabilities document itself
+pub const CAPABILITIES_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct Capabilities {
+    pub capabilities_version: u32,
+    /// Version of log-time-analyzer
+    pub version: &'static str,
+    /// Version of the JSON/CSV output schemas (`--print-schema`)
+    pub output_schema_version: u32,
+    pub input_formats: Vec<&'static str>,
+    pub output_formats: Vec<OutputFormatCapability>,
+    /// Compressed log files read transparently
+    pub compression: Vec<&'static str>,
+    pub timestamp_formats: Vec<TimestampFormat>,
+    /// Cargo features and whether this build has them
+    pub features: BTreeMap<&'static str, bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OutputFormatCapability {
+    pub name: &'static str,
+    /// Whether the format can print one interval at a time (`--follow`, `--stream`)
+    pub streaming: bool,
+}
+
+/// Capabilities of this build
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        capabilities_version: CAPABILITIES_VERSION,
+        version: env!("CARGO_PKG_VERSION"),
+        output_schema_version: SCHEMA_VERSION,
+        input_formats: InputFormat::ALL.into_iter().map(InputFormat::name).collect(),
+        output_formats: OutputFormat::ALL
+            .into_iter()
+            .map(|format| OutputFormatCapability {
+                name: format.name(),
+                streaming: format.supports_streaming(),
+            })
+            .collect(),
+        compression: Compression::SUPPORTED.into_iter().map(Compression::name).collect(),
+        timestamp_formats: get_builtin_formats(),
+        features: BTreeMap::from([("async", cfg!(feature = "async")), ("sql", cfg!(feature = "sql"))]),
+    }
+}
+
+impl Capabilities {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_list_every_format() {
+        let document: serde_json::Value = serde_json::from_str(&capabilities().to_json()).unwrap();
+
+        assert_eq!(document["capabilities_version"], CAPABILITIES_VERSION);
+        let outputs = document["output_formats"].as_array().unwrap();
+        assert_eq!(outputs.len(), OutputFormat::ALL.len());
+        for output in outputs {
+            let format = output["name"].as_str().unwrap().parse::<OutputFormat>().unwrap();
+            assert_eq!(output["streaming"], format.supports_streaming());
+        }
+        assert_eq!(document["input_formats"], serde_json::json!(["text", "json"]));
+        assert_eq!(document["timestamp_formats"].as_array().unwrap().len(), get_builtin_formats().len());
+        assert_eq!(document["features"]["async"], cfg!(feature = "async"));
+        assert_eq!(document["features"]["sql"], cfg!(feature = "sql"));
+    }
+}
@@ -0,0 +1,181 @@
+/// Clock offset calibration between merged logs: events known to happen at the same moment
+/// on every host (anchors, e.g. a broadcast) give each source's offset from the first one,
+/// which is then added to its timestamps before intervals are measured
+use chrono::Duration;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::analyzer::format_duration;
+use crate::parser::LogMatch;
+
+/// Offset of one source from the reference source
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceOffset {
+    pub source: String,
+    /// Added to the source's timestamps; `None` when it shares no anchor with the reference
+    pub offset: Option<Duration>,
+    /// Number of anchor events paired with the reference
+    pub anchors: usize,
+}
+
+/// Offsets of every merged source, relative to the reference (the first source)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockOffsets {
+    pub reference: String,
+    pub sources: Vec<SourceOffset>,
+}
+
+/// Offsets of `sources` from the first of them, from the matches of the `anchors` patterns
+/// (labels of message patterns). The n-th occurrence of an anchor in a source is paired with
+/// the n-th in the reference, and the median of the differences is the source's offset, so
+/// an anchor missing from one log or logged late does not throw it off.
+pub fn calibrate(matches: &[LogMatch], sources: &[String], anchors: &[String]) -> ClockOffsets {
+    let reference = sources.first().cloned().unwrap_or_default();
+    let occurrences = |source: &str| {
+        let mut by_anchor: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+        for m in matches.iter().filter(|m| m.source.as_deref() == Some(source) && anchors.contains(&m.pattern)) {
+            by_anchor.entry(m.pattern.as_str()).or_default().push(m.timestamp);
+        }
+        by_anchor
+    };
+    let reference_times = occurrences(&reference);
+
+    let sources = sources
+        .iter()
+        .skip(1)
+        .map(|source| {
+            let mut differences: Vec<Duration> = Vec::new();
+            for (anchor, times) in occurrences(source) {
+                if let Some(reference_times) = reference_times.get(anchor) {
+                    differences.extend(reference_times.iter().zip(&times).map(|(reference, time)| *reference - *time));
+                }
+            }
+            differences.sort();
+            SourceOffset {
+                source: source.clone(),
+                offset: differences.get(differences.len() / 2).copied(),
+                anchors: differences.len(),
+            }
+        })
+        .collect();
+
+    ClockOffsets { reference, sources }
+}
+
+impl ClockOffsets {
+    /// `matches` with each source's offset added to its timestamps and the anchor matches
+    /// left out, back in timestamp order
+    pub fn apply(&self, matches: Vec<LogMatch>, anchors: &[String]) -> Vec<LogMatch> {
+        let offsets: BTreeMap<&str, Duration> = self
+            .sources
+            .iter()
+            .filter_map(|s| Some((s.source.as_str(), s.offset?)))
+            .collect();
+        let anchors: HashSet<&str> = anchors.iter().map(String::as_str).collect();
+
+        let mut matches: Vec<LogMatch> = matches
+            .into_iter()
+            .filter(|m| !anchors.contains(m.pattern.as_str()))
+            .map(|mut m| {
+                if let Some(offset) = m.source.as_deref().and_then(|source| offsets.get(source)) {
+                    m.timestamp += *offset;
+                }
+                m
+            })
+            .collect();
+        // Stable sort keeps each file's own ordering for identical timestamps
+        matches.sort_by_key(|m| m.timestamp);
+        matches
+    }
+
+    /// One line per source with the offset applied to it, for stderr
+    pub fn format_report(&self) -> String {
+        let mut lines = vec![format!("Clock offsets from anchor events (relative to {}):", self.reference)];
+        for s in &self.sources {
+            lines.push(match s.offset {
+                Some(offset) => {
+                    let sign = if offset < Duration::zero() { "-" } else { "+" };
+                    format!("  {}: {}{} ({} anchors)", s.source, sign, format_duration(&offset.abs()), s.anchors)
+                }
+                None => format!("  {}: no anchor in common, left unchanged", s.source),
+            });
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn log_match(pattern: &str, source: &str, time: &str) -> LogMatch {
+        LogMatch {
+            pattern: pattern.to_string(),
+            timestamp: NaiveDateTime::parse_from_str(time, "%H:%M:%S%.3f %Y-%m-%d").unwrap(),
+            source: Some(source.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_offsets_align_anchors() {
+        let anchors = vec!["epoch".to_string()];
+        let sources: Vec<String> = ["web-1.log", "web-2.log", "web-3.log"].map(String::from).to_vec();
+        // web-2's clock runs 1.5s ahead; one of its anchors was logged late
+        let matches = vec![
+            log_match("epoch", "web-1.log", "10:00:00.000 2025-11-13"),
+            log_match("epoch", "web-2.log", "10:00:01.500 2025-11-13"),
+            log_match("request", "web-1.log", "10:00:01.000 2025-11-13"),
+            log_match("response", "web-2.log", "10:00:02.700 2025-11-13"),
+            log_match("epoch", "web-1.log", "10:01:00.000 2025-11-13"),
+            log_match("epoch", "web-2.log", "10:01:01.500 2025-11-13"),
+            log_match("epoch", "web-1.log", "10:02:00.000 2025-11-13"),
+            log_match("epoch", "web-2.log", "10:02:04.000 2025-11-13"),
+            log_match("request", "web-3.log", "10:00:00.500 2025-11-13"),
+        ];
+
+        let offsets = calibrate(&matches, &sources, &anchors);
+        assert_eq!(offsets.reference, "web-1.log");
+        assert_eq!(offsets.sources[0].offset, Some(Duration::milliseconds(-1500)));
+        assert_eq!(offsets.sources[0].anchors, 3);
+        assert_eq!(offsets.sources[1].offset, None);
+        assert!(offsets.format_report().contains("web-2.log: -1s 500ms (3 anchors)"));
+
+        let aligned = offsets.apply(matches, &anchors);
+        let order: Vec<(&str, String)> = aligned
+            .iter()
+            .map(|m| (m.pattern.as_str(), m.timestamp.format("%H:%M:%S%.3f").to_string()))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("request", "10:00:00.500".to_string()),
+                ("request", "10:00:01.000".to_string()),
+                ("response", "10:00:01.200".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sources_without_shared_anchors_are_left_unchanged() {
+        let sources: Vec<String> = ["web-1.log", "web-2.log"].map(String::from).to_vec();
+        let matches = vec![
+            log_match("epoch", "web-2.log", "10:00:01.500 2025-11-13"),
+            log_match("request", "web-1.log", "10:00:01.000 2025-11-13"),
+            log_match("response", "web-2.log", "10:00:02.700 2025-11-13"),
+        ];
+
+        // The reference never logged the anchor, and an unknown anchor matches nothing
+        for anchors in [vec!["epoch".to_string()], vec!["restart".to_string()]] {
+            let offsets = calibrate(&matches, &sources, &anchors);
+            assert_eq!(offsets.sources[0], SourceOffset { source: "web-2.log".to_string(), offset: None, anchors: 0 });
+            assert!(offsets.format_report().ends_with("web-2.log: no anchor in common, left unchanged"));
+            let times: Vec<_> = offsets.apply(matches.clone(), &anchors).iter().map(|m| m.timestamp).collect();
+            assert!(times.iter().all(|time| matches.iter().any(|m| m.timestamp == *time)));
+        }
+
+        // Without sources there is no reference to align to
+        let offsets = calibrate(&matches, &[], &["epoch".to_string()]);
+        assert_eq!(offsets, ClockOffsets { reference: String::new(), sources: Vec::new() });
+    }
+}
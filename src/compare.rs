@@ -0,0 +1,487 @@
+/// Per-transition changes between a baseline run and the analyzed log (`compare`)
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use log_time_analyzer::analyzer::Interval;
+use log_time_analyzer::stats::{self, human_ms};
+
+/// Standard normal quantile of a two-sided 95% confidence interval
+const Z_95: f64 = 1.959964;
+
+/// Timings of one transition (and `--group-by` group) in one run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timings {
+    pub from_pattern: String,
+    pub to_pattern: String,
+    pub group: Option<String>,
+    pub count: usize,
+    pub mean_ms: f64,
+    pub p95_ms: f64,
+    /// Population standard deviation
+    pub stddev_ms: f64,
+    /// Durations in ascending order; empty for a saved `--stats` run
+    pub sorted_ms: Vec<i64>,
+}
+
+impl Timings {
+    /// Timings of every transition of the intervals, in order of first appearance
+    pub fn of(intervals: &[Interval]) -> Vec<Timings> {
+        stats::compute(intervals, None)
+            .into_iter()
+            .map(|s| Timings {
+                count: s.count,
+                mean_ms: s.mean_ms,
+                p95_ms: s.percentile(95.0),
+                stddev_ms: s.stddev_ms,
+                sorted_ms: s.sorted_ms,
+                from_pattern: s.from_pattern,
+                to_pattern: s.to_pattern,
+                group: s.group,
+            })
+            .collect()
+    }
+
+    fn key(&self) -> (&str, &str, Option<&str>) {
+        (&self.from_pattern, &self.to_pattern, self.group.as_deref())
+    }
+
+    /// Half-width of the 95% confidence interval of the mean; `None` for a single interval
+    pub fn mean_margin(&self) -> Option<f64> {
+        (self.count > 1).then(|| Z_95 * self.sample_variance().sqrt() / (self.count as f64).sqrt())
+    }
+
+    fn sample_variance(&self) -> f64 {
+        let n = self.count as f64;
+        self.stddev_ms.powi(2) * n / (n - 1.0)
+    }
+
+    fn transition(&self) -> String {
+        match &self.group {
+            Some(group) => format!("{} -> {} ({})", self.from_pattern, self.to_pattern, group),
+            None => format!("{} -> {}", self.from_pattern, self.to_pattern),
+        }
+    }
+}
+
+/// A record of a saved `-f json` run: an interval, or a `--stats` row
+#[derive(Debug, Deserialize)]
+struct SavedRecord {
+    from_pattern: String,
+    to_pattern: String,
+    #[serde(default)]
+    group: Option<String>,
+    duration_ms: Option<i64>,
+    count: Option<usize>,
+    mean_ms: Option<f64>,
+    p95_ms: Option<f64>,
+    stddev_ms: Option<f64>,
+    /// Captured fields of intervals (`from_<name>`, `to_<name>`), among other keys
+    #[serde(flatten)]
+    other: HashMap<String, Value>,
+}
+
+/// Timings from a saved run: the `-f json` intervals (an array, a page object, or JSON Lines)
+/// or `--stats -f json` rows. `None` when the text isn't such output, e.g. a log to analyze
+/// (JSON logs included). Saved intervals are grouped by their `group_by` capture, unless
+/// they were grouped already.
+pub fn parse_saved(text: &str, group_by: Option<&str>) -> Result<Option<Vec<Timings>>> {
+    let values: Vec<Value> = match serde_json::from_str(text) {
+        Ok(Value::Array(values)) => values,
+        // A `--page-size` page
+        Ok(Value::Object(mut page)) if page.contains_key("intervals") => match page.remove("intervals") {
+            Some(Value::Array(values)) => values,
+            _ => return Ok(None),
+        },
+        Ok(value) => vec![value],
+        Err(_) => match text.lines().filter(|line| !line.trim().is_empty()).map(serde_json::from_str).collect() {
+            Ok(lines) => lines,
+            Err(_) => return Ok(None),
+        },
+    };
+    if values.first().is_none_or(|value| value.get("from_pattern").is_none()) {
+        return Ok(None);
+    }
+    let records: Vec<SavedRecord> = values
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<std::result::Result<_, _>>()
+        .context("Invalid baseline JSON")?;
+
+    let intervals: Vec<Interval> = records
+        .iter()
+        .filter_map(|record| {
+            let mut interval = Interval {
+                from_pattern: record.from_pattern.clone(),
+                to_pattern: record.to_pattern.clone(),
+                group: record.group.clone(),
+                duration: chrono::Duration::milliseconds(record.duration_ms?),
+                ..Default::default()
+            };
+            for (key, value) in &record.other {
+                let Value::String(value) = value else {
+                    continue;
+                };
+                if let Some(name) = key.strip_prefix("from_") {
+                    interval.from_fields.insert(name.to_string(), value.clone());
+                } else if let Some(name) = key.strip_prefix("to_") {
+                    interval.to_fields.insert(name.to_string(), value.clone());
+                }
+            }
+            if let (None, Some(capture)) = (&interval.group, group_by) {
+                interval.group_by(capture);
+            }
+            Some(interval)
+        })
+        .collect();
+    if !intervals.is_empty() {
+        return Ok(Some(Timings::of(&intervals)));
+    }
+
+    records
+        .into_iter()
+        .map(|record| match (record.count, record.mean_ms, record.p95_ms, record.stddev_ms) {
+            (Some(count), Some(mean_ms), Some(p95_ms), Some(stddev_ms)) => Ok(Timings {
+                from_pattern: record.from_pattern,
+                to_pattern: record.to_pattern,
+                group: record.group,
+                count,
+                mean_ms,
+                p95_ms,
+                stddev_ms,
+                sorted_ms: Vec::new(),
+            }),
+            _ => anyhow::bail!(
+                "Baseline JSON records need duration_ms (intervals) or count, mean_ms, p95_ms, and stddev_ms (--stats)"
+            ),
+        })
+        .collect::<Result<_>>()
+        .map(Some)
+}
+
+/// Parse a regression threshold such as `10%` or `10` (percent)
+pub fn parse_threshold(input: &str) -> Result<f64> {
+    let value: f64 = input
+        .trim()
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid threshold '{}', expected a percentage such as 10%", input))?;
+    if !(value.is_finite() && value >= 0.0) {
+        anyhow::bail!("The threshold must be a percentage of at least 0, got '{}'", input);
+    }
+    Ok(value)
+}
+
+/// Parse a significance level such as `0.05` or `5%`: the chance of a slowdown being mere
+/// noise below which it counts as a regression
+pub fn parse_significance(input: &str) -> Result<f64> {
+    let text = input.trim();
+    let value: f64 = match text.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|p| p / 100.0),
+        None => text.parse(),
+    }
+    .map_err(|_| anyhow::anyhow!("Invalid significance level '{}', expected a probability such as 0.05", input))?;
+    if !(value > 0.0 && value <= 1.0) {
+        anyhow::bail!("The significance level must be above 0 and at most 1, got '{}'", input);
+    }
+    Ok(value)
+}
+
+/// One transition in the baseline, the candidate, or both
+#[derive(Debug)]
+pub struct Change {
+    pub baseline: Option<Timings>,
+    pub candidate: Option<Timings>,
+}
+
+impl Change {
+    /// Percentage change of the mean and p95; `None` without both runs or from a zero baseline
+    pub fn percent(&self) -> Option<(Option<f64>, Option<f64>)> {
+        let (baseline, candidate) = (self.baseline.as_ref()?, self.candidate.as_ref()?);
+        let change = |before: f64, after: f64| (before > 0.0).then(|| (after - before) / before * 100.0);
+        Some((change(baseline.mean_ms, candidate.mean_ms), change(baseline.p95_ms, candidate.p95_ms)))
+    }
+
+    /// Whether the mean or p95 got slower by more than `threshold` percent
+    pub fn slower(&self, threshold: f64) -> bool {
+        match self.percent() {
+            Some((mean, p95)) => [mean, p95].into_iter().flatten().any(|change| change > threshold),
+            None => false,
+        }
+    }
+
+    /// One-sided p-value of the candidate being slower than the baseline: a Mann-Whitney U
+    /// test when both runs have their durations, otherwise Welch's test of the means. `None`
+    /// without both runs or with a single interval in one of them.
+    pub fn p_value(&self) -> Option<f64> {
+        let (baseline, candidate) = (self.baseline.as_ref()?, self.candidate.as_ref()?);
+        if baseline.count < 2 || candidate.count < 2 {
+            return None;
+        }
+        let z = if !baseline.sorted_ms.is_empty() && !candidate.sorted_ms.is_empty() {
+            mann_whitney_z(&baseline.sorted_ms, &candidate.sorted_ms)
+        } else {
+            let error = (baseline.sample_variance() / baseline.count as f64
+                + candidate.sample_variance() / candidate.count as f64)
+                .sqrt();
+            match candidate.mean_ms - baseline.mean_ms {
+                difference if error > 0.0 => difference / error,
+                difference if difference > 0.0 => f64::INFINITY,
+                difference if difference < 0.0 => f64::NEG_INFINITY,
+                _ => 0.0,
+            }
+        };
+        Some(normal_sf(z))
+    }
+
+    /// Slower by more than `threshold` percent, and unlikely to be noise at the `significance`
+    /// level
+    pub fn regressed(&self, threshold: f64, significance: f64) -> bool {
+        self.slower(threshold) && self.p_value().is_some_and(|p| p < significance)
+    }
+}
+
+/// Normal approximation of the Mann-Whitney U statistic of `after` exceeding `before`, with
+/// continuity and tie corrections. Both slices are sorted.
+fn mann_whitney_z(before: &[i64], after: &[i64]) -> f64 {
+    let (n1, n2) = (before.len() as f64, after.len() as f64);
+    let mut merged: Vec<(i64, bool)> =
+        before.iter().map(|&ms| (ms, false)).chain(after.iter().map(|&ms| (ms, true))).collect();
+    merged.sort_unstable();
+
+    // Sum of the (average, for ties) ranks of `after`, and the tie correction term
+    let (mut rank_sum, mut ties) = (0.0, 0.0);
+    let mut start = 0;
+    while start < merged.len() {
+        let end = start + merged[start..].iter().take_while(|(ms, _)| *ms == merged[start].0).count();
+        let tied = (end - start) as f64;
+        let rank = (start + end + 1) as f64 / 2.0;
+        rank_sum += rank * merged[start..end].iter().filter(|(_, is_after)| *is_after).count() as f64;
+        ties += tied.powi(3) - tied;
+        start = end;
+    }
+
+    let u = rank_sum - n2 * (n2 + 1.0) / 2.0;
+    let n = n1 + n2;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return 0.0;
+    }
+    let deviation = u - n1 * n2 / 2.0;
+    (deviation.abs() - 0.5).max(0.0).copysign(deviation) / variance.sqrt()
+}
+
+/// Probability of a standard normal value above `z`
+fn normal_sf(z: f64) -> f64 {
+    0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function, to a fractional error below 1.2e-7 (Numerical Recipes)
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.5 * x.abs());
+    let polynomial = -x * x - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let value = t * polynomial.exp();
+    if x >= 0.0 {
+        value
+    } else {
+        2.0 - value
+    }
+}
+
+/// Pair up the transitions of both runs: the candidate's in order, then those only in the
+/// baseline
+pub fn compare(baseline: &[Timings], candidate: &[Timings]) -> Vec<Change> {
+    let mut baseline_by_key: HashMap<_, &Timings> = baseline.iter().map(|t| (t.key(), t)).collect();
+    let mut changes: Vec<Change> = candidate
+        .iter()
+        .map(|t| Change {
+            baseline: baseline_by_key.remove(&t.key()).cloned(),
+            candidate: Some(t.clone()),
+        })
+        .collect();
+    changes.extend(
+        baseline
+            .iter()
+            .filter(|t| baseline_by_key.contains_key(&t.key()))
+            .map(|t| Change {
+                baseline: Some(t.clone()),
+                candidate: None,
+            }),
+    );
+    changes
+}
+
+/// A line per transition with the mean (and its 95% confidence interval) and p95 of both runs,
+/// their change and its p-value, regressions marked, and a summary. Slowdowns that may be noise
+/// are marked `noise`.
+pub fn format_report(changes: &[Change], threshold: f64, significance: f64) -> String {
+    let percent = |change: Option<f64>| change.map(|c| format!(" ({:+.1}%)", c)).unwrap_or_default();
+    let mean = |timings: &Timings| match timings.mean_margin() {
+        Some(margin) => format!("{} ±{}", human_ms(timings.mean_ms), human_ms(margin)),
+        None => human_ms(timings.mean_ms),
+    };
+    let mut output = String::new();
+
+    for change in changes {
+        let line = match (&change.baseline, &change.candidate) {
+            (Some(before), Some(after)) => {
+                let (mean_change, p95_change) = change.percent().unwrap_or_default();
+                let status = if change.regressed(threshold, significance) {
+                    "REGRESSED"
+                } else if change.slower(threshold) {
+                    "noise"
+                } else {
+                    "ok"
+                };
+                let p_value = match change.p_value() {
+                    Some(p) if p < 0.001 => ", p<0.001".to_string(),
+                    Some(p) => format!(", p={:.3}", p),
+                    None => ", too few intervals to test".to_string(),
+                };
+                format!(
+                    "{:<9} {}: mean {} -> {}{}, p95 {} -> {}{}, n {} -> {}{}",
+                    status,
+                    after.transition(),
+                    mean(before),
+                    mean(after),
+                    percent(mean_change),
+                    human_ms(before.p95_ms),
+                    human_ms(after.p95_ms),
+                    percent(p95_change),
+                    before.count,
+                    after.count,
+                    p_value
+                )
+            }
+            (None, Some(after)) => format!(
+                "{:<9} {}: mean {}, p95 {}, n {} (not in baseline)",
+                "new",
+                after.transition(),
+                mean(after),
+                human_ms(after.p95_ms),
+                after.count
+            ),
+            (Some(before), None) => format!("{:<9} {}: (not in candidate)", "missing", before.transition()),
+            (None, None) => continue,
+        };
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    let compared = changes.iter().filter(|change| change.percent().is_some()).count();
+    let regressed = changes.iter().filter(|change| change.regressed(threshold, significance)).count();
+    let noise = changes.iter().filter(|change| change.slower(threshold)).count() - regressed;
+    output.push_str(&format!(
+        "\n{} of {} transitions in both runs regressed by more than {}% in mean or p95 (p < {})",
+        regressed, compared, threshold, significance
+    ));
+    if noise > 0 {
+        output.push_str(&format!(", {} more slowed down but not significantly", noise));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log_time_analyzer::stats::percentile;
+
+    fn timings(from: &str, to: &str, ms: &[i64]) -> Timings {
+        let mut sorted = ms.to_vec();
+        sorted.sort_unstable();
+        let mean_ms = ms.iter().sum::<i64>() as f64 / ms.len() as f64;
+        Timings {
+            from_pattern: from.to_string(),
+            to_pattern: to.to_string(),
+            group: None,
+            count: ms.len(),
+            mean_ms,
+            p95_ms: percentile(&sorted, 95.0),
+            stddev_ms: (ms.iter().map(|&d| (d as f64 - mean_ms).powi(2)).sum::<f64>() / ms.len() as f64).sqrt(),
+            sorted_ms: sorted,
+        }
+    }
+
+    #[test]
+    fn test_regressions_over_threshold() {
+        let baseline = vec![
+            timings("A", "B", &[100, 100, 100, 100, 100, 100]),
+            timings("B", "C", &[50]),
+            timings("C", "D", &[10]),
+        ];
+        let candidate = vec![
+            timings("A", "B", &[150, 150, 150, 150, 150, 150]),
+            timings("B", "C", &[52]),
+            timings("D", "E", &[5]),
+        ];
+
+        let changes = compare(&baseline, &candidate);
+        assert_eq!(changes.len(), 4);
+        assert!(changes[0].regressed(10.0, 0.05));
+        assert!(!changes[1].regressed(10.0, 0.05));
+        let report = format_report(&changes, 10.0, 0.05);
+        assert!(
+            report.contains("REGRESSED A -> B: mean 100ms ±0ms -> 150ms ±0ms (+50.0%), p95 100ms -> 150ms (+50.0%), n 6 -> 6, p<0.001"),
+            "{}",
+            report
+        );
+        assert!(report.contains("ok        B -> C: mean 50ms -> 52ms (+4.0%), p95 50ms -> 52ms (+4.0%), n 1 -> 1, too few intervals to test"), "{}", report);
+        assert!(report.contains("new       D -> E"), "{}", report);
+        assert!(report.contains("missing   C -> D: (not in candidate)"), "{}", report);
+        assert!(report.ends_with("1 of 2 transitions in both runs regressed by more than 10% in mean or p95 (p < 0.05)"));
+    }
+
+    #[test]
+    fn test_noisy_slowdowns_are_not_regressions() {
+        let noisy = Change {
+            baseline: Some(timings("A", "B", &[100, 400, 120, 380, 90])),
+            candidate: Some(timings("A", "B", &[160, 420, 130, 400, 150])),
+        };
+        assert!(noisy.slower(10.0));
+        assert!(noisy.p_value().unwrap() > 0.05);
+        assert!(!noisy.regressed(10.0, 0.05));
+        assert!(format_report(&[noisy], 10.0, 0.05).starts_with("noise     A -> B: mean 218ms ±"));
+
+        // Saved statistics have no durations, so their means are compared
+        let mut baseline = timings("A", "B", &[100, 110, 90, 105, 95]);
+        baseline.sorted_ms.clear();
+        let slower = Change {
+            baseline: Some(baseline),
+            candidate: Some(timings("A", "B", &[150, 160, 140, 155, 145])),
+        };
+        assert!(slower.p_value().unwrap() < 0.001);
+
+        assert!((normal_sf(1.959964) - 0.025).abs() < 1e-6);
+        assert_eq!(parse_significance("5%").unwrap(), 0.05);
+        assert!(parse_significance("0").is_err());
+    }
+
+    #[test]
+    fn test_saved_baselines() {
+        let intervals = r#"[{"from_pattern": "A", "to_pattern": "B", "duration_ms": 100},
+                            {"from_pattern": "A", "to_pattern": "B", "duration_ms": 300}]"#;
+        assert_eq!(parse_saved(intervals, None).unwrap().unwrap(), vec![timings("A", "B", &[100, 300])]);
+
+        let lines = "{\"from_pattern\": \"A\", \"to_pattern\": \"B\", \"duration_ms\": 100}\n{\"from_pattern\": \"A\", \"to_pattern\": \"B\", \"duration_ms\": 300}\n";
+        assert_eq!(parse_saved(lines, None).unwrap().unwrap()[0].mean_ms, 200.0);
+
+        let captured = r#"[{"from_pattern": "A", "to_pattern": "B", "duration_ms": 100, "from_path": "/a"},
+                           {"from_pattern": "A", "to_pattern": "B", "duration_ms": 300, "to_path": "/b"}]"#;
+        let groups: Vec<Option<String>> =
+            parse_saved(captured, Some("path")).unwrap().unwrap().into_iter().map(|t| t.group).collect();
+        assert_eq!(groups, vec![Some("/a".to_string()), Some("/b".to_string())]);
+
+        let stats = r#"[{"from_pattern": "A", "to_pattern": "B", "count": 2, "mean_ms": 200.0, "p95_ms": 290.0, "stddev_ms": 100.0}]"#;
+        assert_eq!(parse_saved(stats, None).unwrap().unwrap()[0].p95_ms, 290.0);
+
+        assert!(parse_saved("2025-11-13 10:00:00 start", None).unwrap().is_none());
+        assert!(parse_saved("{\"ts\": \"2025-11-13 10:00:00\", \"msg\": \"start\"}", None).unwrap().is_none());
+        assert!(parse_saved(r#"[{"from_pattern": "A", "to_pattern": "B"}]"#, None).is_err());
+    }
+}
@@ -1,32 +1,433 @@
-use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+use crate::duration::parse_duration;
+use crate::error::{LogLineError, Result};
+use crate::expr::Expr;
+use crate::maintenance::Span;
+use crate::style::DurationColors;
+use crate::tags::Condition;
+
+/// Analysis settings, loaded from YAML (`Config::from_file`) or built with `Config::builder`
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct Config {
-    /// Regular expression to extract timestamps from log lines
+    /// Regular expression to extract timestamps from log lines (may be left out, with
+    /// `timestamp_format`, when every timestamp comes from the file names)
+    #[serde(default)]
     pub timestamp_regex: String,
     
     /// Format string for parsing timestamps (chrono format)
+    #[serde(default)]
     pub timestamp_format: String,
     
-    /// Array of message patterns to search for in order
-    pub message_patterns: Vec<String>,
+    /// Array of message patterns to search for in order: regexes, or `{ name, regex }` entries
+    /// whose name is shown in the output instead of the regex
+    pub message_patterns: Vec<MessagePattern>,
+    
+    /// Lines matching any of these regexes are skipped before timestamp/pattern matching
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_patterns: Vec<String>,
+    
+    /// Lines longer than this many bytes are skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_line_length: Option<usize>,
+    
+    /// Only accept timestamps at the start of the line (after an optional BOM,
+    /// whitespace, or ANSI color codes) so date-like text in messages is ignored
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub anchor_timestamp: bool,
+    
+    /// Remove ANSI color/escape sequences from lines before timestamp and pattern matching
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strip_ansi: bool,
+    
+    /// Keep trying every built-in format on every line in auto-detection mode, for logs that
+    /// mix timestamp formats, instead of settling on the format that keeps parsing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mixed_timestamp_formats: bool,
+    
+    /// Read entries that span several lines, such as stack traces: a line without a timestamp
+    /// continues the entry before it, and patterns are matched against the whole entry
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multiline: bool,
+    
+    /// Regex for the lines that continue the entry before them (e.g. `^\s+at `) instead of
+    /// every line without a timestamp; setting it turns `multiline` on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continuation_regex: Option<String>,
+    
+    /// Drop intervals shorter than this duration from the output (e.g. "50ms")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_duration: Option<String>,
+    
+    /// Drop the first this many intervals of every transition, e.g. the warm-up iterations
+    /// of a benchmark
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_iterations: Option<usize>,
+    
+    /// Drop the intervals starting within this duration of the first match (e.g. "30s")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_duration: Option<String>,
+    
+    /// Regex over each log file's name whose `timestamp` group (or group 1) holds a base time,
+    /// e.g. `app-(\d{8}-\d{6})\.log`; lines without a timestamp of their own get this time
+    /// plus their `offset_regex` offset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename_timestamp_regex: Option<String>,
+    
+    /// Format of the file name's base time (chrono format), e.g. `%Y%m%d-%H%M%S`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename_timestamp_format: Option<String>,
+    
+    /// Regex whose `offset` group (or group 1) holds a line's time since the file name's base
+    /// time: seconds (`12.5`) or a duration (`250ms`); lines without one keep the last offset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset_regex: Option<String>,
+    
+    /// Regex applied repeatedly across each line to find events embedded with timestamps of
+    /// their own, e.g. `(?P<label>\w+)=(?P<timestamp>\d{2}:\d{2}:\d{2}\.\d{3})` for
+    /// `queued=10:00:00.100 started=10:00:00.350`; every occurrence becomes a match named by
+    /// its `label` group, and other named groups become its fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedded_event_regex: Option<String>,
+    
+    /// Format of the embedded events' timestamps (chrono format); defaults to `timestamp_format`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedded_timestamp_format: Option<String>,
+    
+    /// Fail the run (non-zero exit) when any interval is longer than this duration, e.g. for
+    /// CI gates on startup time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_if_over: Option<String>,
+    
+    /// Durations from which the human, table, and waterfall formats color a duration yellow
+    /// and red when colors are on, e.g. `500ms,2s` (default `1s,5s`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_thresholds: Option<String>,
+    
+    /// Built-in timestamp format to try first in auto-detection mode (name or unique part of it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefer_format: Option<String>,
+    
+    /// Per-transition settings keyed on the (from, to) pattern pair
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transitions: Vec<TransitionRule>,
+    
+    /// Explicit start/end pairs: when set, intervals are measured from each start to its
+    /// matching end instead of between consecutive matches
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pairs: Vec<PairRule>,
+    
+    /// Message patterns (by name, or regex when unnamed) logged at the same moment on every
+    /// host, e.g. a broadcast; when several logs are merged, each log's clock offset from the
+    /// first is computed from them and applied, and their matches are then left out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clock_anchors: Vec<String>,
+    
+    /// Known-noisy periods such as maintenance or backups; intervals overlapping one are
+    /// dropped, or kept and tagged but left out of statistics and SLOs (see `ExcludedWindow`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_windows: Vec<ExcludedWindow>,
+    
+    /// Regex whose `correlation` group (or group 1) extracts a request ID from each matched
+    /// line; intervals are then only measured between matches sharing the same ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_regex: Option<String>,
+    
+    /// Values computed from each interval, e.g. `duration_ms / extracted.items`; they are
+    /// added to the output as extra fields/columns
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub derived: Vec<DerivedValue>,
+    
+    /// Duration the server reported for the request that ends each interval, such as the
+    /// `time-taken` field of IIS/W3C logs, to compare with the observed interval
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reported_duration: Option<ReportedDuration>,
+    
+    /// Rules that tag intervals meeting a condition, e.g. `slow` if `duration > 1s`; tags
+    /// appear in every output and can be filtered on with `--tag`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<TagRule>,
+    
+    /// How each log line is read: plain text, or one JSON object per line
+    #[serde(default, skip_serializing_if = "InputFormat::is_text")]
+    pub input_format: InputFormat,
+    
+    /// Dot-separated path of the timestamp field in JSON input, e.g. `meta.ts`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_field: Option<String>,
+    
+    /// Dot-separated path of the field message patterns are matched against in JSON input
+    /// (the whole line when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_field: Option<String>,
     
     /// Whether this config is for auto-detection mode
     #[serde(skip)]
     pub is_auto_detect: bool,
 }
 
+/// A named value computed per interval with an expression (see `crate::expr::Expr`)
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct DerivedValue {
+    /// Name of the output field/column
+    pub name: String,
+    
+    /// Expression over `duration_ms`, `from.<field>`, `to.<field>`, `extracted.<field>`, and
+    /// earlier derived values
+    pub expr: String,
+}
+
+/// A duration carried by the log lines themselves, captured by the message patterns
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct ReportedDuration {
+    /// Named capture group holding the number, e.g. `time_taken`
+    pub field: String,
+    
+    /// Unit of the number: `us`, `ms` (the default, as IIS logs it), or `s`
+    #[serde(default = "ReportedDuration::default_unit")]
+    pub unit: String,
+}
+
+impl ReportedDuration {
+    /// Derived values added for a reported duration: the reported time of the ending match,
+    /// and how much of the observed interval it leaves unexplained
+    pub const VALUES: [&'static str; 2] = ["reported_ms", "unaccounted_ms"];
+    
+    fn default_unit() -> String {
+        "ms".to_string()
+    }
+    
+    /// Parse `FIELD` or `FIELD:UNIT`
+    pub fn parse(spec: &str) -> Self {
+        match spec.split_once(':') {
+            Some((field, unit)) => ReportedDuration {
+                field: field.to_string(),
+                unit: unit.to_string(),
+            },
+            None => ReportedDuration {
+                field: spec.to_string(),
+                unit: Self::default_unit(),
+            },
+        }
+    }
+    
+    /// Expressions computing `VALUES`
+    fn derived(&self) -> Result<[DerivedValue; 2]> {
+        let scale = match self.unit.as_str() {
+            "us" => " / 1000",
+            "ms" => "",
+            "s" => " * 1000",
+            other => {
+                return Err(LogLineError::config(format!(
+                    "Invalid reported_duration unit '{}'. Valid options: us, ms, s",
+                    other
+                )))
+            }
+        };
+        let [reported, unaccounted] = Self::VALUES;
+        Ok([
+            DerivedValue {
+                name: reported.to_string(),
+                expr: format!("to.{}{}", self.field, scale),
+            },
+            DerivedValue {
+                name: unaccounted.to_string(),
+                expr: format!("duration_ms - {}", reported),
+            },
+        ])
+    }
+}
+
+/// A message pattern: the regex to match, and optionally a human-friendly name that matches
+/// (and so intervals, pairs, and transitions) go by instead of the regex text
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[serde(from = "PatternEntry", into = "PatternEntry")]
+pub struct MessagePattern {
+    pub name: Option<String>,
+    pub regex: String,
+}
+
+/// YAML form of a message pattern: a plain regex or a `{ name, regex }` map
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum PatternEntry {
+    Regex(String),
+    Named {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        regex: String,
+    },
+}
+
+impl MessagePattern {
+    /// A pattern with a display name
+    pub fn named(name: impl Into<String>, regex: impl Into<String>) -> Self {
+        MessagePattern {
+            name: Some(name.into()),
+            regex: regex.into(),
+        }
+    }
+    
+    /// Parse the CLI form: `NAME=REGEX`, or a bare regex. Everything up to the first `=` is
+    /// the name, so a regex containing `=` needs a name or a leading `=` (`=status=5\d\d`).
+    pub fn parse(spec: &str) -> Self {
+        match spec.split_once('=') {
+            Some(("", regex)) => MessagePattern::from(regex),
+            Some((name, regex)) => MessagePattern::named(name.trim(), regex),
+            None => MessagePattern::from(spec),
+        }
+    }
+    
+    /// What matches of this pattern are called: its name, or else the regex itself
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.regex)
+    }
+}
+
+impl From<&str> for MessagePattern {
+    fn from(regex: &str) -> Self {
+        MessagePattern::from(regex.to_string())
+    }
+}
+
+impl From<String> for MessagePattern {
+    fn from(regex: String) -> Self {
+        MessagePattern { name: None, regex }
+    }
+}
+
+impl From<PatternEntry> for MessagePattern {
+    fn from(entry: PatternEntry) -> Self {
+        match entry {
+            PatternEntry::Regex(regex) => MessagePattern::from(regex),
+            PatternEntry::Named { name, regex } => MessagePattern { name, regex },
+        }
+    }
+}
+
+impl From<MessagePattern> for PatternEntry {
+    fn from(pattern: MessagePattern) -> Self {
+        match pattern.name {
+            Some(name) => PatternEntry::Named { name: Some(name), regex: pattern.regex },
+            None => PatternEntry::Regex(pattern.regex),
+        }
+    }
+}
+
+/// A tag added to every interval whose condition holds (see `crate::tags::Condition`)
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct TagRule {
+    /// The tag, e.g. `slow`
+    pub tag: String,
+    
+    /// Clauses joined by `and`, such as `duration > 1s` or `from matches /checkout/`
+    #[serde(rename = "if")]
+    pub condition: String,
+}
+
+/// A period whose intervals don't count: between two timestamps (`2025-12-24 00:00`), or
+/// every day between two times of day (`02:00` to `03:30`, which may wrap past midnight)
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct ExcludedWindow {
+    /// Name of the window, e.g. `nightly-backup`; annotated intervals get it as a tag
+    pub name: String,
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub action: WindowAction,
+}
+
+/// What happens to the intervals overlapping an excluded window
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowAction {
+    /// Left out of every output
+    #[default]
+    Drop,
+    /// Listed with the window's name as a tag, but left out of statistics and SLOs
+    Annotate,
+}
+
+/// Layout of the log lines
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFormat {
+    /// Plain text lines; the timestamp and patterns are matched against the whole line
+    #[default]
+    Text,
+    /// JSON lines; the timestamp and message are read from fields of each object
+    Json,
+}
+
+impl InputFormat {
+    pub const ALL: [InputFormat; 2] = [InputFormat::Text, InputFormat::Json];
+    
+    /// Name of the format in the config (`input_format`) and on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            InputFormat::Text => "text",
+            InputFormat::Json => "json",
+        }
+    }
+    
+    fn is_text(&self) -> bool {
+        *self == InputFormat::Text
+    }
+}
+
+impl std::str::FromStr for InputFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, ()> {
+        let s = s.to_lowercase();
+        Self::ALL.into_iter().find(|format| format.name() == s).ok_or(())
+    }
+}
+
+/// Settings that apply to a single transition between two patterns
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct TransitionRule {
+    /// Pattern the interval starts at
+    pub from: String,
+    
+    /// Pattern the interval ends at
+    pub to: String,
+    
+    /// Drop intervals of this transition shorter than this duration (overrides `min_duration`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_duration: Option<String>,
+    
+    /// Fail the run when an interval of this transition is longer than this duration
+    /// (overrides `fail_if_over`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_if_over: Option<String>,
+    
+    /// Longest an interval of this transition should take; the `sla` command lists every
+    /// interval over it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_max: Option<String>,
+}
+
+/// A start/end pair measured in pair mode
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct PairRule {
+    /// Pattern (or captured label) that opens the interval
+    pub start: String,
+    
+    /// Pattern (or captured label) that closes it
+    pub end: String,
+}
+
 impl Config {
     /// Load configuration from a YAML file
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let contents = fs::read_to_string(path.as_ref())
-            .with_context(|| format!("Failed to read config file: {:?}", path.as_ref()))?;
+            .map_err(|e| LogLineError::io(format!("Failed to read config file: {:?}", path.as_ref()), e))?;
         
         let mut config: Config = serde_yaml::from_str(&contents)
-            .context("Failed to parse YAML configuration")?;
+            .map_err(|e| LogLineError::config_caused_by("Failed to parse YAML configuration", e))?;
         
         config.is_auto_detect = false;
         config.validate()?;
@@ -35,17 +436,16 @@ impl Config {
     }
     
     /// Create a config for auto-detection mode
-    pub fn for_auto_detection(message_patterns: Vec<String>) -> Result<Self> {
+    pub fn for_auto_detection(message_patterns: Vec<MessagePattern>) -> Result<Self> {
         let config = Config {
-            timestamp_regex: String::new(),
-            timestamp_format: String::new(),
             message_patterns,
             is_auto_detect: true,
+            ..Default::default()
         };
         
         // Only validate message patterns for auto-detection
         if config.message_patterns.len() < 2 {
-            anyhow::bail!("Configuration must have at least 2 message patterns");
+            return Err(LogLineError::config("Configuration must have at least 2 message patterns"));
         }
         
         Ok(config)
@@ -56,10 +456,10 @@ impl Config {
         path: Option<&Path>,
         timestamp_regex: Option<String>,
         timestamp_format: Option<String>,
-        message_patterns: Option<Vec<String>>,
+        message_patterns: Option<Vec<MessagePattern>>,
     ) -> Result<Self> {
         // Start with config file if provided
-        let mut config = if let Some(path) = path {
+        let config = if let Some(path) = path {
             Config::from_file(path)?
         } else {
             // If no config file, check if we can use auto-detection
@@ -71,62 +471,268 @@ impl Config {
                 }
                 // User provided some timestamp config but not all
                 if timestamp_regex.is_none() || timestamp_format.is_none() {
-                    anyhow::bail!(
+                    return Err(LogLineError::config(
                         "When providing timestamp configuration, both --timestamp-regex and --timestamp-format are required"
-                    );
-                }
-                Config {
-                    timestamp_regex: String::new(),
-                    timestamp_format: String::new(),
-                    message_patterns: Vec::new(),
-                    is_auto_detect: false,
+                    ));
                 }
+                Config::default()
             } else {
-                anyhow::bail!(
+                return Err(LogLineError::config(
                     "When no config file is provided, at least 2 --pattern arguments must be specified.\n\
                      Timestamp format will be auto-detected, or you can manually specify:\n\
                      --timestamp-regex and --timestamp-format"
-                );
+                ));
             }
         };
         
-        // Apply CLI overrides
+        config.with_overrides(timestamp_regex, timestamp_format, message_patterns)
+    }
+    
+    /// Apply CLI overrides on top of this configuration (a file or a built-in profile) and validate
+    pub fn with_overrides(
+        mut self,
+        timestamp_regex: Option<String>,
+        timestamp_format: Option<String>,
+        message_patterns: Option<Vec<MessagePattern>>,
+    ) -> Result<Self> {
         if let Some(regex) = timestamp_regex {
-            config.timestamp_regex = regex;
-            config.is_auto_detect = false;
+            self.timestamp_regex = regex;
+            self.is_auto_detect = false;
         }
         
         if let Some(format) = timestamp_format {
-            config.timestamp_format = format;
-            config.is_auto_detect = false;
+            self.timestamp_format = format;
+            self.is_auto_detect = false;
         }
         
         if let Some(patterns) = message_patterns {
             if !patterns.is_empty() {
-                config.message_patterns = patterns;
+                self.message_patterns = patterns;
             }
         }
         
-        config.validate()?;
+        self.validate()?;
         
-        Ok(config)
+        Ok(self)
     }
     
     /// Validate configuration
-    fn validate(&self) -> Result<()> {
+    /// The `derived` values with those of `reported_duration` first, in evaluation order
+    pub fn derived_values(&self) -> Result<Vec<DerivedValue>> {
+        let mut values = match &self.reported_duration {
+            Some(reported) => reported.derived()?.to_vec(),
+            None => Vec::new(),
+        };
+        values.extend(self.derived.iter().cloned());
+        Ok(values)
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        match (&self.filename_timestamp_regex, &self.filename_timestamp_format) {
+            (Some(_), None) | (None, Some(_)) => {
+                return Err(LogLineError::config(
+                    "filename_timestamp_regex and filename_timestamp_format must be given together",
+                ));
+            }
+            (Some(_), Some(_)) if self.input_format != InputFormat::Text => {
+                return Err(LogLineError::config("Timestamps from file names only apply to text input"));
+            }
+            (None, None) if self.offset_regex.is_some() => {
+                return Err(LogLineError::config(
+                    "offset_regex needs a base time from filename_timestamp_regex and filename_timestamp_format",
+                ));
+            }
+            _ => {}
+        }
+        
+        // Lines may have no timestamps at all when the file names or embedded events provide them
+        let without_line_timestamps = (self.filename_timestamp_regex.is_some()
+            || self.embedded_event_regex.is_some())
+            && self.timestamp_regex.is_empty()
+            && self.timestamp_format.is_empty();
+        
         // Skip timestamp validation for auto-detection mode
-        if !self.is_auto_detect {
+        if !self.is_auto_detect && !without_line_timestamps {
             if self.timestamp_regex.is_empty() {
-                anyhow::bail!("timestamp_regex cannot be empty");
+                return Err(LogLineError::config("timestamp_regex cannot be empty"));
             }
             
             if self.timestamp_format.is_empty() {
-                anyhow::bail!("timestamp_format cannot be empty");
+                return Err(LogLineError::config("timestamp_format cannot be empty"));
+            }
+        }
+        
+        if self.multiline || self.continuation_regex.is_some() {
+            if self.input_format != InputFormat::Text {
+                return Err(LogLineError::config("Multiline entries only apply to text input"));
+            }
+            if without_line_timestamps && self.continuation_regex.is_none() {
+                return Err(LogLineError::config(
+                    "multiline needs a continuation_regex when lines have no timestamps of their own",
+                ));
             }
         }
         
-        if self.message_patterns.len() < 2 {
-            anyhow::bail!("Configuration must have at least 2 message patterns");
+        match &self.embedded_event_regex {
+            Some(_) if self.embedded_timestamp_format.is_none() && self.timestamp_format.is_empty() => {
+                return Err(LogLineError::config(
+                    "embedded_event_regex needs embedded_timestamp_format (or a timestamp_format)",
+                ));
+            }
+            None if self.embedded_timestamp_format.is_some() => {
+                return Err(LogLineError::config("embedded_timestamp_format needs an embedded_event_regex"));
+            }
+            _ => {}
+        }
+        
+        // Embedded events name themselves, so the message patterns are optional with them
+        if self.message_patterns.len() < 2 && self.embedded_event_regex.is_none() {
+            return Err(LogLineError::config("Configuration must have at least 2 message patterns"));
+        }
+        
+        if let Some(pattern) = self.message_patterns.iter().find(|p| p.name.as_deref().is_some_and(str::is_empty)) {
+            return Err(LogLineError::config(format!("Message pattern {} has an empty name", pattern.regex)));
+        }
+        
+        if let Some(min) = &self.min_duration {
+            parse_duration(min)
+                .map_err(|e| LogLineError::config_caused_by(format!("Invalid min_duration: {}", min), e))?;
+        }
+        
+        if let Some(limit) = &self.fail_if_over {
+            parse_duration(limit)
+                .map_err(|e| LogLineError::config_caused_by(format!("Invalid fail_if_over: {}", limit), e))?;
+        }
+        
+        if let Some(thresholds) = &self.color_thresholds {
+            DurationColors::parse(thresholds).map_err(|e| {
+                LogLineError::config_caused_by(format!("Invalid color_thresholds: {}", thresholds), e)
+            })?;
+        }
+        
+        if let Some(warmup) = &self.skip_duration {
+            parse_duration(warmup)
+                .map_err(|e| LogLineError::config_caused_by(format!("Invalid skip_duration: {}", warmup), e))?;
+        }
+        
+        match self.input_format {
+            InputFormat::Json if self.timestamp_field.as_deref().is_none_or(str::is_empty) => {
+                return Err(LogLineError::config("JSON input needs a timestamp_field"));
+            }
+            InputFormat::Text if self.timestamp_field.is_some() || self.message_field.is_some() => {
+                return Err(LogLineError::config(
+                    "timestamp_field and message_field only apply to JSON input (input_format: json)",
+                ));
+            }
+            _ => {}
+        }
+        
+        for anchor in &self.clock_anchors {
+            if !self.message_patterns.iter().any(|pattern| pattern.label() == anchor) {
+                return Err(LogLineError::config(format!(
+                    "Clock anchor '{}' is not the name or regex of a message pattern",
+                    anchor
+                )));
+            }
+        }
+        
+        for window in &self.excluded_windows {
+            if window.name.is_empty() || window.name.contains(|c: char| c == ',' || c.is_whitespace()) {
+                return Err(LogLineError::config(format!(
+                    "Excluded window name '{}' must be non-empty, without commas or whitespace",
+                    window.name
+                )));
+            }
+            Span::parse(&window.start, &window.end).map_err(|e| {
+                LogLineError::config_caused_by(format!("Invalid excluded window '{}'", window.name), e)
+            })?;
+        }
+        
+        for pair in &self.pairs {
+            if pair.start.is_empty() || pair.end.is_empty() {
+                return Err(LogLineError::config("Pairs need both a start and an end pattern"));
+            }
+            if pair.start == pair.end {
+                return Err(LogLineError::config(format!(
+                    "Pair start and end must differ, got '{}' for both",
+                    pair.start
+                )));
+            }
+        }
+        
+        if let Some(reported) = &self.reported_duration {
+            let is_identifier = reported.field.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                && reported.field.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if !is_identifier {
+                return Err(LogLineError::config(format!(
+                    "reported_duration field '{}' must be the name of a capture group",
+                    reported.field
+                )));
+            }
+        }
+        
+        let derived_values = self.derived_values()?;
+        let mut derived_names: Vec<&str> = Vec::new();
+        for derived in &derived_values {
+            let name = derived.name.as_str();
+            let is_identifier = name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if !is_identifier {
+                return Err(LogLineError::config(format!(
+                    "Derived value name '{}' must be made of letters, digits, and underscores",
+                    name
+                )));
+            }
+            // Keep clear of the standard output columns and the from_/to_ capture columns
+            const RESERVED: [&str; 7] =
+                ["duration_ms", "duration_s", "duration_human", "correlation", "tags", "start", "end"];
+            if RESERVED.contains(&name)
+                || name.starts_with("from_")
+                || name.starts_with("to_")
+                || derived_names.contains(&name)
+            {
+                return Err(LogLineError::config(format!("Derived value name '{}' is reserved or already used", name)));
+            }
+            Expr::parse(&derived.expr, &derived_names)?;
+            derived_names.push(name);
+        }
+        
+        for rule in &self.tags {
+            if rule.tag.is_empty() || rule.tag.contains(|c: char| c == ',' || c.is_whitespace()) {
+                return Err(LogLineError::config(format!(
+                    "Tag '{}' must be non-empty, without commas or whitespace",
+                    rule.tag
+                )));
+            }
+            Condition::parse(&rule.condition, &derived_names)
+                .map_err(|e| LogLineError::config_caused_by(format!("Invalid tags rule for '{}'", rule.tag), e))?;
+        }
+        
+        for rule in &self.transitions {
+            if let Some(min) = &rule.min_duration {
+                parse_duration(min).map_err(|e| {
+                    LogLineError::config_caused_by(
+                        format!("Invalid min_duration for transition {} -> {}: {}", rule.from, rule.to, min),
+                        e,
+                    )
+                })?;
+            }
+            if let Some(limit) = &rule.fail_if_over {
+                parse_duration(limit).map_err(|e| {
+                    LogLineError::config_caused_by(
+                        format!("Invalid fail_if_over for transition {} -> {}: {}", rule.from, rule.to, limit),
+                        e,
+                    )
+                })?;
+            }
+            if let Some(limit) = &rule.expected_max {
+                parse_duration(limit).map_err(|e| {
+                    LogLineError::config_caused_by(
+                        format!("Invalid expected_max for transition {} -> {}: {}", rule.from, rule.to, limit),
+                        e,
+                    )
+                })?;
+            }
         }
         
         Ok(())
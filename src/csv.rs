@@ -0,0 +1,121 @@
+/// CSV field separators and quoting (`--csv-delimiter`, `--csv-quote`)
+use crate::error::{LogLineError, Result};
+
+/// Which CSV fields are wrapped in quotes. Fields containing the delimiter, a quote, or a
+/// line break are always quoted (with quotes doubled), as RFC 4180 requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quoting {
+    /// Quote every text field, but not numbers or the header (the default)
+    #[default]
+    Text,
+    /// Quote only the fields that need it
+    Minimal,
+    /// Quote every field, header included
+    All,
+}
+
+impl std::str::FromStr for Quoting {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, ()> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Quoting::Text),
+            "minimal" => Ok(Quoting::Minimal),
+            "all" => Ok(Quoting::All),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How the CSV output separates and quotes its fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub delimiter: char,
+    pub quoting: Quoting,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        CsvDialect {
+            delimiter: ',',
+            quoting: Quoting::default(),
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Delimiter given on the command line: one character, or `tab`/`\t` for a tab
+    pub fn parse_delimiter(text: &str) -> Result<char> {
+        let delimiter = match text {
+            "tab" | "\\t" => '\t',
+            _ => {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => c,
+                    _ => return Err(LogLineError::config(format!("CSV delimiter must be a single character, got '{}'", text))),
+                }
+            }
+        };
+        if matches!(delimiter, '"' | '\n' | '\r') {
+            return Err(LogLineError::config("CSV delimiter cannot be a quote or a line break"));
+        }
+        Ok(delimiter)
+    }
+
+    /// One field; `text` marks a text value rather than a number or a column name
+    pub fn field(&self, value: &str, text: bool) -> String {
+        let needs_quotes = value.contains(self.delimiter) || value.contains(['"', '\n', '\r']);
+        let quoted = needs_quotes
+            || match self.quoting {
+                Quoting::Text => text,
+                Quoting::Minimal => false,
+                Quoting::All => true,
+            };
+        if quoted {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// The header line of column names
+    pub fn header(&self, names: &[&str]) -> String {
+        self.join(names.iter().map(|name| self.field(name, false)))
+    }
+
+    /// One row of `(value, is text)` fields
+    pub fn record(&self, fields: &[(String, bool)]) -> String {
+        self.join(fields.iter().map(|(value, text)| self.field(value, *text)))
+    }
+
+    fn join(&self, fields: impl Iterator<Item = String>) -> String {
+        fields.collect::<Vec<_>>().join(&self.delimiter.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_are_quoted_by_policy_and_when_needed() {
+        let row = [
+            ("GET /a,b".to_string(), true),
+            ("say \"hi\"\nbye".to_string(), true),
+            ("1500".to_string(), false),
+            ("plain".to_string(), true),
+        ];
+
+        assert_eq!(CsvDialect::default().record(&row), "\"GET /a,b\",\"say \"\"hi\"\"\nbye\",1500,\"plain\"");
+        let minimal = CsvDialect { delimiter: ';', quoting: Quoting::Minimal };
+        assert_eq!(minimal.record(&row), "GET /a,b;\"say \"\"hi\"\"\nbye\";1500;plain");
+        let all = CsvDialect { delimiter: ';', quoting: Quoting::All };
+        assert_eq!(all.header(&["from", "to"]), "\"from\";\"to\"");
+        assert_eq!(all.field("1500", false), "\"1500\"");
+
+        assert_eq!(CsvDialect::parse_delimiter(";").unwrap(), ';');
+        assert_eq!(CsvDialect::parse_delimiter("tab").unwrap(), '\t');
+        assert!(CsvDialect::parse_delimiter(";;").is_err());
+        assert!(CsvDialect::parse_delimiter("\"").is_err());
+    }
+}
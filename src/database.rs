@@ -0,0 +1,183 @@
+/// Keeping the results of every run in a SQLite database (`--output-db`): a `runs` table with
+/// a row per run, and `matches` and `intervals` tables whose rows point at their run, so past
+/// analyses can be queried and joined across runs with SQL
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use log_time_analyzer::analyzer::Interval;
+use log_time_analyzer::parser::LogMatch;
+
+use crate::query::{integer, text, timestamp, TIMESTAMP_FORMAT};
+
+/// Created when missing, so every run appends to the same tables. Captured fields and derived
+/// values differ between configs, so they are kept as JSON objects (see SQLite's
+/// `json_extract`) rather than in columns of their own.
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    label TEXT,
+    started_at TEXT NOT NULL,
+    log_files TEXT NOT NULL,
+    version TEXT NOT NULL,
+    partial INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS matches (
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    pattern TEXT NOT NULL,
+    timestamp TEXT,
+    source TEXT,
+    line INTEGER,
+    correlation TEXT,
+    fields TEXT
+);
+CREATE TABLE IF NOT EXISTS intervals (
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    from_pattern TEXT NOT NULL,
+    to_pattern TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    start TEXT,
+    "end" TEXT,
+    from_line INTEGER,
+    to_line INTEGER,
+    correlation TEXT,
+    "group" TEXT,
+    from_source TEXT,
+    to_source TEXT,
+    tags TEXT,
+    fields TEXT,
+    derived TEXT
+);
+CREATE INDEX IF NOT EXISTS matches_run_id ON matches (run_id);
+CREATE INDEX IF NOT EXISTS intervals_run_id ON intervals (run_id);
+"#;
+
+/// What is recorded about a run in the `runs` table
+pub struct Run<'a> {
+    /// `--run-id`, when given
+    pub label: Option<&'a str>,
+    pub started_at: NaiveDateTime,
+    pub log_files: &'a [PathBuf],
+    /// Interrupted, so the matches and intervals are partial
+    pub partial: bool,
+}
+
+/// A JSON object of `fields`, or NULL without any
+fn json<V: serde::Serialize>(fields: &BTreeMap<String, V>) -> Result<SqlValue> {
+    if fields.is_empty() {
+        return Ok(SqlValue::Null);
+    }
+    Ok(SqlValue::Text(serde_json::to_string(fields)?))
+}
+
+/// Append `run` with its matches and intervals to the database at `path` in one transaction,
+/// creating the database and its tables when needed; returns the id of the run's row
+pub fn write_run(path: &Path, run: &Run, matches: &[LogMatch], intervals: &[Interval]) -> Result<i64> {
+    let mut db = Connection::open(path).with_context(|| format!("Failed to open database {:?}", path))?;
+    db.execute_batch(SCHEMA)
+        .with_context(|| format!("Failed to create the tables of database {:?}", path))?;
+    let tx = db.transaction()?;
+
+    let log_files: Vec<String> = run.log_files.iter().map(|path| path.display().to_string()).collect();
+    tx.execute(
+        "INSERT INTO runs (label, started_at, log_files, version, partial) VALUES (?, ?, ?, ?, ?)",
+        params![
+            run.label,
+            run.started_at.format(TIMESTAMP_FORMAT).to_string(),
+            log_files.join("\n"),
+            env!("CARGO_PKG_VERSION"),
+            run.partial
+        ],
+    )?;
+    let run_id = tx.last_insert_rowid();
+
+    {
+        let mut insert = tx.prepare("INSERT INTO matches VALUES (?, ?, ?, ?, ?, ?, ?)")?;
+        for m in matches {
+            insert.execute(rusqlite::params_from_iter([
+                SqlValue::Integer(run_id),
+                SqlValue::Text(m.pattern.clone()),
+                timestamp(Some(m.timestamp)),
+                text(&m.source),
+                integer(m.line_number),
+                text(&m.correlation),
+                json(&m.fields)?,
+            ]))?;
+        }
+
+        let mut insert = tx.prepare("INSERT INTO intervals VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")?;
+        for i in intervals {
+            // Prefixed as in the JSON output, since both ends may capture the same names
+            let fields: BTreeMap<String, &String> = i
+                .from_fields
+                .iter()
+                .map(|(name, value)| (format!("from_{}", name), value))
+                .chain(i.to_fields.iter().map(|(name, value)| (format!("to_{}", name), value)))
+                .collect();
+            insert.execute(rusqlite::params_from_iter([
+                SqlValue::Integer(run_id),
+                SqlValue::Text(i.from_pattern.clone()),
+                SqlValue::Text(i.to_pattern.clone()),
+                SqlValue::Integer(i.duration.num_milliseconds()),
+                timestamp(i.start),
+                timestamp(i.end),
+                integer(i.from_line),
+                integer(i.to_line),
+                text(&i.correlation),
+                text(&i.group),
+                text(&i.from_source),
+                text(&i.to_source),
+                SqlValue::Text(i.tags.join(",")),
+                json(&fields)?,
+                json(&i.derived)?,
+            ]))?;
+        }
+    }
+
+    tx.commit().with_context(|| format!("Failed to write to database {:?}", path))?;
+    Ok(run_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+    use chrono::Duration;
+
+    #[test]
+    fn test_runs_accumulate_in_one_database() {
+        let path = std::env::temp_dir().join(format!("ll-output-db-test-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let started_at = NaiveDateTime::parse_from_str("2025-11-13 10:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let log_files = [PathBuf::from("app.log")];
+        let interval = |ms: i64, endpoint: &str| Interval {
+            from_fields: BTreeMap::from([("endpoint".to_string(), endpoint.to_string())]),
+            ..Interval::new("start", "end", Duration::milliseconds(ms))
+        };
+
+        let first = Run { label: Some("before"), started_at, log_files: &log_files, partial: false };
+        let second = Run { label: None, started_at, log_files: &log_files, partial: true };
+        assert_eq!(write_run(&path, &first, &[LogMatch::default()], &[interval(120, "GET /users")]).unwrap(), 1);
+        assert_eq!(write_run(&path, &second, &[], &[interval(300, "GET /users"), interval(80, "GET /")]).unwrap(), 2);
+
+        let db = Connection::open(&path).unwrap();
+        let rows: Vec<(i64, Option<String>, i64, i64)> = db
+            .prepare(
+                "SELECT runs.id, label, partial, max(duration_ms) FROM runs JOIN intervals ON intervals.run_id = runs.id
+                 WHERE json_extract(fields, '$.from_endpoint') = 'GET /users' GROUP BY runs.id",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(rows, [(1, Some("before".to_string()), 0, 120), (2, None, 1, 300)]);
+        let matches: i64 = db.query_row("SELECT count(*) FROM matches", [], |row| row.get(0)).unwrap();
+        assert_eq!(matches, 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
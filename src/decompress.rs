@@ -0,0 +1,114 @@
+/// Opening log files that may be compressed (rotated `.gz`, `.zst`, or `.bz2` archives)
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor};
+use std::path::Path;
+
+use crate::error::{LogLineError, Result};
+
+/// Compression recognized from a file's leading magic bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// Every compression that is read transparently
+    pub const SUPPORTED: [Compression; 3] = [Compression::Gzip, Compression::Zstd, Compression::Bzip2];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+        }
+    }
+
+
+    /// Identify the compression from the first bytes of a file; the extension isn't
+    /// trusted since rotation tools don't always add one
+    pub fn detect(header: &[u8]) -> Self {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if header.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Open a log file for line reading, decompressing it on the fly when needed
+pub fn open_log_file(path: &Path) -> Result<Box<dyn BufRead>> {
+    let file = File::open(path).map_err(|e| LogLineError::io(format!("Failed to open log file: {:?}", path), e))?;
+    let mut reader = BufReader::new(file);
+    let header = reader
+        .fill_buf()
+        .map_err(|e| LogLineError::io(format!("Failed to read log file: {:?}", path), e))?;
+
+    Ok(match Compression::detect(header) {
+        Compression::None => Box::new(reader),
+        // Multi-member so files made by concatenating gzip archives read completely
+        Compression::Gzip => Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader))),
+        Compression::Zstd => {
+            let decoder = zstd::stream::read::Decoder::with_buffer(reader)
+                .map_err(|e| LogLineError::io(format!("Failed to start zstd decompression: {:?}", path), e))?;
+            Box::new(BufReader::new(decoder))
+        }
+        Compression::Bzip2 => Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(reader))),
+    })
+}
+
+/// Like `open_log_file`, but memory-map a plain file instead of copying it through a read
+/// buffer, so the operating system pages it in as the lines are read and drops the pages
+/// already read under memory pressure. Compressed files are opened as usual.
+pub fn open_log_file_mapped(path: &Path) -> Result<Box<dyn BufRead>> {
+    let file = File::open(path).map_err(|e| LogLineError::io(format!("Failed to open log file: {:?}", path), e))?;
+    // SAFETY: the map is only read. A file truncated by another process while mapped makes
+    // later reads fault, which is why mapping is opt-in rather than the default.
+    let map = unsafe { Mmap::map(&file) }.map_err(|e| LogLineError::io(format!("Failed to map log file: {:?}", path), e))?;
+    if Compression::detect(&map[..map.len().min(4)]) != Compression::None {
+        return open_log_file(path);
+    }
+    Ok(Box::new(Cursor::new(map)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_compressed_logs_read_like_plain_ones() {
+        let log = "2025-11-13 10:00:00 start\n2025-11-13 10:00:02 end\n";
+        let dir = std::env::temp_dir().join(format!("ll-decompress-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(log.as_bytes()).unwrap();
+        let mut bzip = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bzip.write_all(log.as_bytes()).unwrap();
+
+        let files = [
+            ("plain.log", log.as_bytes().to_vec()),
+            ("app.log.gz", gzip.finish().unwrap()),
+            ("app.log.zst", zstd::encode_all(log.as_bytes(), 0).unwrap()),
+            // No extension: detection goes by content
+            ("app.log.1", bzip.finish().unwrap()),
+        ];
+        for (name, bytes) in files {
+            let path = dir.join(name);
+            std::fs::write(&path, bytes).unwrap();
+            let lines: Vec<String> = open_log_file(&path).unwrap().lines().map(|l| l.unwrap()).collect();
+            assert_eq!(lines.join("\n") + "\n", log, "{}", name);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
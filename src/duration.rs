@@ -0,0 +1,119 @@
+/// Parsing of human-written durations such as `50ms`, `1.5s`, or `1h30m`
+use crate::error::{LogLineError, Result};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+/// Timestamp layouts accepted for `--since`/`--until`, tried in order
+const TIME_BOUND_FORMATS: [&str; 4] = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"];
+
+/// Parse a duration made of one or more `<number><unit>` parts.
+///
+/// Supported units: `us`, `ms`, `s`, `m`, `h`, `d`. A bare number is taken as
+/// milliseconds, matching the `duration_ms` columns in the output.
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let text = input.trim();
+    if text.is_empty() {
+        return Err(LogLineError::config("Empty duration"));
+    }
+
+    if let Ok(ms) = text.parse::<f64>() {
+        return Ok(Duration::microseconds((ms * 1_000.0).round() as i64));
+    }
+
+    let mut total_us = 0.0;
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .ok_or_else(|| LogLineError::config(format!("Missing unit in duration '{}'", input)))?;
+        let (number, after_number) = rest.split_at(number_len);
+        let value: f64 = number
+            .parse()
+            .map_err(|_| LogLineError::config(format!("Invalid number in duration '{}'", input)))?;
+
+        let unit_len = after_number
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(after_number.len());
+        let (unit, remaining) = after_number.split_at(unit_len);
+
+        let unit_us = match unit.trim() {
+            "us" | "µs" => 1.0,
+            "ms" => 1_000.0,
+            "s" | "sec" => 1_000_000.0,
+            "m" | "min" => 60_000_000.0,
+            "h" => 3_600_000_000.0,
+            "d" => 86_400_000_000.0,
+            other => return Err(LogLineError::config(format!(
+                "Unknown unit '{}' in duration '{}'. Use us, ms, s, m, h, or d",
+                other,
+                input
+            ))),
+        };
+
+        total_us += value * unit_us;
+        rest = remaining.trim_start();
+    }
+
+    Ok(Duration::microseconds(total_us.round() as i64))
+}
+
+/// Parse a timestamp such as `2025-11-13 10:00:00` or `2025-11-13T10:00`, or a date alone for
+/// its midnight
+pub fn parse_timestamp(input: &str) -> Option<NaiveDateTime> {
+    let text = input.trim().trim_end_matches('Z');
+    TIME_BOUND_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))
+}
+
+/// Parse a point in time given either as a timestamp (see `parse_timestamp`) or as a duration
+/// before `now` (`2h` for two hours ago)
+pub fn parse_time_bound(input: &str, now: NaiveDateTime) -> Result<NaiveDateTime> {
+    if let Some(time) = parse_timestamp(input) {
+        return Ok(time);
+    }
+
+    let text = input.trim();
+    let ago = parse_duration(text).map_err(|_| {
+        LogLineError::config(format!(
+            "Invalid time '{}'. Use a timestamp such as 2025-11-13 10:00:00, a date, or a duration ago such as 2h",
+            input
+        ))
+    })?;
+    Ok(now - ago)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("50ms").unwrap(), Duration::milliseconds(50));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::milliseconds(1500));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::days(2));
+        assert_eq!(parse_duration("250").unwrap(), Duration::milliseconds(250));
+        assert_eq!(parse_duration("750us").unwrap(), Duration::microseconds(750));
+    }
+
+    #[test]
+    fn test_parse_duration_errors() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("5 parsecs").is_err());
+        assert!(parse_duration("ms").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_bound() {
+        let now = NaiveDateTime::parse_from_str("2025-11-13 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f").unwrap();
+
+        assert_eq!(parse_time_bound("2025-11-13 10:15:30.5", now).unwrap(), at("2025-11-13 10:15:30.5"));
+        assert_eq!(parse_time_bound("2025-11-13T10:15Z", now).unwrap(), at("2025-11-13 10:15:00"));
+        assert_eq!(parse_time_bound("2025-11-12", now).unwrap(), at("2025-11-12 00:00:00"));
+        assert_eq!(parse_time_bound("2h", now).unwrap(), at("2025-11-13 10:00:00"));
+        assert!(parse_time_bound("yesterday", now).is_err());
+    }
+}
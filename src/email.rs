@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use log_time_analyzer::output::OutputFormat;
+
+/// SMTP delivery settings for emailing a finished report
+#[derive(Debug, Clone)]
+pub struct EmailSettings {
+    /// Recipient addresses
+    pub to: Vec<String>,
+    /// Sender address
+    pub from: String,
+    /// SMTP relay host
+    pub smtp_host: String,
+    /// SMTP relay port
+    pub smtp_port: u16,
+}
+
+/// Send a report in `format` as an email through the configured SMTP relay: an HTML email
+/// for the `html` format, plain text for all others.
+///
+/// This speaks plain SMTP without TLS or authentication, which is what local
+/// and internal relays (postfix on localhost, a mail gateway inside the VPC) expect.
+pub fn send_report(settings: &EmailSettings, subject: &str, body: &str, format: OutputFormat) -> Result<()> {
+    if settings.to.is_empty() {
+        anyhow::bail!("At least one email recipient is required");
+    }
+    // A line break would end the header (or SMTP command) early and start one of the caller's
+    check_header("sender", &settings.from)?;
+    for recipient in &settings.to {
+        check_header("recipient", recipient)?;
+    }
+    check_header("subject", subject)?;
+
+    let message = build_message(settings, subject, body, format);
+
+    let stream = TcpStream::connect((settings.smtp_host.as_str(), settings.smtp_port))
+        .with_context(|| {
+            format!(
+                "Failed to connect to SMTP server {}:{}",
+                settings.smtp_host, settings.smtp_port
+            )
+        })?;
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(30)))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    expect_reply(&mut reader, &[220])?;
+    command(&mut writer, &mut reader, "EHLO log-time-analyzer", &[250])?;
+    command(
+        &mut writer,
+        &mut reader,
+        &format!("MAIL FROM:<{}>", settings.from),
+        &[250],
+    )?;
+    for recipient in &settings.to {
+        command(
+            &mut writer,
+            &mut reader,
+            &format!("RCPT TO:<{}>", recipient),
+            &[250, 251],
+        )?;
+    }
+    command(&mut writer, &mut reader, "DATA", &[354])?;
+
+    writer.write_all(message.as_bytes())?;
+    writer.write_all(b".\r\n")?;
+    writer.flush()?;
+    expect_reply(&mut reader, &[250])?;
+
+    command(&mut writer, &mut reader, "QUIT", &[221])?;
+
+    Ok(())
+}
+
+fn check_header(name: &str, value: &str) -> Result<()> {
+    if value.contains(['\r', '\n']) {
+        anyhow::bail!("The email {} cannot contain a line break: {:?}", name, value);
+    }
+    Ok(())
+}
+
+/// Build the RFC 5322 message, with CRLF line endings and dot-stuffed body lines
+fn build_message(settings: &EmailSettings, subject: &str, body: &str, format: OutputFormat) -> String {
+    let content_type = match format {
+        OutputFormat::Html => "text/html",
+        _ => "text/plain",
+    };
+    let mut message = String::new();
+    message.push_str(&format!("From: {}\r\n", settings.from));
+    message.push_str(&format!("To: {}\r\n", settings.to.join(", ")));
+    message.push_str(&format!("Subject: {}\r\n", subject));
+    message.push_str(&format!("Date: {}\r\n", Local::now().to_rfc2822()));
+    message.push_str("MIME-Version: 1.0\r\n");
+    message.push_str(&format!("Content-Type: {}; charset=utf-8\r\n", content_type));
+    message.push_str("Content-Transfer-Encoding: 8bit\r\n");
+    message.push_str("\r\n");
+
+    for line in body.lines() {
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+
+    message
+}
+
+fn command<W: Write, R: BufRead>(
+    writer: &mut W,
+    reader: &mut R,
+    line: &str,
+    expected: &[u16],
+) -> Result<()> {
+    writer.write_all(line.as_bytes())?;
+    writer.write_all(b"\r\n")?;
+    writer.flush()?;
+
+    expect_reply(reader, expected)
+        .with_context(|| format!("SMTP command failed: {}", line))
+}
+
+/// Read a (possibly multi-line) SMTP reply and check its status code
+fn expect_reply<R: BufRead>(reader: &mut R, expected: &[u16]) -> Result<()> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            anyhow::bail!("SMTP server closed the connection");
+        }
+
+        let code: u16 = line
+            .get(..3)
+            .and_then(|c| c.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("Malformed SMTP reply: {}", line.trim_end()))?;
+
+        // "250-..." continues a multi-line reply, "250 ..." ends it
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+
+        if !expected.contains(&code) {
+            anyhow::bail!("Unexpected SMTP reply: {}", line.trim_end());
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> EmailSettings {
+        EmailSettings {
+            to: vec!["a@example.com".to_string(), "b@example.com".to_string()],
+            from: "ll@example.com".to_string(),
+            smtp_host: "localhost".to_string(),
+            // Nothing listens here; the checks must fail before connecting
+            smtp_port: 9,
+        }
+    }
+
+    #[test]
+    fn test_build_message_dot_stuffing() {
+        let message = build_message(&settings(), "Report", "first\n.hidden\nlast", OutputFormat::Table);
+
+        assert!(message.contains("To: a@example.com, b@example.com\r\n"));
+        assert!(message.contains("Subject: Report\r\n"));
+        assert!(message.contains("Content-Type: text/plain; charset=utf-8\r\n"));
+        assert!(message.ends_with("\r\n\r\nfirst\r\n..hidden\r\nlast\r\n"));
+
+        let message = build_message(&settings(), "Report", "<!DOCTYPE html>", OutputFormat::Html);
+        assert!(message.contains("Content-Type: text/html; charset=utf-8\r\n"));
+    }
+
+    #[test]
+    fn test_line_breaks_in_headers_are_rejected() {
+        let error = send_report(&settings(), "Report\r\nBcc: x@example.com", "body", OutputFormat::Human).unwrap_err();
+        assert!(error.to_string().contains("subject cannot contain a line break"), "{}", error);
+
+        let mut bad_recipient = settings();
+        bad_recipient.to.push("c@example.com>\r\nRCPT TO:<d@example.com".to_string());
+        assert!(send_report(&bad_recipient, "Report", "body", OutputFormat::Human).is_err());
+
+        let mut bad_sender = settings();
+        bad_sender.from = "ll@example.com\nBcc: x@example.com".to_string();
+        assert!(send_report(&bad_sender, "Report", "body", OutputFormat::Human).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+/// Error types for configuration loading and log parsing
+use std::error::Error as StdError;
+use thiserror::Error;
+
+/// Errors returned by `Config` and `LogParser`, so callers can tell the kinds apart
+/// without matching on message text
+#[derive(Debug, Error)]
+pub enum LogLineError {
+    /// The configuration is invalid or could not be parsed
+    #[error("{message}")]
+    Config {
+        message: String,
+        #[source]
+        source: Option<Box<dyn StdError + Send + Sync>>,
+    },
+
+    /// A timestamp regex, message pattern, or exclude pattern failed to compile
+    #[error("Invalid {what}: {pattern}")]
+    Regex {
+        /// Which kind of regex this is, e.g. "message pattern regex"
+        what: String,
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
+
+    /// A timestamp was found but did not match the configured chrono format
+    #[error("Failed to parse timestamp: {text}")]
+    TimestampParse {
+        text: String,
+        format: String,
+        #[source]
+        source: chrono::ParseError,
+    },
+
+    /// Reading a config or log file failed
+    #[error("{message}")]
+    Io {
+        message: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, LogLineError>;
+
+impl LogLineError {
+    /// A configuration error with no underlying cause
+    pub fn config(message: impl Into<String>) -> Self {
+        LogLineError::Config {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// A configuration error caused by another error
+    pub fn config_caused_by(message: impl Into<String>, source: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        LogLineError::Config {
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    pub fn regex(what: impl Into<String>, pattern: impl Into<String>, source: regex::Error) -> Self {
+        LogLineError::Regex {
+            what: what.into(),
+            pattern: pattern.into(),
+            source,
+        }
+    }
+
+    pub fn io(message: impl Into<String>, source: std::io::Error) -> Self {
+        LogLineError::Io {
+            message: message.into(),
+            source,
+        }
+    }
+}
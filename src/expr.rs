@@ -0,0 +1,326 @@
+/// A small arithmetic expression language for values derived from each interval
+use crate::analyzer::Interval;
+use crate::error::{LogLineError, Result};
+
+/// A parsed expression such as `duration_ms / extracted.items`.
+///
+/// Supports numbers, `+ - * / %`, parentheses, unary minus, the functions `min`, `max`,
+/// `abs`, and `round`, and these variables:
+///
+/// - `duration_ms`, `duration_s`: the interval's duration
+/// - `from.<name>`, `to.<name>`: a named capture of the starting or ending match
+/// - `extracted.<name>`: a named capture of the ending match, or else of the starting one
+/// - the name of a derived value defined earlier
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Variable(Variable),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Variable {
+    DurationMs,
+    DurationS,
+    From(String),
+    To(String),
+    Extracted(String),
+    Derived(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Min,
+    Max,
+    Abs,
+    Round,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    Open,
+    Close,
+    Comma,
+}
+
+impl Expr {
+    /// Parse an expression; `derived` lists the derived values it may refer to by name
+    pub fn parse(text: &str, derived: &[&str]) -> Result<Expr> {
+        let error = |msg: String| LogLineError::config(format!("Invalid expression '{}': {}", text, msg));
+
+        let tokens = tokenize(text).map_err(error)?;
+        let mut parser = ExprParser { tokens: &tokens, pos: 0, derived };
+        let expr = parser.expr().map_err(error)?;
+        if let Some(token) = tokens.get(parser.pos) {
+            return Err(error(format!("unexpected {:?}", token)));
+        }
+        Ok(expr)
+    }
+
+    /// Evaluate against an interval; `None` when a field is missing or not a number, or on
+    /// division by zero
+    pub fn eval(&self, interval: &Interval) -> Option<f64> {
+        let value = match self {
+            Expr::Number(n) => *n,
+            Expr::Variable(variable) => variable.eval(interval)?,
+            Expr::Neg(inner) => -inner.eval(interval)?,
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (left.eval(interval)?, right.eval(interval)?);
+                match op {
+                    Op::Add => left + right,
+                    Op::Sub => left - right,
+                    Op::Mul => left * right,
+                    Op::Div if right == 0.0 => return None,
+                    Op::Div => left / right,
+                    Op::Rem if right == 0.0 => return None,
+                    Op::Rem => left % right,
+                }
+            }
+            Expr::Call(function, args) => {
+                let args = args.iter().map(|arg| arg.eval(interval)).collect::<Option<Vec<f64>>>()?;
+                match function {
+                    Function::Min => args.into_iter().reduce(f64::min)?,
+                    Function::Max => args.into_iter().reduce(f64::max)?,
+                    Function::Abs => args[0].abs(),
+                    Function::Round => args[0].round(),
+                }
+            }
+        };
+        value.is_finite().then_some(value)
+    }
+}
+
+impl Variable {
+    fn eval(&self, interval: &Interval) -> Option<f64> {
+        let field = |value: Option<&String>| value?.trim().parse::<f64>().ok();
+        match self {
+            Variable::DurationMs => Some(interval.duration.num_microseconds()? as f64 / 1000.0),
+            Variable::DurationS => Some(interval.duration.num_microseconds()? as f64 / 1_000_000.0),
+            Variable::From(name) => field(interval.from_fields.get(name)),
+            Variable::To(name) => field(interval.to_fields.get(name)),
+            Variable::Extracted(name) => field(interval.to_fields.get(name).or_else(|| interval.from_fields.get(name))),
+            Variable::Derived(name) => interval.derived.get(name).copied(),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let number = &text[start..end];
+            tokens.push(Token::Number(number.parse().map_err(|_| format!("bad number '{}'", number))?));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Ident(text[start..end].to_string()));
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' | '%' => Token::Op(c),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                ',' => Token::Comma,
+                _ => return Err(format!("unexpected character '{}'", c)),
+            });
+            chars.next();
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive-descent parser: sums of products of (possibly negated) primaries
+struct ExprParser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    derived: &'a [&'a str],
+}
+
+impl ExprParser<'_> {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn peek_op(&self, ops: &[char]) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) if ops.contains(op) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self) -> std::result::Result<Expr, String> {
+        let mut left = self.term()?;
+        while let Some(op) = self.peek_op(&['+', '-']) {
+            self.pos += 1;
+            let op = if op == '+' { Op::Add } else { Op::Sub };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> std::result::Result<Expr, String> {
+        let mut left = self.unary()?;
+        while let Some(op) = self.peek_op(&['*', '/', '%']) {
+            self.pos += 1;
+            let op = match op {
+                '*' => Op::Mul,
+                '/' => Op::Div,
+                _ => Op::Rem,
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> std::result::Result<Expr, String> {
+        if self.peek_op(&['-']).is_some() {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> std::result::Result<Expr, String> {
+        match self.next().cloned() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Open) => {
+                let inner = self.expr()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(Token::Ident(name)) if self.tokens.get(self.pos) == Some(&Token::Open) => {
+                self.pos += 1;
+                self.call(&name)
+            }
+            Some(Token::Ident(name)) => self.variable(&name).map(Expr::Variable),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> std::result::Result<Expr, String> {
+        let (function, arity) = match name {
+            "min" => (Function::Min, None),
+            "max" => (Function::Max, None),
+            "abs" => (Function::Abs, Some(1)),
+            "round" => (Function::Round, Some(1)),
+            _ => return Err(format!("unknown function '{}'", name)),
+        };
+
+        let mut args = Vec::new();
+        if self.tokens.get(self.pos) == Some(&Token::Close) {
+            self.pos += 1;
+        } else {
+            loop {
+                args.push(self.expr()?);
+                match self.next() {
+                    Some(Token::Comma) => continue,
+                    Some(Token::Close) => break,
+                    _ => return Err(format!("missing ')' after arguments of {}", name)),
+                }
+            }
+        }
+
+        match arity {
+            Some(n) if args.len() != n => Err(format!("{} takes {} argument", name, n)),
+            None if args.is_empty() => Err(format!("{} needs at least one argument", name)),
+            _ => Ok(Expr::Call(function, args)),
+        }
+    }
+
+    fn variable(&self, name: &str) -> std::result::Result<Variable, String> {
+        let field = |prefix: &str| name.strip_prefix(prefix).filter(|rest| !rest.is_empty()).map(str::to_string);
+        if let Some(field) = field("from.") {
+            Ok(Variable::From(field))
+        } else if let Some(field) = field("to.") {
+            Ok(Variable::To(field))
+        } else if let Some(field) = field("extracted.") {
+            Ok(Variable::Extracted(field))
+        } else if name == "duration_ms" {
+            Ok(Variable::DurationMs)
+        } else if name == "duration_s" {
+            Ok(Variable::DurationS)
+        } else if self.derived.contains(&name) {
+            Ok(Variable::Derived(name.to_string()))
+        } else {
+            Err(format!(
+                "unknown variable '{}' (use duration_ms, duration_s, from.<field>, to.<field>, extracted.<field>, or an earlier derived value)",
+                name
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
+    use chrono::Duration;
+
+    fn interval() -> Interval {
+        let mut interval = Interval::new("start", "end", Duration::milliseconds(1500));
+        interval.from_fields.insert("items".to_string(), "4".to_string());
+        interval.to_fields.insert("bytes".to_string(), "2048".to_string());
+        interval.to_fields.insert("status".to_string(), "ok".to_string());
+        interval
+    }
+
+    fn eval(text: &str) -> Option<f64> {
+        Expr::parse(text, &["per_item"]).unwrap().eval(&interval())
+    }
+
+    #[test]
+    fn test_eval() {
+        assert_eq!(eval("duration_ms / extracted.items"), Some(375.0));
+        assert_eq!(eval("-2 + 3 * (duration_s - 0.5) % 2"), Some(-1.0));
+        assert_eq!(eval("to.bytes / 1024 / max(from.items, 1, 0.5)"), Some(0.5));
+        assert_eq!(eval("round(abs(-2.6)) + min(1, 2)"), Some(4.0));
+        assert_eq!(eval("from.bytes"), None);
+        assert_eq!(eval("extracted.status * 2"), None);
+        assert_eq!(eval("duration_ms / (from.items - 4)"), None);
+        assert_eq!(eval("per_item"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for text in ["", "1 +", "(1", "1 2", "foo", "from.", "sqrt(4)", "abs(1, 2)", "max()", "1 $ 2"] {
+            assert!(Expr::parse(text, &[]).is_err(), "{} should not parse", text);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+/// Test-only constructors for intervals built by hand, shared by the tests of the library
+/// (through `analyzer::fixtures`) and of the `ll` binary
+use chrono::{Duration, NaiveDateTime};
+
+use super::Interval;
+
+// Each crate's tests use only some of the constructors
+#[allow(dead_code)]
+pub trait IntervalFixture {
+    /// An interval of `duration` from `from` to `to`, without timestamps or captures
+    fn new(from: &str, to: &str, duration: Duration) -> Self;
+    /// The same interval starting at `start` and ending `duration` later
+    fn starting_at(self, start: NaiveDateTime) -> Self;
+}
+
+impl IntervalFixture for Interval {
+    fn new(from: &str, to: &str, duration: Duration) -> Self {
+        Interval { from_pattern: from.to_string(), to_pattern: to.to_string(), duration, ..Default::default() }
+    }
+
+    fn starting_at(self, start: NaiveDateTime) -> Self {
+        Interval { start: Some(start), end: Some(start + self.duration), ..self }
+    }
+}
@@ -0,0 +1,312 @@
+/// Synthetic logs with known interval distributions (`generate` subcommand)
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDateTime};
+use serde::Deserialize;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use log_time_analyzer::duration::parse_duration;
+
+/// Line layout when the scenario doesn't give one
+const DEFAULT_LINE: &str = "{timestamp} [INFO] {message}";
+
+/// First flow start when the scenario doesn't give one, so runs are reproducible
+const DEFAULT_START: &str = "2025-01-01 00:00:00";
+
+/// A scenario file: the steps every flow logs, with the delay before each step
+#[derive(Debug, Deserialize)]
+pub struct Scenario {
+    /// Number of flows to generate (overridden by `--count`)
+    #[serde(default = "default_flows")]
+    pub flows: usize,
+
+    /// Timestamp of the first flow, e.g. `2025-11-13 10:00:00`
+    #[serde(default)]
+    pub start: Option<String>,
+
+    /// Layout of each line with `{timestamp}` and `{message}` placeholders
+    #[serde(default)]
+    pub line: Option<String>,
+
+    /// Steps of each flow in order; the first has no delay
+    pub steps: Vec<Step>,
+
+    /// Unrelated messages, one of which is logged at a random time during each flow
+    #[serde(default)]
+    pub noise: Vec<String>,
+}
+
+/// One message of a flow; `{id}` in it becomes the flow's number
+#[derive(Debug, Deserialize)]
+pub struct Step {
+    pub log: String,
+
+    /// Delay since the previous step: a duration (`200ms`) or a distribution
+    #[serde(default)]
+    pub after: Option<Delay>,
+}
+
+/// How long a step takes: fixed, or drawn from a distribution of durations
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Delay {
+    Fixed(String),
+    Random(Distribution),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "distribution", rename_all = "lowercase")]
+pub enum Distribution {
+    Uniform { min: String, max: String },
+    /// Draws below zero are clamped to zero
+    Normal { mean: String, stddev: String },
+    Exponential { mean: String },
+    /// Log-normal with the given median, and `sigma` of the underlying normal
+    Lognormal { median: String, sigma: f64 },
+}
+
+/// Delay sampler with its durations parsed, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sampler {
+    Fixed(f64),
+    Uniform(f64, f64),
+    Normal(f64, f64),
+    Exponential(f64),
+    Lognormal(f64, f64),
+}
+
+/// SplitMix64: small, fast, and good enough for test data, and the same sequence for a seed
+/// on every platform
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal (Box-Muller)
+    fn next_normal(&mut self) -> f64 {
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+impl Sampler {
+    fn new(delay: &Delay) -> Result<Self> {
+        let ms = |text: &str| -> Result<f64> {
+            let duration = parse_duration(text).with_context(|| format!("Invalid duration '{}'", text))?;
+            Ok(duration.num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0)
+        };
+        let sampler = match delay {
+            Delay::Fixed(value) => Sampler::Fixed(ms(value)?),
+            Delay::Random(Distribution::Uniform { min, max }) => Sampler::Uniform(ms(min)?, ms(max)?),
+            Delay::Random(Distribution::Normal { mean, stddev }) => Sampler::Normal(ms(mean)?, ms(stddev)?),
+            Delay::Random(Distribution::Exponential { mean }) => Sampler::Exponential(ms(mean)?),
+            Delay::Random(Distribution::Lognormal { median, sigma }) => Sampler::Lognormal(ms(median)?, *sigma),
+        };
+        match sampler {
+            Sampler::Uniform(min, max) if min > max => anyhow::bail!("Uniform min is above max"),
+            Sampler::Lognormal(_, sigma) if sigma < 0.0 => anyhow::bail!("Log-normal sigma must not be negative"),
+            _ => Ok(sampler),
+        }
+    }
+
+    fn sample_ms(self, rng: &mut Rng) -> f64 {
+        match self {
+            Sampler::Fixed(ms) => ms,
+            Sampler::Uniform(min, max) => min + (max - min) * rng.next_f64(),
+            Sampler::Normal(mean, stddev) => (mean + stddev * rng.next_normal()).max(0.0),
+            Sampler::Exponential(mean) => -mean * (1.0 - rng.next_f64()).ln(),
+            Sampler::Lognormal(median, sigma) => median * (sigma * rng.next_normal()).exp(),
+        }
+    }
+}
+
+impl Scenario {
+    /// Load and check a scenario file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).with_context(|| format!("Failed to read scenario file: {:?}", path))?;
+        let scenario: Scenario = serde_yaml::from_str(&contents).context("Failed to parse YAML scenario")?;
+        if scenario.steps.len() < 2 {
+            anyhow::bail!("A scenario needs at least 2 steps");
+        }
+        scenario.samplers()?;
+        Ok(scenario)
+    }
+
+    fn samplers(&self) -> Result<Vec<Sampler>> {
+        self.steps
+            .iter()
+            .skip(1)
+            .map(|step| {
+                let delay = step.after.clone().unwrap_or_else(|| Delay::Fixed("0ms".to_string()));
+                Sampler::new(&delay).with_context(|| format!("Invalid delay before step '{}'", step.log))
+            })
+            .collect()
+    }
+}
+
+/// Parse a flow rate such as `100/s`, `30/m`, or `2/h` into the gap between flow starts
+pub fn parse_rate(text: &str) -> Result<Duration> {
+    let (count, unit) = text
+        .split_once('/')
+        .ok_or_else(|| anyhow::anyhow!("Invalid rate '{}', expected e.g. 100/s", text))?;
+    let count: f64 = count
+        .trim()
+        .parse()
+        .ok()
+        .filter(|count: &f64| *count > 0.0)
+        .ok_or_else(|| anyhow::anyhow!("Invalid rate '{}': the count must be a positive number", text))?;
+    let per_us = match unit.trim() {
+        "ms" => 1_000.0,
+        "s" => 1_000_000.0,
+        "m" | "min" => 60_000_000.0,
+        "h" => 3_600_000_000.0,
+        _ => anyhow::bail!("Invalid rate '{}': use /ms, /s, /m, or /h", text),
+    };
+    Ok(Duration::microseconds((per_us / count).round().max(1.0) as i64))
+}
+
+/// chrono format for a `--format` name, or the argument itself when it is a chrono format
+pub fn timestamp_format(name: &str) -> Result<String> {
+    Ok(match name {
+        "standard" => "%Y-%m-%d %H:%M:%S%.3f",
+        "iso8601" => "%Y-%m-%dT%H:%M:%S%.3f",
+        "syslog" => "%b %e %H:%M:%S",
+        "apache" => "%d/%b/%Y:%H:%M:%S +0000",
+        custom if custom.contains('%') => custom,
+        _ => anyhow::bail!(
+            "Unknown timestamp format '{}'. Valid options: standard, iso8601, syslog, apache, or a chrono format",
+            name
+        ),
+    }
+    .to_string())
+}
+
+/// Settings of one `generate` run
+pub struct Options {
+    pub format: String,
+    pub gap: Duration,
+    pub flows: Option<usize>,
+    pub seed: u64,
+}
+
+/// Write the scenario's flows as log lines in timestamp order; returns the number of lines
+pub fn generate(scenario: &Scenario, options: &Options, out: &mut impl Write) -> Result<usize> {
+    let samplers = scenario.samplers()?;
+    let start_text = scenario.start.as_deref().unwrap_or(DEFAULT_START);
+    let start = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(start_text, format).ok())
+        .ok_or_else(|| anyhow::anyhow!("Invalid scenario start '{}', expected e.g. 2025-11-13 10:00:00", start_text))?;
+    let layout = scenario.line.as_deref().unwrap_or(DEFAULT_LINE);
+    let mut rng = Rng(options.seed);
+    let gap_us = options.gap.num_microseconds().unwrap_or(i64::MAX);
+
+    let mut lines: Vec<(NaiveDateTime, String)> = Vec::new();
+    for flow in 0..options.flows.unwrap_or(scenario.flows) {
+        let id = (flow + 1).to_string();
+        let flow_start = start + Duration::microseconds(gap_us.saturating_mul(flow as i64));
+        let mut time = flow_start;
+        for (index, step) in scenario.steps.iter().enumerate() {
+            if index > 0 {
+                let ms = samplers[index - 1].sample_ms(&mut rng);
+                time += Duration::microseconds((ms * 1000.0).round() as i64);
+            }
+            lines.push((time, step.log.replace("{id}", &id)));
+        }
+        if !scenario.noise.is_empty() {
+            let message = &scenario.noise[rng.next_u64() as usize % scenario.noise.len()];
+            let offset = (gap_us as f64 * rng.next_f64()) as i64;
+            lines.push((flow_start + Duration::microseconds(offset), message.replace("{id}", &id)));
+        }
+    }
+
+    // Stable sort keeps the steps of a flow in order when they share a timestamp
+    lines.sort_by_key(|(time, _)| *time);
+    for (time, message) in &lines {
+        let line = layout
+            .replace("{timestamp}", &time.format(&options.format).to_string())
+            .replace("{message}", message);
+        writeln!(out, "{}", line).context("Failed to write generated log")?;
+    }
+    out.flush().context("Failed to write generated log")?;
+
+    Ok(lines.len())
+}
+
+fn default_flows() -> usize {
+    100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(yaml: &str) -> Scenario {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_generate_fixed_delays() {
+        let scenario = scenario(
+            r#"
+flows: 2
+start: '2025-11-13 10:00:00'
+steps:
+  - log: 'Checkout started order={id}'
+  - log: 'Payment done order={id}'
+    after: 1500ms
+"#,
+        );
+        let options = Options {
+            format: timestamp_format("standard").unwrap(),
+            gap: parse_rate("1/s").unwrap(),
+            flows: None,
+            seed: 0,
+        };
+        let mut out = Vec::new();
+        assert_eq!(generate(&scenario, &options, &mut out).unwrap(), 4);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "2025-11-13 10:00:00.000 [INFO] Checkout started order=1\n\
+             2025-11-13 10:00:01.000 [INFO] Checkout started order=2\n\
+             2025-11-13 10:00:01.500 [INFO] Payment done order=1\n\
+             2025-11-13 10:00:02.500 [INFO] Payment done order=2\n"
+        );
+    }
+
+    #[test]
+    fn test_distributions_have_their_mean() {
+        let mut rng = Rng(7);
+        let mean = |sampler: Sampler, rng: &mut Rng| (0..20_000).map(|_| sampler.sample_ms(rng)).sum::<f64>() / 20_000.0;
+        assert!((mean(Sampler::Uniform(100.0, 300.0), &mut rng) - 200.0).abs() < 5.0);
+        assert!((mean(Sampler::Normal(200.0, 20.0), &mut rng) - 200.0).abs() < 5.0);
+        assert!((mean(Sampler::Exponential(200.0), &mut rng) - 200.0).abs() < 10.0);
+
+        let delay: Delay = serde_yaml::from_str("{ distribution: normal, mean: 200ms, stddev: 20ms }").unwrap();
+        assert_eq!(Sampler::new(&delay).unwrap(), Sampler::Normal(200.0, 20.0));
+        let delay: Delay = serde_yaml::from_str("{ distribution: uniform, min: 2s, max: 1s }").unwrap();
+        assert!(Sampler::new(&delay).is_err());
+    }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("100/s").unwrap(), Duration::milliseconds(10));
+        assert_eq!(parse_rate("2/m").unwrap(), Duration::seconds(30));
+        assert!(parse_rate("0/s").is_err());
+        assert!(parse_rate("100").is_err());
+        assert!(parse_rate("5/d").is_err());
+    }
+}
@@ -0,0 +1,209 @@
+/// Grafana dashboard JSON (`-f grafana`): a table panel of every transition's statistics and a
+/// time series panel of each interval's duration, with the data embedded as CSV for Grafana's
+/// built-in TestData data source, so the file can be imported as is
+use chrono::NaiveDateTime;
+use serde_json::{json, Value};
+
+use crate::analyzer::Interval;
+use crate::csv::{CsvDialect, Quoting};
+use crate::stats::TransitionStats;
+
+/// Grafana's built-in data source that can serve CSV pasted into a query
+const DATASOURCE_TYPE: &str = "grafana-testdata-datasource";
+
+/// Dashboard JSON schema the panels are written for (Grafana 10 and later)
+const SCHEMA_VERSION: u32 = 39;
+
+fn datasource() -> Value {
+    json!({ "type": DATASOURCE_TYPE, "uid": DATASOURCE_TYPE })
+}
+
+/// A query of the TestData data source returning `csv` as one data frame
+fn csv_target(ref_id: &str, csv: String) -> Value {
+    json!({
+        "refId": ref_id,
+        "datasource": datasource(),
+        "scenarioId": "csv_content",
+        "csvContent": csv,
+    })
+}
+
+/// RFC 3339 in UTC, which Grafana recognizes as a time column; log timestamps carry no zone,
+/// so the dashboard is set to UTC to show them as they appear in the log
+fn time(timestamp: NaiveDateTime) -> String {
+    timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+/// Milliseconds rounded to the microsecond
+fn ms(value: f64) -> String {
+    ((value * 1000.0).round() / 1000.0).to_string()
+}
+
+/// `A`, `B`, ..., `Z`, `AA`, `AB`, ... like the query letters Grafana assigns itself
+fn ref_id(index: usize) -> String {
+    let mut id = String::new();
+    let mut n = index + 1;
+    while n > 0 {
+        n -= 1;
+        id.insert(0, (b'A' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    id
+}
+
+fn table_panel(stats: &[&TransitionStats], dialect: CsvDialect) -> Value {
+    let mut lines =
+        vec![dialect.header(&["transition", "count", "min", "mean", "median", "p95", "p99", "max"])];
+    for s in stats {
+        lines.push(dialect.record(&[
+            (s.transition(), true),
+            (s.count.to_string(), false),
+            (s.min_ms.to_string(), false),
+            (ms(s.mean_ms), false),
+            (ms(s.percentile(50.0)), false),
+            (ms(s.percentile(95.0)), false),
+            (ms(s.percentile(99.0)), false),
+            (s.max_ms.to_string(), false),
+        ]));
+    }
+    json!({
+        "id": 1,
+        "type": "table",
+        "title": "Durations per transition",
+        "datasource": datasource(),
+        "gridPos": { "h": 8, "w": 24, "x": 0, "y": 0 },
+        "fieldConfig": {
+            "defaults": { "unit": "ms" },
+            "overrides": [
+                {
+                    "matcher": { "id": "byName", "options": "count" },
+                    "properties": [{ "id": "unit", "value": "none" }],
+                },
+                {
+                    "matcher": { "id": "byName", "options": "transition" },
+                    "properties": [{ "id": "unit", "value": "string" }],
+                },
+            ],
+        },
+        "targets": [csv_target("A", lines.join("\n"))],
+    })
+}
+
+/// One series per transition, of the intervals with a start timestamp
+fn timeseries_panel(series: Vec<(String, Vec<&Interval>)>, dialect: CsvDialect) -> Value {
+    let targets: Vec<Value> = series
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, intervals))| {
+            let mut lines = vec![dialect.header(&["time", &name])];
+            lines.extend(intervals.iter().filter_map(|i| {
+                let start = i.start?;
+                Some(dialect.record(&[(time(start), false), (i.duration.num_milliseconds().to_string(), false)]))
+            }));
+            csv_target(&ref_id(index), lines.join("\n"))
+        })
+        .collect();
+    json!({
+        "id": 2,
+        "type": "timeseries",
+        "title": "Interval durations",
+        "datasource": datasource(),
+        "gridPos": { "h": 10, "w": 24, "x": 0, "y": 8 },
+        "fieldConfig": {
+            "defaults": {
+                "unit": "ms",
+                "custom": { "drawStyle": "points", "pointSize": 5 },
+            },
+            "overrides": [],
+        },
+        "options": { "legend": { "displayMode": "table", "placement": "bottom", "calcs": ["mean", "max"] } },
+        "targets": targets,
+    })
+}
+
+/// A dashboard to import into Grafana: the statistics of every transition with intervals in a
+/// table, and, when `intervals` have timestamps, their durations over time with a series per
+/// transition; its time range spans the intervals
+pub fn format_dashboard(intervals: &[Interval], stats: &[TransitionStats]) -> String {
+    let dialect = CsvDialect { delimiter: ',', quoting: Quoting::Minimal };
+    let stats: Vec<&TransitionStats> = stats.iter().filter(|s| s.count > 0).collect();
+    let mut panels = vec![table_panel(&stats, dialect)];
+
+    let timed: Vec<&Interval> = intervals.iter().filter(|i| i.start.is_some()).collect();
+    if !timed.is_empty() {
+        let series = stats
+            .iter()
+            .map(|s| {
+                let intervals = timed
+                    .iter()
+                    .copied()
+                    .filter(|i| i.from_pattern == s.from_pattern && i.to_pattern == s.to_pattern && i.group == s.group)
+                    .collect();
+                (s.transition(), intervals)
+            })
+            .collect();
+        panels.push(timeseries_panel(series, dialect));
+    }
+
+    let start = timed.iter().filter_map(|i| i.start).min();
+    let end = timed.iter().filter_map(|i| i.end.or(i.start)).max();
+    let range = match (start, end) {
+        (Some(start), Some(end)) => json!({ "from": time(start), "to": time(end) }),
+        _ => json!({ "from": "now-6h", "to": "now" }),
+    };
+
+    let dashboard = json!({
+        "title": "Log-line intervals",
+        "tags": ["log-line"],
+        "timezone": "utc",
+        "editable": true,
+        "schemaVersion": SCHEMA_VERSION,
+        "time": range,
+        "panels": panels,
+    });
+    serde_json::to_string_pretty(&dashboard).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
+    use crate::stats;
+    use chrono::Duration;
+
+    #[test]
+    fn test_dashboard_panels() {
+        let start = NaiveDateTime::parse_from_str("2025-11-13 10:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let interval = |seconds: i64, ms: i64, to: &str| {
+            Interval::new("request", to, Duration::milliseconds(ms)).starting_at(start + Duration::seconds(seconds))
+        };
+        let intervals = [interval(0, 100, "done"), interval(60, 300, "done"), interval(90, 50, "failed, retrying")];
+        let dashboard: Value =
+            serde_json::from_str(&format_dashboard(&intervals, &stats::compute(&intervals, None))).unwrap();
+
+        assert_eq!(dashboard["time"], json!({ "from": "2025-11-13T10:00:00.000Z", "to": "2025-11-13T10:01:30.050Z" }));
+        let table = &dashboard["panels"][0];
+        assert_eq!(table["type"], "table");
+        assert_eq!(
+            table["targets"][0]["csvContent"],
+            "transition,count,min,mean,median,p95,p99,max\n\
+             request -> done,2,100,200,200,290,298,300\n\
+             \"request -> failed, retrying\",1,50,50,50,50,50,50"
+        );
+
+        let series = &dashboard["panels"][1]["targets"];
+        assert_eq!(series[0]["refId"], "A");
+        assert_eq!(
+            series[0]["csvContent"],
+            "time,request -> done\n2025-11-13T10:00:00.000Z,100\n2025-11-13T10:01:00.000Z,300"
+        );
+        assert_eq!(series[1]["refId"], "B");
+
+        let untimed = [Interval { start: None, end: None, ..intervals[0].clone() }];
+        let dashboard: Value =
+            serde_json::from_str(&format_dashboard(&untimed, &stats::compute(&untimed, None))).unwrap();
+        assert_eq!(dashboard["panels"].as_array().unwrap().len(), 1);
+        assert_eq!(dashboard["time"]["from"], "now-6h");
+        assert_eq!(ref_id(26), "AA");
+    }
+}
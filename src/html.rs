@@ -0,0 +1,409 @@
+/// Standalone HTML report (`-f html`): transition statistics, duration histograms and a
+/// timeline as inline SVG, and a sortable table of the intervals. The CSS and JavaScript are
+/// inline and nothing is loaded from the network, so the file can be attached to a ticket.
+use chrono::NaiveDateTime;
+
+use crate::analyzer::Interval;
+use crate::locale::Locale;
+use crate::markers::Marker;
+use crate::output::{extra_columns, localized_count, localized_ms, HistogramOptions};
+use crate::stats::TransitionStats;
+
+/// Intervals beyond this many are left out of the table and the timeline, which would
+/// otherwise make the page too large to open
+const MAX_INTERVALS: usize = 10_000;
+
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.6em; } h2 { font-size: 1.25em; margin-top: 2em; }
+table { border-collapse: collapse; font-size: 0.9em; }
+th, td { border: 1px solid #ccc; padding: 3px 8px; text-align: left; white-space: nowrap; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+table.sortable th { cursor: pointer; background: #f3f3f3; user-select: none; }
+th[aria-sort=ascending]::after { content: ' \\25B2'; } th[aria-sort=descending]::after { content: ' \\25BC'; }
+figure { display: inline-block; margin: 0 2em 1.5em 0; } figcaption { font-weight: 600; margin-bottom: 4px; }
+svg text { font-size: 11px; fill: #444; } rect.bar { fill: #4a7fb5; } rect.bar:hover { fill: #e07b39; }
+line.marker { stroke: #c0392b; stroke-dasharray: 4 3; } svg text.marker { fill: #c0392b; }
+.note { color: #777; }
+.legend { display: inline-block; width: 120px; height: 0.8em; vertical-align: middle;
+  background: linear-gradient(to right, hsl(120, 70%, 82%), hsl(60, 70%, 82%), hsl(0, 70%, 82%)); }";
+
+/// Sorts a table by the clicked column: by each cell's `data-value` when it has one,
+/// otherwise by its text
+const SCRIPT: &str = "\
+document.querySelectorAll('table.sortable th').forEach(function (th) {
+  th.addEventListener('click', function () {
+    var table = th.closest('table'), body = table.tBodies[0], col = th.cellIndex;
+    var ascending = th.getAttribute('aria-sort') !== 'ascending';
+    table.querySelectorAll('th').forEach(function (other) { other.removeAttribute('aria-sort'); });
+    th.setAttribute('aria-sort', ascending ? 'ascending' : 'descending');
+    var key = function (row) {
+      var cell = row.cells[col], value = cell.getAttribute('data-value');
+      return value === null ? cell.textContent : parseFloat(value);
+    };
+    var rows = Array.prototype.slice.call(body.rows);
+    rows.sort(function (a, b) {
+      var x = key(a), y = key(b);
+      var order = typeof x === 'number' && typeof y === 'number'
+        ? x - y : String(x).localeCompare(String(y), undefined, { numeric: true });
+      return ascending ? order : -order;
+    });
+    rows.forEach(function (row) { body.appendChild(row); });
+  });
+});";
+
+/// Escape text for HTML content and attribute values
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// The report page: statistics and histograms of every transition, and with `intervals` a
+/// timeline with the `markers` in its span and a table of the intervals as well
+pub fn format_report(
+    intervals: Option<&[Interval]>,
+    stats: &[TransitionStats],
+    histogram: HistogramOptions,
+    markers: &[Marker],
+    locale: Option<Locale>,
+) -> String {
+    let title = "Log time analysis";
+    let mut body = format!("<h1>{}</h1>\n", title);
+    let count: usize = stats.iter().map(|s| s.count).sum();
+    body.push_str(&format!(
+        "<p>{} intervals of {} transitions{}</p>\n",
+        localized_count(count, locale),
+        localized_count(stats.len(), locale),
+        intervals.and_then(period).unwrap_or_default()
+    ));
+
+    body.push_str("<h2>Transitions</h2>\n");
+    body.push_str(&stats_table(stats, locale));
+    body.push_str("<h2>Durations</h2>\n");
+    for s in stats.iter().filter(|s| s.count > 0) {
+        body.push_str(&histogram_figure(s, histogram, locale));
+    }
+
+    if let Some(intervals) = intervals {
+        let shown = &intervals[..intervals.len().min(MAX_INTERVALS)];
+        let note = if shown.len() < intervals.len() {
+            format!(
+                "<p class=\"note\">The first {} of {} intervals are shown.</p>\n",
+                localized_count(shown.len(), locale),
+                localized_count(intervals.len(), locale)
+            )
+        } else {
+            String::new()
+        };
+        if let Some(timeline) = timeline(shown, stats, markers, locale) {
+            body.push_str("<h2>Timeline</h2>\n");
+            body.push_str(&note);
+            body.push_str(&timeline);
+        }
+        body.push_str("<h2>Intervals</h2>\n");
+        body.push_str(&note);
+        body.push_str(
+            "<p class=\"note\">Durations are colored by their rank within their transition, from the fastest \
+             <span class=\"legend\"></span> to the slowest. Click a column header to sort by it.</p>\n",
+        );
+        body.push_str(&interval_table(shown, stats, locale));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n\
+         <style>\n{}\n</style>\n</head>\n<body>\n{}<script>\n{}\n</script>\n</body>\n</html>",
+        title, STYLE, body, SCRIPT
+    )
+}
+
+/// `, from <first start> to <last end>`, when the intervals have timestamps
+fn period(intervals: &[Interval]) -> Option<String> {
+    let first = intervals.iter().filter_map(|i| i.start).min()?;
+    let last = intervals.iter().filter_map(|i| i.end).max()?;
+    Some(format!(", from {} to {}", timestamp(first), timestamp(last)))
+}
+
+fn timestamp(timestamp: NaiveDateTime) -> String {
+    timestamp.format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+}
+
+/// A `<td>` of a duration, sorted by its milliseconds
+fn duration_cell(ms: f64, locale: Option<Locale>) -> String {
+    format!("<td class=\"num\" data-value=\"{}\">{}</td>", ms, escape(&localized_ms(ms, locale)))
+}
+
+/// A `<td>` of an interval's duration, colored from green to red by how it ranks among the
+/// durations of its transition (`stats`)
+fn heat_cell(ms: i64, stats: &TransitionStats, locale: Option<Locale>) -> String {
+    let rank = percentile_rank(&stats.sorted_ms, ms);
+    format!(
+        "<td class=\"num\" data-value=\"{}\" style=\"background: hsl({:.0}, 70%, 82%)\" title=\"p{:.0} of {}\">{}</td>",
+        ms,
+        120.0 * (1.0 - rank),
+        rank * 100.0,
+        escape(&stats.transition()),
+        escape(&localized_ms(ms as f64, locale))
+    )
+}
+
+/// Share (0-1) of `sorted` durations below `ms`, counting equal ones as half below; 0.5 for
+/// a transition with a single duration, which is neither fast nor slow
+fn percentile_rank(sorted: &[i64], ms: i64) -> f64 {
+    if sorted.len() < 2 {
+        return 0.5;
+    }
+    let below = sorted.partition_point(|&d| d < ms);
+    let equal = sorted[below..].partition_point(|&d| d == ms);
+    ((below as f64 + equal as f64 / 2.0) / sorted.len() as f64).clamp(0.0, 1.0)
+}
+
+/// Index and statistics of the transition (and group) `interval` belongs to
+fn transition_of<'a>(interval: &Interval, stats: &'a [TransitionStats]) -> Option<(usize, &'a TransitionStats)> {
+    stats.iter().enumerate().find(|(_, s)| {
+        s.from_pattern == interval.from_pattern && s.to_pattern == interval.to_pattern && s.group == interval.group
+    })
+}
+
+fn stats_table(stats: &[TransitionStats], locale: Option<Locale>) -> String {
+    let mut table = String::from(
+        "<table class=\"sortable\">\n<thead><tr><th>Transition</th><th>Count</th><th>Min</th><th>Max</th>\
+         <th>Mean</th><th>Median</th><th>P95</th><th>P99</th><th>Stddev</th></tr></thead>\n<tbody>\n",
+    );
+    for s in stats {
+        table.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num\" data-value=\"{}\">{}</td>",
+            escape(&s.transition()),
+            s.count,
+            localized_count(s.count, locale)
+        ));
+        for ms in [
+            s.min_ms as f64,
+            s.max_ms as f64,
+            s.mean_ms,
+            s.percentile(50.0),
+            s.percentile(95.0),
+            s.percentile(99.0),
+            s.stddev_ms,
+        ] {
+            table.push_str(&duration_cell(ms, locale));
+        }
+        table.push_str("</tr>\n");
+    }
+    table.push_str("</tbody>\n</table>\n");
+    table
+}
+
+/// Bars of the number of intervals per duration bucket, as in the histogram format
+fn histogram_figure(stats: &TransitionStats, options: HistogramOptions, locale: Option<Locale>) -> String {
+    const WIDTH: f64 = 480.0;
+    const HEIGHT: f64 = 120.0;
+
+    let buckets = options.buckets(stats);
+    let fullest = buckets.iter().map(|&(_, _, count)| count).max().unwrap_or(0).max(1) as f64;
+    let bar_width = WIDTH / buckets.len() as f64;
+    let mut svg = format!(
+        "<svg width=\"{}\" height=\"{}\" role=\"img\">\n",
+        WIDTH,
+        HEIGHT + 18.0
+    );
+    for (i, &(low, high, count)) in buckets.iter().enumerate() {
+        let height = (count as f64 / fullest * HEIGHT).max(if count > 0 { 1.0 } else { 0.0 });
+        svg.push_str(&format!(
+            "<rect class=\"bar\" x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\">\
+             <title>{} - {}: {}</title></rect>\n",
+            i as f64 * bar_width + 1.0,
+            HEIGHT - height,
+            (bar_width - 2.0).max(1.0),
+            height,
+            escape(&localized_ms(low, locale)),
+            escape(&localized_ms(high, locale)),
+            localized_count(count, locale)
+        ));
+    }
+    if let (Some(&(low, _, _)), Some(&(_, high, _))) = (buckets.first(), buckets.last()) {
+        svg.push_str(&format!(
+            "<text x=\"0\" y=\"{}\">{}</text><text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n",
+            HEIGHT + 14.0,
+            escape(&localized_ms(low, locale)),
+            WIDTH,
+            HEIGHT + 14.0,
+            escape(&localized_ms(high, locale))
+        ));
+    }
+    svg.push_str("</svg>");
+
+    format!(
+        "<figure><figcaption>{} (n={})</figcaption>\n{}\n</figure>\n",
+        escape(&stats.transition()),
+        localized_count(stats.count, locale),
+        svg
+    )
+}
+
+/// One lane per transition with a bar per interval from its start, over the time the
+/// intervals span, crossed by a dashed line at each marker in that time; `None` without
+/// timestamps
+fn timeline(intervals: &[Interval], stats: &[TransitionStats], markers: &[Marker], locale: Option<Locale>) -> Option<String> {
+    const LABEL_WIDTH: f64 = 260.0;
+    const PLOT_WIDTH: f64 = 900.0;
+    const LANE_HEIGHT: f64 = 18.0;
+
+    // Out-of-order timestamps give negative durations; those are drawn from the earlier end
+    let span = |i: &Interval| {
+        let start = i.start?;
+        let end = start + i.duration;
+        Some((start.min(end), start.max(end)))
+    };
+    let origin = intervals.iter().filter_map(span).map(|(start, _)| start).min()?;
+    let last = intervals.iter().filter_map(span).map(|(_, end)| end).max()?;
+    let total_ms = ((last - origin).num_milliseconds() as f64).max(1.0);
+    let x = |time: NaiveDateTime| LABEL_WIDTH + (time - origin).num_milliseconds() as f64 / total_ms * PLOT_WIDTH;
+
+    let markers: Vec<&Marker> = markers.iter().filter(|m| (origin..=last).contains(&m.time)).collect();
+
+    let height = stats.len() as f64 * LANE_HEIGHT;
+    // The marker labels get a row of their own below the time axis
+    let labels_height = if markers.is_empty() { 0.0 } else { 16.0 };
+    let mut svg = format!(
+        "<svg width=\"{}\" height=\"{}\" role=\"img\">\n",
+        LABEL_WIDTH + PLOT_WIDTH + 10.0,
+        height + 18.0 + labels_height
+    );
+    for (n, s) in stats.iter().enumerate() {
+        svg.push_str(&format!(
+            "<text x=\"0\" y=\"{:.1}\">{}</text>\n",
+            n as f64 * LANE_HEIGHT + 13.0,
+            escape(&s.transition())
+        ));
+    }
+    for interval in intervals {
+        let (Some((start, end)), Some((n, _))) = (span(interval), transition_of(interval, stats)) else {
+            continue;
+        };
+        svg.push_str(&format!(
+            "<rect class=\"bar\" x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\">\
+             <title>{}: {} at {}</title></rect>\n",
+            x(start),
+            n as f64 * LANE_HEIGHT + 3.0,
+            (x(end) - x(start)).max(1.0),
+            LANE_HEIGHT - 6.0,
+            escape(&format!("{} -> {}", interval.from_pattern, interval.to_pattern)),
+            escape(&localized_ms(interval.duration.num_milliseconds() as f64, locale)),
+            timestamp(start)
+        ));
+    }
+    for marker in markers {
+        svg.push_str(&format!(
+            "<line class=\"marker\" x1=\"{x:.1}\" y1=\"0\" x2=\"{x:.1}\" y2=\"{:.1}\"><title>{}</title></line>\n\
+             <text class=\"marker\" x=\"{x:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>\n",
+            height,
+            escape(&format!("{} at {}", marker.label, timestamp(marker.time))),
+            height + 30.0,
+            escape(&marker.label),
+            x = x(marker.time)
+        ));
+    }
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\">{}</text><text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n</svg>\n",
+        LABEL_WIDTH,
+        height + 14.0,
+        timestamp(origin),
+        LABEL_WIDTH + PLOT_WIDTH,
+        height + 14.0,
+        timestamp(last)
+    ));
+    Some(svg)
+}
+
+fn interval_table(intervals: &[Interval], stats: &[TransitionStats], locale: Option<Locale>) -> String {
+    let extras = extra_columns(intervals, locale);
+    let mut table = String::from(
+        "<table class=\"sortable\">\n<thead><tr><th>#</th><th>From</th><th>To</th><th>Duration</th><th>Start</th>",
+    );
+    for column in &extras {
+        table.push_str(&format!("<th>{}</th>", escape(&column.title)));
+    }
+    table.push_str("</tr></thead>\n<tbody>\n");
+
+    for (n, interval) in intervals.iter().enumerate() {
+        table.push_str(&format!(
+            "<tr><td class=\"num\" data-value=\"{}\">{}</td><td>{}</td><td>{}</td>{}<td>{}</td>",
+            n + 1,
+            n + 1,
+            escape(&interval.from_pattern),
+            escape(&interval.to_pattern),
+            match transition_of(interval, stats) {
+                Some((_, stats)) => heat_cell(interval.duration.num_milliseconds(), stats, locale),
+                None => duration_cell(interval.duration.num_milliseconds() as f64, locale),
+            },
+            interval.start.map(timestamp).unwrap_or_default()
+        ));
+        for column in &extras {
+            table.push_str(&format!("<td>{}</td>", escape(&(column.value)(interval))));
+        }
+        table.push_str("</tr>\n");
+    }
+    table.push_str("</tbody>\n</table>\n");
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
+    use crate::stats;
+    use chrono::Duration;
+
+    #[test]
+    fn test_report_is_self_contained() {
+        let start = NaiveDateTime::parse_from_str("2025-11-13 10:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let intervals: Vec<Interval> = [120, 900, 300]
+            .iter()
+            .enumerate()
+            .map(|(n, &ms)| Interval::new("<start>", "end", Duration::milliseconds(ms)).starting_at(start + Duration::seconds(n as i64)))
+            .collect();
+        let stats = stats::compute(&intervals, None);
+
+        let markers = [
+            Marker { time: start + Duration::seconds(1), label: "deploy <v2>".to_string() },
+            Marker { time: start + Duration::hours(1), label: "later".to_string() },
+        ];
+
+        let html = format_report(Some(&intervals), &stats, HistogramOptions::default(), &markers, None);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.ends_with("</html>"));
+        assert!(!html.contains("http"), "nothing is loaded from the network");
+        assert!(html.contains("<p>3 intervals of 1 transitions, from 2025-11-13 10:00:00.000 to 2025-11-13 10:00:02.300</p>"));
+        assert!(html.contains("<td>&lt;start&gt; -&gt; end</td>"), "text is escaped");
+        assert!(html.contains("<td class=\"num\" data-value=\"900\">900ms</td>"), "the statistics' max");
+        assert_eq!(html.matches("<h2>").count(), 4);
+        assert_eq!(html.matches("<title>&lt;start&gt; -&gt; end: ").count(), 3, "a timeline bar per interval");
+        assert!(html.contains("text-anchor=\"middle\">deploy &lt;v2&gt;</text>"), "a marker in the timeline's span");
+        assert_eq!(html.matches("<line class=\"marker\"").count(), 1, "markers outside the span are left out");
+
+        let stats_only = format_report(None, &stats, HistogramOptions::default(), &[], None);
+        assert!(!stats_only.contains("<h2>Intervals</h2>") && !stats_only.contains("<h2>Timeline</h2>"));
+    }
+
+    #[test]
+    fn test_duration_cells_are_colored_by_rank_in_their_transition() {
+        let interval = |from: &str, ms: i64| Interval::new(from, "end", Duration::milliseconds(ms));
+        // The same 100ms is the slowest of one transition and the fastest of the other
+        let intervals = vec![
+            interval("a", 10),
+            interval("a", 50),
+            interval("a", 100),
+            interval("b", 100),
+            interval("b", 500),
+            interval("b", 900),
+        ];
+        let stats = stats::compute(&intervals, None);
+        let html = format_report(Some(&intervals), &stats, HistogramOptions::default(), &[], None);
+        assert!(html.contains("data-value=\"100\" style=\"background: hsl(20, 70%, 82%)\" title=\"p83 of a -&gt; end\""));
+        assert!(html.contains("data-value=\"100\" style=\"background: hsl(100, 70%, 82%)\" title=\"p17 of b -&gt; end\""));
+
+        assert_eq!(percentile_rank(&[100], 100), 0.5);
+        assert_eq!(percentile_rank(&[10, 20, 20, 30], 20), 0.5);
+        assert_eq!(percentile_rank(&[10, 20], 5), 0.0);
+    }
+}
@@ -0,0 +1,95 @@
+/// Durable append-only record of the intervals found in follow mode (`--journal`)
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use log_time_analyzer::analyzer::Interval;
+
+/// An NDJSON file with one interval per line, synced to disk after every interval so a
+/// crash or reboot loses at most the line being written
+pub struct Journal {
+    file: File,
+    path: PathBuf,
+}
+
+impl Journal {
+    /// Open `path` for appending, creating it if needed; records of earlier runs are kept
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .with_context(|| format!("Failed to open journal {:?}", path))?;
+
+        // A crash mid-write leaves a partial last line; start the next record on a line of its own
+        let len = file.metadata().with_context(|| format!("Failed to read journal {:?}", path))?.len();
+        if len > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::Start(len - 1))
+                .and_then(|_| file.read_exact(&mut last))
+                .with_context(|| format!("Failed to read journal {:?}", path))?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n").with_context(|| format!("Failed to write journal {:?}", path))?;
+            }
+        }
+
+        Ok(Journal {
+            file,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Append one interval as a JSON line and wait until it is on disk
+    pub fn append(&mut self, interval: &Interval) -> Result<()> {
+        let mut line = serde_json::to_string(interval).context("Failed to serialize interval")?;
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .and_then(|_| self.file.sync_data())
+            .with_context(|| format!("Failed to write journal {:?}", self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+    use chrono::Duration;
+    use std::fs;
+
+    #[test]
+    fn test_journal_appends_across_runs_after_partial_line() {
+        let path = std::env::temp_dir().join(format!("ll-journal-test-{}.ndjson", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let interval = |ms: i64| Interval::new("A", "B", Duration::milliseconds(ms));
+
+        Journal::open(&path).unwrap().append(&interval(10)).unwrap();
+        // Simulate a crash in the middle of the next record
+        fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"from_pat").unwrap();
+        Journal::open(&path).unwrap().append(&interval(20)).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                r#"{"from_pattern":"A","to_pattern":"B","duration_ms":10}"#,
+                r#"{"from_pat"#,
+                r#"{"from_pattern":"A","to_pattern":"B","duration_ms":20}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_journal_in_missing_directory_is_an_error() {
+        let path = std::env::temp_dir()
+            .join(format!("ll-journal-missing-{}", std::process::id()))
+            .join("intervals.ndjson");
+        let error = Journal::open(&path).err().unwrap();
+        assert!(error.to_string().starts_with("Failed to open journal"), "{}", error);
+        assert!(!path.exists());
+    }
+}
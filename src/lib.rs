@@ -0,0 +1,63 @@
+//! Measure the time between messages in log files.
+//!
+//! A [`Config`] names the message patterns to look for and how timestamps are read (or
+//! that they are auto-detected). A [`LogParser`] turns log lines into [`LogMatch`]es,
+//! [`Analyzer`] turns matches into [`Interval`]s, and [`OutputFormatter`] renders them in
+//! any of the CLI's output formats:
+//!
+//! ```
+//! use log_time_analyzer::{Analyzer, LogParser, OutputFormat, OutputFormatter};
+//!
+//! let log = "\
+//! 2025-11-13 10:00:00.100 request received
+//! 2025-11-13 10:00:00.350 response sent
+//! ";
+//! let mut parser = LogParser::builder()
+//!     .pattern("request received")
+//!     .pattern("response sent")
+//!     .build()?;
+//! let matches = parser.parse_reader(log.as_bytes())?;
+//! let intervals = Analyzer::analyze(matches);
+//!
+//! assert_eq!(intervals[0].duration.num_milliseconds(), 250);
+//! println!("{}", OutputFormatter::format_intervals(&intervals, OutputFormat::Json));
+//! # Ok::<(), log_time_analyzer::LogLineError>(())
+//! ```
+//!
+//! With the `async` feature, `LogParser` also gains Tokio-based `parse_async_reader`,
+//! `parse_file_async`, and `follow_async`.
+
+pub mod analyzer;
+#[cfg(feature = "async")]
+mod async_io;
+pub mod builder;
+pub mod capabilities;
+pub mod clock;
+pub mod config;
+pub mod csv;
+pub mod decompress;
+pub mod duration;
+pub mod error;
+pub mod expr;
+pub mod grafana;
+pub mod html;
+pub mod locale;
+pub mod maintenance;
+pub mod markers;
+pub mod output;
+pub mod parser;
+pub mod progress;
+pub mod prometheus;
+pub mod schema;
+pub mod spans;
+pub mod stats;
+pub mod style;
+pub mod tags;
+pub mod timestamp_formats;
+
+pub use analyzer::{Analyzer, Interval};
+pub use builder::{ConfigBuilder, ParserBuilder};
+pub use config::Config;
+pub use error::{LogLineError, Result};
+pub use output::{OutputFormat, OutputFormatter};
+pub use parser::{LogMatch, LogParser, ParseStats};
@@ -0,0 +1,174 @@
+/// Locale-aware numbers and durations for the outputs people read (`--locale`)
+use chrono::Duration;
+
+use crate::error::{LogLineError, Result};
+
+/// Separates numbers from their units, and groups digits in some locales
+const NARROW_NO_BREAK_SPACE: char = '\u{202F}';
+
+/// Separators of one locale. Durations use narrow unit symbols (`h`, `min`, `s`, `ms`)
+/// after a narrow no-break space in every locale, e.g. `1,5 s` or `2 min 5 s`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    decimal: char,
+    group: char,
+    /// Whether a space goes before `%`, as in `12,5 %`
+    space_before_percent: bool,
+}
+
+impl Locale {
+    /// English separators: `1,234.5`
+    pub const ENGLISH: Locale = Locale {
+        decimal: '.',
+        group: ',',
+        space_before_percent: false,
+    };
+
+    /// Locale for a language tag such as `de`, `fr-FR`, or `pt_BR.UTF-8`; `None` for
+    /// languages without known separators
+    pub fn parse(tag: &str) -> Option<Self> {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default().replace('_', "-").to_lowercase();
+        let language = tag.split('-').next().unwrap_or_default();
+        let (decimal, group) = match (language, tag.as_str()) {
+            (_, "de-ch" | "it-ch") => ('.', '\u{2019}'),
+            ("en" | "c" | "posix" | "ja" | "zh" | "ko" | "he", _) => ('.', ','),
+            ("de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el", _) => (',', '.'),
+            ("fr" | "sv" | "nb" | "nn" | "no" | "fi" | "pl" | "cs" | "sk" | "ru" | "uk" | "hu", _) => {
+                (',', NARROW_NO_BREAK_SPACE)
+            }
+            _ => return None,
+        };
+        Some(Locale {
+            decimal,
+            group,
+            space_before_percent: decimal == ',',
+        })
+    }
+
+    /// Locale of the environment (`LC_ALL`, `LC_NUMERIC`, then `LANG`), if set and known
+    pub fn from_env() -> Option<Self> {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|tag| Locale::parse(&tag))
+    }
+
+    /// Whole number with digit grouping, e.g. `12.345`
+    pub fn integer(self, value: i64) -> String {
+        let digits = value.unsigned_abs().to_string();
+        let mut grouped = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(self.group);
+            }
+            grouped.push(digit);
+        }
+        if value < 0 {
+            format!("-{}", grouped)
+        } else {
+            grouped
+        }
+    }
+
+    /// Number with at most `decimals` decimals and no trailing zeros, e.g. `1.234,5`
+    pub fn number(self, value: f64, decimals: usize) -> String {
+        let text = format!("{:.*}", decimals, value.abs());
+        let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        let fraction = fraction.trim_end_matches('0');
+        let mut output = self.integer(whole.parse().unwrap_or(0));
+        if !fraction.is_empty() {
+            output.push(self.decimal);
+            output.push_str(fraction);
+        }
+        if value < 0.0 && output.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            output.insert(0, '-');
+        }
+        output
+    }
+
+    /// Signed percentage with one decimal, e.g. `+53,8 %`
+    pub fn percent(self, value: f64) -> String {
+        let sign = if value >= 0.0 { "+" } else { "-" };
+        let space = if self.space_before_percent { NARROW_NO_BREAK_SPACE.to_string() } else { String::new() };
+        format!("{}{}{}%", sign, self.number(value.abs(), 1), space)
+    }
+
+    /// Duration in narrow units, with seconds to the millisecond under a minute, e.g.
+    /// `1 h 1 min 1 s`, `1,5 s`, or `500 ms`
+    pub fn duration(self, duration: &Duration) -> String {
+        let ms = duration.num_milliseconds();
+        let sign = if ms < 0 { "-" } else { "" };
+        let ms = ms.abs();
+        let (hours, minutes, seconds) = (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60);
+        let unit = |value: String, unit: &str| format!("{}{}{}", value, NARROW_NO_BREAK_SPACE, unit);
+
+        let text = if hours > 0 {
+            format!(
+                "{} {} {}",
+                unit(self.integer(hours), "h"),
+                unit(minutes.to_string(), "min"),
+                unit(seconds.to_string(), "s")
+            )
+        } else if minutes > 0 {
+            format!("{} {}", unit(minutes.to_string(), "min"), unit(seconds.to_string(), "s"))
+        } else if seconds > 0 {
+            unit(self.number(ms as f64 / 1000.0, 3), "s")
+        } else {
+            unit(ms.to_string(), "ms")
+        };
+        format!("{}{}", sign, text)
+    }
+
+    /// Duration given in (possibly fractional) milliseconds, as in the statistics
+    pub fn ms(self, ms: f64) -> String {
+        self.duration(&Duration::microseconds((ms * 1000.0).round() as i64))
+    }
+}
+
+/// Locale for `--locale`: a language tag, or `auto` for the environment's (English when
+/// unset or unknown)
+pub fn resolve(tag: &str) -> Result<Locale> {
+    if tag.eq_ignore_ascii_case("auto") {
+        return Ok(Locale::from_env().unwrap_or(Locale::ENGLISH));
+    }
+    Locale::parse(tag).ok_or_else(|| {
+        LogLineError::config(format!(
+            "Unknown locale '{}'. Use a language tag such as en, de, fr, or pt-BR, or auto",
+            tag
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_separators_follow_the_locale() {
+        let en = Locale::parse("en_US.UTF-8").unwrap();
+        let de = Locale::parse("de").unwrap();
+        let fr = Locale::parse("fr-FR").unwrap();
+        let swiss = Locale::parse("de_CH").unwrap();
+
+        assert_eq!(en.number(12345.678, 2), "12,345.68");
+        assert_eq!(de.number(12345.678, 2), "12.345,68");
+        assert_eq!(fr.number(12345.5, 3), "12\u{202F}345,5");
+        assert_eq!(swiss.integer(-1234567), "-1\u{2019}234\u{2019}567");
+        assert_eq!(de.number(-0.0001, 3), "0");
+        assert_eq!(en.percent(53.84), "+53.8%");
+        assert_eq!(de.percent(-4.0), "-4\u{202F}%");
+        assert!(Locale::parse("xx").is_none());
+    }
+
+    #[test]
+    fn test_durations_use_narrow_units() {
+        let de = Locale::parse("de").unwrap();
+
+        assert_eq!(de.duration(&Duration::seconds(3661)), "1\u{202F}h 1\u{202F}min 1\u{202F}s");
+        assert_eq!(de.duration(&Duration::seconds(125)), "2\u{202F}min 5\u{202F}s");
+        assert_eq!(de.duration(&Duration::milliseconds(1500)), "1,5\u{202F}s");
+        assert_eq!(de.duration(&Duration::milliseconds(-500)), "-500\u{202F}ms");
+        assert_eq!(Locale::ENGLISH.ms(1234.4), "1.234\u{202F}s");
+    }
+}
@@ -1,34 +1,194 @@
-mod config;
-mod parser;
-mod analyzer;
-mod output;
-mod timestamp_formats;
+mod assertions;
+mod batch;
+mod compare;
+#[cfg(feature = "sql")]
+mod database;
+mod email;
+#[cfg(test)]
+mod fixtures;
+mod generate;
+mod journal;
+mod notify;
+mod outliers;
+mod priority;
+mod profiles;
+#[cfg(feature = "sql")]
+mod query;
+mod report;
+mod sink;
+mod sla;
+mod slo;
+mod traces;
 
 use anyhow::{Context, Result};
-use clap::Parser as ClapParser;
-use std::path::PathBuf;
-use std::io::{self, IsTerminal};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser as ClapParser, Subcommand};
+use std::path::{Path, PathBuf};
+use std::io::{self, BufRead, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use config::Config;
-use parser::LogParser;
-use analyzer::Analyzer;
-use output::{OutputFormat, OutputFormatter};
+use log_time_analyzer::analyzer::{format_duration, Analyzer, ConsecutiveTracker, Interval, IntervalRules, PairTracker, Warmup};
+use log_time_analyzer::config::{Config, DerivedValue, InputFormat, MessagePattern, ReportedDuration};
+use log_time_analyzer::output::{BarScale, HistogramOptions, OutputFormat, OutputFormatter, Page, WaterfallOptions};
+use log_time_analyzer::maintenance::ExcludedWindows;
+use log_time_analyzer::markers::Marker;
+use log_time_analyzer::parser::{self, FollowEvent, LogMatch, LogParser};
+use log_time_analyzer::csv::{self, CsvDialect};
+use log_time_analyzer::stats::TransitionStats;
+use log_time_analyzer::style::{self, ColorChoice, DurationColors};
+use log_time_analyzer::tags::Condition;
+use log_time_analyzer::{capabilities, clock, decompress, duration, grafana, html, locale, progress, prometheus, schema, stats, timestamp_formats};
+
+use email::EmailSettings;
+use sink::{OutputSink, SinkTarget, SplitWindow};
+
+/// Exit status after an interrupted run, as for a shell job stopped by SIGINT
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// How often `--follow` checks a log file for new lines
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Matches or intervals buffered between `--follow` stages before the earlier stage waits
+const FOLLOW_CHANNEL_CAPACITY: usize = 1024;
 
 #[derive(ClapParser, Debug)]
 #[command(name = "log-time-analyzer")]
 #[command(about = "Analyze log files to find time intervals between specific message patterns", long_about = None)]
 struct Args {
-    /// Path to the log file to analyze (omit to read from stdin)
-    #[arg(short, long)]
-    log_file: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+    
+    #[command(flatten)]
+    options: AnalyzeArgs,
+    
+    /// Email the finished report to this address (can be specified multiple times)
+    #[arg(long = "email-to", global = true)]
+    email_to: Vec<String>,
+    
+    /// Sender address for emailed reports
+    #[arg(long, global = true, default_value = "log-time-analyzer@localhost")]
+    email_from: String,
+    
+    /// Subject line for emailed reports
+    #[arg(long, global = true)]
+    email_subject: Option<String>,
+    
+    /// SMTP relay host used to deliver --email-to reports
+    #[arg(long, global = true, default_value = "localhost")]
+    smtp_host: String,
+    
+    /// SMTP relay port used to deliver --email-to reports
+    #[arg(long, global = true, default_value_t = 25)]
+    smtp_port: u16,
+}
+
+impl Args {
+    /// SMTP settings when the report should be emailed
+    fn email_settings(&self) -> Option<EmailSettings> {
+        if self.email_to.is_empty() {
+            return None;
+        }
+        
+        Some(EmailSettings {
+            to: self.email_to.clone(),
+            from: self.email_from.clone(),
+            smtp_host: self.smtp_host.clone(),
+            smtp_port: self.smtp_port,
+        })
+    }
+}
+
+/// Options of an analysis run, taken by `analyze` and `stats` and, for backward compatibility,
+/// without a subcommand
+#[derive(clap::Args, Debug)]
+struct AnalyzeArgs {
+    /// Path to the log file to analyze (omit to read from stdin); takes several files or
+    /// glob patterns such as 'logs/app-*.log' to merge them
+    #[arg(short, long, num_args = 1..)]
+    log_file: Vec<PathBuf>,
     
     /// Path to the YAML configuration file (optional if CLI args provided)
     #[arg(short, long)]
     config: Option<PathBuf>,
     
-    /// Output format: human, json, csv, tsv, table, simple, or waterfall
-    #[arg(short = 'f', long, default_value = "human")]
-    format: String,
+    /// Use a profile for a well-known log type instead of a config file: one of your own in
+    /// ~/.config/log-line/profiles/<NAME>.yaml, or a built-in one (dotnet, gradle, maven,
+    /// github-actions, jenkins, systemd)
+    #[arg(long, conflicts_with = "config")]
+    profile: Option<String>,
+    
+    /// Use the profile file at this https:// URL instead of a config file, e.g. a versioned
+    /// file in a team's repository
+    #[arg(long, value_name = "URL", conflicts_with_all = ["config", "profile"])]
+    profile_url: Option<String>,
+    
+    /// Output format: human, json, jsonl, csv, tsv, table, markdown, simple, waterfall,
+    /// histogram, chrome-trace, html, prometheus, grafana, zipkin, or otlp
+    /// (comma-separated or repeated to print several formats from one pass)
+    #[arg(short = 'f', long = "format", value_delimiter = ',', default_value = "human")]
+    formats: Vec<String>,
+    
+    /// Write the durations and numbers of the human and table formats and of reports in this
+    /// locale's conventions, e.g. `de` for `1,5 s` and `12.345`, or `auto` for the environment's
+    /// (LC_ALL/LC_NUMERIC/LANG); machine-readable formats are never localized
+    #[arg(long, value_name = "TAG")]
+    locale: Option<String>,
+    
+    /// Color durations of the human, table, and waterfall formats green, yellow, or red by
+    /// --color-thresholds: auto (when printing to a terminal and NO_COLOR is unset), always,
+    /// or never
+    #[arg(long, value_name = "WHEN", default_value = "auto")]
+    color: String,
+    
+    /// Durations from which a colored duration turns yellow and red, e.g. `500ms,2s`
+    /// (default `1s,5s`; overrides config's `color_thresholds`)
+    #[arg(long, value_name = "WARN,SLOW")]
+    color_thresholds: Option<String>,
+    
+    /// Field separator of the csv format, e.g. ';' for spreadsheets in locales with a decimal
+    /// comma, or `tab`
+    #[arg(long, value_name = "CHAR", default_value = ",")]
+    csv_delimiter: String,
+    
+    /// Which csv fields are quoted: text (text fields, the default), minimal (only fields
+    /// containing the delimiter, a quote, or a line break), or all
+    #[arg(long, value_name = "POLICY", default_value = "text")]
+    csv_quote: String,
+    
+    /// Send results to a sink: comma-separated format=<fmt> plus path=<file>, socket=<host:port|unix:path>,
+    /// or exec=<command> (stdout when no destination). Repeat for several sinks; replaces the default stdout output
+    #[arg(short = 'o', long = "output")]
+    outputs: Vec<String>,
+    
+    /// Add the intervals to the end of each path= output instead of overwriting it, so repeated
+    /// (e.g. cron) runs accumulate one dataset: csv keeps a single header line, json is written
+    /// as JSON Lines
+    #[arg(long, conflicts_with_all = ["follow", "stats", "page_size", "baseline"])]
+    append: bool,
+    
+    /// Write the intervals of each day or hour (by their start) to path= outputs of their
+    /// own, named after the window, e.g. out.json becomes out-2025-11-13.json
+    #[arg(long, value_name = "WINDOW", conflicts_with_all = ["follow", "page_size"])]
+    split_output_by: Option<String>,
+    
+    /// With --append, start every row with a run_id column or field holding this ID (the run's
+    /// start time when no ID is given) to tell the runs apart; with --output-db, label the run
+    /// with it
+    #[arg(long, value_name = "ID", num_args = 0..=1, default_missing_value = "")]
+    run_id: Option<String>,
+    
+    /// Also append the run, its matches, and its intervals to this SQLite database (created
+    /// when missing), to query past runs with SQL; needs the sql feature
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["follow", "stream"])]
+    output_db: Option<PathBuf>,
+    
+    /// Log of a baseline run to compare against: the waterfall format then draws paired
+    /// baseline/candidate bars per step with the change between them
+    #[arg(long)]
+    baseline: Option<PathBuf>,
     
     /// Regular expression to extract timestamps (overrides config file)
     #[arg(short = 'r', long)]
@@ -38,44 +198,821 @@ struct Args {
     #[arg(short = 't', long)]
     timestamp_format: Option<String>,
     
-    /// Message patterns to search for (can be specified multiple times, overrides config file)
-    #[arg(short = 'p', long = "pattern")]
+    /// Built-in timestamp format to try first when auto-detecting (name or a unique part of it,
+    /// e.g. "European date format"), overriding the ranking by how consistently formats parse
+    #[arg(long)]
+    prefer_format: Option<String>,
+    
+    /// Layout of the log lines: text (default) or json, one object per line (overrides config)
+    #[arg(long)]
+    input_format: Option<String>,
+    
+    /// Dot-separated path of the timestamp field in JSON input, e.g. meta.ts (overrides config)
+    #[arg(long)]
+    timestamp_field: Option<String>,
+    
+    /// Dot-separated path of the JSON field to match patterns against, instead of the whole line
+    /// (overrides config)
+    #[arg(long)]
+    message_field: Option<String>,
+    
+    /// For lines without a timestamp, take a base time from the log file's name with this regex
+    /// (its `timestamp` group, or group 1), parsed with --filename-timestamp-format (overrides config)
+    #[arg(long, value_name = "REGEX", requires = "filename_timestamp_format")]
+    filename_timestamp: Option<String>,
+    
+    /// Format of the time in the file name (chrono format), e.g. %Y%m%d-%H%M%S
+    #[arg(long, value_name = "FORMAT", requires = "filename_timestamp")]
+    filename_timestamp_format: Option<String>,
+    
+    /// Regex whose `offset` group (or group 1) captures a line's time since the file name's time,
+    /// in seconds (`12.5`) or as a duration (`250ms`) (overrides config)
+    #[arg(long, value_name = "REGEX")]
+    offset_regex: Option<String>,
+    
+    /// Regex finding events embedded in a line with timestamps of their own, repeated across
+    /// the line: each `label`/`timestamp` group pair becomes a match, e.g.
+    /// `(?P<label>\w+)=(?P<timestamp>[\d:.]+)` (overrides config)
+    #[arg(long, value_name = "REGEX")]
+    embedded_events: Option<String>,
+    
+    /// Format of the embedded events' timestamps (chrono format; defaults to the timestamp format)
+    #[arg(long, value_name = "FORMAT", requires = "embedded_events")]
+    embedded_timestamp_format: Option<String>,
+    
+    /// Message patterns to search for (can be specified multiple times, overrides config file);
+    /// `NAME=REGEX` shows NAME instead of the regex in the output, e.g. "DB connect=Connecting to .*"
+    #[arg(short = 'p', long = "pattern", value_name = "[NAME=]REGEX")]
     patterns: Vec<String>,
+    
+    /// Message pattern (name, or regex when unnamed) logged at the same moment in every merged
+    /// log, e.g. a broadcast; each log's clock offset from the first is computed from these
+    /// events and applied before measuring, and the anchors themselves are left out
+    /// (repeatable, overrides config's `clock_anchors`)
+    #[arg(long = "clock-anchor", value_name = "PATTERN")]
+    clock_anchors: Vec<String>,
+    
+    /// Regex extracting a request ID from matched lines (its `correlation` group, or group 1);
+    /// intervals are then only measured between matches with the same ID (overrides config)
+    #[arg(long)]
+    correlation_regex: Option<String>,
+    
+    /// Add a value computed from each interval as an extra output field, e.g.
+    /// `per_item_ms=duration_ms / extracted.items` (repeatable, added to the config's `derived`)
+    #[arg(long = "derive", value_name = "NAME=EXPR")]
+    derive: Vec<String>,
+    
+    /// Named capture holding the duration the server reported for each request, e.g. the
+    /// `time-taken` of IIS logs, as FIELD or FIELD:UNIT (us, ms, s); adds reported_ms and
+    /// unaccounted_ms to each interval and compares them per transition on stderr (overrides config)
+    #[arg(long, value_name = "FIELD[:UNIT]")]
+    reported_duration: Option<String>,
+    
+    /// Only output intervals carrying this tag of the config's `tags` rules (comma-separated
+    /// or repeated to keep intervals with any of them)
+    #[arg(long = "tag", value_delimiter = ',')]
+    tags: Vec<String>,
+    
+    /// Only output intervals for which this condition holds, e.g.
+    /// `duration_ms > 100 && to ~ "Response"`, written as the conditions of `tags` rules
+    /// (repeatable; all must hold)
+    #[arg(long = "where", value_name = "CONDITION")]
+    conditions: Vec<String>,
+    
+    /// Label every interval with the value of this named capture of its patterns (e.g. the
+    /// endpoint or job ID), and compute statistics per transition and value
+    #[arg(long, value_name = "CAPTURE", conflicts_with = "baseline")]
+    group_by: Option<String>,
+    
+    /// Only analyze matches at or after this time: a timestamp such as `2025-11-13 10:00:00`,
+    /// a date, or a duration ago such as `2h` (relative to the local clock)
+    #[arg(long, value_name = "TIME")]
+    since: Option<String>,
+    
+    /// Only analyze matches before this time (same forms as --since)
+    #[arg(long, value_name = "TIME")]
+    until: Option<String>,
+    
+    /// Drop intervals shorter than this duration from the output, e.g. 50ms or 1.5s (overrides config)
+    #[arg(long)]
+    min_duration: Option<String>,
+    
+    /// Drop the first N intervals of every transition, e.g. a benchmark's warm-up iterations
+    /// (overrides config)
+    #[arg(long, value_name = "N")]
+    skip_iterations: Option<usize>,
+    
+    /// Drop the intervals starting within this duration of the first match, e.g. 30s
+    /// (overrides config)
+    #[arg(long, value_name = "DURATION")]
+    skip_duration: Option<String>,
+    
+    /// Exit non-zero after printing the results when any interval is longer than this
+    /// duration, e.g. 30s, to gate CI on timings (overrides config's `fail_if_over`); with
+    /// --follow, the budget --notify alerts on instead
+    #[arg(long)]
+    fail_if_over: Option<String>,
+    
+    /// Report compliance with a latency objective such as `p99 < 500ms over 30d` and the rate
+    /// each transition burns its error budget at, instead of the intervals; warns on stderr
+    /// when a budget burns too fast
+    #[arg(long, value_name = "TARGET", conflicts_with_all = ["follow", "baseline", "stats", "page_size", "outputs", "fail_if_over", "notify", "email_to"])]
+    slo: Option<String>,
+    
+    /// Report the intervals that are statistical outliers of their transition, with their
+    /// timestamps and line numbers, instead of every interval: `iqr` (more than 1.5 IQR
+    /// outside the quartiles, the default) or `zscore` (more than 3 standard deviations from
+    /// the mean), optionally with another multiplier, e.g. `zscore:2.5`
+    #[arg(
+        long,
+        value_name = "METHOD",
+        num_args = 0..=1,
+        default_missing_value = "iqr",
+        conflicts_with_all = ["follow", "baseline", "stats", "page_size", "outputs", "slo"]
+    )]
+    outliers: Option<String>,
+    
+    /// Report the N slowest complete traces, the intervals sharing a correlation key, each
+    /// with the duration of every step, instead of every interval (needs correlation_regex or
+    /// a `correlation` group)
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["follow", "baseline", "stats", "page_size", "outputs", "slo", "outliers"]
+    )]
+    slowest_traces: Option<usize>,
+    
+    /// Add each interval's start and end timestamps and the line numbers of its two matches
+    /// (within their files) to the JSON, CSV, TSV, table, and simple output
+    #[arg(long)]
+    include_source: bool,
+    
+    /// With --include-source, also add the two matched lines themselves
+    #[arg(long, requires = "include_source")]
+    raw_lines: bool,
+    
+    /// Add columns to the table and markdown formats comparing each interval with its
+    /// transition's p50 and p95 over this run, and a pass/fail glyph for staying within the p95
+    #[arg(long, conflicts_with_all = ["follow", "stats"])]
+    percentile_columns: bool,
+    
+    /// Exclude this percentage of the shortest and longest durations of each transition
+    /// from the aggregate statistics in the run summary, e.g. 1% (implies --summary)
+    #[arg(long)]
+    trim: Option<String>,
+    
+    /// Skip lines matching this regex before analysis (can be specified multiple times)
+    #[arg(long = "exclude")]
+    exclude_patterns: Vec<String>,
+    
+    /// Skip lines longer than this many bytes
+    #[arg(long)]
+    max_line_length: Option<usize>,
+    
+    /// Only accept timestamps at the start of a line (after an optional BOM, whitespace, or ANSI codes)
+    #[arg(long)]
+    anchor_timestamp: bool,
+    
+    /// Remove ANSI color/escape sequences before matching (for logs captured from CI or docker)
+    #[arg(long)]
+    strip_ansi: bool,
+    
+    /// When auto-detecting, keep trying every built-in format on every line instead of locking
+    /// onto the format that parsed the last 100 lines in a row (for logs that mix formats)
+    #[arg(long)]
+    mixed_timestamp_formats: bool,
+    
+    /// Read entries spanning several lines, such as stack traces: a line without a timestamp
+    /// continues the entry before it, and patterns match against the whole entry
+    #[arg(long)]
+    multiline: bool,
+    
+    /// Regex for the lines continuing the entry before them, e.g. `^\s`, instead of every line
+    /// without a timestamp (implies --multiline; overrides config)
+    #[arg(long, value_name = "REGEX")]
+    continuation_regex: Option<String>,
+    
+    /// Print the fully merged configuration (config file + CLI overrides + auto-detected
+    /// timestamp format) as YAML instead of running the analysis
+    #[arg(long)]
+    dump_effective_config: bool,
+    
+    /// Keep reading the log file (or stdin) as lines are appended and print each interval as
+    /// soon as it completes, like `tail -f` (human, simple, json, or jsonl output; stop with Ctrl-C)
+    #[arg(long, conflicts_with_all = ["baseline", "outputs", "dump_effective_config", "trim", "email_to"])]
+    follow: bool,
+    
+    /// With --follow, also append every interval to this NDJSON file, synced to disk as each
+    /// one is found, so the history since startup survives a crash or reboot
+    #[arg(long, value_name = "PATH", requires = "follow")]
+    journal: Option<PathBuf>,
+    
+    /// With --follow, first read these archived logs (rotated or compressed files, or glob
+    /// patterns) in timestamp order, then carry on with the followed log, so monitoring
+    /// starts with the full history
+    #[arg(long, value_name = "PATH", num_args = 1.., requires = "follow")]
+    backfill: Vec<PathBuf>,
+    
+    /// Print each interval of a single log (or stdin) as soon as its end is found instead of
+    /// collecting every match first, so logs of any size are analyzed in constant memory
+    /// (human, simple, json, or jsonl output)
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "follow", "baseline", "outputs", "dump_effective_config", "trim", "notify", "email_to",
+            "stats", "page_size", "append", "split_output_by", "percentile_columns", "slo", "outliers",
+            "slowest_traces", "fail_if_over",
+        ]
+    )]
+    stream: bool,
+    
+    /// Read plain log files through a memory map instead of a read buffer, leaving the
+    /// caching of huge logs to the operating system; the file must not be truncated meanwhile
+    #[arg(long, conflicts_with = "follow")]
+    mmap: bool,
+    
+    /// Output aggregate statistics per transition (count, min, max, mean, median, p95, p99,
+    /// stddev) instead of every interval; honors --trim
+    #[arg(long, conflicts_with_all = ["baseline", "follow"])]
+    stats: bool,
+    
+    /// Print the JSON Schema of the json or jsonl output, or the column dictionary of the csv
+    /// (and tsv) output, and exit; describes the --stats rows when combined with --stats
+    #[arg(long, value_name = "FORMAT")]
+    print_schema: Option<String>,
+    
+    /// Print a JSON document of this build's version, input and output formats, built-in
+    /// timestamp formats, and features, for wrapper tools, and exit
+    #[arg(long)]
+    capabilities: bool,
+    
+    /// Print only one page of this many intervals (see --page); JSON output then becomes an
+    /// object with the page position and a next_page continuation marker
+    #[arg(long, conflicts_with_all = ["baseline", "follow", "stats"])]
+    page_size: Option<usize>,
+    
+    /// Page to print with --page-size, starting at 1
+    #[arg(long, requires = "page_size", default_value_t = 1)]
+    page: usize,
+    
+    /// Number of equal-width buckets per transition in the histogram and html formats
+    #[arg(long, default_value_t = 10)]
+    buckets: usize,
+    
+    /// Fixed bucket width for the histogram and html formats (e.g., "50ms", "1s"); overrides --buckets
+    #[arg(long, value_name = "DURATION")]
+    bucket_width: Option<String>,
+    
+    /// Mark a point in time in the waterfall, html, and chrome-trace outputs, as
+    /// <TIME>=<LABEL> (e.g. '2025-11-13T10:05:00=deploy v1.2'; can be specified multiple times)
+    #[arg(long = "mark", value_name = "TIME=LABEL")]
+    marks: Vec<String>,
+    
+    /// Waterfall bars: vertical (a column per interval) or horizontal (a row per interval)
+    #[arg(long, value_name = "DIRECTION", default_value = "vertical")]
+    bars: String,
+    
+    /// Waterfall bar lengths: linear, or log so one huge interval doesn't flatten the others
+    #[arg(long, value_name = "SCALE", default_value = "linear")]
+    scale: String,
+    
+    /// Columns the waterfall may take up (default: the terminal's width, or 100 when not
+    /// printing to a terminal)
+    #[arg(long, value_name = "COLUMNS", value_parser = clap::value_parser!(u16).range(20..))]
+    width: Option<u16>,
+    
+    /// Threads for parsing a large uncompressed log in chunks and for pairing the matches of
+    /// many request IDs (default: one per CPU core; 1 runs sequentially)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    threads: Option<u16>,
+    
+    /// Run at this niceness (0-19, higher yields more CPU to other processes), so a heavy
+    /// analysis does not starve the service on a shared host
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(i32).range(0..=19))]
+    nice: Option<i32>,
+    
+    /// I/O scheduling priority on Linux: idle, or best-effort[:0-7] (7 is lowest)
+    #[arg(long, value_name = "CLASS")]
+    io_priority: Option<String>,
+    
+    /// Write progress events as JSON lines to this inherited file descriptor (e.g.
+    /// `--progress-fd 3 3>progress.jsonl`): start, a heartbeat every second, and done
+    #[arg(long, value_name = "FD")]
+    progress_fd: Option<i32>,
+    
+    /// After the run, print how long each message/exclude pattern took to match and how often
+    /// it hit, to find the pattern that slows a large config down
+    #[arg(long, conflicts_with = "follow")]
+    profile_patterns: bool,
+    
+    /// Print a run summary with line counts and skip reasons to stderr
+    #[arg(long)]
+    summary: bool,
+    
+    /// Post a run summary to a chat webhook: slack://<host/path> or teams://<host/path>
+    /// (repeatable); with --follow, post an alert for every interval over its fail_if_over
+    #[arg(long = "notify")]
+    notify: Vec<String>,
+    
+    /// With --follow, ring the terminal bell (on stderr) for every interval over its
+    /// fail_if_over
+    #[arg(long, requires = "follow")]
+    bell: bool,
+    
+    /// With --follow, show a desktop notification (notify-send, or osascript on macOS) for
+    /// every interval over its fail_if_over
+    #[arg(long, requires = "follow")]
+    notify_desktop: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the intervals between matched patterns (what runs without a subcommand, e.g.
+    /// `ll analyze -l app.log -c config.yaml`)
+    Analyze(AnalyzeArgs),
+    /// Print aggregate statistics per transition instead of every interval (same as `analyze --stats`)
+    Stats(AnalyzeArgs),
+    /// Report how well each built-in timestamp format parses a log, and the regex and format
+    /// of the best one to pin in a config file
+    Detect {
+        /// Log file(s) to sample (omit to read from stdin)
+        #[arg(short, long, num_args = 1..)]
+        log_file: Vec<PathBuf>,
+        
+        /// Built-in timestamp format to rank first (name or a unique part of it)
+        #[arg(long)]
+        prefer_format: Option<String>,
+        
+        /// Only accept timestamps at the start of a line
+        #[arg(long)]
+        anchor_timestamp: bool,
+        
+        /// Also show, for each of the first --lines lines, every built-in format that finds a
+        /// timestamp on it and what the format parses it to
+        #[arg(long)]
+        explain: bool,
+        
+        /// Number of lines at the start of the (first) log to explain and take samples from
+        #[arg(long, default_value_t = 10)]
+        lines: usize,
+    },
+    /// List the built-in timestamp formats tried by auto-detection (what `formats list` prints)
+    Formats {
+        #[command(subcommand)]
+        action: Option<FormatsCommand>,
+        
+        /// Output format: table or json
+        #[arg(short = 'f', long = "format", global = true, default_value = "table")]
+        format: String,
+    },
+    /// Check a configuration file (YAML syntax, required settings, and that every regex and
+    /// expression compiles) without analyzing a log; exits non-zero when it is invalid
+    ValidateConfig {
+        /// Configuration file to check
+        #[arg(short, long)]
+        config: PathBuf,
+    },
+    /// Run many (log file, config, output) jobs from a YAML manifest in parallel
+    Batch {
+        /// Path to the YAML manifest listing the jobs
+        #[arg(short, long)]
+        manifest: PathBuf,
+        
+        /// Maximum number of jobs to run at once (overrides the manifest)
+        #[arg(short, long)]
+        jobs: Option<usize>,
+    },
+    /// Check expected timings from a rules file against the analysis (global options select
+    /// the log and patterns, e.g. `ll -l app.log -c config.yaml assert --rules rules.yaml`);
+    /// exits non-zero when any check fails
+    Assert {
+        /// YAML file with rules such as `pair: [A, B]` and `expect: [p95 < 200ms, count >= 10]`
+        #[arg(short, long)]
+        rules: PathBuf,
+    },
+    /// Check every interval against the `expected_max` of its transition in the config and
+    /// list the violations with a pass/fail line per transition (global options select the
+    /// log, e.g. `ll -l job.log -c config.yaml sla`); exits non-zero on any violation
+    Sla,
+    /// Compare the timings of each transition with a baseline run, e.g. the previous release
+    /// (global options select the new log, e.g. `ll -l new.log -c config.yaml compare old.log`);
+    /// exits non-zero when a transition's mean or p95 regressed by more than the threshold
+    Compare {
+        /// Baseline log file, or the saved `-f json` (intervals or `--stats`) output of a run
+        baseline: PathBuf,
+        
+        /// Slowdown of the mean or p95 that counts as a regression, in percent
+        #[arg(long, default_value = "10%")]
+        threshold: String,
+        
+        /// Chance of a slowdown being noise below which it counts as a regression (one-sided
+        /// Mann-Whitney U test of the durations)
+        #[arg(long, value_name = "LEVEL", default_value = "0.05")]
+        significance: String,
+    },
+    /// Load the matches and intervals into an in-memory SQLite database and print the result
+    /// of a SQL query over them (global options select the log, e.g.
+    /// `ll -l app.log -c config.yaml query --sql 'select ...'`); needs the `sql` feature
+    Query {
+        /// SQL statement over the `matches` and `intervals` tables
+        #[arg(long)]
+        sql: String,
+    },
+    /// Analyze every log file in a directory (rotated and compressed files included) over a
+    /// recent period and print per-day trends of each transition (global options select the
+    /// patterns, e.g. `ll -c config.yaml report --dir /var/log/app --since 7d`)
+    Report {
+        /// Directory searched recursively for log files
+        #[arg(short, long)]
+        dir: PathBuf,
+        
+        /// How far back to report, e.g. 7d or 24h
+        #[arg(long, default_value = "7d")]
+        since: String,
+        
+        /// Report format: markdown or html
+        #[arg(long = "as", default_value = "markdown")]
+        report_format: String,
+    },
+    /// Write a synthetic log to stdout from a scenario file whose steps take known
+    /// (fixed or randomly distributed) times, to test configs and analysis pipelines
+    Generate {
+        /// YAML scenario with the steps of each flow and the delay before each step
+        #[arg(short, long)]
+        scenario: PathBuf,
+        
+        /// Timestamp format: standard, iso8601, syslog, apache, or a chrono format
+        #[arg(long, default_value = "standard")]
+        format: String,
+        
+        /// How often a new flow starts, e.g. 100/s, 30/m, or 2/h
+        #[arg(long, default_value = "1/s")]
+        rate: String,
+        
+        /// Number of flows (overrides the scenario)
+        #[arg(long)]
+        count: Option<usize>,
+        
+        /// Seed of the random delays; the same seed gives the same log
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum FormatsCommand {
+    /// Print the name, chrono format, example, and regex of every built-in timestamp format,
+    /// in the order auto-detection tries them
+    List,
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    
+    // `analyze` and `stats` take the same options as a run without a subcommand
+    if matches!(args.command, Some(Command::Analyze(_) | Command::Stats(_))) {
+        let command = Args::command();
+        let misplaced = command.get_arguments().find(|arg| {
+            !arg.is_global_set() && matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+        });
+        if let Some(arg) = misplaced {
+            anyhow::bail!(
+                "--{} goes after the {} subcommand name",
+                arg.get_long().unwrap_or_default(),
+                matches.subcommand_name().unwrap_or_default()
+            );
+        }
+    }
+    match args.command.take() {
+        Some(Command::Analyze(options)) => args.options = options,
+        Some(Command::Stats(options)) => args.options = AnalyzeArgs { stats: true, ..options },
+        command => args.command = command,
+    }
     
-    // Parse output format
-    let output_format = OutputFormat::from_str(&args.format)
-        .ok_or_else(|| anyhow::anyhow!(
-            "Invalid output format '{}'. Valid options: human, json, csv, tsv, table, simple, waterfall",
-            args.format
-        ))?;
+    match &args.command {
+        Some(Command::Formats { action: None | Some(FormatsCommand::List), format }) => {
+            let format = match format.parse::<OutputFormat>() {
+                Ok(format @ (OutputFormat::Table | OutputFormat::Markdown | OutputFormat::Json)) => format,
+                _ => anyhow::bail!("Invalid format '{}' for the formats list. Valid options: table, markdown, json", format),
+            };
+            let formats = timestamp_formats::get_builtin_formats();
+            println!("{}", OutputFormatter::format_timestamp_formats(&formats, format));
+            return Ok(());
+        }
+        Some(Command::Detect { log_file, prefer_format, anchor_timestamp, explain, lines }) => {
+            return run_detect(log_file, prefer_format.clone(), *anchor_timestamp, *explain, *lines);
+        }
+        Some(Command::ValidateConfig { config }) => return run_validate_config(config),
+        _ => {}
+    }
     
-    // Load configuration with CLI overrides
-    let patterns = if args.patterns.is_empty() {
-        None
+    // Before any worker thread starts, since threads inherit the priority of their creator
+    let io_priority = args.options.io_priority.as_deref().map(priority::IoPriority::parse).transpose()?;
+    priority::apply(args.options.nice, io_priority)?;
+    
+    if let Some(Command::Batch { manifest, jobs }) = &args.command {
+        return run_batch(&args, manifest, *jobs);
+    }
+    
+    if args.options.capabilities {
+        println!("{}", capabilities::capabilities().to_json());
+        return Ok(());
+    }
+    
+    if let Some(format) = &args.options.print_schema {
+        let format = format.parse::<schema::SchemaFormat>().map_err(|_| {
+            anyhow::anyhow!("Invalid schema format '{}'. Valid options: json, jsonl, csv", format)
+        })?;
+        println!("{}", schema::print_schema(format, args.options.stats));
+        return Ok(());
+    }
+    
+    if let Some(Command::Generate { scenario, format, rate, count, seed }) = &args.command {
+        let scenario = generate::Scenario::from_file(scenario).context("Failed to load scenario")?;
+        let options = generate::Options {
+            format: generate::timestamp_format(format)?,
+            gap: generate::parse_rate(rate)?,
+            flows: *count,
+            seed: *seed,
+        };
+        let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+        let lines = generate::generate(&scenario, &options, &mut out)?;
+        if args.options.summary {
+            eprintln!("Generated {} lines", lines);
+        }
+        return Ok(());
+    }
+    
+    let webhooks = args
+        .options
+        .notify
+        .iter()
+        .map(|spec| notify::Webhook::parse(spec))
+        .collect::<Result<Vec<_>>>()?;
+    
+    let rules = match &args.command {
+        Some(Command::Assert { rules }) => Some(
+            assertions::RuleSet::from_file(rules).context("Failed to load assertion rules")?,
+        ),
+        _ => None,
+    };
+    // Commands that check or query the intervals of a finished run rather than printing them
+    let check_command = match &args.command {
+        Some(Command::Assert { .. }) => Some("assert"),
+        Some(Command::Sla) => Some("sla"),
+        Some(Command::Compare { .. }) => Some("compare"),
+        Some(Command::Query { .. }) => Some("query"),
+        _ => None,
+    };
+    if let (Some(command), Some(_)) = (check_command, &args.options.outliers) {
+        anyhow::bail!("The {} command replaces the interval output and cannot be combined with --outliers", command);
+    }
+    if let (Some(command), Some(_)) = (check_command, args.options.slowest_traces) {
+        anyhow::bail!("The {} command replaces the interval output and cannot be combined with --slowest-traces", command);
+    }
+    if args.options.slowest_traces == Some(0) {
+        anyhow::bail!("--slowest-traces must be at least 1");
+    }
+    
+    let trim = args.options.trim.as_deref().map(stats::parse_trim).transpose()?;
+    let show_summary = args.options.summary || trim.is_some();
+    
+    let email = args.email_settings();
+    let report_title = args.email_subject.clone().unwrap_or_else(|| {
+        let source = if args.options.log_file.is_empty() {
+            "stdin".to_string()
+        } else {
+            args.options.log_file
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        format!("Log analysis report: {}", source)
+    });
+    
+    // Parse output formats
+    let output_formats = args
+        .options
+        .formats
+        .iter()
+        .map(|format| {
+            format.parse::<OutputFormat>().map_err(|_| anyhow::anyhow!(
+                "Invalid output format '{}'. Valid options: human, json, jsonl, csv, tsv, table, markdown, simple, waterfall, histogram, chrome-trace, html, prometheus, grafana, zipkin, otlp",
+                format
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let locale = args.options.locale.as_deref().map(locale::resolve).transpose()?;
+    let csv_dialect = CsvDialect {
+        delimiter: CsvDialect::parse_delimiter(&args.options.csv_delimiter)?,
+        quoting: args.options.csv_quote.parse::<csv::Quoting>().map_err(|_| {
+            anyhow::anyhow!("Invalid --csv-quote '{}'. Valid options: text, minimal, all", args.options.csv_quote)
+        })?,
+    };
+    
+    let sinks = if args.options.outputs.is_empty() {
+        output_formats
+            .iter()
+            .map(|&format| OutputSink {
+                format,
+                target: SinkTarget::Stdout,
+            })
+            .collect()
     } else {
-        Some(args.patterns)
+        args.options.outputs
+            .iter()
+            .map(|spec| OutputSink::parse(spec, output_formats[0]))
+            .collect::<Result<Vec<_>>>()?
+    };
+    if args.options.append {
+        for sink in &sinks {
+            if !matches!(sink.target, SinkTarget::File(_)) {
+                anyhow::bail!("--append adds to files: give each output a path, e.g. -o format=csv,path=runs.csv");
+            }
+            if !matches!(sink.format, OutputFormat::Csv | OutputFormat::Json | OutputFormat::Jsonl) {
+                anyhow::bail!("--append writes csv, json, or jsonl outputs (both as JSON Lines)");
+            }
+        }
+    }
+    let split_window = match &args.options.split_output_by {
+        Some(window) => {
+            let window = window.parse::<SplitWindow>().map_err(|_| {
+                anyhow::anyhow!("Invalid --split-output-by '{}'. Valid options: day, hour", window)
+            })?;
+            if sinks.iter().any(|sink| !matches!(sink.target, SinkTarget::File(_))) {
+                anyhow::bail!("--split-output-by writes one file per window: give each output a path, e.g. -o format=json,path=out.json");
+            }
+            Some(window)
+        }
+        None => None,
     };
+    if args.options.run_id.is_some() && !args.options.append && args.options.output_db.is_none() {
+        anyhow::bail!("--run-id labels the rows of --append or the run in --output-db; add one of them");
+    }
+    #[cfg(not(feature = "sql"))]
+    if args.options.output_db.is_some() {
+        anyhow::bail!("--output-db needs a build with the sql feature: cargo build --release --features sql");
+    }
+    let run_id = args.options.run_id.as_ref().map(|id| match id.as_str() {
+        "" => chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+        id => id.to_string(),
+    });
     
-    let config = Config::from_file_with_overrides(
-        args.config.as_deref(),
-        args.timestamp_regex,
-        args.timestamp_format,
-        patterns,
-    )
-    .context("Failed to load configuration")?;
+    let mut config = load_config(&args.options)?;
+    if args.options.buckets == 0 {
+        anyhow::bail!("--buckets must be at least 1");
+    }
+    let bucket_width_ms = match &args.options.bucket_width {
+        Some(width) => {
+            let width = duration::parse_duration(width).with_context(|| format!("Invalid --bucket-width: {}", width))?;
+            let ms = width.num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0;
+            if ms <= 0.0 {
+                anyhow::bail!("--bucket-width must be greater than zero");
+            }
+            Some(ms)
+        }
+        None => None,
+    };
+    let slo = args.options.slo.as_deref().map(slo::Slo::parse).transpose()?;
+    let outlier_method = args.options.outliers.as_deref().map(outliers::Method::parse).transpose()?;
+    let histogram = HistogramOptions {
+        buckets: args.options.buckets,
+        bucket_width_ms,
+    };
+    let waterfall = WaterfallOptions {
+        horizontal: match args.options.bars.to_lowercase().as_str() {
+            "vertical" => false,
+            "horizontal" => true,
+            other => anyhow::bail!("Invalid --bars '{}'. Valid options: vertical, horizontal", other),
+        },
+        width: args
+            .options
+            .width
+            .map(usize::from)
+            .or_else(style::terminal_width)
+            .unwrap_or(WaterfallOptions::default().width),
+        scale: args.options.scale.parse::<BarScale>().map_err(|_| {
+            anyhow::anyhow!("Invalid --scale '{}'. Valid options: linear, log", args.options.scale)
+        })?,
+    };
     
     // Create parser
-    let parser = LogParser::new(&config)
+    let mut parser = LogParser::new(&config)
         .context("Failed to create log parser")?;
+    let interrupted = install_interrupt_handler()?;
+    parser.set_stop_flag(interrupted.clone());
+    if let Some(threads) = args.options.threads {
+        parser.set_threads(threads.into());
+        // Correlated matches are paired on rayon's global pool
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads.into())
+            .build_global()
+            .context("Failed to start analysis threads")?;
+    }
+    if args.options.profile_patterns {
+        parser.enable_pattern_profiling();
+    }
+    if let Some(capture) = &args.options.group_by {
+        if !parser.captures_field(capture) {
+            anyhow::bail!("--group-by {}: no message pattern or timestamp regex has a (?P<{}>...) named group", capture, capture);
+        }
+        if matches!(args.command, Some(Command::Assert { .. })) {
+            anyhow::bail!("The assert command checks whole transitions and cannot be combined with --group-by");
+        }
+    }
+    parser.set_memory_mapped(args.options.mmap);
+    let now = chrono::Local::now().naive_local();
+    let time_bound = |flag: &str, value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|value| duration::parse_time_bound(value, now).with_context(|| format!("Invalid --{}", flag)))
+            .transpose()
+    };
+    let (since, until) = (time_bound("since", &args.options.since)?, time_bound("until", &args.options.until)?);
+    if let (Some(since), Some(until)) = (since, until) {
+        if since >= until {
+            anyhow::bail!("--since ({}) must be before --until ({})", since, until);
+        }
+    }
+    parser.set_time_window(since, until);
+    let markers = args
+        .options
+        .marks
+        .iter()
+        .map(|spec| Marker::parse(spec, now).with_context(|| format!("Invalid --mark '{}'", spec)))
+        .collect::<Result<Vec<_>>>()?;
+    let exclusions = ExcludedWindows::new(&config.excluded_windows)?;
+    // The outlier and trace reports point at the lines they list, and queries can select them
+    let query = matches!(args.command, Some(Command::Query { .. }));
+    if args.options.include_source || outlier_method.is_some() || args.options.slowest_traces.is_some() || query {
+        parser.record_source_lines(args.options.raw_lines);
+    }
+    
+    if let Some(Command::Report { dir, since, report_format }) = &args.command {
+        let period = duration::parse_duration(since).with_context(|| format!("Invalid --since: {}", since))?;
+        let format = report_format.parse::<report::ReportFormat>().map_err(|_| {
+            anyhow::anyhow!("Invalid report format '{}'. Valid options: markdown, html", report_format)
+        })?;
+        let report = report::build(&mut parser, &config, dir, period)?;
+        if show_summary {
+            eprintln!("{}", parser.stats().format_summary());
+        }
+        println!("{}", report.format(format, locale));
+        return Ok(());
+    }
+    
+    let log_files = parser::expand_log_files(&args.options.log_file)?;
+    // Dropped when `main` returns (or explicitly before exiting), which writes the `done` event
+    let progress_reporter = match args.options.progress_fd {
+        Some(fd) => Some(start_progress(fd, &mut parser, &log_files, &interrupted)?),
+        None => None,
+    };
     
-    // Parse log from file or stdin
-    let matches = if let Some(log_file) = args.log_file {
+    if args.options.follow {
+        if let Some(command) = check_command {
+            anyhow::bail!("The {} command needs a finished run and cannot be combined with --follow", command);
+        }
+        let sinks = FollowSinks {
+            journal: args.options.journal.as_deref().map(journal::Journal::open).transpose()?,
+            webhooks: &webhooks,
+            title: &report_title,
+            bell: args.options.bell,
+            desktop: args.options.notify_desktop,
+        };
+        run_follow(&mut parser, &config, &log_files, &output_formats, &args.options, sinks, &interrupted)?;
+        if show_summary {
+            eprintln!("{}", parser.stats().format_summary());
+        }
+        return Ok(());
+    }
+    
+    if args.options.stream {
+        if let Some(command) = check_command {
+            anyhow::bail!("The {} command needs a finished run and cannot be combined with --stream", command);
+        }
+        run_stream(&mut parser, &config, &log_files, &output_formats, &args.options)?;
+        if parser.stats().interrupted {
+            eprintln!("Interrupted after {} lines; the results are partial", parser.stats().lines_read);
+        }
+        if show_summary {
+            eprintln!("{}", parser.stats().format_summary());
+        }
+        if let Some(report) = parser.format_pattern_profile() {
+            eprintln!("{}", report);
+        }
+        return Ok(());
+    }
+    
+    // Parse log from file(s) or stdin
+    let matches = if log_files.len() > 1 {
+        // Merge several files, attributing each match to its file
+        parser.parse_files(&log_files)
+            .context("Failed to parse log files")?
+    } else if let Some(log_file) = log_files.first() {
         // Parse from file
-        parser.parse_file(&log_file)
+        parser.parse_file(log_file)
             .context("Failed to parse log file")?
     } else {
         // Check if stdin is a terminal (not piped)
@@ -90,6 +1027,126 @@ fn main() -> Result<()> {
             .context("Failed to parse log from stdin")?
     };
     
+    // Line up the clocks of merged logs on their anchor events, which are not measured
+    let matches = if config.clock_anchors.is_empty() {
+        matches
+    } else {
+        let sources: Vec<String> = log_files.iter().map(|path| path.display().to_string()).collect();
+        let offsets = clock::calibrate(&matches, &sources, &config.clock_anchors);
+        if sources.len() > 1 {
+            eprintln!("{}", offsets.format_report());
+        }
+        offsets.apply(matches, &config.clock_anchors)
+    };
+    
+    let partial = parser.stats().interrupted;
+    if partial {
+        eprintln!(
+            "Interrupted after {} lines; the results are partial",
+            parser.stats().lines_read
+        );
+    }
+    
+    if show_summary {
+        eprintln!("{}", parser.stats().format_summary());
+        if let Some(report) = parser.format_detection_report() {
+            eprintln!("{}", report);
+        }
+    }
+    if let Some(report) = parser.format_pattern_profile() {
+        eprintln!("{}", report);
+    }
+    
+    if args.options.dump_effective_config {
+        if config.is_auto_detect {
+            // Freeze the detected format so the dumped config no longer needs auto-detection
+            let detected = parser.detected_format().ok_or_else(|| {
+                anyhow::anyhow!("No timestamps could be detected automatically, so there is no timestamp format to export")
+            })?;
+            config.timestamp_regex = detected.regex.to_string();
+            config.timestamp_format = detected.format.to_string();
+            config.prefer_format = None;
+            config.is_auto_detect = false;
+        }
+        
+        let yaml = serde_yaml::to_string(&config)
+            .context("Failed to serialize effective configuration")?;
+        print!("{}", yaml);
+        return Ok(());
+    }
+    
+    if let Some(rules) = rules {
+        // Evaluate even when nothing matched, so `count` checks can fail loudly
+        let intervals = Analyzer::apply_min_duration(Analyzer::analyze_with(matches, &config), &config)?;
+        let results = rules.evaluate(&exclusions.exclude(intervals))?;
+        println!("{}", assertions::format_report(&results));
+        
+        let failed = results.iter().filter(|r| !r.passed).count();
+        if failed > 0 {
+            anyhow::bail!("{} of {} timing checks failed", failed, results.len());
+        }
+        return Ok(());
+    }
+    
+    if matches!(args.command, Some(Command::Sla)) {
+        let intervals = exclusions.exclude(Analyzer::apply_min_duration(Analyzer::analyze_with(matches, &config), &config)?);
+        let results = sla::evaluate(&intervals, &config)?;
+        println!("{}", sla::format_report(&results, &intervals));
+        
+        let violations: usize = results.iter().map(|result| result.violations.len()).sum();
+        if violations > 0 {
+            anyhow::bail!("{} of {} intervals exceeded their transition's expected_max", violations, intervals.len());
+        }
+        return Ok(());
+    }
+    
+    if let Some(Command::Compare { baseline, threshold, significance }) = &args.command {
+        let threshold = compare::parse_threshold(threshold)?;
+        let significance = compare::parse_significance(significance)?;
+        let mut reader = decompress::open_log_file(baseline).context("Failed to open baseline")?;
+        // Only what may be a saved JSON run is read into memory up front
+        let mut text = String::new();
+        if matches!(reader.fill_buf()?.trim_ascii_start().first(), Some(b'[' | b'{')) {
+            reader.read_to_string(&mut text).with_context(|| format!("Failed to read baseline {:?}", baseline))?;
+        }
+        let baseline = match compare::parse_saved(&text, args.options.group_by.as_deref())? {
+            Some(timings) => timings,
+            None => compare::Timings::of(&analyze_baseline(baseline, &config, &args.options, &interrupted)?),
+        };
+        let mut intervals = exclusions.exclude(Analyzer::apply_min_duration(Analyzer::analyze_with(matches, &config), &config)?);
+        if let Some(capture) = &args.options.group_by {
+            intervals.iter_mut().for_each(|interval| interval.group_by(capture));
+        }
+        let changes = compare::compare(&baseline, &compare::Timings::of(&intervals));
+        println!("{}", compare::format_report(&changes, threshold, significance));
+        
+        let regressed = changes.iter().filter(|change| change.regressed(threshold, significance)).count();
+        if regressed > 0 {
+            anyhow::bail!("{} transitions significantly regressed by more than {}%", regressed, threshold);
+        }
+        return Ok(());
+    }
+    
+    if let Some(Command::Query { sql }) = &args.command {
+        #[cfg(feature = "sql")]
+        {
+            let mut intervals = Analyzer::apply_min_duration(Analyzer::analyze_with(matches.clone(), &config), &config)?;
+            if let Some(capture) = &args.options.group_by {
+                intervals.iter_mut().for_each(|interval| interval.group_by(capture));
+            }
+            let db = query::load(&matches, &intervals)?;
+            let result = query::run(&db, sql)?;
+            let outputs: Vec<String> = output_formats.iter().map(|&format| result.format(format, csv_dialect)).collect();
+            println!("{}", outputs.join("\n\n"));
+            return Ok(());
+        }
+        #[cfg(not(feature = "sql"))]
+        {
+            let _ = sql;
+            anyhow::bail!("The query command needs a build with the sql feature: cargo build --release --features sql");
+        }
+    }
+    
     if matches.is_empty() {
         if config.is_auto_detect {
             eprintln!("Error: No matching patterns found in log file with timestamps.");
@@ -120,17 +1177,1000 @@ fn main() -> Result<()> {
         return Ok(());
     }
     
+    // Kept for --output-db, since the analysis consumes them
+    #[cfg(feature = "sql")]
+    let db_matches = args.options.output_db.as_ref().map(|_| matches.clone());
+    
     // Analyze and find intervals
-    let intervals = Analyzer::analyze(matches);
+    let mut intervals = Analyzer::analyze_with(matches, &config);
+    if let Some(capture) = &args.options.group_by {
+        intervals.iter_mut().for_each(|interval| interval.group_by(capture));
+    }
     
     if intervals.is_empty() {
         eprintln!("Not enough matches to calculate intervals");
         return Ok(());
     }
     
-    // Format and output results
-    let output = OutputFormatter::format_intervals(&intervals, output_format);
-    println!("{}", output);
+    if args.options.percentile_columns
+        && !sinks.iter().any(|sink| matches!(sink.format, OutputFormat::Table | OutputFormat::Markdown))
+    {
+        anyhow::bail!("--percentile-columns is only used by the table and markdown formats; add -f table");
+    }
+    
+    let baseline = match &args.options.baseline {
+        Some(path) => {
+            if !sinks.iter().any(|sink| sink.format == OutputFormat::Waterfall) {
+                anyhow::bail!("--baseline is only used by the waterfall format; add -f waterfall");
+            }
+            Some(analyze_baseline(path, &config, &args.options, &interrupted)?)
+        }
+        None => None,
+    };
+    
+    let total_intervals = intervals.len();
+    let intervals = Analyzer::apply_min_duration(intervals, &config)?;
+    if show_summary && intervals.len() < total_intervals {
+        eprintln!(
+            "  {:<26} {:>8}",
+            "Below min duration:",
+            total_intervals - intervals.len()
+        );
+    }
+    
+    if intervals.is_empty() {
+        eprintln!("No intervals reach the minimum duration");
+        return Ok(());
+    }
+    
+    let intervals = if exclusions.is_empty() {
+        intervals
+    } else {
+        let (kept, dropped, annotated) = exclusions.apply(intervals);
+        if show_summary {
+            eprintln!("  {:<26} {:>8}", "In excluded windows:", dropped);
+            eprintln!("  {:<26} {:>8}", "Annotated, not in stats:", annotated);
+        }
+        if kept.is_empty() {
+            eprintln!("Every interval falls into an excluded window");
+            return Ok(());
+        }
+        kept
+    };
+    
+    let intervals = if args.options.tags.is_empty() {
+        intervals
+    } else {
+        let reached = intervals.len();
+        let tagged: Vec<Interval> = intervals.into_iter().filter(|i| i.has_any_tag(&args.options.tags)).collect();
+        if show_summary && tagged.len() < reached {
+            eprintln!("  {:<26} {:>8}", "Filtered out by --tag:", reached - tagged.len());
+        }
+        if tagged.is_empty() {
+            eprintln!("No intervals have the tags {}", args.options.tags.join(", "));
+            return Ok(());
+        }
+        tagged
+    };
+    
+    let intervals = if args.options.conditions.is_empty() {
+        intervals
+    } else {
+        let conditions = where_conditions(&args.options, &config)?;
+        let reached = intervals.len();
+        let kept: Vec<Interval> = intervals
+            .into_iter()
+            .filter(|i| conditions.iter().all(|condition| condition.matches(i)))
+            .collect();
+        if show_summary && kept.len() < reached {
+            eprintln!("  {:<26} {:>8}", "Filtered out by --where:", reached - kept.len());
+        }
+        if kept.is_empty() {
+            eprintln!("No intervals satisfy --where {}", args.options.conditions.join(" --where "));
+            return Ok(());
+        }
+        kept
+    };
+    
+    // Intervals annotated by an excluded window are listed, but not counted
+    let counted = exclusions.counted(&intervals);
+    
+    if let Some(slo) = slo {
+        let report = slo.evaluate(&counted);
+        println!("{}", report.format());
+        for warning in report.warnings() {
+            eprintln!("Warning: {}", warning);
+        }
+        if partial {
+            drop(progress_reporter);
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        return Ok(());
+    }
+    
+    if let Some(method) = outlier_method {
+        println!("{}", outliers::format_report(&outliers::detect(&counted, method), &counted, method));
+        if partial {
+            drop(progress_reporter);
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        return Ok(());
+    }
+    
+    if let Some(limit) = args.options.slowest_traces {
+        println!("{}", traces::format_report(&traces::collect(&counted, &config)?, limit));
+        if partial {
+            drop(progress_reporter);
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        return Ok(());
+    }
+    
+    let transition_stats = stats::compute(&counted, trim);
+    if show_summary {
+        eprintln!("{}", stats::format_summary(&transition_stats, &counted, trim));
+    }
+    if let Some(reported) = &config.reported_duration {
+        if let Some(comparison) = stats::format_reported_comparison(&intervals, &reported.field) {
+            eprintln!("{}", comparison);
+        }
+    }
+    
+    let page = match args.options.page_size {
+        Some(size) => {
+            if size == 0 || args.options.page == 0 {
+                anyhow::bail!("--page-size and --page must be at least 1");
+            }
+            let page = Page { number: args.options.page, size };
+            let pages = page.count(intervals.len());
+            if page.number > pages {
+                anyhow::bail!(
+                    "Page {} is past the last page ({} intervals make {} pages)",
+                    page.number,
+                    intervals.len(),
+                    pages
+                );
+            }
+            let range = page.range(intervals.len());
+            let next = match page.next(intervals.len()) {
+                Some(next) => format!("; next: --page {}", next),
+                None => "; last page".to_string(),
+            };
+            eprintln!(
+                "Page {} of {} (intervals {}-{} of {}){}",
+                page.number, pages, range.start + 1, range.end, intervals.len(), next
+            );
+            Some(page)
+        }
+        None => None,
+    };
+    
+    // Format and deliver results to every sink, or to the files of every window with
+    // --split-output-by; the first output is also used for email
+    let mut report = None;
+    let mut wrote_stdout = false;
+    // The first output doubles as the email body, which must not carry terminal escape codes
+    let colors = stdout_colors(&args.options, &config)?.filter(|_| args.email_to.is_empty());
+    let mut deliver = |intervals: &[Interval], transition_stats: &[TransitionStats], window: Option<&str>| -> Result<()> {
+        for sink in &sinks {
+            let window_sink = window.map(|label| sink.for_window(label));
+            let sink = window_sink.as_ref().unwrap_or(sink);
+            let colors = colors.filter(|_| sink.target == SinkTarget::Stdout);
+            let output = match (&baseline, sink.format) {
+                _ if args.options.append => {
+                    OutputFormatter::format_appendable(intervals, sink.format, run_id.as_deref(), csv_dialect)
+                }
+                (Some(baseline), OutputFormat::Waterfall) => {
+                    OutputFormatter::format_waterfall_comparison(baseline, intervals)
+                }
+                (_, OutputFormat::Waterfall) if args.options.stats => {
+                    OutputFormatter::format_stats_waterfall_with(transition_stats, waterfall, colors)
+                }
+                (_, OutputFormat::Waterfall) => {
+                    let rows = page.map_or(intervals, |page| &intervals[page.range(intervals.len())]);
+                    OutputFormatter::format_waterfall_with(rows, waterfall, &markers, colors)
+                }
+                (_, OutputFormat::ChromeTrace) if !args.options.stats => {
+                    let rows = page.map_or(intervals, |page| &intervals[page.range(intervals.len())]);
+                    OutputFormatter::format_chrome_trace_with(rows, &markers)
+                }
+                (_, OutputFormat::Histogram) => OutputFormatter::format_histogram(transition_stats, histogram),
+                (_, OutputFormat::Prometheus) => prometheus::format_metrics(transition_stats),
+                (_, OutputFormat::Grafana) => {
+                    let rows = page.map_or(intervals, |page| &intervals[page.range(intervals.len())]);
+                    grafana::format_dashboard(if args.options.stats { &[] } else { rows }, transition_stats)
+                }
+                (_, OutputFormat::Html) => {
+                    let rows = page.map_or(intervals, |page| &intervals[page.range(intervals.len())]);
+                    html::format_report((!args.options.stats).then_some(rows), transition_stats, histogram, &markers, locale)
+                }
+                (_, OutputFormat::Table) if args.options.percentile_columns => {
+                    let rows = page.map_or(intervals, |page| &intervals[page.range(intervals.len())]);
+                    OutputFormatter::format_table_with_percentiles(rows, transition_stats, locale, colors)
+                }
+                (_, OutputFormat::Markdown) if args.options.percentile_columns => {
+                    let rows = page.map_or(intervals, |page| &intervals[page.range(intervals.len())]);
+                    OutputFormatter::format_markdown_with_percentiles(rows, transition_stats, locale)
+                }
+                (_, OutputFormat::Csv) if args.options.stats => {
+                    OutputFormatter::format_stats_csv_with(transition_stats, csv_dialect)
+                }
+                (_, OutputFormat::Csv) => {
+                    let rows = page.map_or(intervals, |page| &intervals[page.range(intervals.len())]);
+                    OutputFormatter::format_csv_with(rows, csv_dialect)
+                }
+                _ if args.options.stats => {
+                    OutputFormatter::format_stats_styled(transition_stats, sink.format, locale, colors)
+                }
+                _ => match page {
+                    Some(page) => OutputFormatter::format_page(intervals, page, sink.format, locale, colors),
+                    None => OutputFormatter::format_intervals_styled(intervals, sink.format, locale, colors),
+                },
+            };
+            if sink.target == SinkTarget::Stdout {
+                // Separate consecutive formats printed to the terminal
+                if wrote_stdout {
+                    println!();
+                }
+                wrote_stdout = true;
+            }
+            if args.options.append {
+                sink.append(&output)?;
+            } else {
+                sink.write(&output)?;
+            }
+            report.get_or_insert((sink.format, output));
+        }
+        Ok(())
+    };
+    match split_window {
+        Some(window) => {
+            for (label, intervals) in window.split(&intervals) {
+                deliver(&intervals, &stats::compute(&exclusions.counted(&intervals), trim), Some(&label))?;
+            }
+        }
+        None => deliver(&intervals, &transition_stats, None)?,
+    }
+    let (report_format, output) = report.unwrap_or((OutputFormat::Human, String::new()));
+    
+    #[cfg(feature = "sql")]
+    if let (Some(path), Some(matches)) = (&args.options.output_db, &db_matches) {
+        let run = database::Run {
+            label: run_id.as_deref(),
+            started_at: now,
+            log_files: &log_files,
+            partial,
+        };
+        let id = database::write_run(path, &run, matches, &intervals)?;
+        if show_summary {
+            eprintln!("Saved as run {} in {}", id, path.display());
+        }
+    }
+    
+    if partial {
+        // Don't notify anyone about a run the user cut short
+        drop(progress_reporter);
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    }
+    
+    // Computed before notifying, so the summaries list the budget failures
+    let violations = Analyzer::threshold_violations(&counted, &config)?;
+    for webhook in &webhooks {
+        notify::post_summary(webhook, &report_title, &intervals, &violations)
+            .context("Failed to send webhook notification")?;
+    }
+    
+    if let Some(email) = email {
+        email::send_report(&email, &report_title, &output, report_format)
+            .context("Failed to email report")?;
+    }
+    
+    // Failed last so the results are still printed and delivered for the failing run
+    if !violations.is_empty() {
+        for (interval, limit) in &violations {
+            eprintln!("Over {}: {}", format_duration(limit), interval.format());
+        }
+        anyhow::bail!(
+            "{} of {} intervals exceeded their fail_if_over threshold",
+            violations.len(),
+            intervals.len()
+        );
+    }
+    
+    Ok(())
+}
+
+/// Intervals of a baseline log file (`--baseline`, `compare`), analyzed with the same config
+/// as the run, with `--group-by` labels
+fn analyze_baseline(path: &Path, config: &Config, options: &AnalyzeArgs, interrupted: &Arc<AtomicBool>) -> Result<Vec<Interval>> {
+    let mut parser = LogParser::new(config)
+        .context("Failed to create log parser")?;
+    parser.set_stop_flag(interrupted.clone());
+    if let Some(threads) = options.threads {
+        parser.set_threads(threads.into());
+    }
+    let matches = parser.parse_file(path)
+        .context("Failed to parse baseline log file")?;
+    let intervals = Analyzer::apply_min_duration(Analyzer::analyze_with(matches, config), config)?;
+    let mut intervals = ExcludedWindows::new(&config.excluded_windows)?.exclude(intervals);
+    if let Some(capture) = &options.group_by {
+        intervals.iter_mut().for_each(|interval| interval.group_by(capture));
+    }
+    
+    Ok(intervals)
+}
+
+/// The `--where` conditions, which may refer to the config's derived values
+fn where_conditions(options: &AnalyzeArgs, config: &Config) -> Result<Vec<Condition>> {
+    let derived_values = config.derived_values()?;
+    let names: Vec<&str> = derived_values.iter().map(|derived| derived.name.as_str()).collect();
+    options
+        .conditions
+        .iter()
+        .map(|text| Condition::parse(text, &names).with_context(|| format!("Invalid --where '{}'", text)))
+        .collect()
+}
+
+/// The configuration file or profile with the command line's overrides applied, validated
+fn load_config(options: &AnalyzeArgs) -> Result<Config> {
+    let patterns = if options.patterns.is_empty() {
+        None
+    } else {
+        Some(options.patterns.iter().map(|spec| MessagePattern::parse(spec)).collect())
+    };
+    
+    let profile = match (&options.profile, &options.profile_url) {
+        (Some(name), _) => Some(profiles::load_profile(name, profiles::profile_dir().as_deref())?),
+        (None, Some(url)) => Some(profiles::fetch_profile(url)?),
+        (None, None) => None,
+    };
+    let mut config = match profile {
+        Some(profile) => profile.with_overrides(
+            options.timestamp_regex.clone(),
+            options.timestamp_format.clone(),
+            patterns,
+        ),
+        None => Config::from_file_with_overrides(
+            options.config.as_deref(),
+            options.timestamp_regex.clone(),
+            options.timestamp_format.clone(),
+            patterns,
+        ),
+    }
+    .context("Failed to load configuration")?;
+    
+    config.exclude_patterns.extend(options.exclude_patterns.iter().cloned());
+    if let Some(min) = options.min_duration.clone() {
+        duration::parse_duration(&min).with_context(|| format!("Invalid --min-duration: {}", min))?;
+        config.min_duration = Some(min);
+    }
+    if let Some(iterations) = options.skip_iterations {
+        config.skip_iterations = Some(iterations);
+    }
+    if let Some(warmup) = options.skip_duration.clone() {
+        duration::parse_duration(&warmup).with_context(|| format!("Invalid --skip-duration: {}", warmup))?;
+        config.skip_duration = Some(warmup);
+    }
+    if let Some(limit) = options.fail_if_over.clone() {
+        duration::parse_duration(&limit).with_context(|| format!("Invalid --fail-if-over: {}", limit))?;
+        config.fail_if_over = Some(limit);
+    }
+    if let Some(thresholds) = options.color_thresholds.clone() {
+        DurationColors::parse(&thresholds).with_context(|| format!("Invalid --color-thresholds: {}", thresholds))?;
+        config.color_thresholds = Some(thresholds);
+    }
+    if let Some(format) = &options.input_format {
+        config.input_format = format.parse::<InputFormat>().map_err(|_| {
+            anyhow::anyhow!("Invalid input format '{}'. Valid options: text, json", format)
+        })?;
+    }
+    if options.timestamp_field.is_some() {
+        config.timestamp_field = options.timestamp_field.clone();
+    }
+    if options.message_field.is_some() {
+        config.message_field = options.message_field.clone();
+    }
+    if options.filename_timestamp.is_some() {
+        config.filename_timestamp_regex = options.filename_timestamp.clone();
+        config.filename_timestamp_format = options.filename_timestamp_format.clone();
+    }
+    if options.offset_regex.is_some() {
+        config.offset_regex = options.offset_regex.clone();
+    }
+    if options.embedded_events.is_some() {
+        config.embedded_event_regex = options.embedded_events.clone();
+        config.embedded_timestamp_format = options.embedded_timestamp_format.clone();
+    }
+    if let Some(spec) = &options.reported_duration {
+        config.reported_duration = Some(ReportedDuration::parse(spec));
+    }
+    for spec in &options.derive {
+        let (name, expr) = spec
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Invalid --derive '{}', expected NAME=EXPR", spec))?;
+        config.derived.push(DerivedValue {
+            name: name.trim().to_string(),
+            expr: expr.trim().to_string(),
+        });
+    }
+    if !options.clock_anchors.is_empty() {
+        config.clock_anchors = options.clock_anchors.clone();
+    }
+    config.multiline |= options.multiline;
+    if options.continuation_regex.is_some() {
+        config.continuation_regex = options.continuation_regex.clone();
+    }
+    config.validate().context("Invalid configuration")?;
+    if let Some(tag) = options.tags.iter().find(|tag| !config.tags.iter().any(|rule| &rule.tag == *tag)) {
+        anyhow::bail!("--tag {} is not defined by any of the config's tags rules", tag);
+    }
+    where_conditions(options, &config)?;
+    if options.correlation_regex.is_some() {
+        config.correlation_regex = options.correlation_regex.clone();
+    }
+    if options.max_line_length.is_some() {
+        config.max_line_length = options.max_line_length;
+    }
+    if options.prefer_format.is_some() {
+        config.prefer_format = options.prefer_format.clone();
+    }
+    config.anchor_timestamp |= options.anchor_timestamp;
+    config.strip_ansi |= options.strip_ansi;
+    config.mixed_timestamp_formats |= options.mixed_timestamp_formats;
+    
+    Ok(config)
+}
+
+/// Colors of durations printed to stdout, or `None` when `--color` leaves them off
+fn stdout_colors(options: &AnalyzeArgs, config: &Config) -> Result<Option<DurationColors>> {
+    let choice = options.color.parse::<ColorChoice>().map_err(|_| {
+        anyhow::anyhow!("Invalid --color '{}'. Valid options: auto, always, never", options.color)
+    })?;
+    if !choice.enabled(io::stdout().is_terminal()) {
+        return Ok(None);
+    }
+    let colors = match &config.color_thresholds {
+        Some(thresholds) => DurationColors::parse(thresholds)?,
+        None => DurationColors::default(),
+    };
+    Ok(Some(colors))
+}
+
+/// Report the parse's progress on the descriptor `fd` until the returned reporter is dropped
+fn start_progress(
+    fd: i32,
+    parser: &mut LogParser,
+    log_files: &[PathBuf],
+    interrupted: &Arc<AtomicBool>,
+) -> Result<progress::ProgressReporter> {
+    let out = progress::open_fd(fd)?;
+    let total_bytes = (!log_files.is_empty()).then(|| {
+        log_files.iter().filter_map(|path| std::fs::metadata(path).ok()).map(|metadata| metadata.len()).sum()
+    });
+    let start = progress::ProgressEvent::Start {
+        files: log_files.len(),
+        total_bytes,
+    };
+    let counters = Arc::new(progress::Progress::default());
+    parser.set_progress(counters.clone());
+    Ok(progress::ProgressReporter::start(
+        Box::new(out),
+        counters,
+        start,
+        progress::HEARTBEAT_INTERVAL,
+        interrupted.clone(),
+    ))
+}
+
+/// Stop parsing gracefully on the first Ctrl-C/SIGTERM so partial results can still be
+/// printed; a second signal exits immediately
+fn install_interrupt_handler() -> Result<Arc<AtomicBool>> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = interrupted.clone();
+    ctrlc::set_handler(move || {
+        if flag.swap(true, Ordering::SeqCst) {
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+    })
+    .context("Failed to install the Ctrl-C handler")?;
+    
+    Ok(interrupted)
+}
+
+/// What `--follow` does with each interval besides printing it: keep it in the journal, and
+/// report it when it is over its `fail_if_over` budget
+struct FollowSinks<'a> {
+    journal: Option<journal::Journal>,
+    webhooks: &'a [notify::Webhook],
+    /// Title of the alerts, as of the run summaries
+    title: &'a str,
+    bell: bool,
+    desktop: bool,
+}
+
+impl FollowSinks<'_> {
+    fn alerts(&self) -> bool {
+        !self.webhooks.is_empty() || self.bell || self.desktop
+    }
+    
+    /// Ring the bell, show the desktop notification, and post the alert to every webhook. A
+    /// webhook that fails is reported and following goes on; a failing desktop notifier is
+    /// reported once and not tried again.
+    fn over_budget(&mut self, interval: &Interval, limit: &chrono::Duration) {
+        if self.bell {
+            eprint!("\x07");
+        }
+        if self.desktop {
+            if let Err(e) = notify::notify_desktop(self.title, interval, limit) {
+                eprintln!("Warning: {:#}; desktop notifications are off", e);
+                self.desktop = false;
+            }
+        }
+        for webhook in self.webhooks {
+            if let Err(e) = notify::post_alert(webhook, self.title, interval, limit) {
+                eprintln!("Warning: {:#}", e);
+            }
+        }
+    }
+}
+
+/// Stream intervals from a growing log file or stdin until the input ends or the user stops it.
+///
+/// Reading, pairing, and printing run as separate stages connected by bounded channels, so a
+/// slow consumer makes the reader wait instead of queueing an unbounded backlog in memory.
+/// Edits to the config file are picked up between two lines (see `watch_config`).
+fn run_follow(
+    parser: &mut LogParser,
+    config: &Config,
+    log_files: &[PathBuf],
+    formats: &[OutputFormat],
+    options: &AnalyzeArgs,
+    mut sinks: FollowSinks,
+    stop: &AtomicBool,
+) -> Result<()> {
+    let &[format] = formats else {
+        anyhow::bail!("--follow prints a single output format");
+    };
+    let has_budget = config.fail_if_over.is_some() || config.transitions.iter().any(|t| t.fail_if_over.is_some());
+    if sinks.alerts() && !has_budget {
+        anyhow::bail!(
+            "--notify, --bell, and --notify-desktop with --follow alert on intervals over fail_if_over; set --fail-if-over or fail_if_over in the config"
+        );
+    }
+    if sinks.desktop && cfg!(windows) {
+        anyhow::bail!("--notify-desktop is not supported on Windows");
+    }
+    if !format.supports_streaming() {
+        anyhow::bail!("--follow supports the human, simple, json, and jsonl formats, which can print one interval at a time");
+    }
+    let locale = options.locale.as_deref().map(locale::resolve).transpose()?;
+    let colors = stdout_colors(options, config)?;
+    let filters = IntervalFilters::new(config, options)?;
+    
+    let log_file = match log_files {
+        [] => {
+            if io::stdin().is_terminal() {
+                anyhow::bail!("No log file provided and stdin is not piped. Use --log-file or pipe input.");
+            }
+            None
+        }
+        [log_file] => {
+            let file = std::fs::File::open(log_file)
+                .with_context(|| format!("Failed to open log file: {:?}", log_file))?;
+            let mut reader = io::BufReader::new(file);
+            let header = reader.fill_buf().with_context(|| format!("Failed to read log file: {:?}", log_file))?;
+            if decompress::Compression::detect(header) != decompress::Compression::None {
+                anyhow::bail!("{:?} is compressed and cannot be followed", log_file);
+            }
+            Some(reader)
+        }
+        _ => anyhow::bail!("--follow reads a single log file; give archived logs with --backfill"),
+    };
+    let backfill = parser::expand_log_files(&options.backfill)?;
+    
+    let (match_sender, match_receiver) = mpsc::sync_channel::<FollowEvent>(FOLLOW_CHANNEL_CAPACITY);
+    // Each interval with the `fail_if_over` budget it exceeded, if any
+    let (interval_sender, interval_receiver) =
+        mpsc::sync_channel::<(Interval, Option<chrono::Duration>)>(FOLLOW_CHANNEL_CAPACITY);
+    let (update_sender, updates) = mpsc::channel::<Config>();
+    let watched = options.config.as_deref();
+    let reader_done = AtomicBool::new(false);
+    
+    thread::scope(|scope| {
+        let parser = &mut *parser;
+        let updates = watched.map(|_| updates);
+        let reader = scope.spawn(move || {
+            // A failed send means the later stages have stopped; there is nobody left to tell
+            let on_event = |event| {
+                let _ = match_sender.send(event);
+            };
+            // History first, so intervals spanning the switch to the live log are measured too
+            let mut history = Vec::new();
+            for path in &backfill {
+                history.extend(parser.parse_file(path).with_context(|| format!("Failed to read backfill log {:?}", path))?);
+            }
+            // Stable sort keeps each file's own ordering for identical timestamps
+            history.sort_by_key(|m| m.timestamp);
+            history.into_iter().for_each(|m| on_event(FollowEvent::Match(m)));
+            
+            match log_file {
+                Some(reader) => parser
+                    .follow_reloading(reader, Some(FOLLOW_POLL_INTERVAL), updates.as_ref(), on_event)
+                    .context("Failed to follow log file"),
+                None => parser
+                    .follow_reloading(io::stdin().lock(), None, updates.as_ref(), on_event)
+                    .context("Failed to follow log from stdin"),
+            }
+        });
+        
+        if let Some(path) = watched {
+            let reader_done = &reader_done;
+            scope.spawn(move || watch_config(path, options, config, update_sender, reader_done));
+        }
+        
+        scope.spawn(move || {
+            let mut config = config.clone();
+            let mut consecutive = ConsecutiveTracker::new();
+            let mut pairs = PairTracker::new(&config.pairs);
+            let mut warmup = Warmup::new(&config);
+            let mut filters = filters;
+            for event in match_receiver {
+                let log_match = match event {
+                    FollowEvent::Match(log_match) => log_match,
+                    FollowEvent::Reloaded(reloaded) => {
+                        // `watch_config` only sends configurations whose filters compile
+                        let Ok(reloaded_filters) = IntervalFilters::new(&reloaded, options) else {
+                            continue;
+                        };
+                        // Starts still open under unchanged pairs keep waiting for their ends
+                        if reloaded.pairs != config.pairs {
+                            pairs = PairTracker::new(&reloaded.pairs);
+                        }
+                        config = *reloaded;
+                        filters = reloaded_filters;
+                        continue;
+                    }
+                };
+                let completed = complete_intervals(&log_match, &mut consecutive, &mut pairs, &mut warmup, &config, &filters, options);
+                for interval in completed {
+                    // Budgets were validated with the configuration, which may have been reloaded;
+                    // intervals in annotated windows never count against them
+                    let counted = filters.exclusions.counted(std::slice::from_ref(&interval));
+                    let over = Analyzer::threshold_violations(&counted, &config)
+                        .ok()
+                        .and_then(|violations| violations.first().map(|&(_, limit)| limit));
+                    if interval_sender.send((interval, over)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        
+        let mut journal_error = None;
+        for (interval, over) in interval_receiver {
+            if let Some(journal) = sinks.journal.as_mut() {
+                if let Err(e) = journal.append(&interval) {
+                    // Stop following rather than carry on without the durable record
+                    stop.store(true, Ordering::SeqCst);
+                    journal_error = Some(e);
+                    break;
+                }
+            }
+            println!("{}", OutputFormatter::format_streamed(&interval, format, locale, colors));
+            if let Some(limit) = over {
+                sinks.over_budget(&interval, &limit);
+            }
+        }
+        
+        let read = reader.join();
+        reader_done.store(true, Ordering::SeqCst);
+        read.map_err(|_| anyhow::anyhow!("The log reader thread panicked"))??;
+        journal_error.map_or(Ok(()), Err)
+    })
+}
+
+/// Print every interval of a single log file or stdin as soon as it completes (`--stream`),
+/// holding only the open matches in memory rather than all matches and intervals
+fn run_stream(
+    parser: &mut LogParser,
+    config: &Config,
+    log_files: &[PathBuf],
+    formats: &[OutputFormat],
+    options: &AnalyzeArgs,
+) -> Result<()> {
+    let &[format] = formats else {
+        anyhow::bail!("--stream prints a single output format");
+    };
+    if !format.supports_streaming() {
+        anyhow::bail!("--stream supports the human, simple, json, and jsonl formats, which can print one interval at a time");
+    }
+    let locale = options.locale.as_deref().map(locale::resolve).transpose()?;
+    let colors = stdout_colors(options, config)?;
+    
+    let matches = match log_files {
+        [] => {
+            if io::stdin().is_terminal() {
+                anyhow::bail!("No log file provided and stdin is not piped. Use --log-file or pipe input.");
+            }
+            let stdin: Box<dyn BufRead> = Box::new(io::stdin().lock());
+            parser.matches(stdin).context("Failed to parse log from stdin")?
+        }
+        [log_file] => parser.file_matches(log_file).context("Failed to parse log file")?,
+        _ => anyhow::bail!("--stream reads a single log file; merging several sorts all of their matches"),
+    };
+    
+    let mut consecutive = ConsecutiveTracker::new();
+    let mut pairs = PairTracker::new(&config.pairs);
+    let mut warmup = Warmup::new(config);
+    let filters = IntervalFilters::new(config, options)?;
+    for log_match in matches {
+        let log_match = log_match.context("Failed to parse log")?;
+        for interval in complete_intervals(&log_match, &mut consecutive, &mut pairs, &mut warmup, config, &filters, options) {
+            println!("{}", OutputFormatter::format_streamed(&interval, format, locale, colors));
+        }
+    }
+    Ok(())
+}
+
+/// The intervals one more match completes: between start/end pairs when `config` has
+/// `pairs`, consecutive matches otherwise, with derived values, tags, and the `--group-by`
+/// label added and only those past the warm-up and passing the minimum durations, the
+/// excluded windows, and the `--tag` and `--where` filters kept; clock anchors complete none
+fn complete_intervals(
+    log_match: &LogMatch,
+    consecutive: &mut ConsecutiveTracker,
+    pairs: &mut PairTracker,
+    warmup: &mut Warmup,
+    config: &Config,
+    filters: &IntervalFilters,
+    options: &AnalyzeArgs,
+) -> Vec<Interval> {
+    // A single log has no clock offsets to correct, but anchors are still not measured
+    if config.clock_anchors.contains(&log_match.pattern) {
+        return Vec::new();
+    }
+    warmup.observe(log_match.timestamp);
+    let mut completed: Vec<Interval> = if config.pairs.is_empty() {
+        consecutive.push(log_match).into_iter().collect()
+    } else {
+        pairs.push(log_match)
+    };
+    completed.retain(|interval| warmup.keep(interval));
+    filters.rules.derive_values(&mut completed);
+    filters.rules.tag(&mut completed);
+    if let Some(capture) = &options.group_by {
+        completed.iter_mut().for_each(|interval| interval.group_by(capture));
+    }
+    completed.retain(|interval| filters.rules.long_enough(interval));
+    if !filters.exclusions.is_empty() {
+        completed = filters.exclusions.apply(completed).0;
+    }
+    completed.retain(|interval| {
+        (options.tags.is_empty() || interval.has_any_tag(&options.tags))
+            && filters.conditions.iter().all(|condition| condition.matches(interval))
+    });
+    completed
+}
+
+/// What `complete_intervals` applies to every interval, compiled once per configuration
+struct IntervalFilters {
+    rules: IntervalRules,
+    /// The `--where` conditions
+    conditions: Vec<Condition>,
+    exclusions: ExcludedWindows,
+}
+
+impl IntervalFilters {
+    fn new(config: &Config, options: &AnalyzeArgs) -> Result<Self> {
+        Ok(IntervalFilters {
+            rules: IntervalRules::new(config)?,
+            conditions: where_conditions(options, config)?,
+            exclusions: ExcludedWindows::new(&config.excluded_windows)?,
+        })
+    }
+}
+
+/// Poll the config file of a `--follow` run and send every valid edit, with the command
+/// line's overrides applied again, to the reader until it is done. An edit that doesn't load
+/// is reported and the previous configuration stays in effect.
+fn watch_config(
+    path: &Path,
+    options: &AnalyzeArgs,
+    initial: &Config,
+    updates: mpsc::Sender<Config>,
+    done: &AtomicBool,
+) {
+    let modified = || std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let mut last_modified = modified();
+    
+    while !done.load(Ordering::SeqCst) {
+        thread::sleep(FOLLOW_POLL_INTERVAL);
+        let now = modified();
+        if now == last_modified {
+            continue;
+        }
+        last_modified = now;
+        
+        let loaded = load_config(options).and_then(|config| {
+            LogParser::new(&config).context("Failed to create log parser")?;
+            IntervalFilters::new(&config, options)?;
+            Ok(config)
+        });
+        let config = match loaded {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Warning: keeping the previous configuration, {} did not load: {:#}", path.display(), e);
+                continue;
+            }
+        };
+        if !same_timestamp_settings(&config, initial) {
+            eprintln!("Warning: timestamp and input settings of {} only change on restart", path.display());
+        }
+        if updates.send(config).is_err() {
+            return;
+        }
+        eprintln!("Reloaded {}", path.display());
+    }
+}
+
+/// Whether two configurations read timestamps the same way, which a reload cannot change
+fn same_timestamp_settings(a: &Config, b: &Config) -> bool {
+    a.timestamp_regex == b.timestamp_regex
+        && a.timestamp_format == b.timestamp_format
+        && a.input_format == b.input_format
+        && a.timestamp_field == b.timestamp_field
+        && a.message_field == b.message_field
+        && a.filename_timestamp_regex == b.filename_timestamp_regex
+        && a.filename_timestamp_format == b.filename_timestamp_format
+        && a.offset_regex == b.offset_regex
+        && a.prefer_format == b.prefer_format
+}
+
+/// Timestamps of the detected format shown by `detect`
+const DETECT_SAMPLE_TIMESTAMPS: usize = 3;
+
+fn run_detect(
+    log_files: &[PathBuf],
+    prefer_format: Option<String>,
+    anchor_timestamp: bool,
+    explain: bool,
+    lines: usize,
+) -> Result<()> {
+    // No message patterns: only the timestamps are of interest
+    let config = Config {
+        is_auto_detect: true,
+        prefer_format,
+        anchor_timestamp,
+        ..Default::default()
+    };
+    let mut parser = LogParser::new(&config).context("Failed to create log parser")?;
+    
+    // The first lines, labelled with where they come from, to explain and take samples from
+    let log_files = parser::expand_log_files(log_files)?;
+    let first_lines: Vec<(String, String)> = match log_files.first() {
+        None => {
+            if io::stdin().is_terminal() {
+                anyhow::bail!("No log file provided and stdin is not piped. Use --log-file or pipe input.");
+            }
+            let mut input = String::new();
+            io::Read::read_to_string(&mut io::stdin().lock(), &mut input).context("Failed to read log from stdin")?;
+            parser.parse_reader(io::Cursor::new(&input)).context("Failed to parse log from stdin")?;
+            input
+                .lines()
+                .take(lines)
+                .enumerate()
+                .map(|(i, line)| (format!("line {}", i + 1), line.to_string()))
+                .collect()
+        }
+        Some(first) => {
+            parser.parse_files(&log_files).context("Failed to parse log files")?;
+            decompress::open_log_file(first)?
+                .lines()
+                .take(lines)
+                .enumerate()
+                .map(|(i, line)| Ok((format!("{}:{}", first.display(), i + 1), line?)))
+                .collect::<io::Result<_>>()
+                .with_context(|| format!("Failed to read log file: {:?}", first))?
+        }
+    };
+    let detected = parser.detected_format().map(|format| format.name);
+    
+    if explain {
+        for (location, line) in &first_lines {
+            println!("{}: {}", location, line);
+            let candidates = parser.explain_line(line);
+            if candidates.is_empty() {
+                println!("    no built-in format finds a timestamp");
+            }
+            let width = candidates.iter().map(|c| c.format.len()).max().unwrap_or(0);
+            for candidate in candidates {
+                let outcome = match &candidate.parsed {
+                    Ok(time) => time.clone(),
+                    Err(e) => format!("does not parse: {}", e),
+                };
+                let chosen = if Some(candidate.format) == detected { "  <- chosen" } else { "" };
+                println!(
+                    "    {:<width$}  \"{}\" -> {}{}",
+                    candidate.format, candidate.text, outcome, chosen, width = width
+                );
+            }
+        }
+        println!();
+    }
+    
+    if let Some(report) = parser.format_detection_report() {
+        println!("{}", report);
+        println!();
+    }
+    let Some(detected) = parser.detected_format() else {
+        let hint = if explain { "" } else { "; run `detect --explain` to see what each line holds" };
+        anyhow::bail!("No timestamps could be detected automatically{}", hint);
+    };
+    
+    let samples: Vec<String> = first_lines
+        .iter()
+        .filter_map(|(location, line)| {
+            let candidate = parser.explain_line(line).into_iter().find(|c| c.format == detected.name)?;
+            Some(format!("  {}: \"{}\" -> {}", location, candidate.text, candidate.parsed.ok()?))
+        })
+        .take(DETECT_SAMPLE_TIMESTAMPS)
+        .collect();
+    if !samples.is_empty() {
+        println!("Sample timestamps parsed with {}:", detected.name);
+        println!("{}", samples.join("\n"));
+        println!();
+    }
+    
+    let mut pinned = serde_yaml::Mapping::new();
+    pinned.insert("timestamp_regex".into(), detected.regex.into());
+    pinned.insert("timestamp_format".into(), detected.format.into());
+    let yaml = serde_yaml::to_string(&pinned).context("Failed to serialize the detected format")?;
+    println!("Detected {}; to pin it in a config file:", detected.name);
+    print!("{}", yaml);
+    Ok(())
+}
+
+fn run_validate_config(path: &Path) -> Result<()> {
+    let config = Config::from_file(path).with_context(|| format!("{} is invalid", path.display()))?;
+    // Loading checked the settings and expressions; the parser compiles every regex
+    LogParser::new(&config).with_context(|| format!("{} is invalid", path.display()))?;
+    
+    println!(
+        "{}: OK ({} message patterns, {} pairs, {} tags rules)",
+        path.display(),
+        config.message_patterns.len(),
+        config.pairs.len(),
+        config.tags.len()
+    );
+    Ok(())
+}
+
+fn run_batch(args: &Args, manifest_path: &Path, jobs: Option<usize>) -> Result<()> {
+    let manifest = batch::Manifest::from_file(manifest_path)
+        .context("Failed to load batch manifest")?;
+    
+    let start = Instant::now();
+    let results = batch::run(&manifest, jobs);
+    let summary = batch::format_summary(&results, start.elapsed());
+    println!("{}", summary);
+    
+    let failed = results.iter().filter(|r| r.outcome.is_err()).count();
+    
+    if let Some(email) = args.email_settings() {
+        let subject = args.email_subject.clone().unwrap_or_else(|| {
+            format!(
+                "Batch analysis summary: {} jobs, {} failed",
+                results.len(),
+                failed
+            )
+        });
+        email::send_report(&email, &subject, &summary, OutputFormat::Human)
+            .context("Failed to email batch summary")?;
+    }
+    
+    if failed > 0 {
+        anyhow::bail!("{} of {} batch jobs failed", failed, results.len());
+    }
     
     Ok(())
 }
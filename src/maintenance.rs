@@ -0,0 +1,199 @@
+/// Excluded time windows (`excluded_windows`): maintenance, backups, and other known-noisy
+/// periods whose intervals are dropped, or tagged and left out of statistics and SLOs
+use crate::error::{LogLineError, Result};
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+use std::borrow::Cow;
+
+use crate::analyzer::Interval;
+use crate::config::{ExcludedWindow, WindowAction};
+use crate::duration::parse_timestamp;
+
+/// When an excluded window applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Span {
+    /// From one timestamp up to (not including) another
+    Once(NaiveDateTime, NaiveDateTime),
+    /// Every day from one time of day up to another, which is on the next day when earlier
+    Daily(NaiveTime, NaiveTime),
+}
+
+impl Span {
+    /// Parse the start and end of a window: two timestamps, or two times of day
+    pub fn parse(start: &str, end: &str) -> Result<Self> {
+        let time_of_day = |text: &str| {
+            ["%H:%M:%S", "%H:%M"]
+                .iter()
+                .find_map(|format| NaiveTime::parse_from_str(text.trim(), format).ok())
+        };
+        if let (Some(start), Some(end)) = (time_of_day(start), time_of_day(end)) {
+            if start == end {
+                return Err(LogLineError::config(format!("The window from {} to {} is empty", start, end)));
+            }
+            return Ok(Span::Daily(start, end));
+        }
+
+        match (parse_timestamp(start), parse_timestamp(end)) {
+            (Some(from), Some(to)) if from < to => Ok(Span::Once(from, to)),
+            (Some(_), Some(_)) => Err(LogLineError::config(format!("The window must end after it starts, got {} to {}", start, end))),
+            _ => Err(LogLineError::config(format!(
+                "Expected two timestamps such as '2025-12-24 00:00' or two times of day such as '02:00', got '{}' and '{}'",
+                start,
+                end
+            ))),
+        }
+    }
+
+    /// Whether anything from `from` to `to` (both included) falls into the window
+    pub fn overlaps(&self, from: NaiveDateTime, to: NaiveDateTime) -> bool {
+        match *self {
+            Span::Once(start, end) => from < end && to >= start,
+            Span::Daily(_, _) if to - from >= Duration::days(1) => true,
+            Span::Daily(start, end) => {
+                // Starting with the day before, whose window may run past midnight
+                let mut day = from.date() - Duration::days(1);
+                while day <= to.date() {
+                    let window_start = day.and_time(start);
+                    let window_end = if end > start { day.and_time(end) } else { (day + Duration::days(1)).and_time(end) };
+                    if from < window_end && to >= window_start {
+                        return true;
+                    }
+                    day += Duration::days(1);
+                }
+                false
+            }
+        }
+    }
+}
+
+/// The configured windows, parsed
+#[derive(Debug, Clone, Default)]
+pub struct ExcludedWindows {
+    windows: Vec<(String, Span, WindowAction)>,
+}
+
+impl ExcludedWindows {
+    pub fn new(windows: &[ExcludedWindow]) -> Result<Self> {
+        let windows = windows
+            .iter()
+            .map(|window| Ok((window.name.clone(), Span::parse(&window.start, &window.end)?, window.action)))
+            .collect::<Result<_>>()?;
+        Ok(ExcludedWindows { windows })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// The first window the interval overlaps; intervals without timestamps are in none
+    fn window_of(&self, interval: &Interval) -> Option<&(String, Span, WindowAction)> {
+        let start = interval.start?;
+        let end = interval.end.unwrap_or(start + interval.duration);
+        let (from, to) = (start.min(end), start.max(end));
+        self.windows.iter().find(|(_, span, _)| span.overlaps(from, to))
+    }
+
+    /// Drop the intervals in a `drop` window and tag those in an `annotate` window with its
+    /// name; returns the remaining intervals and how many were dropped and annotated
+    pub fn apply(&self, intervals: Vec<Interval>) -> (Vec<Interval>, usize, usize) {
+        let (mut dropped, mut annotated) = (0, 0);
+        let kept = intervals
+            .into_iter()
+            .filter_map(|mut interval| {
+                match self.window_of(&interval) {
+                    Some((_, _, WindowAction::Drop)) => {
+                        dropped += 1;
+                        return None;
+                    }
+                    Some((name, _, WindowAction::Annotate)) => {
+                        annotated += 1;
+                        if !interval.tags.contains(name) {
+                            interval.tags.push(name.clone());
+                        }
+                    }
+                    None => {}
+                }
+                Some(interval)
+            })
+            .collect();
+        (kept, dropped, annotated)
+    }
+
+    /// The intervals that count towards statistics and SLOs: all but those `apply` tagged
+    /// for an `annotate` window
+    pub fn counted<'a>(&self, intervals: &'a [Interval]) -> Cow<'a, [Interval]> {
+        let annotated: Vec<String> = self
+            .windows
+            .iter()
+            .filter(|(_, _, action)| *action == WindowAction::Annotate)
+            .map(|(name, _, _)| name.clone())
+            .collect();
+        if annotated.is_empty() {
+            return Cow::Borrowed(intervals);
+        }
+        Cow::Owned(intervals.iter().filter(|i| !i.has_any_tag(&annotated)).cloned().collect())
+    }
+
+    /// The intervals outside every window, whatever its action, for the checks that only
+    /// look at statistics (assertions, SLAs, comparisons)
+    pub fn exclude(&self, intervals: Vec<Interval>) -> Vec<Interval> {
+        intervals.into_iter().filter(|interval| self.window_of(interval).is_none()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
+
+    fn time(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn interval(start: &str, seconds: i64) -> Interval {
+        Interval::new("request", "response", Duration::seconds(seconds)).starting_at(time(start))
+    }
+
+    #[test]
+    fn test_daily_window_wraps_past_midnight() {
+        let span = Span::parse("23:30", "00:30").unwrap();
+        assert!(span.overlaps(time("2025-11-13 23:45:00"), time("2025-11-13 23:46:00")));
+        assert!(span.overlaps(time("2025-11-14 00:10:00"), time("2025-11-14 00:11:00")));
+        assert!(span.overlaps(time("2025-11-13 23:00:00"), time("2025-11-13 23:30:00")), "ends as it opens");
+        assert!(!span.overlaps(time("2025-11-14 00:30:00"), time("2025-11-14 23:29:59")));
+
+        assert!(Span::parse("02:00", "02:00").is_err());
+        assert!(Span::parse("2025-12-24", "2025-12-23").is_err());
+        assert!(Span::parse("02:00", "2025-12-24").is_err());
+    }
+
+    #[test]
+    fn test_drop_and_annotate() {
+        let windows = ExcludedWindows::new(&[
+            ExcludedWindow {
+                name: "deploy".to_string(),
+                start: "2025-11-13 10:00".to_string(),
+                end: "2025-11-13 10:30".to_string(),
+                action: WindowAction::Drop,
+            },
+            ExcludedWindow {
+                name: "backup".to_string(),
+                start: "02:00".to_string(),
+                end: "03:00".to_string(),
+                action: WindowAction::Annotate,
+            },
+        ])
+        .unwrap();
+        let intervals = vec![
+            interval("2025-11-13 09:59:00", 30),
+            interval("2025-11-13 09:59:00", 90),
+            interval("2025-11-14 02:59:00", 5),
+            interval("2025-11-14 03:00:00", 5),
+        ];
+
+        let (kept, dropped, annotated) = windows.apply(intervals.clone());
+        assert_eq!((kept.len(), dropped, annotated), (3, 1, 1));
+        assert_eq!(kept[1].tags, ["backup"]);
+        assert_eq!(windows.counted(&kept).len(), 2);
+        assert_eq!(windows.exclude(intervals).len(), 2);
+    }
+}
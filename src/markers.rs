@@ -0,0 +1,84 @@
+/// Named points in time (`--mark`), e.g. a deploy, drawn into the waterfall, html, and
+/// chrome-trace outputs so changes in the intervals can be lined up with outside events
+use crate::error::{LogLineError, Result};
+use chrono::NaiveDateTime;
+
+use crate::analyzer::Interval;
+use crate::duration::parse_time_bound;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    pub time: NaiveDateTime,
+    pub label: String,
+}
+
+impl Marker {
+    /// Parse `<time>=<label>`, the time given as for `--since` (e.g. `2025-11-13T10:05:00`,
+    /// or `2h` for two hours before `now`)
+    pub fn parse(spec: &str, now: NaiveDateTime) -> Result<Self> {
+        let (time, label) = spec
+            .split_once('=')
+            .ok_or_else(|| LogLineError::config(format!("Expected <time>=<label>, such as '2025-11-13T10:05:00=deploy v1.2', got '{}'", spec)))?;
+        let label = label.trim();
+        if label.is_empty() {
+            return Err(LogLineError::config(format!("Marker '{}' has no label", spec)));
+        }
+        Ok(Marker {
+            time: parse_time_bound(time, now)?,
+            label: label.to_string(),
+        })
+    }
+
+    /// Where the marker goes in a list of intervals: before the first one starting at or after
+    /// it, or after all of them
+    pub fn position(&self, intervals: &[Interval]) -> usize {
+        intervals
+            .iter()
+            .position(|interval| interval.start.is_some_and(|start| start >= self.time))
+            .unwrap_or(intervals.len())
+    }
+
+    /// `-- 2025-11-13 10:05:00 deploy v1.2 --`, a line of its own between intervals
+    pub fn rule(&self) -> String {
+        format!("-- {} {} --", self.time.format("%Y-%m-%d %H:%M:%S"), self.label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
+    use chrono::Duration;
+
+    fn time(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_place_markers() {
+        let now = time("2025-11-13 12:00:00");
+        let marker = Marker::parse("2025-11-13T10:05:00=deploy v1.2", now).unwrap();
+        assert_eq!(marker, Marker { time: time("2025-11-13 10:05:00"), label: "deploy v1.2".to_string() });
+        assert_eq!(marker.rule(), "-- 2025-11-13 10:05:00 deploy v1.2 --");
+        assert_eq!(Marker::parse("1h=failover", now).unwrap().time, time("2025-11-13 11:00:00"));
+        assert!(Marker::parse("2025-11-13T10:05:00", now).is_err());
+        assert!(Marker::parse("2025-11-13T10:05:00= ", now).is_err());
+
+        let intervals: Vec<Interval> = ["10:00:00", "10:04:59", "10:05:00", "10:10:00"]
+            .iter()
+            .map(|start| Interval::new("A", "B", Duration::seconds(1)).starting_at(time(&format!("2025-11-13 {}", start))))
+            .collect();
+        assert_eq!(marker.position(&intervals), 2);
+        assert_eq!(Marker::parse("2025-11-14=later", now).unwrap().position(&intervals), 4);
+    }
+
+    #[test]
+    fn test_invalid_markers() {
+        let now = time("2025-11-13 12:00:00");
+        let error = Marker::parse("deploy v1.2", now).unwrap_err();
+        assert!(error.to_string().starts_with("Expected <time>=<label>"), "{}", error);
+        let error = Marker::parse("after lunch=deploy", now).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid time 'after lunch'"), "{}", error);
+        assert!(Marker::parse("=deploy", now).is_err());
+    }
+}
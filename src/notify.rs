@@ -0,0 +1,354 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+
+use log_time_analyzer::analyzer::{format_duration, Interval};
+
+/// Number of slowest transitions included in a webhook summary
+const TOP_TRANSITIONS: usize = 5;
+
+/// Number of budget failures listed in a webhook summary; the rest are only counted
+const TOP_VIOLATIONS: usize = 5;
+
+/// An interval over its `fail_if_over` budget, with that budget
+pub type Violation<'a> = (&'a Interval, chrono::Duration);
+
+/// A chat webhook that receives run summaries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Webhook {
+    /// Slack incoming webhook (`slack://hooks.slack.com/services/...`)
+    Slack(String),
+    /// Microsoft Teams incoming webhook (`teams://example.webhook.office.com/...`)
+    Teams(String),
+}
+
+impl Webhook {
+    /// Parse a `slack://` or `teams://` notification target into its HTTPS endpoint
+    pub fn parse(spec: &str) -> Result<Self> {
+        if let Some(rest) = spec.strip_prefix("slack://") {
+            Ok(Webhook::Slack(format!("https://{}", rest)))
+        } else if let Some(rest) = spec.strip_prefix("teams://") {
+            Ok(Webhook::Teams(format!("https://{}", rest)))
+        } else {
+            anyhow::bail!(
+                "Invalid notification target '{}'. Expected slack://<webhook-host/path> or teams://<webhook-host/path>",
+                spec
+            )
+        }
+    }
+
+    fn url(&self) -> &str {
+        match self {
+            Webhook::Slack(url) | Webhook::Teams(url) => url,
+        }
+    }
+}
+
+/// Aggregated view of one transition used in summaries
+#[derive(Debug)]
+struct TransitionSummary {
+    from_pattern: String,
+    to_pattern: String,
+    count: usize,
+    max_ms: i64,
+    mean_ms: i64,
+}
+
+/// Group intervals by transition and return the slowest ones by maximum duration
+fn slowest_transitions(intervals: &[Interval], limit: usize) -> Vec<TransitionSummary> {
+    let mut groups: HashMap<(&str, &str), Vec<i64>> = HashMap::new();
+    for interval in intervals {
+        groups
+            .entry((&interval.from_pattern, &interval.to_pattern))
+            .or_default()
+            .push(interval.duration.num_milliseconds());
+    }
+
+    let mut summaries: Vec<TransitionSummary> = groups
+        .into_iter()
+        .map(|((from, to), durations)| TransitionSummary {
+            from_pattern: from.to_string(),
+            to_pattern: to.to_string(),
+            count: durations.len(),
+            max_ms: durations.iter().copied().max().unwrap_or(0),
+            mean_ms: durations.iter().sum::<i64>() / durations.len() as i64,
+        })
+        .collect();
+
+    summaries.sort_by_key(|t| std::cmp::Reverse(t.max_ms));
+    summaries.truncate(limit);
+    summaries
+}
+
+fn human_ms(ms: i64) -> String {
+    format_duration(&chrono::Duration::milliseconds(ms))
+}
+
+/// `from → to: took 1s 200ms, budget 1s 0ms`, with the interval's start when it has one
+fn violation_line(interval: &Interval, limit: &chrono::Duration) -> String {
+    let start = interval.start.map(|start| format!(" at {}", start.format("%Y-%m-%d %H:%M:%S"))).unwrap_or_default();
+    format!(
+        "{} → {}: took {}, budget {}{}",
+        interval.from_pattern,
+        interval.to_pattern,
+        format_duration(&interval.duration),
+        format_duration(limit),
+        start
+    )
+}
+
+/// The first `TOP_VIOLATIONS` budget failures, and a line counting the others
+fn violation_lines(violations: &[Violation]) -> Vec<String> {
+    let mut lines: Vec<String> = violations
+        .iter()
+        .take(TOP_VIOLATIONS)
+        .map(|(interval, limit)| violation_line(interval, limit))
+        .collect();
+    if violations.len() > TOP_VIOLATIONS {
+        lines.push(format!("…and {} more", violations.len() - TOP_VIOLATIONS));
+    }
+    lines
+}
+
+/// Build the Slack Block Kit payload for a run summary
+fn slack_payload(title: &str, intervals: &[Interval], violations: &[Violation]) -> Value {
+    let slowest = slowest_transitions(intervals, TOP_TRANSITIONS);
+
+    let lines: Vec<String> = slowest
+        .iter()
+        .map(|t| {
+            format!(
+                "• `{}` → `{}`: max *{}*, mean {} ({}x)",
+                t.from_pattern,
+                t.to_pattern,
+                human_ms(t.max_ms),
+                human_ms(t.mean_ms),
+                t.count
+            )
+        })
+        .collect();
+
+    let mut payload = json!({
+        "text": format!("{}: {} intervals", title, intervals.len()),
+        "blocks": [
+            {
+                "type": "header",
+                "text": { "type": "plain_text", "text": title }
+            },
+            {
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": format!("*{}* intervals analyzed. Slowest transitions:", intervals.len())
+                }
+            },
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": lines.join("\n") }
+            }
+        ]
+    });
+    if !violations.is_empty() {
+        let failures: Vec<String> = violation_lines(violations).iter().map(|line| format!("• {}", line)).collect();
+        payload["text"] = json!(format!("{}: {} intervals, {} over budget", title, intervals.len(), violations.len()));
+        if let Some(blocks) = payload["blocks"].as_array_mut() {
+            blocks.push(json!({
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": format!(":rotating_light: *{}* budget failures (`fail_if_over`):\n{}", violations.len(), failures.join("\n"))
+                }
+            }));
+        }
+    }
+    payload
+}
+
+/// Build the Teams MessageCard payload for a run summary
+fn teams_payload(title: &str, intervals: &[Interval], violations: &[Violation]) -> Value {
+    let facts: Vec<Value> = slowest_transitions(intervals, TOP_TRANSITIONS)
+        .iter()
+        .map(|t| {
+            json!({
+                "name": format!("{} → {}", t.from_pattern, t.to_pattern),
+                "value": format!(
+                    "max {}, mean {} ({}x)",
+                    human_ms(t.max_ms),
+                    human_ms(t.mean_ms),
+                    t.count
+                )
+            })
+        })
+        .collect();
+
+    let mut sections = vec![json!({
+        "activityTitle": format!("{} intervals analyzed. Slowest transitions:", intervals.len()),
+        "facts": facts
+    })];
+    if !violations.is_empty() {
+        sections.push(json!({
+            "activityTitle": format!("{} budget failures (fail_if_over):", violations.len()),
+            "text": violation_lines(violations).join("<br>")
+        }));
+    }
+    json!({
+        "@type": "MessageCard",
+        "@context": "http://schema.org/extensions",
+        "summary": title,
+        "title": title,
+        "themeColor": if violations.is_empty() { "0078D7" } else { "D70000" },
+        "sections": sections
+    })
+}
+
+/// Build the payload of an alert about one interval over its budget, for follow mode
+fn alert_payload(webhook: &Webhook, title: &str, interval: &Interval, limit: &chrono::Duration) -> Value {
+    let line = violation_line(interval, limit);
+    match webhook {
+        Webhook::Slack(_) => json!({
+            "text": format!("{}: over budget", title),
+            "blocks": [
+                {
+                    "type": "section",
+                    "text": { "type": "mrkdwn", "text": format!(":rotating_light: *{}*: {}", title, line) }
+                }
+            ]
+        }),
+        Webhook::Teams(_) => json!({
+            "@type": "MessageCard",
+            "@context": "http://schema.org/extensions",
+            "summary": format!("{}: over budget", title),
+            "title": format!("{}: over budget", title),
+            "themeColor": "D70000",
+            "text": line
+        }),
+    }
+}
+
+fn post(webhook: &Webhook, payload: &Value) -> Result<()> {
+    ureq::post(webhook.url())
+        .set("Content-Type", "application/json")
+        .send_string(&payload.to_string())
+        .with_context(|| format!("Failed to post to webhook {}", webhook.url()))?;
+    Ok(())
+}
+
+/// Post a compact run summary (slowest transitions and budget failures) to a chat webhook
+pub fn post_summary(webhook: &Webhook, title: &str, intervals: &[Interval], violations: &[Violation]) -> Result<()> {
+    let payload = match webhook {
+        Webhook::Slack(_) => slack_payload(title, intervals, violations),
+        Webhook::Teams(_) => teams_payload(title, intervals, violations),
+    };
+    post(webhook, &payload)
+}
+
+/// Post an alert about an interval over its `fail_if_over` budget, as `--follow` finds it
+pub fn post_alert(webhook: &Webhook, title: &str, interval: &Interval, limit: &chrono::Duration) -> Result<()> {
+    post(webhook, &alert_payload(webhook, title, interval, limit))
+}
+
+/// The command showing a desktop notification: `notify-send` on Linux and the BSDs, and
+/// `osascript` on macOS
+fn desktop_command(title: &str, body: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!("display notification {} with title {}", quote(body), quote(title)));
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args(["--urgency=critical", "--app-name=log-time-analyzer", "--", title, body]);
+        command
+    }
+}
+
+/// Show a desktop notification about an interval over its `fail_if_over` budget
+/// (`--notify-desktop`); the notifier runs in the background, so following isn't held up
+pub fn notify_desktop(title: &str, interval: &Interval, limit: &chrono::Duration) -> Result<()> {
+    if cfg!(windows) {
+        anyhow::bail!("Desktop notifications are not supported on Windows");
+    }
+    let mut command = desktop_command(&format!("{}: over budget", title), &violation_line(interval, limit));
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to run {:?} for a desktop notification", command.get_program()))?;
+    // Reaped by a thread of its own, so it doesn't linger as a zombie
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+    use chrono::Duration;
+
+    fn interval(from: &str, to: &str, ms: i64) -> Interval {
+        Interval::new(from, to, Duration::milliseconds(ms))
+    }
+
+    #[test]
+    fn test_parse_webhook() {
+        assert_eq!(
+            Webhook::parse("slack://hooks.slack.com/services/T/B/X").unwrap(),
+            Webhook::Slack("https://hooks.slack.com/services/T/B/X".to_string())
+        );
+        assert!(Webhook::parse("https://example.com").is_err());
+    }
+
+    #[test]
+    fn test_slowest_transitions() {
+        let intervals = vec![
+            interval("A", "B", 100),
+            interval("B", "C", 900),
+            interval("A", "B", 300),
+        ];
+
+        let slowest = slowest_transitions(&intervals, 5);
+        assert_eq!(slowest[0].from_pattern, "B");
+        assert_eq!(slowest[1].count, 2);
+        assert_eq!(slowest[1].max_ms, 300);
+        assert_eq!(slowest[1].mean_ms, 200);
+    }
+
+    #[test]
+    fn test_budget_failures_in_summaries() {
+        let intervals: Vec<Interval> = (1..=7).map(|i| interval("A", "B", i * 1000)).collect();
+        let limit = Duration::milliseconds(500);
+        let violations: Vec<Violation> = intervals.iter().map(|i| (i, limit)).collect();
+
+        let slack = slack_payload("nightly", &intervals, &violations);
+        assert_eq!(slack["text"], "nightly: 7 intervals, 7 over budget");
+        let failures = slack["blocks"][3]["text"]["text"].as_str().unwrap();
+        assert!(failures.starts_with(":rotating_light: *7* budget failures"), "{}", failures);
+        assert!(failures.contains("• A → B: took 1s 0ms, budget 500ms\n"), "{}", failures);
+        assert!(failures.ends_with("• …and 2 more"), "{}", failures);
+        assert_eq!(slack_payload("nightly", &intervals, &[])["blocks"].as_array().unwrap().len(), 3);
+
+        let teams = teams_payload("nightly", &intervals, &violations[..1]);
+        assert_eq!(teams["sections"][1]["activityTitle"], "1 budget failures (fail_if_over):");
+        assert_eq!(teams["themeColor"], "D70000");
+        assert!(teams_payload("nightly", &intervals, &[])["sections"].as_array().unwrap().len() == 1);
+
+        let alert = alert_payload(&Webhook::Teams(String::new()), "app.log", &intervals[0], &limit);
+        assert_eq!(alert["text"], "A → B: took 1s 0ms, budget 500ms");
+    }
+
+    #[test]
+    fn test_desktop_command() {
+        let command = desktop_command("app.log: over budget", "--A → B");
+        let args: Vec<&std::ffi::OsStr> = command.get_args().collect();
+        if cfg!(target_os = "macos") {
+            assert_eq!(command.get_program(), "osascript");
+            assert_eq!(args[1], "display notification \"--A → B\" with title \"app.log: over budget\"");
+        } else {
+            assert_eq!(command.get_program(), "notify-send");
+            // Past `--`, so a message starting with a dash isn't read as an option
+            assert_eq!(args[2..], ["--", "app.log: over budget", "--A → B"]);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+/// Statistical outliers among the intervals of each transition (`--outliers`)
+use anyhow::Result;
+use std::collections::HashMap;
+
+use log_time_analyzer::analyzer::{format_duration, Interval};
+use log_time_analyzer::stats::{human_ms, percentile};
+
+/// Transitions with fewer intervals than this have no meaningful spread and are skipped
+const MIN_INTERVALS: usize = 4;
+
+/// How an interval is judged an outlier of its transition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    /// Beyond this many interquartile ranges below the first or above the third quartile
+    Iqr(f64),
+    /// More than this many standard deviations from the mean
+    ZScore(f64),
+}
+
+impl Method {
+    /// `iqr` (1.5 IQRs) or `zscore` (3 standard deviations), optionally with the multiplier,
+    /// e.g. `iqr:3` or `zscore:2.5`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (name, k) = match spec.split_once(':') {
+            Some((name, k)) => {
+                let k: f64 = k.trim().parse().map_err(|_| anyhow::anyhow!("Invalid outlier multiplier '{}'", k))?;
+                if !(k.is_finite() && k > 0.0) {
+                    anyhow::bail!("The outlier multiplier must be greater than zero, got '{}'", spec);
+                }
+                (name, Some(k))
+            }
+            None => (spec, None),
+        };
+        match name.trim().to_lowercase().as_str() {
+            "iqr" => Ok(Method::Iqr(k.unwrap_or(1.5))),
+            "zscore" | "z-score" | "z" => Ok(Method::ZScore(k.unwrap_or(3.0))),
+            _ => anyhow::bail!("Invalid outlier method '{}'. Valid options: iqr[:K], zscore[:K]", spec),
+        }
+    }
+
+    fn describe(self) -> String {
+        match self {
+            Method::Iqr(k) => format!("more than {} IQR outside the quartiles", k),
+            Method::ZScore(k) => format!("more than {} standard deviations from the mean", k),
+        }
+    }
+}
+
+/// The outliers of one transition (and `--group-by` group)
+#[derive(Debug)]
+pub struct TransitionOutliers {
+    pub from_pattern: String,
+    pub to_pattern: String,
+    pub group: Option<String>,
+    pub count: usize,
+    /// Durations in milliseconds outside `low..=high` are outliers
+    pub low: f64,
+    pub high: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    /// Indexes (into the analyzed intervals) of the outliers, in log order
+    pub outliers: Vec<usize>,
+}
+
+impl TransitionOutliers {
+    fn transition(&self) -> String {
+        match &self.group {
+            Some(group) => format!("{} -> {} ({})", self.from_pattern, self.to_pattern, group),
+            None => format!("{} -> {}", self.from_pattern, self.to_pattern),
+        }
+    }
+}
+
+/// Find the outliers of every transition with enough intervals, in order of first appearance
+pub fn detect(intervals: &[Interval], method: Method) -> Vec<TransitionOutliers> {
+    let mut order = Vec::new();
+    let mut groups: HashMap<(&str, &str, Option<&str>), Vec<usize>> = HashMap::new();
+    for (i, interval) in intervals.iter().enumerate() {
+        let key = (interval.from_pattern.as_str(), interval.to_pattern.as_str(), interval.group.as_deref());
+        groups
+            .entry(key)
+            .or_insert_with(|| {
+                order.push(key);
+                Vec::new()
+            })
+            .push(i);
+    }
+
+    let ms = |i: usize| intervals[i].duration.num_milliseconds() as f64;
+    order
+        .into_iter()
+        .filter_map(|key| {
+            let indexes = groups.remove(&key)?;
+            if indexes.len() < MIN_INTERVALS {
+                return None;
+            }
+            let count = indexes.len() as f64;
+            let mean = indexes.iter().map(|&i| ms(i)).sum::<f64>() / count;
+            let stddev = (indexes.iter().map(|&i| (ms(i) - mean).powi(2)).sum::<f64>() / count).sqrt();
+            let (low, high) = match method {
+                Method::Iqr(k) => {
+                    let mut sorted: Vec<i64> = indexes.iter().map(|&i| intervals[i].duration.num_milliseconds()).collect();
+                    sorted.sort_unstable();
+                    let (q1, q3) = (percentile(&sorted, 25.0), percentile(&sorted, 75.0));
+                    (q1 - k * (q3 - q1), q3 + k * (q3 - q1))
+                }
+                Method::ZScore(k) => (mean - k * stddev, mean + k * stddev),
+            };
+
+            Some(TransitionOutliers {
+                from_pattern: key.0.to_string(),
+                to_pattern: key.1.to_string(),
+                group: key.2.map(str::to_string),
+                count: indexes.len(),
+                low,
+                high,
+                mean,
+                stddev,
+                outliers: indexes.into_iter().filter(|&i| !(low..=high).contains(&ms(i))).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Each transition's bounds and outlier count, then every outlier in log order with its
+/// timestamp and line number when they were recorded
+pub fn format_report(results: &[TransitionOutliers], intervals: &[Interval], method: Method) -> String {
+    let mut output = format!("Outliers: intervals {}\n", method.describe());
+    if results.is_empty() {
+        output.push_str(&format!("No transition has the {} intervals needed to find outliers", MIN_INTERVALS));
+        return output;
+    }
+
+    for result in results {
+        output.push_str(&format!(
+            "  {}: {} of {} intervals outside {} - {}\n",
+            result.transition(),
+            result.outliers.len(),
+            result.count,
+            human_ms(result.low.max(0.0)),
+            human_ms(result.high)
+        ));
+    }
+
+    let mut outliers: Vec<(usize, &TransitionOutliers)> =
+        results.iter().flat_map(|result| result.outliers.iter().map(move |&i| (i, result))).collect();
+    outliers.sort_by_key(|&(i, _)| i);
+    if !outliers.is_empty() {
+        output.push('\n');
+    }
+    for (i, result) in &outliers {
+        let interval = &intervals[*i];
+        output.push_str(&format!("{}: {}", result.transition(), format_duration(&interval.duration)));
+        if result.stddev > 0.0 {
+            let z = (interval.duration.num_milliseconds() as f64 - result.mean) / result.stddev;
+            output.push_str(&format!(" (z={:+.1})", z));
+        }
+        if let Some(start) = interval.start {
+            output.push_str(&format!(" at {}", start.format("%Y-%m-%d %H:%M:%S%.3f")));
+        }
+        if let Some(line) = interval.from_line {
+            output.push_str(&format!(", line {}", line));
+        }
+        if let Some(source) = &interval.from_source {
+            output.push_str(&format!(" [{}]", source));
+        }
+        output.push('\n');
+    }
+
+    let total: usize = results.iter().map(|result| result.outliers.len()).sum();
+    output.push_str(&format!("\n{} outliers in {} intervals", total, intervals.len()));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+    use chrono::Duration;
+
+    fn interval(from: &str, ms: i64) -> Interval {
+        Interval::new(from, "done", Duration::milliseconds(ms))
+    }
+
+    #[test]
+    fn test_outliers_per_transition() {
+        let mut intervals: Vec<Interval> = [100, 110, 90, 105, 5000, 95, 100].iter().map(|&ms| interval("request", ms)).collect();
+        intervals.extend([10, 20].map(|ms| interval("short", ms)));
+
+        let results = detect(&intervals, Method::parse("iqr").unwrap());
+        assert_eq!(results.len(), 1, "transitions with too few intervals are skipped");
+        assert_eq!(results[0].outliers, vec![4]);
+        let report = format_report(&results, &intervals, Method::Iqr(1.5));
+        assert!(report.contains("request -> done: 5s 0ms (z=+2.4)"), "{}", report);
+        assert!(report.ends_with("1 outliers in 9 intervals"));
+
+        // Seven intervals can't put one more than 3 standard deviations out
+        assert!(detect(&intervals, Method::parse("zscore").unwrap())[0].outliers.is_empty());
+        assert_eq!(detect(&intervals, Method::parse("zscore:2").unwrap())[0].outliers, vec![4]);
+        assert!(Method::parse("zscore:0").is_err());
+        assert!(Method::parse("mad").is_err());
+    }
+
+    #[test]
+    fn test_invalid_methods_and_too_few_intervals() {
+        let error = Method::parse("iqr:wide").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid outlier multiplier 'wide'");
+        assert!(Method::parse("iqr:-1").is_err());
+        assert!(Method::parse("zscore:inf").is_err());
+        assert!(Method::parse("").is_err());
+
+        let intervals: Vec<Interval> = [100, 5000, 90].iter().map(|&ms| interval("request", ms)).collect();
+        let results = detect(&intervals, Method::Iqr(1.5));
+        assert!(results.is_empty());
+        assert!(format_report(&results, &intervals, Method::Iqr(1.5))
+            .ends_with("No transition has the 4 intervals needed to find outliers"));
+    }
+}
@@ -1,5 +1,18 @@
-use crate::analyzer::Interval;
+use crate::analyzer::{format_number, Interval};
+use crate::csv::CsvDialect;
+use crate::grafana;
+use crate::html;
+use crate::prometheus;
+use crate::spans;
+use crate::locale::Locale;
+use crate::markers::Marker;
+use crate::schema::SCHEMA_VERSION;
+use crate::stats::{self, human_ms, TransitionStats};
+use crate::style::{self, DurationColors};
+use crate::timestamp_formats::TimestampFormat;
+use chrono::{Duration, NaiveDateTime};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputFormat {
@@ -7,6 +20,8 @@ pub enum OutputFormat {
     Human,
     /// JSON format for easy parsing
     Json,
+    /// JSON Lines: one compact JSON object per interval and line, for `jq` and log shippers
+    Jsonl,
     /// CSV format for spreadsheets
     Csv,
     /// TSV (tab-separated) format
@@ -17,106 +32,974 @@ pub enum OutputFormat {
     Simple,
     /// Waterfall visualization with vertical bars
     Waterfall,
+    /// ASCII histogram of the durations of each transition
+    Histogram,
+    /// Chrome Trace Event JSON for Perfetto or `chrome://tracing`
+    ChromeTrace,
+    /// GitHub-flavored Markdown table, to paste into PRs and issues
+    Markdown,
+    /// Standalone HTML report with charts and a sortable table of the intervals
+    Html,
+    /// Prometheus text exposition format of the statistics, for the textfile collector
+    Prometheus,
+    /// Grafana dashboard JSON with a statistics table and a time series of the durations
+    Grafana,
+    /// Zipkin v2 JSON spans, a trace per correlation ID
+    Zipkin,
+    /// OpenTelemetry (OTLP) JSON trace export request, a trace per correlation ID
+    Otlp,
 }
 
 impl OutputFormat {
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "human" => Some(OutputFormat::Human),
-            "json" => Some(OutputFormat::Json),
-            "csv" => Some(OutputFormat::Csv),
-            "tsv" => Some(OutputFormat::Tsv),
-            "table" => Some(OutputFormat::Table),
-            "simple" => Some(OutputFormat::Simple),
-            "waterfall" => Some(OutputFormat::Waterfall),
-            _ => None,
+    pub const ALL: [OutputFormat; 16] = [
+        OutputFormat::Human,
+        OutputFormat::Json,
+        OutputFormat::Jsonl,
+        OutputFormat::Csv,
+        OutputFormat::Tsv,
+        OutputFormat::Table,
+        OutputFormat::Simple,
+        OutputFormat::Waterfall,
+        OutputFormat::Histogram,
+        OutputFormat::ChromeTrace,
+        OutputFormat::Markdown,
+        OutputFormat::Html,
+        OutputFormat::Prometheus,
+        OutputFormat::Grafana,
+        OutputFormat::Zipkin,
+        OutputFormat::Otlp,
+    ];
+    
+    /// Name of the format on the command line (`-f`)
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Human => "human",
+            OutputFormat::Json => "json",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Table => "table",
+            OutputFormat::Simple => "simple",
+            OutputFormat::Waterfall => "waterfall",
+            OutputFormat::Histogram => "histogram",
+            OutputFormat::ChromeTrace => "chrome-trace",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Html => "html",
+            OutputFormat::Prometheus => "prometheus",
+            OutputFormat::Grafana => "grafana",
+            OutputFormat::Zipkin => "zipkin",
+            OutputFormat::Otlp => "otlp",
+        }
+    }
+    
+    /// Whether intervals can be printed one at a time as they complete, which rules out
+    /// formats that align columns, print headers, or scale to the whole run
+    pub fn supports_streaming(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Human | OutputFormat::Simple | OutputFormat::Json | OutputFormat::Jsonl
+        )
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let s = s.to_lowercase();
+        if s == "ndjson" {
+            return Ok(OutputFormat::Jsonl);
         }
+        Self::ALL.into_iter().find(|format| format.name() == s).ok_or(())
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct IntervalJson {
+    /// Run the row belongs to, in a dataset appended to by every run (`--run-id`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    run_id: Option<String>,
     from_pattern: String,
     to_pattern: String,
     duration_ms: i64,
     duration_human: String,
+    /// Endpoint timestamps and line numbers, only when source lines were recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from_raw_line: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to_raw_line: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    correlation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from_source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to_source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cross_source: Option<bool>,
+    /// Named captures of both endpoints, keyed `from_<name>` / `to_<name>`
+    #[serde(flatten)]
+    fields: BTreeMap<String, String>,
+    /// Derived values, keyed by name
+    #[serde(flatten)]
+    derived: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+impl From<&Interval> for IntervalJson {
+    fn from(interval: &Interval) -> Self {
+        let located = interval.has_source_lines();
+        IntervalJson {
+            run_id: None,
+            from_pattern: interval.from_pattern.clone(),
+            to_pattern: interval.to_pattern.clone(),
+            duration_ms: interval.duration.num_milliseconds(),
+            duration_human: interval.format_duration(),
+            start: interval.start.filter(|_| located),
+            end: interval.end.filter(|_| located),
+            from_line: interval.from_line,
+            to_line: interval.to_line,
+            from_raw_line: interval.from_raw_line.clone(),
+            to_raw_line: interval.to_raw_line.clone(),
+            correlation: interval.correlation.clone(),
+            group: interval.group.clone(),
+            from_source: interval.from_source.clone(),
+            to_source: interval.to_source.clone(),
+            cross_source: (interval.from_source.is_some() || interval.to_source.is_some())
+                .then(|| interval.is_cross_source()),
+            fields: interval
+                .from_fields
+                .iter()
+                .map(|(name, value)| (format!("from_{}", name), value.clone()))
+                .chain(
+                    interval
+                        .to_fields
+                        .iter()
+                        .map(|(name, value)| (format!("to_{}", name), value.clone())),
+                )
+                .collect(),
+            derived: interval.derived.clone(),
+            tags: interval.tags.clone(),
+        }
+    }
+}
+
+/// A trace in the Chrome Trace Event format
+#[derive(Debug, Serialize)]
+struct ChromeTrace {
+    #[serde(rename = "traceEvents")]
+    trace_events: Vec<TraceEvent>,
+    #[serde(rename = "displayTimeUnit")]
+    display_time_unit: &'static str,
+    /// Wall-clock time of timestamp 0, since event timestamps are relative to it
+    #[serde(rename = "otherData")]
+    other_data: BTreeMap<&'static str, String>,
+}
+
+/// A complete event (`ph: X`) per interval, an instant event (`ph: i`) per marker, or a
+/// metadata event (`ph: M`) naming a track
+#[derive(Debug, Serialize)]
+struct TraceEvent {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cat: Option<&'static str>,
+    ph: &'static str,
+    /// Start and duration in microseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<i64>,
+    pid: u32,
+    tid: usize,
+    /// Instant events drawn across every track (`g`) rather than on one
+    #[serde(rename = "s", skip_serializing_if = "Option::is_none")]
+    scope: Option<&'static str>,
+    args: serde_json::Value,
+}
+
+impl TraceEvent {
+    fn metadata(name: &str, tid: usize, value: &str) -> Self {
+        TraceEvent {
+            name: name.to_string(),
+            cat: None,
+            ph: "M",
+            ts: None,
+            dur: None,
+            pid: 1,
+            tid,
+            scope: None,
+            args: serde_json::json!({ "name": value }),
+        }
+    }
+}
+
+/// A page of intervals in JSON (`--page-size`), with a `next_page` continuation marker
+/// that is null on the last page
+#[derive(Debug, Serialize)]
+struct IntervalPageJson {
+    schema_version: u32,
+    page: usize,
+    page_size: usize,
+    total_pages: usize,
+    total_intervals: usize,
+    next_page: Option<usize>,
+    intervals: Vec<IntervalJson>,
+}
+
+/// One page of the interval listing (`--page-size`/`--page`), numbered from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub number: usize,
+    pub size: usize,
+}
+
+impl Page {
+    /// Number of pages needed for `total` intervals (at least one)
+    pub fn count(self, total: usize) -> usize {
+        total.div_ceil(self.size.max(1)).max(1)
+    }
+    
+    /// Indexes of the intervals on this page
+    pub fn range(self, total: usize) -> std::ops::Range<usize> {
+        let start = (self.number.saturating_sub(1) * self.size).min(total);
+        start..(start + self.size).min(total)
+    }
+    
+    /// Number of the following page, if there is one
+    pub fn next(self, total: usize) -> Option<usize> {
+        (self.number < self.count(total)).then_some(self.number + 1)
+    }
+}
+
+/// Aggregate statistics of one transition (`--stats`), durations in milliseconds
+#[derive(Debug, Serialize)]
+struct TransitionStatsRow {
+    /// Captured value the row's intervals share, with `--group-by`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    from_pattern: String,
+    to_pattern: String,
+    count: usize,
+    min_ms: i64,
+    max_ms: i64,
+    mean_ms: f64,
+    median_ms: f64,
+    p95_ms: f64,
+    p99_ms: f64,
+    stddev_ms: f64,
+}
+
+impl TransitionStatsRow {
+    /// Column names used by the CSV and TSV headers
+    const HEADERS: [&'static str; 10] = [
+        "from_pattern", "to_pattern", "count", "min_ms", "max_ms", "mean_ms",
+        "median_ms", "p95_ms", "p99_ms", "stddev_ms",
+    ];
+    
+    /// Rounded to the microsecond so fractional values stay readable
+    fn new(s: &TransitionStats) -> Self {
+        let round = |ms: f64| (ms * 1000.0).round() / 1000.0;
+        TransitionStatsRow {
+            group: s.group.clone(),
+            from_pattern: s.from_pattern.clone(),
+            to_pattern: s.to_pattern.clone(),
+            count: s.count,
+            min_ms: s.min_ms,
+            max_ms: s.max_ms,
+            mean_ms: round(s.mean_ms),
+            median_ms: round(s.percentile(50.0)),
+            p95_ms: round(s.percentile(95.0)),
+            p99_ms: round(s.percentile(99.0)),
+            stddev_ms: round(s.stddev_ms),
+        }
+    }
+    
+    /// Column names, led by `group` when the rows are grouped
+    fn headers(grouped: bool) -> Vec<&'static str> {
+        grouped.then_some("group").into_iter().chain(Self::HEADERS).collect()
+    }
+    
+    /// The group (when the rows are grouped) and the patterns, in column order
+    fn labels(&self, grouped: bool) -> Vec<String> {
+        let group = grouped.then(|| self.group.clone().unwrap_or_default());
+        group.into_iter().chain([self.from_pattern.clone(), self.to_pattern.clone()]).collect()
+    }
+    
+    /// `from -> to`, followed by the group in parentheses when grouped
+    fn transition(&self) -> String {
+        match &self.group {
+            Some(group) => format!("{} -> {} ({})", self.from_pattern, self.to_pattern, group),
+            None => format!("{} -> {}", self.from_pattern, self.to_pattern),
+        }
+    }
+    
+    /// The duration statistics in column order
+    fn durations(&self) -> [f64; 7] {
+        [
+            self.min_ms as f64, self.max_ms as f64, self.mean_ms, self.median_ms,
+            self.p95_ms, self.p99_ms, self.stddev_ms,
+        ]
+    }
+}
+
+/// How the histogram format buckets durations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramOptions {
+    /// Number of equal-width buckets from the shortest to the longest duration
+    pub buckets: usize,
+    /// Fixed bucket width in milliseconds, with buckets aligned to multiples of it
+    /// (overrides `buckets`)
+    pub bucket_width_ms: Option<f64>,
+}
+
+impl Default for HistogramOptions {
+    fn default() -> Self {
+        HistogramOptions {
+            buckets: 10,
+            bucket_width_ms: None,
+        }
+    }
+}
+
+/// How bar lengths follow durations in the waterfall format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarScale {
+    /// Proportional to the duration
+    Linear,
+    /// Proportional to the logarithm of the duration, so one huge interval doesn't flatten
+    /// all the others
+    Log,
+}
+
+impl std::str::FromStr for BarScale {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_lowercase().as_str() {
+            "linear" => Ok(BarScale::Linear),
+            "log" => Ok(BarScale::Log),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How the waterfall format draws its bars
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaterfallOptions {
+    /// A row with a horizontal bar per interval, instead of a column per interval
+    pub horizontal: bool,
+    /// Columns the chart may take up, usually the terminal's width
+    pub width: usize,
+    pub scale: BarScale,
+}
+
+impl Default for WaterfallOptions {
+    fn default() -> Self {
+        WaterfallOptions {
+            horizontal: false,
+            width: 100,
+            scale: BarScale::Linear,
+        }
+    }
+}
+
+impl WaterfallOptions {
+    /// Length of a `ms` long duration's bar as a fraction of the longest one's, `max_ms`
+    fn fraction(self, ms: f64, max_ms: f64) -> f64 {
+        let (ms, max_ms) = (ms.max(0.0), max_ms.max(1.0));
+        match self.scale {
+            BarScale::Linear => ms / max_ms,
+            BarScale::Log => ms.ln_1p() / max_ms.ln_1p(),
+        }
+    }
+}
+
+impl HistogramOptions {
+    /// Most buckets drawn per transition; a fixed width needing more is widened
+    const MAX_BUCKETS: usize = 200;
+    
+    /// Low and high end (ms) and interval count of each bucket of a transition's durations
+    pub(crate) fn buckets(self, stats: &TransitionStats) -> Vec<(f64, f64, usize)> {
+        let (start, width, buckets) = self.layout(stats.min_ms as f64, stats.max_ms as f64);
+        let mut counts = vec![0usize; buckets];
+        for &ms in &stats.sorted_ms {
+            let bucket = ((ms as f64 - start) / width).floor().max(0.0) as usize;
+            counts[bucket.min(buckets - 1)] += 1;
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| {
+                let low = start + i as f64 * width;
+                (low, low + width, count)
+            })
+            .collect()
+    }
+    
+    /// First bucket start, bucket width, and bucket count for durations from `min` to `max` ms
+    fn layout(self, min: f64, max: f64) -> (f64, f64, usize) {
+        match self.bucket_width_ms {
+            Some(width) => {
+                let needed = ((max - (min / width).floor() * width) / width).floor() as usize + 1;
+                let width = width * needed.div_ceil(Self::MAX_BUCKETS) as f64;
+                let start = (min / width).floor() * width;
+                (start, width, ((max - start) / width).floor() as usize + 1)
+            }
+            None if max > min => {
+                let buckets = self.buckets.clamp(1, Self::MAX_BUCKETS);
+                (min, (max - min) / buckets as f64, buckets)
+            }
+            None => (min, 1.0, 1),
+        }
+    }
+}
+
+/// An optional per-interval column, only emitted when the analysis has data for it
+pub(crate) struct ExtraColumn {
+    /// Machine-friendly header used by CSV/TSV
+    name: String,
+    /// Header used by the table format
+    pub(crate) title: String,
+    pub(crate) value: Box<dyn Fn(&Interval) -> String>,
 }
 
+impl ExtraColumn {
+    fn new(name: impl Into<String>, title: impl Into<String>, value: impl Fn(&Interval) -> String + 'static) -> Self {
+        ExtraColumn {
+            name: name.into(),
+            title: title.into(),
+            value: Box::new(value),
+        }
+    }
+}
+
+/// ISO 8601 without a zone, as in the JSON output
+fn format_timestamp(timestamp: NaiveDateTime) -> String {
+    timestamp.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+/// A bar `cells` columns long of full blocks and a last partial block, drawn to the nearest
+/// eighth of a column; never empty, so the shortest durations still show
+fn block_bar(cells: f64) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let eighths = ((cells * 8.0).round() as usize).max(1);
+    let mut bar = "█".repeat(eighths / 8);
+    let partial = PARTIAL[eighths % 8];
+    if partial != ' ' {
+        bar.push(partial);
+    }
+    bar
+}
+
+/// Statistics duration in `locale`'s conventions, or as everywhere else without one
+pub(crate) fn localized_ms(ms: f64, locale: Option<Locale>) -> String {
+    locale.map_or_else(|| human_ms(ms), |locale| locale.ms(ms))
+}
+
+pub(crate) fn localized_count(count: usize, locale: Option<Locale>) -> String {
+    locale.map_or_else(|| count.to_string(), |locale| locale.integer(count as i64))
+}
+
+/// Extra columns to append after the standard ones for this set of intervals; derived values
+/// follow `locale` when given
+pub(crate) fn extra_columns(intervals: &[Interval], locale: Option<Locale>) -> Vec<ExtraColumn> {
+    let mut columns = Vec::new();
+    
+    if intervals.iter().any(Interval::has_source_lines) {
+        columns.push(ExtraColumn::new("start", "Start", |i| {
+            i.start.map(format_timestamp).unwrap_or_default()
+        }));
+        columns.push(ExtraColumn::new("end", "End", |i| i.end.map(format_timestamp).unwrap_or_default()));
+        columns.push(ExtraColumn::new("from_line", "From Line", |i| {
+            i.from_line.map(|n| n.to_string()).unwrap_or_default()
+        }));
+        columns.push(ExtraColumn::new("to_line", "To Line", |i| {
+            i.to_line.map(|n| n.to_string()).unwrap_or_default()
+        }));
+    }
+    
+    if intervals.iter().any(|i| i.from_raw_line.is_some() || i.to_raw_line.is_some()) {
+        columns.push(ExtraColumn::new("from_raw_line", "From Raw Line", |i| {
+            i.from_raw_line.clone().unwrap_or_default()
+        }));
+        columns.push(ExtraColumn::new("to_raw_line", "To Raw Line", |i| {
+            i.to_raw_line.clone().unwrap_or_default()
+        }));
+    }
+    
+    if intervals.iter().any(|i| i.correlation.is_some()) {
+        columns.push(ExtraColumn::new("correlation", "Correlation", |i| {
+            i.correlation.clone().unwrap_or_default()
+        }));
+    }
+    
+    if intervals.iter().any(|i| i.group.is_some()) {
+        columns.push(ExtraColumn::new("group", "Group", |i| i.group.clone().unwrap_or_default()));
+    }
+    
+    if intervals.iter().any(|i| i.from_source.is_some() || i.to_source.is_some()) {
+        columns.push(ExtraColumn::new("from_source", "From Source", |i| {
+            i.from_source.clone().unwrap_or_default()
+        }));
+        columns.push(ExtraColumn::new("to_source", "To Source", |i| {
+            i.to_source.clone().unwrap_or_default()
+        }));
+        columns.push(ExtraColumn::new("cross_source", "Cross Source", |i| {
+            i.is_cross_source().to_string()
+        }));
+    }
+    
+    // One column per named capture seen on either endpoint
+    let from_names: BTreeSet<&String> = intervals.iter().flat_map(|i| i.from_fields.keys()).collect();
+    for name in from_names {
+        let key = name.clone();
+        columns.push(ExtraColumn::new(format!("from_{}", name), format!("From {}", name), move |i| {
+            i.from_fields.get(&key).cloned().unwrap_or_default()
+        }));
+    }
+    let to_names: BTreeSet<&String> = intervals.iter().flat_map(|i| i.to_fields.keys()).collect();
+    for name in to_names {
+        let key = name.clone();
+        columns.push(ExtraColumn::new(format!("to_{}", name), format!("To {}", name), move |i| {
+            i.to_fields.get(&key).cloned().unwrap_or_default()
+        }));
+    }
+    
+    let derived_names: BTreeSet<&String> = intervals.iter().flat_map(|i| i.derived.keys()).collect();
+    for name in derived_names {
+        let key = name.clone();
+        columns.push(ExtraColumn::new(name.clone(), name.clone(), move |i| {
+            let format = |value: f64| locale.map_or_else(|| format_number(value), |locale| locale.number(value, 3));
+            i.derived.get(&key).map(|&value| format(value)).unwrap_or_default()
+        }));
+    }
+    
+    if intervals.iter().any(|i| !i.tags.is_empty()) {
+        columns.push(ExtraColumn::new("tags", "Tags", |i| i.tags.join(",")));
+    }
+    
+    columns
+}
+
+/// Table columns comparing each interval with the p50 and p95 of its transition (and group)
+/// in `stats`, and whether it stayed within the p95
+fn percentile_columns(stats: &[TransitionStats], locale: Option<Locale>) -> Vec<ExtraColumn> {
+    type Key = (String, String, Option<String>);
+    let thresholds: HashMap<Key, (f64, f64)> = stats
+        .iter()
+        .map(|s| {
+            let key = (s.from_pattern.clone(), s.to_pattern.clone(), s.group.clone());
+            (key, (s.percentile(50.0), s.percentile(95.0)))
+        })
+        .collect();
+    let lookup = move |i: &Interval| {
+        thresholds.get(&(i.from_pattern.clone(), i.to_pattern.clone(), i.group.clone())).copied()
+    };
+    // Change from the percentile, blank when there is nothing to compare with
+    let versus = move |i: &Interval, threshold: f64| {
+        if threshold <= 0.0 {
+            return String::new();
+        }
+        let change = (i.duration.num_milliseconds() as f64 / threshold - 1.0) * 100.0;
+        locale.map_or_else(|| format!("{:+.1}%", change), |locale| locale.percent(change))
+    };
+    
+    let (p50_lookup, p95_lookup, pass_lookup) = (lookup.clone(), lookup.clone(), lookup);
+    vec![
+        ExtraColumn::new("vs_p50", "vs p50", move |i| {
+            p50_lookup(i).map(|(p50, _)| versus(i, p50)).unwrap_or_default()
+        }),
+        ExtraColumn::new("vs_p95", "vs p95", move |i| {
+            p95_lookup(i).map(|(_, p95)| versus(i, p95)).unwrap_or_default()
+        }),
+        ExtraColumn::new("within_p95", "Within p95", move |i| match pass_lookup(i) {
+            Some((_, p95)) if i.duration.num_milliseconds() as f64 <= p95 => "✓".to_string(),
+            Some(_) => "✗".to_string(),
+            None => String::new(),
+        }),
+    ]
+}
+
+/// Renders intervals and transition statistics in the output formats
 pub struct OutputFormatter;
 
 impl OutputFormatter {
     pub fn format_intervals(intervals: &[Interval], format: OutputFormat) -> String {
+        Self::format_intervals_with_locale(intervals, format, None)
+    }
+    
+    /// Like `format_intervals`, with the durations and numbers of the human and table formats
+    /// in `locale`'s conventions; the machine-readable formats never change
+    pub fn format_intervals_with_locale(intervals: &[Interval], format: OutputFormat, locale: Option<Locale>) -> String {
+        Self::format_intervals_styled(intervals, format, locale, None)
+    }
+    
+    /// Like `format_intervals_with_locale`, with the durations of the human, table, and
+    /// waterfall formats in the colors of their thresholds in `colors`
+    pub fn format_intervals_styled(
+        intervals: &[Interval],
+        format: OutputFormat,
+        locale: Option<Locale>,
+        colors: Option<DurationColors>,
+    ) -> String {
         match format {
-            OutputFormat::Human => Self::format_human(intervals),
+            OutputFormat::Human => Self::format_human(intervals, locale, colors),
             OutputFormat::Json => Self::format_json(intervals),
-            OutputFormat::Csv => Self::format_csv(intervals),
+            OutputFormat::Jsonl => Self::format_appendable(intervals, format, None, CsvDialect::default()),
+            OutputFormat::Csv => Self::format_csv(intervals, None, CsvDialect::default()),
             OutputFormat::Tsv => Self::format_tsv(intervals),
-            OutputFormat::Table => Self::format_table(intervals),
+            OutputFormat::Table => Self::format_table(intervals, locale, colors),
             OutputFormat::Simple => Self::format_simple(intervals),
-            OutputFormat::Waterfall => Self::format_waterfall(intervals),
+            OutputFormat::Waterfall => Self::format_waterfall_with(intervals, WaterfallOptions::default(), &[], colors),
+            OutputFormat::Histogram => {
+                Self::format_histogram(&stats::compute(intervals, None), HistogramOptions::default())
+            }
+            OutputFormat::ChromeTrace => Self::format_chrome_trace_with(intervals, &[]),
+            OutputFormat::Markdown => Self::format_markdown(intervals, extra_columns(intervals, locale), locale),
+            OutputFormat::Html => {
+                let stats = stats::compute(intervals, None);
+                html::format_report(Some(intervals), &stats, HistogramOptions::default(), &[], locale)
+            }
+            OutputFormat::Prometheus => prometheus::format_metrics(&stats::compute(intervals, None)),
+            OutputFormat::Grafana => grafana::format_dashboard(intervals, &stats::compute(intervals, None)),
+            OutputFormat::Zipkin => spans::format_zipkin(intervals),
+            OutputFormat::Otlp => spans::format_otlp(intervals),
+        }
+    }
+    
+    /// Render one page of the intervals; JSON gets an object with the page position and
+    /// the next page number, the other formats just the intervals on the page (see
+    /// `format_intervals_styled` for `locale` and `colors`)
+    pub fn format_page(
+        intervals: &[Interval],
+        page: Page,
+        format: OutputFormat,
+        locale: Option<Locale>,
+        colors: Option<DurationColors>,
+    ) -> String {
+        let total = intervals.len();
+        let on_page = &intervals[page.range(total)];
+        match format {
+            OutputFormat::Json => {
+                let json_page = IntervalPageJson {
+                    schema_version: SCHEMA_VERSION,
+                    page: page.number,
+                    page_size: page.size,
+                    total_pages: page.count(total),
+                    total_intervals: total,
+                    next_page: page.next(total),
+                    intervals: on_page.iter().map(IntervalJson::from).collect(),
+                };
+                serde_json::to_string_pretty(&json_page).unwrap_or_else(|_| "{}".to_string())
+            }
+            _ => Self::format_intervals_styled(on_page, format, locale, colors),
         }
     }
     
-    fn format_human(intervals: &[Interval]) -> String {
+    fn format_human(intervals: &[Interval], locale: Option<Locale>, colors: Option<DurationColors>) -> String {
         intervals
             .iter()
-            .map(|interval| interval.format())
+            .map(|interval| interval.format_styled(locale, colors))
             .collect::<Vec<_>>()
             .join("\n")
     }
     
     fn format_json(intervals: &[Interval]) -> String {
-        let json_intervals: Vec<IntervalJson> = intervals
-            .iter()
-            .map(|interval| IntervalJson {
-                from_pattern: interval.from_pattern.clone(),
-                to_pattern: interval.to_pattern.clone(),
-                duration_ms: interval.duration.num_milliseconds(),
-                duration_human: interval.format_duration(),
-            })
-            .collect();
+        let json_intervals: Vec<IntervalJson> = intervals.iter().map(IntervalJson::from).collect();
         
         serde_json::to_string_pretty(&json_intervals)
             .unwrap_or_else(|_| "[]".to_string())
     }
     
-    fn format_csv(intervals: &[Interval]) -> String {
-        let mut output = String::from("from_pattern,to_pattern,duration_ms,duration_human\n");
+    /// Chrome Trace Event JSON: a complete event per interval, on one track per correlation
+    /// ID, or else per source file, or else a single track, and a global instant event per
+    /// marker
+    pub fn format_chrome_trace_with(intervals: &[Interval], markers: &[Marker]) -> String {
+        let origin = intervals
+            .iter()
+            .filter_map(|i| i.start)
+            .chain(markers.iter().map(|m| m.time))
+            .min()
+            .unwrap_or_default();
+        let micros = |duration: Duration| duration.num_microseconds().unwrap_or(i64::MAX);
         
+        let mut tracks: Vec<String> = Vec::new();
+        let mut events = vec![TraceEvent::metadata("process_name", 0, "ll")];
         for interval in intervals {
-            output.push_str(&format!(
-                "\"{}\",\"{}\",{},\"{}\"\n",
-                Self::escape_csv(&interval.from_pattern),
-                Self::escape_csv(&interval.to_pattern),
-                interval.duration.num_milliseconds(),
-                interval.format_duration()
-            ));
+            let track = interval
+                .correlation
+                .clone()
+                .or_else(|| interval.from_source.clone())
+                .unwrap_or_else(|| "intervals".to_string());
+            let tid = match tracks.iter().position(|t| *t == track) {
+                Some(index) => index + 1,
+                None => {
+                    events.push(TraceEvent::metadata("thread_name", tracks.len() + 1, &track));
+                    tracks.push(track);
+                    tracks.len()
+                }
+            };
+            
+            // Out-of-order timestamps give negative durations; draw those from the earlier end
+            let start = interval.start.unwrap_or(origin);
+            let (ts, dur) = if interval.duration < Duration::zero() {
+                (start + interval.duration - origin, -interval.duration)
+            } else {
+                (start - origin, interval.duration)
+            };
+            events.push(TraceEvent {
+                name: format!("{} -> {}", interval.from_pattern, interval.to_pattern),
+                cat: Some("interval"),
+                ph: "X",
+                ts: Some(micros(ts)),
+                dur: Some(micros(dur)),
+                pid: 1,
+                tid,
+                scope: None,
+                args: serde_json::to_value(IntervalJson::from(interval)).unwrap_or_default(),
+            });
+        }
+        for marker in markers {
+            events.push(TraceEvent {
+                name: marker.label.clone(),
+                cat: Some("marker"),
+                ph: "i",
+                ts: Some(micros(marker.time - origin)),
+                dur: None,
+                pid: 1,
+                tid: 0,
+                scope: Some("g"),
+                args: serde_json::json!({ "time": format_timestamp(marker.time) }),
+            });
         }
         
-        output.trim_end().to_string()
+        let trace = ChromeTrace {
+            trace_events: events,
+            display_time_unit: "ms",
+            other_data: BTreeMap::from([
+                ("start", format_timestamp(origin)),
+                ("schema_version", SCHEMA_VERSION.to_string()),
+            ]),
+        };
+        serde_json::to_string_pretty(&trace).unwrap_or_else(|_| "{}".to_string())
+    }
+    
+    /// Render a single interval as soon as it completes (follow mode); only for formats
+    /// where `supports_streaming` holds
+    pub fn format_streamed(
+        interval: &Interval,
+        format: OutputFormat,
+        locale: Option<Locale>,
+        colors: Option<DurationColors>,
+    ) -> String {
+        match format {
+            // One compact object per line (JSON Lines)
+            OutputFormat::Json | OutputFormat::Jsonl => serde_json::to_string(&IntervalJson::from(interval))
+                .unwrap_or_else(|_| "{}".to_string()),
+            _ => Self::format_intervals_styled(std::slice::from_ref(interval), format, locale, colors),
+        }
+    }
+    
+    /// Rows to add to a dataset that grows with every run (`--append`): CSV in `dialect` with
+    /// its header line first, or JSON Lines for any other format. `run_id` adds a leading
+    /// `run_id` column or field to every row.
+    pub fn format_appendable(
+        intervals: &[Interval],
+        format: OutputFormat,
+        run_id: Option<&str>,
+        dialect: CsvDialect,
+    ) -> String {
+        if format == OutputFormat::Csv {
+            return Self::format_csv(intervals, run_id, dialect);
+        }
+        intervals
+            .iter()
+            .map(|interval| {
+                let json = IntervalJson {
+                    run_id: run_id.map(str::to_string),
+                    ..IntervalJson::from(interval)
+                };
+                serde_json::to_string(&json).unwrap_or_else(|_| "{}".to_string())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    
+    /// Intervals as CSV with the delimiter and quoting of `dialect`
+    pub fn format_csv_with(intervals: &[Interval], dialect: CsvDialect) -> String {
+        Self::format_csv(intervals, None, dialect)
+    }
+    
+    fn format_csv(intervals: &[Interval], run_id: Option<&str>, dialect: CsvDialect) -> String {
+        let extras = extra_columns(intervals, None);
+        
+        let mut names: Vec<&str> = run_id.map(|_| "run_id").into_iter().collect();
+        names.extend(["from_pattern", "to_pattern", "duration_ms", "duration_human"]);
+        names.extend(extras.iter().map(|column| column.name.as_str()));
+        let mut lines = vec![dialect.header(&names)];
+        
+        for interval in intervals {
+            let mut fields: Vec<(String, bool)> = run_id.map(|run_id| (run_id.to_string(), true)).into_iter().collect();
+            fields.extend([
+                (interval.from_pattern.clone(), true),
+                (interval.to_pattern.clone(), true),
+                (interval.duration.num_milliseconds().to_string(), false),
+                (interval.format_duration(), true),
+            ]);
+            fields.extend(extras.iter().map(|column| ((column.value)(interval), true)));
+            lines.push(dialect.record(&fields));
+        }
+        
+        lines.join("\n")
     }
     
     fn format_tsv(intervals: &[Interval]) -> String {
-        let mut output = String::from("from_pattern\tto_pattern\tduration_ms\tduration_human\n");
+        let extras = extra_columns(intervals, None);
+        
+        let mut output = String::from("from_pattern\tto_pattern\tduration_ms\tduration_human");
+        for column in &extras {
+            output.push('\t');
+            output.push_str(&column.name);
+        }
+        output.push('\n');
         
         for interval in intervals {
             output.push_str(&format!(
-                "{}\t{}\t{}\t{}\n",
+                "{}\t{}\t{}\t{}",
                 Self::escape_tsv(&interval.from_pattern),
                 Self::escape_tsv(&interval.to_pattern),
                 interval.duration.num_milliseconds(),
                 interval.format_duration()
             ));
+            for column in &extras {
+                output.push('\t');
+                output.push_str(&Self::escape_tsv(&(column.value)(interval)));
+            }
+            output.push('\n');
         }
         
         output.trim_end().to_string()
     }
     
-    fn format_table(intervals: &[Interval]) -> String {
+    fn format_table(intervals: &[Interval], locale: Option<Locale>, colors: Option<DurationColors>) -> String {
+        Self::render_table(intervals, extra_columns(intervals, locale), locale, colors)
+    }
+    
+    /// Table of `intervals` with columns comparing each one to its transition's p50 and p95 in
+    /// `stats` (computed over every interval of the run, even when only a page is shown) and
+    /// a pass/fail glyph for staying within the p95
+    pub fn format_table_with_percentiles(
+        intervals: &[Interval],
+        stats: &[TransitionStats],
+        locale: Option<Locale>,
+        colors: Option<DurationColors>,
+    ) -> String {
+        let mut extras = extra_columns(intervals, locale);
+        extras.extend(percentile_columns(stats, locale));
+        Self::render_table(intervals, extras, locale, colors)
+    }
+    
+    /// Markdown table of `intervals` with the percentile columns of
+    /// `format_table_with_percentiles`
+    pub fn format_markdown_with_percentiles(
+        intervals: &[Interval],
+        stats: &[TransitionStats],
+        locale: Option<Locale>,
+    ) -> String {
+        let mut extras = extra_columns(intervals, locale);
+        extras.extend(percentile_columns(stats, locale));
+        Self::format_markdown(intervals, extras, locale)
+    }
+    
+    fn format_markdown(intervals: &[Interval], extras: Vec<ExtraColumn>, locale: Option<Locale>) -> String {
         if intervals.is_empty() {
             return String::new();
         }
         
+        let mut titles = vec!["From Pattern", "To Pattern", "Duration", "Duration (ms)"];
+        titles.extend(extras.iter().map(|column| column.title.as_str()));
+        let cells: Vec<Vec<String>> = intervals
+            .iter()
+            .map(|i| {
+                let ms = i.duration.num_milliseconds();
+                let mut cells = vec![
+                    i.from_pattern.clone(),
+                    i.to_pattern.clone(),
+                    locale.map_or_else(|| i.format_duration(), |locale| locale.duration(&i.duration)),
+                    locale.map_or_else(|| ms.to_string(), |locale| locale.integer(ms)),
+                ];
+                cells.extend(extras.iter().map(|column| (column.value)(i)));
+                cells
+            })
+            .collect();
+        
+        Self::render_markdown(&titles, &cells, |col| col == 3)
+    }
+    
+    /// GitHub-flavored Markdown table, padded so the source lines up too; `numeric` columns
+    /// are right-aligned. Pipes in cells are escaped and line breaks become spaces.
+    fn render_markdown(titles: &[&str], cells: &[Vec<String>], numeric: impl Fn(usize) -> bool) -> String {
+        let escape = |value: &str| value.replace('|', "\\|").replace(['\r', '\n'], " ");
+        let cells: Vec<Vec<String>> = cells.iter().map(|row| row.iter().map(|value| escape(value)).collect()).collect();
+        // The delimiter row needs at least three dashes per column
+        let widths: Vec<usize> = titles
+            .iter()
+            .enumerate()
+            .map(|(col, title)| cells.iter().map(|row| row[col].chars().count()).max().unwrap_or(0).max(title.len()).max(3))
+            .collect();
+        
+        let render = |values: &mut dyn Iterator<Item = &str>| {
+            let mut line = String::from("|");
+            for (col, (value, width)) in values.zip(&widths).enumerate() {
+                if numeric(col) {
+                    line.push_str(&format!(" {:>width$} |", value, width = width));
+                } else {
+                    line.push_str(&format!(" {:<width$} |", value, width = width));
+                }
+            }
+            line
+        };
+        
+        let mut lines = vec![render(&mut titles.iter().copied())];
+        let mut delimiter = String::from("|");
+        for (col, width) in widths.iter().enumerate() {
+            if numeric(col) {
+                delimiter.push_str(&format!(" {:->width$}: |", "", width = width - 1));
+            } else {
+                delimiter.push_str(&format!(" {:-<width$} |", "", width = width));
+            }
+        }
+        lines.push(delimiter);
+        lines.extend(cells.iter().map(|row| render(&mut row.iter().map(String::as_str))));
+        lines.join("\n")
+    }
+    
+    fn render_table(
+        intervals: &[Interval],
+        extras: Vec<ExtraColumn>,
+        locale: Option<Locale>,
+        colors: Option<DurationColors>,
+    ) -> String {
+        if intervals.is_empty() {
+            return String::new();
+        }
+        
+        let duration = |i: &Interval| locale.map_or_else(|| i.format_duration(), |locale| locale.duration(&i.duration));
+        let millis = |i: &Interval| {
+            let ms = i.duration.num_milliseconds();
+            locale.map_or_else(|| ms.to_string(), |locale| locale.integer(ms))
+        };
+        
         // Calculate column widths
         let max_from = intervals
             .iter()
@@ -134,23 +1017,35 @@ impl OutputFormatter {
         
         let max_duration = intervals
             .iter()
-            .map(|i| i.format_duration().len())
+            .map(|i| duration(i).chars().count())
             .max()
             .unwrap_or(0)
             .max(8); // "Duration" header length
         
         let max_ms = intervals
             .iter()
-            .map(|i| i.duration.num_milliseconds().to_string().len())
+            .map(|i| millis(i).chars().count())
             .max()
             .unwrap_or(0)
             .max(13); // "Duration (ms)" header length
         
+        let extra_widths: Vec<usize> = extras
+            .iter()
+            .map(|column| {
+                intervals
+                    .iter()
+                    .map(|i| (column.value)(i).chars().count())
+                    .max()
+                    .unwrap_or(0)
+                    .max(column.title.len())
+            })
+            .collect();
+        
         let mut output = String::new();
         
         // Header
         output.push_str(&format!(
-            "| {:<width_from$} | {:<width_to$} | {:<width_duration$} | {:>width_ms$} |\n",
+            "| {:<width_from$} | {:<width_to$} | {:<width_duration$} | {:>width_ms$} |",
             "From Pattern",
             "To Pattern",
             "Duration",
@@ -160,10 +1055,14 @@ impl OutputFormatter {
             width_duration = max_duration,
             width_ms = max_ms
         ));
+        for (column, width) in extras.iter().zip(&extra_widths) {
+            output.push_str(&format!(" {:<width$} |", column.title, width = width));
+        }
+        output.push('\n');
         
         // Separator
         output.push_str(&format!(
-            "|{:-<width_from$}|{:-<width_to$}|{:-<width_duration$}|{:-<width_ms$}|\n",
+            "|{:-<width_from$}|{:-<width_to$}|{:-<width_duration$}|{:-<width_ms$}|",
             "-",
             "-",
             "-",
@@ -173,56 +1072,90 @@ impl OutputFormatter {
             width_duration = max_duration + 2,
             width_ms = max_ms + 2
         ));
+        for width in &extra_widths {
+            output.push_str(&format!("{:-<width$}|", "-", width = width + 2));
+        }
+        output.push('\n');
         
-        // Rows
+        // Rows; durations are colored after padding so the escape codes don't count as width
         for interval in intervals {
+            let ms = interval.duration.num_milliseconds() as f64;
             output.push_str(&format!(
-                "| {:<width_from$} | {:<width_to$} | {:<width_duration$} | {:>width_ms$} |\n",
+                "| {:<width_from$} | {:<width_to$} | {} | {} |",
                 interval.from_pattern,
                 interval.to_pattern,
-                interval.format_duration(),
-                interval.duration.num_milliseconds(),
+                style::paint(colors, ms, &format!("{:<width$}", duration(interval), width = max_duration)),
+                style::paint(colors, ms, &format!("{:>width$}", millis(interval), width = max_ms)),
                 width_from = max_from,
-                width_to = max_to,
-                width_duration = max_duration,
-                width_ms = max_ms
+                width_to = max_to
             ));
+            for (column, width) in extras.iter().zip(&extra_widths) {
+                output.push_str(&format!(" {:<width$} |", (column.value)(interval), width = width));
+            }
+            output.push('\n');
         }
         
         output.trim_end().to_string()
     }
     
     fn format_simple(intervals: &[Interval]) -> String {
+        let extras = extra_columns(intervals, None);
+        
         intervals
             .iter()
             .map(|interval| {
-                format!(
+                let mut line = format!(
                     "{}|{}|{}",
                     interval.from_pattern,
                     interval.to_pattern,
                     interval.duration.num_milliseconds()
-                )
+                );
+                for column in &extras {
+                    line.push('|');
+                    line.push_str(&(column.value)(interval));
+                }
+                line
             })
             .collect::<Vec<_>>()
             .join("\n")
     }
     
-    fn escape_csv(s: &str) -> String {
-        s.replace('"', "\"\"")
-    }
-    
     fn escape_tsv(s: &str) -> String {
         s.replace('\t', "    ").replace('\n', " ")
     }
     
-    fn format_waterfall(intervals: &[Interval]) -> String {
+    /// Waterfall of every interval, drawn as `options` say, with a line for each of the
+    /// `markers` among them and the durations in the colors of their thresholds in `colors`
+    pub fn format_waterfall_with(
+        intervals: &[Interval],
+        options: WaterfallOptions,
+        markers: &[Marker],
+        colors: Option<DurationColors>,
+    ) -> String {
+        if options.horizontal {
+            Self::format_waterfall_rows(intervals, options, markers, colors)
+        } else {
+            Self::format_waterfall_columns(intervals, options, markers, colors)
+        }
+    }
+    
+    /// Marker lines to put before the interval at `index` (or after the last one, at its length)
+    fn marker_rules(markers: &[Marker], intervals: &[Interval], index: usize) -> Vec<String> {
+        markers.iter().filter(|m| m.position(intervals) == index).map(Marker::rule).collect()
+    }
+    
+    fn format_waterfall_columns(
+        intervals: &[Interval],
+        options: WaterfallOptions,
+        markers: &[Marker],
+        colors: Option<DurationColors>,
+    ) -> String {
         if intervals.is_empty() {
             return String::new();
         }
         
         const MAX_HEIGHT: usize = 40;
         const MIN_HEIGHT: usize = 1;
-        const SCREEN_WIDTH: usize = 100;
         
         // Calculate the maximum duration in milliseconds for normalization
         let max_duration_ms = intervals
@@ -237,7 +1170,7 @@ impl OutputFormatter {
             .iter()
             .map(|interval| {
                 let duration_ms = interval.duration.num_milliseconds();
-                let normalized = (duration_ms as f64 / max_duration_ms as f64) * (MAX_HEIGHT as f64);
+                let normalized = options.fraction(duration_ms as f64, max_duration_ms as f64) * (MAX_HEIGHT as f64);
                 normalized.ceil().max(MIN_HEIGHT as f64) as usize
             })
             .collect();
@@ -245,11 +1178,11 @@ impl OutputFormatter {
         let num_intervals = intervals.len();
         
         // Calculate width per interval (spread evenly)
-        let width_per_interval = if num_intervals > 0 {
-            (SCREEN_WIDTH / num_intervals).max(1)
-        } else {
-            1
-        };
+        let width_per_interval = options
+            .width
+            .checked_div(num_intervals)
+            .unwrap_or(1)
+            .max(1);
         
         // Find the maximum height we'll actually use
         let actual_max_height = *heights.iter().max().unwrap_or(&MIN_HEIGHT);
@@ -261,7 +1194,8 @@ impl OutputFormatter {
             for (i, &height) in heights.iter().enumerate() {
                 // Draw the bar if we're within its height
                 if row <= height {
-                    output.push('|');
+                    let ms = intervals[i].duration.num_milliseconds() as f64;
+                    output.push_str(&style::paint(colors, ms, "|"));
                 } else {
                     output.push(' ');
                 }
@@ -289,6 +1223,9 @@ impl OutputFormatter {
         
         // Add labels for each interval (showing pattern transitions)
         for (i, interval) in intervals.iter().enumerate() {
+            for rule in Self::marker_rules(markers, intervals, i) {
+                output.push_str(&format!("\n{}", rule));
+            }
             let label = format!("{}→{}", 
                 Self::truncate_label(&interval.from_pattern, 8),
                 Self::truncate_label(&interval.to_pattern, 8));
@@ -296,9 +1233,454 @@ impl OutputFormatter {
             output.push_str(&format!("\n{}: {} ({})", 
                 i + 1, 
                 label,
-                interval.format_duration()));
+                style::paint(colors, interval.duration.num_milliseconds() as f64, &interval.format_duration())));
+            
+            if let Some(source) = interval.source_label() {
+                output.push_str(&format!(" [{}]", source));
+                if interval.is_cross_source() {
+                    output.push_str(" (cross-source)");
+                }
+            }
+        }
+        for rule in Self::marker_rules(markers, intervals, intervals.len()) {
+            output.push_str(&format!("\n{}", rule));
+        }
+        
+        output
+    }
+    
+    /// A row per interval: its number, transition, and a bar of block characters drawn to
+    /// an eighth of a column, followed by its duration
+    fn format_waterfall_rows(
+        intervals: &[Interval],
+        options: WaterfallOptions,
+        markers: &[Marker],
+        colors: Option<DurationColors>,
+    ) -> String {
+        if intervals.is_empty() {
+            return String::new();
+        }
+        
+        const MAX_LABEL_WIDTH: usize = 40;
+        const MIN_BAR_WIDTH: usize = 10;
+        
+        let labels: Vec<String> = intervals
+            .iter()
+            .map(|interval| {
+                let mut label = format!("{}→{}", interval.from_pattern, interval.to_pattern);
+                if let Some(source) = interval.source_label() {
+                    label.push_str(&format!(" [{}]", source));
+                }
+                if label.chars().count() > MAX_LABEL_WIDTH {
+                    label = label.chars().take(MAX_LABEL_WIDTH - 3).collect::<String>() + "...";
+                }
+                label
+            })
+            .collect();
+        let durations: Vec<String> = intervals.iter().map(Interval::format_duration).collect();
+        let number_width = intervals.len().to_string().len();
+        let label_width = labels.iter().map(|label| label.chars().count()).max().unwrap_or(0);
+        let duration_width = durations.iter().map(|d| d.chars().count()).max().unwrap_or(0);
+        // Two spaces between the columns, and a colon after the number
+        let bar_width = options
+            .width
+            .saturating_sub(number_width + 1 + label_width + duration_width + 6)
+            .max(MIN_BAR_WIDTH);
+        let max_ms = intervals.iter().map(|i| i.duration.num_milliseconds()).max().unwrap_or(1) as f64;
+        
+        let mut output = String::new();
+        for (i, interval) in intervals.iter().enumerate() {
+            for rule in Self::marker_rules(markers, intervals, i) {
+                output.push_str(&format!("{}\n", rule));
+            }
+            let ms = interval.duration.num_milliseconds() as f64;
+            let bar = block_bar(options.fraction(ms, max_ms) * bar_width as f64);
+            let padding = " ".repeat(bar_width.saturating_sub(bar.chars().count()));
+            output.push_str(&format!(
+                "{:>nw$}: {:<lw$}  {}{}  {}\n",
+                i + 1,
+                labels[i],
+                style::paint(colors, ms, &bar),
+                padding,
+                style::paint(colors, ms, &durations[i]),
+                nw = number_width,
+                lw = label_width
+            ));
+        }
+        
+        for rule in Self::marker_rules(markers, intervals, intervals.len()) {
+            output.push_str(&format!("{}\n", rule));
+        }
+        output.push_str(match options.scale {
+            BarScale::Linear => "\nBars are proportional to each interval's duration",
+            BarScale::Log => "\nBars are proportional to the logarithm of each interval's duration",
+        });
+        output
+    }
+    
+    /// Waterfall comparing two runs: for every step (transition), a baseline bar and a
+    /// candidate bar of the step's mean duration, with the change between them
+    pub fn format_waterfall_comparison(baseline: &[Interval], candidate: &[Interval]) -> String {
+        const BAR_WIDTH: usize = 50;
+        
+        let baseline_stats = stats::compute(baseline, None);
+        let candidate_stats = stats::compute(candidate, None);
+        
+        // Candidate steps in order, then steps that only occurred in the baseline
+        let mut steps: Vec<(&str, &str)> = candidate_stats
+            .iter()
+            .map(|s| (s.from_pattern.as_str(), s.to_pattern.as_str()))
+            .collect();
+        for s in &baseline_stats {
+            let step = (s.from_pattern.as_str(), s.to_pattern.as_str());
+            if !steps.contains(&step) {
+                steps.push(step);
+            }
+        }
+        
+        let mean_of = |all: &[stats::TransitionStats], (from, to): (&str, &str)| {
+            all.iter()
+                .find(|s| s.from_pattern == from && s.to_pattern == to)
+                .map(|s| (s.mean_ms, s.count))
+        };
+        
+        let max_ms = baseline_stats
+            .iter()
+            .chain(&candidate_stats)
+            .map(|s| s.mean_ms)
+            .fold(1.0, f64::max);
+        let bar = |fill: char, ms: f64| {
+            let len = ((ms.max(0.0) / max_ms) * BAR_WIDTH as f64).ceil() as usize;
+            format!("{:<width$}", fill.to_string().repeat(len.max(1)), width = BAR_WIDTH)
+        };
+        let describe = |(mean, count): (f64, usize)| {
+            if count > 1 {
+                format!("{} (mean of {})", human_ms(mean), count)
+            } else {
+                human_ms(mean)
+            }
+        };
+        
+        let mut output = String::new();
+        for (i, &step) in steps.iter().enumerate() {
+            let base = mean_of(&baseline_stats, step);
+            let cand = mean_of(&candidate_stats, step);
+            
+            output.push_str(&format!("{}: {}→{}\n", i + 1, step.0, step.1));
+            
+            match base {
+                Some(base) => output.push_str(&format!("   baseline  {} {}\n", bar('=', base.0), describe(base))),
+                None => output.push_str("   baseline  (not in run)\n"),
+            }
+            
+            match cand {
+                Some(cand) => {
+                    output.push_str(&format!("   candidate {} {}", bar('#', cand.0), describe(cand)));
+                    if let Some(base) = base {
+                        let delta = cand.0 - base.0;
+                        let sign = if delta >= 0.0 { "+" } else { "-" };
+                        output.push_str(&format!("  {}{}", sign, human_ms(delta.abs())));
+                        if base.0 > 0.0 {
+                            output.push_str(&format!(" ({:+.1}%)", delta / base.0 * 100.0));
+                        }
+                    }
+                    output.push('\n');
+                }
+                None => output.push_str("   candidate (not in run)\n"),
+            }
+        }
+        
+        output.push_str("\nBars show each step's mean duration: = baseline, # candidate");
+        output
+    }
+    
+    /// Aggregate statistics per transition (`--stats`) in any output format
+    pub fn format_stats(stats: &[TransitionStats], format: OutputFormat) -> String {
+        Self::format_stats_with_locale(stats, format, None)
+    }
+    
+    /// Like `format_stats`, with the counts and durations of the human and table formats in
+    /// `locale`'s conventions
+    pub fn format_stats_with_locale(stats: &[TransitionStats], format: OutputFormat, locale: Option<Locale>) -> String {
+        Self::format_stats_styled(stats, format, locale, None)
+    }
+    
+    /// Like `format_stats_with_locale`, with the durations of the human, table, and waterfall
+    /// formats (all but the standard deviation) in the colors of their thresholds in `colors`
+    pub fn format_stats_styled(
+        stats: &[TransitionStats],
+        format: OutputFormat,
+        locale: Option<Locale>,
+        colors: Option<DurationColors>,
+    ) -> String {
+        let rows: Vec<TransitionStatsRow> = stats.iter().map(TransitionStatsRow::new).collect();
+        let grouped = rows.iter().any(|r| r.group.is_some());
+        let ms = |ms: f64| localized_ms(ms, locale);
+        let painted = |ms: f64| style::paint(colors, ms, &localized_ms(ms, locale));
+        let count = |count: usize| localized_count(count, locale);
+        
+        match format {
+            OutputFormat::Human => rows
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{}: count {}, min {}, max {}, mean {}, median {}, p95 {}, p99 {}, stddev {}",
+                        r.transition(),
+                        count(r.count),
+                        painted(r.min_ms as f64),
+                        painted(r.max_ms as f64),
+                        painted(r.mean_ms),
+                        painted(r.median_ms),
+                        painted(r.p95_ms),
+                        painted(r.p99_ms),
+                        ms(r.stddev_ms)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            // Aggregates have no timeline to trace, so the trace formats fall back to JSON
+            OutputFormat::Json | OutputFormat::ChromeTrace | OutputFormat::Zipkin | OutputFormat::Otlp => {
+                serde_json::to_string_pretty(&rows).unwrap_or_else(|_| "[]".to_string())
+            }
+            OutputFormat::Jsonl => rows
+                .iter()
+                .filter_map(|row| serde_json::to_string(row).ok())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            OutputFormat::Csv => Self::format_stats_csv(&rows, CsvDialect::default()),
+            OutputFormat::Tsv | OutputFormat::Simple => {
+                let (separator, escape): (&str, fn(&str) -> String) = if format == OutputFormat::Tsv {
+                    ("\t", Self::escape_tsv)
+                } else {
+                    ("|", str::to_string)
+                };
+                let mut lines = Vec::new();
+                if format == OutputFormat::Tsv {
+                    lines.push(TransitionStatsRow::headers(grouped).join(separator));
+                }
+                for r in &rows {
+                    let mut values: Vec<String> = r.labels(grouped).iter().map(|label| escape(label)).collect();
+                    values.push(r.count.to_string());
+                    values.extend(r.durations().iter().map(f64::to_string));
+                    lines.push(values.join(separator));
+                }
+                lines.join("\n")
+            }
+            OutputFormat::Table => Self::format_stats_table(&rows, locale, colors),
+            OutputFormat::Markdown => {
+                let mut titles = Vec::from_iter(grouped.then_some("Group"));
+                titles.extend([
+                    "From Pattern", "To Pattern", "Count", "Min", "Max", "Mean", "Median", "P95", "P99", "Stddev",
+                ]);
+                let labels = titles.len() - 8;
+                let cells: Vec<Vec<String>> = rows
+                    .iter()
+                    .map(|r| {
+                        let mut cells = r.labels(grouped);
+                        cells.push(count(r.count));
+                        cells.extend(r.durations().iter().map(|&d| ms(d)));
+                        cells
+                    })
+                    .collect();
+                Self::render_markdown(&titles, &cells, |col| col >= labels)
+            }
+            OutputFormat::Waterfall => Self::format_stats_waterfall(&rows, WaterfallOptions::default(), colors),
+            OutputFormat::Histogram => Self::format_histogram(stats, HistogramOptions::default()),
+            OutputFormat::Html => html::format_report(None, stats, HistogramOptions::default(), &[], locale),
+            OutputFormat::Prometheus => prometheus::format_metrics(stats),
+            OutputFormat::Grafana => grafana::format_dashboard(&[], stats),
+        }
+    }
+    
+    /// Aggregate statistics per transition as CSV with the delimiter and quoting of `dialect`
+    pub fn format_stats_csv_with(stats: &[TransitionStats], dialect: CsvDialect) -> String {
+        let rows: Vec<TransitionStatsRow> = stats.iter().map(TransitionStatsRow::new).collect();
+        Self::format_stats_csv(&rows, dialect)
+    }
+    
+    fn format_stats_csv(rows: &[TransitionStatsRow], dialect: CsvDialect) -> String {
+        let grouped = rows.iter().any(|r| r.group.is_some());
+        let mut lines = vec![dialect.header(&TransitionStatsRow::headers(grouped))];
+        for r in rows {
+            let mut fields: Vec<(String, bool)> = r.labels(grouped).into_iter().map(|label| (label, true)).collect();
+            fields.push((r.count.to_string(), false));
+            fields.extend(r.durations().iter().map(|ms| (ms.to_string(), false)));
+            lines.push(dialect.record(&fields));
+        }
+        lines.join("\n")
+    }
+    
+    fn format_stats_table(rows: &[TransitionStatsRow], locale: Option<Locale>, colors: Option<DurationColors>) -> String {
+        const TITLES: [&str; 10] = [
+            "From Pattern", "To Pattern", "Count", "Min", "Max", "Mean", "Median", "P95", "P99", "Stddev",
+        ];
+        let grouped = rows.iter().any(|r| r.group.is_some());
+        let titles: Vec<String> = grouped.then_some("Group").into_iter().chain(TITLES).map(String::from).collect();
+        let labels = titles.len() - 8;
+        
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|r| {
+                let mut cells = r.labels(grouped);
+                cells.push(localized_count(r.count, locale));
+                let [timings @ .., stddev] = r.durations();
+                cells.extend(timings.iter().map(|&ms| style::paint(colors, ms, &localized_ms(ms, locale))));
+                cells.push(localized_ms(stddev, locale));
+                cells
+            })
+            .collect();
+        let titles: Vec<&str> = titles.iter().map(String::as_str).collect();
+        
+        // Group and patterns are left-aligned, numbers right-aligned
+        Self::render_text_table(&titles, &cells, |col| col >= labels)
+    }
+    
+    /// Rows of `cells` under `titles` as a table, in the markdown format for `Markdown` and
+    /// in the aligned columns of the table format for any other; `numeric` columns are
+    /// right-aligned
+    pub fn format_grid(
+        titles: &[&str],
+        cells: &[Vec<String>],
+        numeric: impl Fn(usize) -> bool,
+        format: OutputFormat,
+    ) -> String {
+        match format {
+            OutputFormat::Markdown => Self::render_markdown(titles, cells, numeric),
+            _ => Self::render_text_table(titles, cells, numeric),
+        }
+    }
+    
+    fn render_text_table(titles: &[&str], cells: &[Vec<String>], numeric: impl Fn(usize) -> bool) -> String {
+        let widths: Vec<usize> = titles
+            .iter()
+            .enumerate()
+            .map(|(col, title)| cells.iter().map(|row| style::visible_width(&row[col])).max().unwrap_or(0).max(title.len()))
+            .collect();
+        // Padded by hand, as `format!` widths would count the escape codes of colored cells
+        let render = |values: &mut dyn Iterator<Item = &str>| {
+            let mut line = String::from("|");
+            for (col, (value, width)) in values.zip(&widths).enumerate() {
+                let padding = " ".repeat(width - style::visible_width(value));
+                if numeric(col) {
+                    line.push_str(&format!(" {}{} |", padding, value));
+                } else {
+                    line.push_str(&format!(" {}{} |", value, padding));
+                }
+            }
+            line
+        };
+        
+        let mut output = render(&mut titles.iter().copied());
+        output.push_str("\n|");
+        for width in &widths {
+            output.push_str(&format!("{:-<width$}|", "-", width = width + 2));
+        }
+        for row in cells {
+            output.push('\n');
+            output.push_str(&render(&mut row.iter().map(String::as_str)));
+        }
+        
+        output
+    }
+    
+    /// Built-in timestamp formats as JSON, or as a table (Markdown or plain) of name, chrono
+    /// format, example, and regex for any other format
+    pub fn format_timestamp_formats(formats: &[TimestampFormat], format: OutputFormat) -> String {
+        if format == OutputFormat::Json {
+            return serde_json::to_string_pretty(formats).unwrap_or_else(|_| "[]".to_string());
+        }
+        
+        let cells: Vec<Vec<String>> = formats
+            .iter()
+            .map(|f| [f.name, f.format, f.example, f.regex].map(str::to_string).to_vec())
+            .collect();
+        Self::format_grid(&["Name", "Chrono Format", "Example", "Regex"], &cells, |_| false, format)
+    }
+    
+    /// One histogram per transition: a row per duration bucket with a bar scaled to the
+    /// fullest bucket and the number of intervals in it
+    pub fn format_histogram(stats: &[TransitionStats], options: HistogramOptions) -> String {
+        const BAR_WIDTH: usize = 40;
+        
+        let mut sections = Vec::new();
+        for s in stats.iter().filter(|s| s.count > 0) {
+            let buckets = options.buckets(s);
+            let counts: Vec<usize> = buckets.iter().map(|&(_, _, count)| count).collect();
+            let labels: Vec<(String, String)> =
+                buckets.iter().map(|&(low, high, _)| (human_ms(low), human_ms(high))).collect();
+            let low_width = labels.iter().map(|(low, _)| low.len()).max().unwrap_or(0);
+            let high_width = labels.iter().map(|(_, high)| high.len()).max().unwrap_or(0);
+            let fullest = counts.iter().copied().max().unwrap_or(0).max(1);
+            
+            let mut section = format!("{} (n={})", s.transition(), s.count);
+            for ((low, high), count) in labels.iter().zip(&counts) {
+                let bar = "#".repeat((count * BAR_WIDTH).div_ceil(fullest));
+                section.push_str(&format!(
+                    "\n  {:>low_width$} - {:<high_width$} | {:<BAR_WIDTH$} {}",
+                    low,
+                    high,
+                    bar,
+                    count,
+                    low_width = low_width,
+                    high_width = high_width,
+                    BAR_WIDTH = BAR_WIDTH
+                ));
+            }
+            sections.push(section);
+        }
+        
+        sections.join("\n\n")
+    }
+    
+    /// Like `format_stats_styled` in the waterfall format, drawn as `options` say
+    pub fn format_stats_waterfall_with(
+        stats: &[TransitionStats],
+        options: WaterfallOptions,
+        colors: Option<DurationColors>,
+    ) -> String {
+        let rows: Vec<TransitionStatsRow> = stats.iter().map(TransitionStatsRow::new).collect();
+        Self::format_stats_waterfall(&rows, options, colors)
+    }
+    
+    /// Horizontal bars of each transition's mean, with the p95 marked by `|`
+    fn format_stats_waterfall(rows: &[TransitionStatsRow], options: WaterfallOptions, colors: Option<DurationColors>) -> String {
+        // Half the width leaves room for the durations after the bar
+        let bar_width = (options.width / 2).max(10);
+        
+        let max_ms = rows.iter().map(|r| r.p95_ms.max(r.mean_ms)).fold(1.0, f64::max);
+        let scale = |ms: f64| (options.fraction(ms, max_ms) * bar_width as f64).ceil() as usize;
+        
+        let mut output = String::new();
+        for (i, r) in rows.iter().enumerate() {
+            let mean_width = scale(r.mean_ms).max(1);
+            let mut bar: Vec<char> = "#".repeat(mean_width).chars().collect();
+            bar.resize(bar_width + 1, ' ');
+            bar[scale(r.p95_ms).min(bar_width)] = '|';
+            // The mean's part of the bar in its color, the p95 marker and the rest plain
+            let mut bar: String = bar.into_iter().collect();
+            if colors.is_some() {
+                let rest = bar.split_off(mean_width);
+                bar = style::paint(colors, r.mean_ms, &bar) + &rest;
+            }
+            
+            let group = r.group.as_ref().map(|group| format!(" ({})", group)).unwrap_or_default();
+            output.push_str(&format!(
+                "{}: {}→{}{}\n   {} mean {}, p95 {} (n={})\n",
+                i + 1,
+                r.from_pattern,
+                r.to_pattern,
+                group,
+                bar,
+                style::paint(colors, r.mean_ms, &human_ms(r.mean_ms)),
+                style::paint(colors, r.p95_ms, &human_ms(r.p95_ms)),
+                r.count
+            ));
         }
         
+        output.push_str(match options.scale {
+            BarScale::Linear => "\nBars show each transition's mean duration; | marks its p95",
+            BarScale::Log => "\nBars show each transition's mean duration on a logarithmic scale; | marks its p95",
+        });
         output
     }
     
@@ -1,25 +1,463 @@
-use anyhow::{Context, Result};
-use chrono::NaiveDateTime;
-use regex::Regex;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use rayon::prelude::*;
+use regex::{Regex, RegexSet};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::Instant;
 
-use crate::config::Config;
-use crate::timestamp_formats::{get_builtin_formats, TimestampFormat};
+use crate::config::{Config, InputFormat};
+use crate::decompress::{open_log_file, open_log_file_mapped, Compression};
+use crate::duration::parse_duration;
+use crate::error::{LogLineError, Result};
+use crate::progress::Progress;
+use crate::timestamp_formats::{find_builtin_format, get_builtin_formats, TimestampFormat};
 
+/// Number of leading lines used to rank the built-in formats in auto-detection mode
+pub(crate) const DETECTION_SAMPLE_LINES: usize = 1000;
+
+/// Consecutive lines one built-in format must parse before auto-detection stops trying the
+/// others on the rest of the input (unless `mixed_timestamp_formats` is set)
+pub const FORMAT_LOCK_LINES: usize = 100;
+
+/// Files are split into chunks parsed in parallel only when each chunk gets at least this many bytes
+const MIN_CHUNK_BYTES: u64 = 32 * 1024 * 1024;
+
+/// What `LogParser::follow_reloading` reports while tailing an input
+#[derive(Debug)]
+pub enum FollowEvent {
+    /// A line matched one of the message patterns
+    Match(LogMatch),
+    /// A configuration from the update channel took effect; later matches were found with it
+    Reloaded(Box<Config>),
+}
+
+/// What one built-in format makes of a line (see `LogParser::explain_line`)
 #[derive(Debug, Clone)]
+pub struct FormatCandidate {
+    /// Name of the built-in format
+    pub format: &'static str,
+    /// Text its regex captured as the timestamp
+    pub text: String,
+    /// The parsed time, or why the text doesn't parse with the format's chrono format
+    pub parsed: std::result::Result<String, String>,
+}
+
+/// A log line that matched one of the message patterns
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct LogMatch {
+    /// The matching pattern, or the text captured by its `label` group
     pub pattern: String,
     pub timestamp: NaiveDateTime,
+    /// File or host the match came from, set when merging several inputs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Named capture groups of the matching pattern (other than `label`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    /// Request ID (or other correlation key) of the line, when correlation is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation: Option<String>,
+    /// Line number (from 1) within its file or stream, when the parser records source lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<usize>,
+    /// The matched line as read, when the parser keeps raw lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<String>,
+}
+
+/// Counters describing how many lines were read and why lines were skipped
+#[derive(Debug, Default, Clone)]
+pub struct ParseStats {
+    pub lines_read: usize,
+    pub matches: usize,
+    /// No timestamp regex matched the line
+    pub no_timestamp: usize,
+    /// A timestamp was found but could not be parsed with the format
+    pub timestamp_parse_failed: usize,
+    /// The line had a timestamp but none of the message patterns matched
+    pub no_pattern_matched: usize,
+    /// The line matched one of the exclude patterns
+    pub excluded: usize,
+    /// The line was longer than the configured maximum line length
+    pub too_long: usize,
+    /// JSON input line that could not be parsed as a JSON value
+    pub invalid_json: usize,
+    /// Timestamps with a leap second (`:60`) clamped to the end of the preceding second
+    pub leap_seconds: usize,
+    /// Matches dropped for falling outside the time window (see `LogParser::set_time_window`)
+    pub outside_time_window: usize,
+    /// Lines joined onto the entry before them in multiline mode
+    pub continuation_lines: usize,
+    /// Reading stopped early on Ctrl-C/SIGTERM, so the counters and matches are partial
+    pub interrupted: bool,
+}
+
+impl ParseStats {
+    /// Add the counters of another parse (e.g. of one chunk of the same file)
+    pub fn merge(&mut self, other: &ParseStats) {
+        self.lines_read += other.lines_read;
+        self.matches += other.matches;
+        self.no_timestamp += other.no_timestamp;
+        self.timestamp_parse_failed += other.timestamp_parse_failed;
+        self.no_pattern_matched += other.no_pattern_matched;
+        self.excluded += other.excluded;
+        self.too_long += other.too_long;
+        self.invalid_json += other.invalid_json;
+        self.leap_seconds += other.leap_seconds;
+        self.outside_time_window += other.outside_time_window;
+        self.continuation_lines += other.continuation_lines;
+        self.interrupted |= other.interrupted;
+    }
+    
+    pub fn skipped(&self) -> usize {
+        self.no_timestamp
+            + self.timestamp_parse_failed
+            + self.no_pattern_matched
+            + self.excluded
+            + self.too_long
+            + self.invalid_json
+    }
+    
+    /// Render the counters as an indented plain-text report
+    pub fn format_summary(&self) -> String {
+        let rows = [
+            ("Lines read", self.lines_read, 0),
+            ("Matches", self.matches, 0),
+            ("Skipped lines", self.skipped(), 0),
+            ("no timestamp", self.no_timestamp, 2),
+            ("timestamp parse failed", self.timestamp_parse_failed, 2),
+            ("no pattern matched", self.no_pattern_matched, 2),
+            ("excluded", self.excluded, 2),
+            ("too long", self.too_long, 2),
+        ];
+        
+        let mut output = String::from("Run summary:\n");
+        let json_row = ("invalid JSON", self.invalid_json, 2);
+        let leap_row = ("Leap seconds clamped", self.leap_seconds, 0);
+        let window_row = ("Outside time window", self.outside_time_window, 0);
+        let continuation_row = ("Continuation lines", self.continuation_lines, 0);
+        let optional_rows = [
+            (self.invalid_json > 0).then_some(json_row),
+            (self.leap_seconds > 0).then_some(leap_row),
+            (self.outside_time_window > 0).then_some(window_row),
+            (self.continuation_lines > 0).then_some(continuation_row),
+        ];
+        for (label, value, indent) in rows.into_iter().chain(optional_rows.into_iter().flatten()) {
+            output.push_str(&format!(
+                "  {:indent$}{:<width$} {:>8}\n",
+                "",
+                format!("{}:", label),
+                value,
+                indent = indent,
+                width = 26 - indent
+            ));
+        }
+        if self.interrupted {
+            output.push_str("  Interrupted: results are partial\n");
+        }
+        
+        output.trim_end().to_string()
+    }
+}
+
+/// Time spent matching one regex and how often it was tried and matched (`--profile-patterns`)
+#[derive(Debug, Default, Clone)]
+pub struct RegexProfile {
+    pub tried: usize,
+    pub hits: usize,
+    pub elapsed: std::time::Duration,
+}
+
+impl RegexProfile {
+    /// Run one match attempt, recording its time and outcome when profiling is enabled
+    fn measure<T>(&mut self, enabled: bool, attempt: impl FnOnce() -> Option<T>) -> Option<T> {
+        if !enabled {
+            return attempt();
+        }
+        
+        let started = Instant::now();
+        let result = attempt();
+        self.elapsed += started.elapsed();
+        self.tried += 1;
+        if result.is_some() {
+            self.hits += 1;
+        }
+        result
+    }
+    
+    fn merge(&mut self, other: &RegexProfile) {
+        self.tried += other.tried;
+        self.hits += other.hits;
+        self.elapsed += other.elapsed;
+    }
+}
+
+/// Where the timestamp and message are found in JSON input
+#[derive(Clone)]
+struct JsonInput {
+    timestamp_path: Vec<String>,
+    /// Patterns match the whole line when unset
+    message_path: Option<Vec<String>>,
+}
+
+impl JsonInput {
+    fn new(config: &Config) -> Option<Self> {
+        if config.input_format != InputFormat::Json {
+            return None;
+        }
+        let split = |path: &str| path.split('.').map(str::to_string).collect();
+        Some(JsonInput {
+            timestamp_path: split(config.timestamp_field.as_deref().unwrap_or_default()),
+            message_path: config.message_field.as_deref().map(split),
+        })
+    }
+    
+    /// The timestamp and message text of a JSON line, `None` for fields that are missing
+    fn extract(&self, line: &str) -> serde_json::Result<(Option<String>, Option<String>)> {
+        let value: serde_json::Value = serde_json::from_str(line)?;
+        let timestamp = json_field(&value, &self.timestamp_path);
+        let message = match &self.message_path {
+            Some(path) => json_field(&value, path),
+            None => Some(line.to_string()),
+        };
+        Ok((timestamp, message))
+    }
+}
+
+/// Timestamps for lines without one of their own: a base time from the file name plus the
+/// offset of the latest relative marker line (`filename_timestamp_regex`, `offset_regex`)
+#[derive(Clone)]
+struct FilenameTimestamps {
+    name_regex: Regex,
+    name_format: String,
+    offset_regex: Option<Regex>,
+    /// Base time of the file being read; `None` for stdin or a name without one
+    base: Option<NaiveDateTime>,
+    /// Offset of the latest marker in the current input
+    offset: Duration,
+}
+
+impl FilenameTimestamps {
+    fn new(config: &Config) -> Result<Option<Self>> {
+        let (Some(pattern), Some(format)) = (&config.filename_timestamp_regex, &config.filename_timestamp_format) else {
+            return Ok(None);
+        };
+        let name_regex = Regex::new(pattern)
+            .map_err(|e| LogLineError::regex("filename timestamp regex", pattern, e))?;
+        let offset_regex = match &config.offset_regex {
+            Some(pattern) => Some(Regex::new(pattern).map_err(|e| LogLineError::regex("offset regex", pattern, e))?),
+            None => None,
+        };
+        
+        Ok(Some(FilenameTimestamps {
+            name_regex,
+            name_format: format.clone(),
+            offset_regex,
+            base: None,
+            offset: Duration::zero(),
+        }))
+    }
+    
+    /// Read the base time from the name of the file about to be parsed
+    fn start_file(&mut self, path: &Path) -> Result<()> {
+        let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        let text = self
+            .name_regex
+            .captures(&name)
+            .and_then(|captures| captures.name("timestamp").or_else(|| captures.get(1)));
+        self.base = match text {
+            Some(text) => Some(parse_base_time(text.as_str(), &self.name_format).map_err(|e| {
+                LogLineError::TimestampParse {
+                    text: text.as_str().to_string(),
+                    format: self.name_format.clone(),
+                    source: e,
+                }
+            })?),
+            None => None,
+        };
+        Ok(())
+    }
+    
+    /// The base time plus this line's offset marker, or the latest one before it
+    fn timestamp(&mut self, line: &str) -> Option<NaiveDateTime> {
+        let base = self.base?;
+        let marker = self
+            .offset_regex
+            .as_ref()
+            .and_then(|regex| regex.captures(line))
+            .and_then(|captures| captures.name("offset").or_else(|| captures.get(1)));
+        if let Some(offset) = marker.and_then(|marker| parse_offset(marker.as_str())) {
+            self.offset = offset;
+        }
+        Some(base + self.offset)
+    }
+}
+
+/// An event found within a line: its label, timestamp, and captured fields
+type EmbeddedEvent = (String, ParsedTimestamp, BTreeMap<String, String>);
+
+/// Events embedded in a line with timestamps of their own (`embedded_event_regex`), e.g.
+/// `queued=10:00:00.100 started=10:00:00.350 done=10:00:01.200`
+#[derive(Clone)]
+struct EmbeddedEvents {
+    regex: Regex,
+    format: String,
+    /// Named groups other than `label` and `timestamp`, captured as fields of each event
+    field_names: Vec<String>,
+}
+
+impl EmbeddedEvents {
+    fn new(config: &Config) -> Result<Option<Self>> {
+        let Some(pattern) = &config.embedded_event_regex else {
+            return Ok(None);
+        };
+        let regex = Regex::new(pattern).map_err(|e| LogLineError::regex("embedded event regex", pattern, e))?;
+        for group in ["label", "timestamp"] {
+            if !regex.capture_names().any(|name| name == Some(group)) {
+                return Err(LogLineError::config(format!(
+                    "Embedded event regex {} needs a '{}' named group",
+                    pattern, group
+                )));
+            }
+        }
+        let field_names = field_names(&regex, &["label", "timestamp"], "embedded event regex")?;
+        let format = config
+            .embedded_timestamp_format
+            .clone()
+            .unwrap_or_else(|| config.timestamp_format.clone());
+        
+        Ok(Some(EmbeddedEvents {
+            regex,
+            format,
+            field_names,
+        }))
+    }
+    
+    /// Every event in the line, in order: its label, parsed timestamp, and fields
+    fn find(&self, line: &str) -> Result<Vec<EmbeddedEvent>> {
+        let mut events = Vec::new();
+        for captures in self.regex.captures_iter(line) {
+            let (Some(label), Some(text)) = (captures.name("label"), captures.name("timestamp")) else {
+                continue;
+            };
+            let parsed = parse_timestamp(text.as_str(), &self.format).map_err(|e| LogLineError::TimestampParse {
+                text: text.as_str().to_string(),
+                format: self.format.clone(),
+                source: e,
+            })?;
+            let fields = self
+                .field_names
+                .iter()
+                .filter_map(|name| Some((name.clone(), captures.name(name)?.as_str().to_string())))
+                .collect();
+            events.push((label.as_str().to_string(), parsed, fields));
+        }
+        Ok(events)
+    }
+}
+
+/// Multiline mode: continuation lines are joined onto the entry before them, which is parsed
+/// as one line once the next entry starts or the input ends
+#[derive(Clone)]
+struct Multiline {
+    /// Lines matching it continue the entry; `None` for every line without a timestamp
+    continuation: Option<Regex>,
+    /// The entry read so far and the number of its first line
+    pending: Option<(String, usize)>,
+}
+
+/// A compiled message pattern
+#[derive(Clone)]
+struct PatternMatcher {
+    /// What matches are called: the pattern's name, or its regex
+    name: String,
+    pattern: String,
+    regex: Regex,
+    /// The regex has a `label` named group whose captured text names the match
+    has_label: bool,
+    /// The regex has a `correlation` named group that captures the match's correlation key
+    has_correlation: bool,
+    /// Other named groups, captured as fields of the match
+    field_names: Vec<String>,
+    profile: RegexProfile,
 }
 
+/// Reads log lines and returns those matching a message pattern, with their timestamps
+#[derive(Clone)]
 pub struct LogParser {
     timestamp_regex: Option<Regex>,
     timestamp_format: Option<String>,
-    pattern_regexes: Vec<(usize, String, Regex)>,
+    /// Named groups of the timestamp regex other than `timestamp`, attached to every match
+    timestamp_field_names: Vec<String>,
+    pattern_regexes: Vec<PatternMatcher>,
+    /// All message patterns in one automaton, to find the first match in a single pass;
+    /// `None` when the set is too large to build, and patterns are then tried one by one
+    pattern_set: Option<RegexSet>,
+    exclude_regexes: Vec<(Regex, RegexProfile)>,
+    /// Field paths for JSON lines; `None` for plain text input
+    json_input: Option<JsonInput>,
+    /// Extracts the correlation key from matched lines whose pattern has no `correlation` group
+    correlation_regex: Option<Regex>,
+    /// Fallback for lines without a timestamp, when configured
+    filename_timestamps: Option<FilenameTimestamps>,
+    /// Events with their own timestamps inside a line, when configured
+    embedded_events: Option<EmbeddedEvents>,
+    /// Joins continuation lines into entries, when configured
+    multiline: Option<Multiline>,
+    /// Record per-regex match times and hit counts
+    profile_patterns: bool,
+    /// Record the line number of every match, and with `keep_raw_lines` the line itself
+    record_line_numbers: bool,
+    keep_raw_lines: bool,
+    /// Lines read from the current input so far
+    line_number: usize,
+    max_line_length: Option<usize>,
+    anchor_timestamp: bool,
+    strip_ansi: bool,
     builtin_formats: Vec<(Regex, TimestampFormat)>,
+    /// Number of lines parsed by each built-in format (same order as `builtin_formats`)
+    format_hits: Vec<usize>,
+    /// Order in which built-in formats are tried, best-ranked first (empty until ranked);
+    /// time-of-day formats are left out when a format with a date ranked first
+    format_order: Vec<usize>,
+    /// Built-in format forced to the front of the ranking (`--prefer-format`)
+    preferred_format: Option<usize>,
+    /// Sample scores per built-in format: (lines parsed, total timestamp length)
+    format_scores: Vec<(usize, usize)>,
+    /// Number of lines in the ranking sample
+    sample_lines: usize,
+    /// Try every built-in format on every line instead of locking onto one
+    mixed_formats: bool,
+    /// Built-in format parsing the latest lines, and how many in a row it parsed
+    format_streak: (usize, usize),
+    /// Built-in format the current input locked onto; the others are no longer tried
+    locked_format: Option<usize>,
+    /// Sample lines that contained something timestamp-like
+    sample_candidates: usize,
     is_auto_detect: bool,
+    stats: ParseStats,
+    /// Set from a signal handler to stop reading early (see `set_stop_flag`)
+    stop_flag: Option<Arc<AtomicBool>>,
+    /// Lines, bytes, and matches counted for a progress reporter (see `set_progress`)
+    progress: Option<Arc<Progress>>,
+    /// Last timestamp resolved from a time-only format, used to infer day rollovers
+    last_time_of_day: Option<NaiveDateTime>,
+    /// Smallest chunk worth a thread of its own when parsing one large file
+    min_chunk_bytes: u64,
+    /// Threads for parsing the chunks of one large file (see `set_threads`); `None` for one
+    /// per available core
+    threads: Option<usize>,
+    /// Memory-map plain log files instead of reading them through a buffer
+    memory_mapped: bool,
+    /// Only matches from the first time up to (not including) the second are kept
+    time_window: (Option<NaiveDateTime>, Option<NaiveDateTime>),
 }
 
 impl LogParser {
@@ -30,116 +468,2187 @@ impl LogParser {
             let mut compiled_formats = Vec::new();
             
             for format in formats {
-                let regex = Regex::new(format.regex)
-                    .with_context(|| format!("Failed to compile built-in regex for format: {}", format.name))?;
+                let regex = Regex::new(format.regex).map_err(|e| {
+                    LogLineError::regex(format!("built-in regex for format {}", format.name), format.regex, e)
+                })?;
                 compiled_formats.push((regex, format));
             }
             
             (None, None, compiled_formats)
+        } else if config.timestamp_regex.is_empty() {
+            // Lines carry no timestamps; they all come from the file names
+            (None, None, Vec::new())
         } else {
             let timestamp_regex = Regex::new(&config.timestamp_regex)
-                .context("Invalid timestamp regex")?;
+                .map_err(|e| LogLineError::regex("timestamp regex", &config.timestamp_regex, e))?;
             
             (Some(timestamp_regex), Some(config.timestamp_format.clone()), Vec::new())
         };
         
+        let preferred_format = match &config.prefer_format {
+            Some(name) if config.is_auto_detect => {
+                let preferred = find_builtin_format(name)
+                    .map_err(|e| LogLineError::config(e.to_string()))?;
+                builtin_formats.iter().position(|(_, format)| format.name == preferred.name)
+            }
+            _ => None,
+        };
+        
+        let timestamp_field_names = match &timestamp_regex {
+            Some(regex) => field_names(regex, &["timestamp"], "timestamp regex")?,
+            None => Vec::new(),
+        };
+        
         let mut pattern_regexes = Vec::new();
-        for (idx, pattern) in config.message_patterns.iter().enumerate() {
+        for message_pattern in &config.message_patterns {
+            let pattern = &message_pattern.regex;
+            let regex = Regex::new(pattern)
+                .map_err(|e| LogLineError::regex("message pattern regex", pattern, e))?;
+            let has_label = regex.capture_names().any(|name| name == Some("label"));
+            let has_correlation = regex.capture_names().any(|name| name == Some("correlation"));
+            let field_names = field_names(&regex, &["label", "correlation"], &format!("message pattern {}", pattern))?;
+            pattern_regexes.push(PatternMatcher {
+                name: message_pattern.label().to_string(),
+                pattern: pattern.clone(),
+                regex,
+                has_label,
+                has_correlation,
+                field_names,
+                profile: RegexProfile::default(),
+            });
+        }
+        
+        let pattern_set = RegexSet::new(config.message_patterns.iter().map(|p| &p.regex)).ok();
+        
+        let mut exclude_regexes = Vec::new();
+        for pattern in &config.exclude_patterns {
             let regex = Regex::new(pattern)
-                .with_context(|| format!("Invalid message pattern regex: {}", pattern))?;
-            pattern_regexes.push((idx, pattern.clone(), regex));
+                .map_err(|e| LogLineError::regex("exclude pattern regex", pattern, e))?;
+            exclude_regexes.push((regex, RegexProfile::default()));
         }
         
+        let correlation_regex = match &config.correlation_regex {
+            Some(pattern) => {
+                let regex = Regex::new(pattern)
+                    .map_err(|e| LogLineError::regex("correlation regex", pattern, e))?;
+                if regex.captures_len() < 2 {
+                    return Err(LogLineError::config(format!(
+                        "Correlation regex {} needs a capture group for the correlation key",
+                        pattern
+                    )));
+                }
+                Some(regex)
+            }
+            None => None,
+        };
+        
+        let multiline = match &config.continuation_regex {
+            Some(pattern) => Some(Multiline {
+                continuation: Some(
+                    Regex::new(pattern).map_err(|e| LogLineError::regex("continuation regex", pattern, e))?,
+                ),
+                pending: None,
+            }),
+            None if config.multiline => Some(Multiline { continuation: None, pending: None }),
+            None => None,
+        };
+        
         Ok(LogParser {
             timestamp_regex,
             timestamp_format,
+            timestamp_field_names,
             pattern_regexes,
+            pattern_set,
+            exclude_regexes,
+            correlation_regex,
+            filename_timestamps: FilenameTimestamps::new(config)?,
+            embedded_events: EmbeddedEvents::new(config)?,
+            multiline,
+            json_input: JsonInput::new(config),
+            profile_patterns: false,
+            record_line_numbers: false,
+            keep_raw_lines: false,
+            line_number: 0,
+            max_line_length: config.max_line_length,
+            anchor_timestamp: config.anchor_timestamp,
+            strip_ansi: config.strip_ansi,
+            format_hits: vec![0; builtin_formats.len()],
+            format_order: Vec::new(),
+            preferred_format,
+            format_scores: vec![(0, 0); builtin_formats.len()],
+            sample_lines: 0,
+            sample_candidates: 0,
+            mixed_formats: config.mixed_timestamp_formats,
+            format_streak: (0, 0),
+            locked_format: None,
             builtin_formats,
             is_auto_detect: config.is_auto_detect,
+            stats: ParseStats::default(),
+            stop_flag: None,
+            progress: None,
+            last_time_of_day: None,
+            min_chunk_bytes: MIN_CHUNK_BYTES,
+            threads: None,
+            memory_mapped: false,
+            time_window: (None, None),
         })
     }
     
-    /// Parse a log file and return all matches in order
-    pub fn parse_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<LogMatch>> {
-        let file = File::open(path.as_ref())
-            .with_context(|| format!("Failed to open log file: {:?}", path.as_ref()))?;
+    /// Stop reading at the next line once `flag` is set (e.g. by a Ctrl-C handler); the
+    /// parse then returns the matches found so far and marks the stats as interrupted
+    pub fn set_stop_flag(&mut self, flag: Arc<AtomicBool>) {
+        self.stop_flag = Some(flag);
+    }
+    
+    /// Count every line read and every file started in `progress` as well, so another thread
+    /// can report how far the parse has come
+    pub fn set_progress(&mut self, progress: Arc<Progress>) {
+        self.progress = Some(progress);
+    }
+    
+    /// Parse the chunks of a large file on at most `threads` threads; 1 reads every file
+    /// sequentially
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = Some(threads.max(1));
+    }
+    
+    /// Read plain log files through a memory map (see `open_log_file_mapped`) when they are
+    /// read sequentially
+    pub fn set_memory_mapped(&mut self, memory_mapped: bool) {
+        self.memory_mapped = memory_mapped;
+    }
+    
+    /// Drop matches timestamped before `since` or at or after `until`, counting them in the
+    /// stats, so only that part of a long log is analyzed
+    pub fn set_time_window(&mut self, since: Option<NaiveDateTime>, until: Option<NaiveDateTime>) {
+        self.time_window = (since, until);
+    }
+    
+    /// True when some match can carry the named capture `name`: a message pattern, the
+    /// timestamp regex, or the embedded event regex has a group of that name
+    pub fn captures_field(&self, name: &str) -> bool {
+        let name = name.to_string();
+        self.pattern_regexes.iter().any(|matcher| matcher.field_names.contains(&name))
+            || self.timestamp_field_names.contains(&name)
+            || self.embedded_events.as_ref().is_some_and(|events| events.field_names.contains(&name))
+    }
+    
+    /// Whether the stop flag was raised, recording the interruption in the stats
+    pub(crate) fn should_stop(&mut self) -> bool {
+        if self.stop_flag.as_ref().is_some_and(|flag| flag.load(Ordering::SeqCst)) {
+            self.stats.interrupted = true;
+        }
+        self.stats.interrupted
+    }
+    
+    /// Record the line number of every match within its file or stream, and with `raw_lines`
+    /// also keep the matched line itself
+    pub fn record_source_lines(&mut self, raw_lines: bool) {
+        self.record_line_numbers = true;
+        self.keep_raw_lines = raw_lines;
+    }
+    
+    /// Switch to the message patterns, exclude patterns, correlation regex, and line filters of
+    /// `config`, keeping the timestamp and multiline settings, the position in the current
+    /// input, and the stats
+    pub fn reload(&mut self, config: &Config) -> Result<()> {
+        let fresh = LogParser::new(config)?;
+        self.pattern_regexes = fresh.pattern_regexes;
+        self.pattern_set = fresh.pattern_set;
+        self.exclude_regexes = fresh.exclude_regexes;
+        self.correlation_regex = fresh.correlation_regex;
+        self.embedded_events = fresh.embedded_events;
+        self.max_line_length = fresh.max_line_length;
+        self.anchor_timestamp = fresh.anchor_timestamp;
+        self.strip_ansi = fresh.strip_ansi;
+        Ok(())
+    }
+    
+    /// Measure how long each message and exclude pattern takes to match (slightly slows parsing)
+    pub fn enable_pattern_profiling(&mut self) {
+        self.profile_patterns = true;
+    }
+    
+    /// Report of regex match time per pattern, slowest first; `None` unless profiling is enabled
+    pub fn format_pattern_profile(&self) -> Option<String> {
+        if !self.profile_patterns {
+            return None;
+        }
+        
+        let mut rows: Vec<(&str, &str, &RegexProfile)> = self
+            .pattern_regexes
+            .iter()
+            .map(|m| ("pattern", m.pattern.as_str(), &m.profile))
+            .chain(self.exclude_regexes.iter().map(|(regex, profile)| ("exclude", regex.as_str(), profile)))
+            .collect();
+        rows.sort_by_key(|(_, _, profile)| std::cmp::Reverse(profile.elapsed));
+        
+        let total: std::time::Duration = rows.iter().map(|(_, _, profile)| profile.elapsed).sum();
+        let mut output = format!("Pattern profile (total regex time {:.1?}):\n", total);
+        output.push_str(&format!(
+            "  {:>10} {:>6} {:>10} {:>10} {:>9}  pattern\n",
+            "time", "share", "tried", "hits", "avg/try"
+        ));
+        for (kind, pattern, profile) in rows {
+            let share = if total.is_zero() {
+                0.0
+            } else {
+                profile.elapsed.as_secs_f64() / total.as_secs_f64() * 100.0
+            };
+            let average = profile.elapsed.checked_div(profile.tried as u32).unwrap_or_default();
+            output.push_str(&format!(
+                "  {:>10} {:>5.1}% {:>10} {:>10} {:>9}  {}{}\n",
+                format!("{:.1?}", profile.elapsed),
+                share,
+                profile.tried,
+                profile.hits,
+                format!("{:.0?}", average),
+                if kind == "exclude" { "(exclude) " } else { "" },
+                pattern
+            ));
+        }
+        output.push_str("Patterns are tried in order until one matches, so later patterns are tried on fewer lines");
+        
+        Some(output)
+    }
+    
+    /// Line and skip-reason counters accumulated by this parser so far
+    pub fn stats(&self) -> &ParseStats {
+        &self.stats
+    }
+    
+    /// The built-in format that parsed the most lines so far in auto-detection mode
+    pub fn detected_format(&self) -> Option<&TimestampFormat> {
+        self.builtin_formats
+            .iter()
+            .zip(&self.format_hits)
+            .filter(|(_, &hits)| hits > 0)
+            // max_by_key returns the last maximum; reverse so earlier formats win ties
+            .rev()
+            .max_by_key(|(_, &hits)| hits)
+            .map(|((_, format), _)| format)
+    }
+    
+    /// Confidence report of the built-in formats ranked on the sample, best first.
+    ///
+    /// `None` outside auto-detection mode or before any input was read.
+    pub fn format_detection_report(&self) -> Option<String> {
+        if self.format_order.is_empty() {
+            return None;
+        }
+        
+        let mut output = format!(
+            "Timestamp format candidates ({} sampled lines, {} with a timestamp):\n",
+            self.sample_lines, self.sample_candidates
+        );
+        for (rank, &idx) in self.format_order.iter().enumerate() {
+            let (hits, _) = self.format_scores[idx];
+            if hits == 0 {
+                continue;
+            }
+            let confidence = hits as f64 / self.sample_candidates.max(1) as f64 * 100.0;
+            let note = match (rank, Some(idx) == self.preferred_format) {
+                (0, true) => "  <- chosen (--prefer-format)",
+                (0, false) => "  <- chosen",
+                _ => "",
+            };
+            output.push_str(&format!(
+                "  {:>5.1}%  {}{}\n",
+                confidence, self.builtin_formats[idx].1.name, note
+            ));
+        }
+        if let Some(idx) = self.locked_format {
+            output.push_str(&format!(
+                "Locked onto {} after {} consecutive lines (--mixed-timestamp-formats keeps trying all formats)\n",
+                self.builtin_formats[idx].1.name, FORMAT_LOCK_LINES
+            ));
+        }
+        
+        Some(output.trim_end().to_string())
+    }
+    
+    /// Every built-in format whose regex finds a timestamp on `line`, in the built-in order,
+    /// with what the format parses it to; empty outside auto-detection mode
+    pub fn explain_line(&self, line: &str) -> Vec<FormatCandidate> {
+        let Some(line) = self.timestamp_text(line) else {
+            return Vec::new();
+        };
+        let line = if self.anchor_timestamp {
+            &line[leading_noise_len(&line)..]
+        } else {
+            &line[..]
+        };
+        
+        self.builtin_formats
+            .iter()
+            .filter_map(|(regex, format)| {
+                let text = regex
+                    .captures(line)
+                    .filter(|captures| !self.anchor_timestamp || captures.get(0).is_some_and(|m| m.start() == 0))?
+                    .get(1)?
+                    .as_str();
+                let parsed = match parse_timestamp(text, format.format) {
+                    Ok(ParsedTimestamp::DateTime(time)) => Ok(time.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+                    Ok(ParsedTimestamp::TimeOfDay(time)) => Ok(time.format("%H:%M:%S%.f").to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                Some(FormatCandidate {
+                    format: format.name,
+                    text: text.to_string(),
+                    parsed,
+                })
+            })
+            .collect()
+    }
+    
+    /// Rank the built-in formats by how consistently they parse the sample.
+    ///
+    /// Formats that parse more lines come first; among equals, the one capturing
+    /// longer timestamps (e.g. with milliseconds or a timezone) wins, then the
+    /// built-in order. A `--prefer-format` choice always goes first.
+    pub(crate) fn rank_formats(&mut self, sample: &[String]) {
+        self.sample_lines = sample.len();
+        for line in sample {
+            let Some(line) = self.timestamp_text(line) else {
+                continue;
+            };
+            let line = if self.anchor_timestamp {
+                &line[leading_noise_len(&line)..]
+            } else {
+                &line[..]
+            };
+            
+            let mut candidate_found = false;
+            let mut dated = false;
+            for (idx, (regex, format)) in self.builtin_formats.iter().enumerate() {
+                // The time of day of a dated line is part of its timestamp, not a format of its
+                // own; the time-of-day format comes last, so it only counts the lines no format
+                // with a date parsed
+                let time_only = is_time_only_format(format.format);
+                if time_only && dated {
+                    continue;
+                }
+                let Some(ts_str) = regex
+                    .captures(line)
+                    .filter(|captures| !self.anchor_timestamp || captures.get(0).is_some_and(|m| m.start() == 0))
+                    .and_then(|captures| captures.get(1))
+                else {
+                    continue;
+                };
+                candidate_found = true;
+                if parse_timestamp(ts_str.as_str(), format.format).is_ok() {
+                    dated |= !time_only;
+                    self.format_scores[idx].0 += 1;
+                    self.format_scores[idx].1 += ts_str.len();
+                }
+            }
+            if candidate_found {
+                self.sample_candidates += 1;
+            }
+        }
+        
+        let scores = &self.format_scores;
+        let preferred = self.preferred_format;
+        let mut order: Vec<usize> = (0..self.builtin_formats.len()).collect();
+        // Stable sort keeps the built-in order among equal scores
+        order.sort_by_key(|&idx| (Some(idx) != preferred, std::cmp::Reverse(scores[idx])));
+        // A bare time of day is only trusted as the detected format: behind a dated one, it
+        // would take a time from the message of a line the dated format doesn't match
+        let is_time_only = |idx: usize| is_time_only_format(self.builtin_formats[idx].1.format);
+        if order.first().is_some_and(|&idx| !is_time_only(idx)) {
+            order.retain(|&idx| !is_time_only(idx));
+        }
+        self.format_order = order;
+    }
+    
+    /// Parse a log file (plain or gzip/zstd/bzip2-compressed) and return all matches in order
+    ///
+    /// Large uncompressed files are split into line-aligned chunks that are parsed in parallel
+    /// and stitched back together in order, with the same result as reading them sequentially.
+    pub fn parse_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<LogMatch>> {
+        let path = path.as_ref();
+        self.start_file(path)?;
+        let chunks = self.chunk_count(path)?;
+        if chunks > 1 {
+            return self.parse_file_chunked(path, chunks);
+        }
         
-        let reader = BufReader::new(file);
+        let reader = self.open_file(path)?;
         self.parse_reader(reader)
     }
     
-    /// Parse log data from any reader (file, stdin, etc.) and return all matches in order
-    pub fn parse_reader<R: BufRead>(&self, reader: R) -> Result<Vec<LogMatch>> {
+    /// Like `matches`, for a log file (plain or compressed)
+    pub fn file_matches<P: AsRef<Path>>(&mut self, path: P) -> Result<Matches<'_, Box<dyn BufRead>>> {
+        let path = path.as_ref();
+        self.start_file(path)?;
+        let reader = self.open_file(path)?;
+        self.matches(reader)
+    }
+    
+    fn open_file(&self, path: &Path) -> Result<Box<dyn BufRead>> {
+        if self.memory_mapped {
+            open_log_file_mapped(path)
+        } else {
+            open_log_file(path)
+        }
+    }
+    
+    /// How many chunks to split a file into: one per thread (by default one per available
+    /// core), but none smaller than `min_chunk_bytes`, and only for plain files that can be
+    /// read from any offset
+    fn chunk_count(&self, path: &Path) -> Result<usize> {
+        if self.threads == Some(1) {
+            return Ok(1);
+        }
+        
+        let io_error = |e| LogLineError::io(format!("Failed to open log file: {:?}", path), e);
+        let mut file = File::open(path).map_err(io_error)?;
+        let len = file.metadata().map_err(io_error)?.len();
+        if len < self.min_chunk_bytes.saturating_mul(2) {
+            return Ok(1);
+        }
+        
+        let mut header = [0u8; 4];
+        let read = file.read(&mut header).map_err(io_error)?;
+        if Compression::detect(&header[..read]) != Compression::None {
+            return Ok(1);
+        }
+        
+        // Honours the CPU affinity mask and, on Linux, a cgroup CPU quota, so a container
+        // limited to two CPUs parses in two chunks however many cores the host has
+        let threads = self
+            .threads
+            .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1));
+        Ok(threads.min((len / self.min_chunk_bytes.max(1)) as usize).max(1))
+    }
+    
+    /// Parse byte ranges of one file on a rayon thread pool, then merge matches and counters
+    fn parse_file_chunked(&mut self, path: &Path, chunks: usize) -> Result<Vec<LogMatch>> {
+        let io_error = |e| LogLineError::io(format!("Failed to read log file: {:?}", path), e);
+        
+        // Rank on the leading lines first, exactly as a sequential parse would
+        if self.needs_detection_sample() {
+            let file = File::open(path).map_err(io_error)?;
+            let sample = BufReader::new(file)
+                .lines()
+                .take(DETECTION_SAMPLE_LINES)
+                .collect::<std::io::Result<Vec<String>>>()
+                .map_err(io_error)?;
+            self.rank_formats(&sample);
+        }
+        
+        // Day rollovers of time-only timestamps, offsets carried over to lines without a
+        // marker, and multiline entries depend on the lines before them
+        if self.uses_time_of_day() || self.filename_timestamps.is_some() || self.multiline.is_some() {
+            let reader = open_log_file(path)?;
+            return self.parse_reader(reader);
+        }
+        
+        let bounds = chunk_bounds(path, chunks).map_err(io_error)?;
+        let workers: Vec<(u64, u64, LogParser)> =
+            bounds.windows(2).map(|range| (range[0], range[1], self.chunk_worker())).collect();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads.unwrap_or(0).min(chunks))
+            .build()
+            .map_err(|e| LogLineError::config_caused_by("Failed to start parser threads", e))?;
+        // Collecting an indexed parallel iterator keeps the chunks in file order
+        let results: Vec<Result<(Vec<LogMatch>, LogParser)>> = pool.install(|| {
+            workers
+                .into_par_iter()
+                .map(|(start, end, mut worker)| {
+                    let mut file = File::open(path).map_err(io_error)?;
+                    file.seek(SeekFrom::Start(start)).map_err(io_error)?;
+                    let matches = worker.parse_reader(BufReader::new(file.take(end - start)))?;
+                    Ok((matches, worker))
+                })
+                .collect()
+        });
+        
         let mut matches = Vec::new();
+        let lines_before_file = self.stats.lines_read;
+        for result in results {
+            let (chunk_matches, worker) = result?;
+            // Each worker numbers the lines of its own chunk from 1
+            let lines_before = self.stats.lines_read - lines_before_file;
+            matches.extend(chunk_matches.into_iter().map(|m| LogMatch {
+                line_number: m.line_number.map(|n| n + lines_before),
+                ..m
+            }));
+            self.absorb_worker(&worker);
+        }
         
-        for line in reader.lines() {
-            let line = line.context("Failed to read line from log")?;
-            
-            if let Some(log_match) = self.parse_line(&line)? {
-                matches.push(log_match);
+        Ok(matches)
+    }
+    
+    /// Whether timestamps are (or are most likely) times of day without a date
+    fn uses_time_of_day(&self) -> bool {
+        if self.embedded_events.as_ref().is_some_and(|embedded| is_time_only_format(&embedded.format)) {
+            return true;
+        }
+        match &self.timestamp_format {
+            Some(format) => is_time_only_format(format),
+            None => self
+                .format_order
+                .first()
+                .is_some_and(|&idx| is_time_only_format(self.builtin_formats[idx].1.format)),
+        }
+    }
+    
+    /// A copy of this parser with zeroed counters, for parsing one chunk
+    fn chunk_worker(&self) -> LogParser {
+        let mut worker = self.clone();
+        worker.stats = ParseStats::default();
+        worker.format_hits.iter_mut().for_each(|hits| *hits = 0);
+        for matcher in &mut worker.pattern_regexes {
+            matcher.profile = RegexProfile::default();
+        }
+        for (_, profile) in &mut worker.exclude_regexes {
+            *profile = RegexProfile::default();
+        }
+        worker
+    }
+    
+    /// Add a chunk worker's counters to this parser's
+    fn absorb_worker(&mut self, worker: &LogParser) {
+        self.stats.merge(&worker.stats);
+        self.locked_format = self.locked_format.or(worker.locked_format);
+        for (hits, worker_hits) in self.format_hits.iter_mut().zip(&worker.format_hits) {
+            *hits += worker_hits;
+        }
+        for (matcher, worker_matcher) in self.pattern_regexes.iter_mut().zip(&worker.pattern_regexes) {
+            matcher.profile.merge(&worker_matcher.profile);
+        }
+        for ((_, profile), (_, worker_profile)) in self.exclude_regexes.iter_mut().zip(&worker.exclude_regexes) {
+            profile.merge(worker_profile);
+        }
+    }
+    
+    /// Parse several log files, tag every match with its file, and merge them in timestamp order
+    pub fn parse_files<P: AsRef<Path>>(&mut self, paths: &[P]) -> Result<Vec<LogMatch>> {
+        let mut matches = Vec::new();
+        
+        for path in paths {
+            if self.should_stop() {
+                break;
             }
+            let source = path.as_ref().display().to_string();
+            let file_matches = self.parse_file(path)?;
+            matches.extend(file_matches.into_iter().map(|m| LogMatch {
+                source: Some(source.clone()),
+                ..m
+            }));
         }
         
+        // Stable sort keeps each file's own ordering for identical timestamps
+        matches.sort_by_key(|m| m.timestamp);
+        
         Ok(matches)
     }
     
-    /// Parse a single log line and return a match if found
-    fn parse_line(&self, line: &str) -> Result<Option<LogMatch>> {
-        // First, extract the timestamp
-        let timestamp = match self.extract_timestamp(line)? {
-            Some(ts) => ts,
-            None => return Ok(None),
-        };
+    /// Parse log data from any reader (file, stdin, etc.) and return all matches in order
+    pub fn parse_reader<R: BufRead>(&mut self, reader: R) -> Result<Vec<LogMatch>> {
+        self.matches(reader)?.collect()
+    }
+    
+    /// Find the matches of a reader one at a time, reading only as far as needed for the next
+    /// one, so a log of any size is parsed in constant memory. When auto-detecting, the
+    /// leading lines are read up front to rank the formats.
+    pub fn matches<R: BufRead>(&mut self, reader: R) -> Result<Matches<'_, R>> {
+        self.start_input();
         
-        // Check each pattern to see if it matches
-        for (_idx, pattern, regex) in &self.pattern_regexes {
-            if regex.is_match(line) {
-                return Ok(Some(LogMatch {
-                    pattern: pattern.clone(),
-                    timestamp,
-                }));
+        let mut lines = reader.lines();
+        let mut sample = Vec::new();
+        if self.needs_detection_sample() {
+            for line in lines.by_ref().take(DETECTION_SAMPLE_LINES) {
+                if self.should_stop() {
+                    break;
+                }
+                sample.push(line.map_err(|e| LogLineError::io("Failed to read line from log", e))?);
             }
+            self.rank_formats(&sample);
         }
         
-        Ok(None)
+        Ok(Matches {
+            parser: self,
+            sample: sample.into_iter(),
+            lines,
+            found: Vec::new().into_iter(),
+            finished: false,
+        })
     }
     
-    /// Extract timestamp from a log line
-    fn extract_timestamp(&self, line: &str) -> Result<Option<NaiveDateTime>> {
-        if self.is_auto_detect {
-            // Try each built-in format until one works
-            for (regex, format) in &self.builtin_formats {
-                if let Some(captures) = regex.captures(line) {
-                    if let Some(ts_str) = captures.get(1) {
-                        // Try to parse with this format
-                        if let Ok(timestamp) = NaiveDateTime::parse_from_str(
-                            ts_str.as_str(),
-                            format.format,
-                        ) {
-                            return Ok(Some(timestamp));
+    /// Keep reading lines as they are appended and report each match as soon as it is found.
+    ///
+    /// With `poll`, reaching the end of the input waits that long and tries again, like
+    /// `tail -f` on a file; without it (a pipe or stdin) the end of the input ends the call.
+    /// When auto-detecting, formats are ranked on the lines written so far the first time
+    /// the reader catches up; a stream is ranked as soon as a line with a timestamp arrives,
+    /// since the lines before it cannot match anyway.
+    pub fn follow<R: BufRead>(
+        &mut self,
+        reader: R,
+        poll: Option<std::time::Duration>,
+        mut on_match: impl FnMut(LogMatch),
+    ) -> Result<()> {
+        self.follow_reloading(reader, poll, None, |event| {
+            if let FollowEvent::Match(log_match) = event {
+                on_match(log_match);
+            }
+        })
+    }
+    
+    /// Like `follow`, also switching to the latest configuration received on `updates` (see
+    /// `reload`) between two lines, so a long-running tail picks up edited patterns in place
+    pub fn follow_reloading<R: BufRead>(
+        &mut self,
+        mut reader: R,
+        poll: Option<std::time::Duration>,
+        updates: Option<&Receiver<Config>>,
+        mut on_event: impl FnMut(FollowEvent),
+    ) -> Result<()> {
+        self.start_input();
+        let mut sample = self.needs_detection_sample().then(Vec::new);
+        let mut pending = String::new();
+        let mut found = Vec::new();
+        
+        loop {
+            if self.should_stop() {
+                return Ok(());
+            }
+            if let Some(config) = updates.and_then(|updates| updates.try_iter().last()) {
+                self.reload(&config)?;
+                on_event(FollowEvent::Reloaded(Box::new(config)));
+            }
+            let read = reader
+                .read_line(&mut pending)
+                .map_err(|e| LogLineError::io("Failed to read line from log", e))?;
+            let finished = read == 0 && poll.is_none();
+            
+            if (read > 0 && pending.ends_with('\n')) || (finished && !pending.is_empty()) {
+                let line = pending.trim_end_matches(['\n', '\r']).to_string();
+                pending.clear();
+                
+                match sample.as_mut() {
+                    Some(lines) => {
+                        let has_candidate = poll.is_none() && self.has_timestamp_candidate(&line);
+                        lines.push(line);
+                        if has_candidate || lines.len() >= DETECTION_SAMPLE_LINES {
+                            let lines = sample.take().unwrap_or_default();
+                            self.feed_sample(lines, &mut |m| on_event(FollowEvent::Match(m)))?;
                         }
                     }
+                    None => {
+                        self.feed_line(&line, &mut found)?;
+                        found.drain(..).for_each(|m| on_event(FollowEvent::Match(m)));
+                    }
                 }
+                continue;
             }
-            Ok(None)
-        } else {
-            // Use the configured format
-            let timestamp_regex = self.timestamp_regex.as_ref().unwrap();
-            let timestamp_format = self.timestamp_format.as_ref().unwrap();
             
-            if let Some(captures) = timestamp_regex.captures(line) {
-                if let Some(ts_str) = captures.get(1) {
-                    let timestamp = NaiveDateTime::parse_from_str(
-                        ts_str.as_str(),
-                        timestamp_format,
-                    )
-                    .with_context(|| format!("Failed to parse timestamp: {}", ts_str.as_str()))?;
-                    
-                    return Ok(Some(timestamp));
-                }
+            if read > 0 {
+                // Partial line: the writer hasn't finished it yet
+                continue;
             }
             
-            Ok(None)
+            // Caught up with the writer; an entry it wrote in one go is complete by now
+            if let Some(lines) = sample.take() {
+                self.feed_sample(lines, &mut |m| on_event(FollowEvent::Match(m)))?;
+            }
+            self.finish_input(&mut found)?;
+            found.drain(..).for_each(|m| on_event(FollowEvent::Match(m)));
+            match poll {
+                Some(interval) => std::thread::sleep(interval),
+                None => return Ok(()),
+            }
+        }
+    }
+    
+    /// Rank the formats on buffered lines, then parse them
+    pub(crate) fn feed_sample(&mut self, lines: Vec<String>, on_match: &mut impl FnMut(LogMatch)) -> Result<()> {
+        self.rank_formats(&lines);
+        let mut found = Vec::new();
+        for line in &lines {
+            self.feed_line(line, &mut found)?;
+            found.drain(..).for_each(&mut *on_match);
+        }
+        Ok(())
+    }
+    
+    /// Whether any built-in format finds a timestamp on this line
+    pub(crate) fn has_timestamp_candidate(&self, line: &str) -> bool {
+        let Some(line) = self.timestamp_text(line) else {
+            return false;
+        };
+        self.builtin_formats.iter().any(|(regex, _)| regex.is_match(&line))
+    }
+    
+    /// The text a timestamp is searched in: the line without ANSI codes (if stripping), or
+    /// the timestamp field of a JSON line
+    fn timestamp_text<'a>(&self, line: &'a str) -> Option<Cow<'a, str>> {
+        let line = if self.strip_ansi {
+            strip_ansi_codes(line)
+        } else {
+            Cow::Borrowed(line)
+        };
+        match &self.json_input {
+            None => Some(line),
+            Some(json) => json.extract(&line).ok()?.0.map(Cow::Owned),
         }
     }
+    
+    /// Reset per-input state before reading a new file or stream
+    pub(crate) fn start_input(&mut self) {
+        // Time-only timestamps of each input start over on the same base day
+        self.last_time_of_day = None;
+        self.format_streak = (0, 0);
+        self.locked_format = None;
+        self.line_number = 0;
+        if let Some(multiline) = &mut self.multiline {
+            multiline.pending = None;
+        }
+        if let Some(filename_timestamps) = &mut self.filename_timestamps {
+            filename_timestamps.offset = Duration::zero();
+        }
+    }
+    
+    /// Take the base time for lines without a timestamp from the name of the file to be read
+    pub(crate) fn start_file(&mut self, path: &Path) -> Result<()> {
+        if let Some(progress) = &self.progress {
+            progress.start_file();
+        }
+        match &mut self.filename_timestamps {
+            Some(filename_timestamps) => filename_timestamps.start_file(path),
+            None => Ok(()),
+        }
+    }
+    
+    /// Whether the built-in formats still need ranking on a sample of leading lines
+    pub(crate) fn needs_detection_sample(&self) -> bool {
+        self.is_auto_detect && self.format_order.is_empty()
+    }
+    
+    /// Parse one line of the current input, adding its matches and updating the statistics.
+    /// In multiline mode the line is held until the entry it starts is complete, so its
+    /// matches are added by a later call or by `finish_input`.
+    pub(crate) fn feed_line(&mut self, line: &str, matches: &mut Vec<LogMatch>) -> Result<()> {
+        self.stats.lines_read += 1;
+        self.line_number += 1;
+        
+        let first = matches.len();
+        if self.multiline.is_some() {
+            let continues = self.continues_entry(line);
+            let multiline = self.multiline.as_mut().expect("checked above");
+            match &mut multiline.pending {
+                Some((entry, _)) if continues => {
+                    entry.push('\n');
+                    entry.push_str(line);
+                    self.stats.continuation_lines += 1;
+                }
+                pending => {
+                    let previous = pending.replace((line.to_string(), self.line_number));
+                    if let Some((entry, line_number)) = previous {
+                        self.parse_entry(&entry, line_number, matches)?;
+                    }
+                }
+            }
+        } else {
+            self.parse_entry(line, self.line_number, matches)?;
+        }
+        if let Some(progress) = &self.progress {
+            progress.record_line(line.len(), matches.len() - first);
+        }
+        
+        Ok(())
+    }
+    
+    /// Parse the entry still held in multiline mode, at the end of an input or when a
+    /// followed log has caught up with its writer
+    pub(crate) fn finish_input(&mut self, matches: &mut Vec<LogMatch>) -> Result<()> {
+        if let Some((entry, line_number)) = self.multiline.as_mut().and_then(|multiline| multiline.pending.take()) {
+            self.parse_entry(&entry, line_number, matches)?;
+        }
+        Ok(())
+    }
+    
+    /// Whether a line continues the entry before it in multiline mode: it matches the
+    /// continuation regex, or without one, has no timestamp (at its start when anchoring)
+    fn continues_entry(&self, line: &str) -> bool {
+        if let Some(continuation) = self.multiline.as_ref().and_then(|multiline| multiline.continuation.as_ref()) {
+            return continuation.is_match(line);
+        }
+        let Some(text) = self.timestamp_text(line) else {
+            return true;
+        };
+        let text = if self.anchor_timestamp { &text[leading_noise_len(&text)..] } else { &text[..] };
+        let starts_timestamp = |regex: &Regex| regex.find(text).is_some_and(|m| !self.anchor_timestamp || m.start() == 0);
+        match &self.timestamp_regex {
+            Some(regex) => !starts_timestamp(regex),
+            None => !self.builtin_formats.iter().any(|(regex, _)| starts_timestamp(regex)),
+        }
+    }
+    
+    /// Parse one entry (a line, or the lines of a multiline entry joined by `\n`) starting on
+    /// line `line_number`
+    fn parse_entry(&mut self, line: &str, line_number: usize, matches: &mut Vec<LogMatch>) -> Result<()> {
+        let first = matches.len();
+        if let Some(text) = self.line_text(line) {
+            if !self.parse_embedded_events(&text, matches)? {
+                matches.extend(self.parse_line(&text)?);
+            }
+        }
+        if self.time_window != (None, None) {
+            let (since, until) = self.time_window;
+            let found = matches.split_off(first);
+            let total = found.len();
+            matches.extend(found.into_iter().filter(|m| {
+                since.is_none_or(|since| m.timestamp >= since) && until.is_none_or(|until| m.timestamp < until)
+            }));
+            self.stats.outside_time_window += total - (matches.len() - first);
+        }
+        for log_match in &mut matches[first..] {
+            self.stats.matches += 1;
+            if self.record_line_numbers {
+                log_match.line_number = Some(line_number);
+            }
+            if self.keep_raw_lines {
+                log_match.line = Some(line.to_string());
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Add a match for each event embedded in the line with its own timestamp; returns
+    /// whether there were any, otherwise the line is matched against the message patterns
+    fn parse_embedded_events(&mut self, line: &str, matches: &mut Vec<LogMatch>) -> Result<bool> {
+        let events = match &self.embedded_events {
+            Some(embedded) => embedded.find(line)?,
+            None => return Ok(false),
+        };
+        if events.is_empty() {
+            return Ok(false);
+        }
+        
+        // Time-only events take the day of the line's own timestamp, when it has one; looking
+        // it up doesn't count the line as skipped
+        if events.iter().any(|(_, parsed, _)| matches!(parsed, ParsedTimestamp::TimeOfDay(_))) {
+            let counters = self.stats.clone();
+            if let Ok(Some((line_timestamp, _))) = self.extract_timestamp(line) {
+                self.last_time_of_day = Some(line_timestamp);
+            }
+            self.stats = counters;
+        }
+        
+        let correlation = correlation_key(self.correlation_regex.as_ref(), line);
+        for (label, parsed, fields) in events {
+            matches.push(LogMatch {
+                pattern: label,
+                timestamp: self.resolve(parsed),
+                source: None,
+                fields,
+                correlation: correlation.clone(),
+                line_number: None,
+                line: None,
+            });
+        }
+        Ok(true)
+    }
+    
+    /// The line as matched: `None` when it is too long or excluded (counting it), else
+    /// without ANSI codes when stripping
+    fn line_text<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        if self.max_line_length.is_some_and(|max| line.len() > max) {
+            self.stats.too_long += 1;
+            return None;
+        }
+        
+        let line = if self.strip_ansi {
+            strip_ansi_codes(line)
+        } else {
+            Cow::Borrowed(line)
+        };
+        
+        let profiling = self.profile_patterns;
+        let excluded = self.exclude_regexes.iter_mut().any(|(regex, profile)| {
+            profile.measure(profiling, || regex.is_match(&line).then_some(())).is_some()
+        });
+        if excluded {
+            self.stats.excluded += 1;
+            return None;
+        }
+        Some(line)
+    }
+    
+    /// Parse a single log line (as returned by `line_text`) and return a match if found
+    fn parse_line(&mut self, line: &str) -> Result<Option<LogMatch>> {
+        let profiling = self.profile_patterns;
+        
+        // JSON lines carry the timestamp and message in their own fields
+        let (timestamp_text, message) = match &self.json_input {
+            None => (Cow::Borrowed(line), Cow::Borrowed(line)),
+            Some(json) => match json.extract(line) {
+                Ok((timestamp_text, message)) => {
+                    let Some(timestamp_text) = timestamp_text else {
+                        self.stats.no_timestamp += 1;
+                        return Ok(None);
+                    };
+                    (Cow::Owned(timestamp_text), Cow::Owned(message.unwrap_or_default()))
+                }
+                Err(_) => {
+                    self.stats.invalid_json += 1;
+                    return Ok(None);
+                }
+            },
+        };
+        
+        // First, extract the timestamp (and any fields captured next to it)
+        let (timestamp, line_fields) = match self.extract_timestamp(&timestamp_text)? {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        let message = message.as_ref();
+        
+        // Find the first pattern (in config order) that matches. One pass of the combined set
+        // finds them all at once; profiling times each pattern, so it tries them one by one
+        let matched = match &self.pattern_set {
+            Some(set) if !profiling => set.matches(message).iter().next(),
+            _ => self.pattern_regexes.iter_mut().position(|matcher| {
+                let regex = &matcher.regex;
+                matcher.profile.measure(profiling, || regex.is_match(message).then_some(())).is_some()
+            }),
+        };
+        let Some(matcher) = matched.map(|idx| &self.pattern_regexes[idx]) else {
+            self.stats.no_pattern_matched += 1;
+            return Ok(None);
+        };
+        
+        // A `label` capture names the match (e.g. the Gradle task or .NET project) instead of
+        // the pattern's name or text, and other named captures become fields
+        let captures = if matcher.has_label || matcher.has_correlation || !matcher.field_names.is_empty() {
+            matcher.regex.captures(message)
+        } else {
+            None
+        };
+        let pattern = captures
+            .as_ref()
+            .and_then(|captures| captures.name("label"))
+            .map_or_else(|| matcher.name.clone(), |label| label.as_str().to_string());
+        let mut fields = line_fields;
+        if let Some(captures) = &captures {
+            fields.extend(
+                matcher
+                    .field_names
+                    .iter()
+                    .filter_map(|name| Some((name.clone(), captures.name(name)?.as_str().to_string()))),
+            );
+        }
+        
+        // The pattern's own `correlation` group wins over the shared correlation regex
+        let correlation = captures
+            .as_ref()
+            .and_then(|captures| captures.name("correlation"))
+            .map(|key| key.as_str().to_string())
+            .or_else(|| correlation_key(self.correlation_regex.as_ref(), line));
+        
+        Ok(Some(LogMatch {
+            pattern,
+            timestamp,
+            source: None,
+            fields,
+            correlation,
+            line_number: None,
+            line: None,
+        }))
+    }
+    
+    /// Extract timestamp from a log line, counting lines where none could be found.
+    ///
+    /// Also returns the named groups of a manually configured timestamp regex; the
+    /// timestamp itself comes from the group named `timestamp`, or else group 1.
+    fn extract_timestamp(&mut self, line: &str) -> Result<Option<(NaiveDateTime, BTreeMap<String, String>)>> {
+        if !self.is_auto_detect && self.timestamp_regex.is_none() {
+            return self.fallback_timestamp(line);
+        }
+        let full_line = line;
+        
+        // When anchoring, only the text after leading noise is searched and the
+        // timestamp match must begin right at that offset
+        let (line, anchored) = if self.anchor_timestamp {
+            (&line[leading_noise_len(line)..], true)
+        } else {
+            (line, false)
+        };
+        let find = |regex: &Regex| {
+            regex
+                .captures(line)
+                .filter(|captures| !anchored || captures.get(0).is_some_and(|m| m.start() == 0))
+        };
+        
+        if self.is_auto_detect {
+            // Try each built-in format, best-ranked first, until one works; once locked,
+            // only the locked format
+            let locked = self.locked_format.map(|idx| [idx]);
+            let order = match &locked {
+                Some(locked) => &locked[..],
+                None => &self.format_order[..],
+            };
+            let mut candidate_found = false;
+            for &idx in order {
+                let (regex, format) = &self.builtin_formats[idx];
+                if let Some(captures) = find(regex) {
+                    if let Some(ts_str) = captures.get(1) {
+                        candidate_found = true;
+                        // Try to parse with this format
+                        if let Ok(parsed) = parse_timestamp(ts_str.as_str(), format.format) {
+                            self.format_hits[idx] += 1;
+                            if !self.mixed_formats && self.locked_format.is_none() {
+                                self.format_streak = match self.format_streak {
+                                    (streak_idx, count) if streak_idx == idx => (idx, count + 1),
+                                    _ => (idx, 1),
+                                };
+                                if self.format_streak.1 >= FORMAT_LOCK_LINES {
+                                    self.locked_format = Some(idx);
+                                }
+                            }
+                            return Ok(Some((self.resolve(parsed), BTreeMap::new())));
+                        }
+                    }
+                }
+            }
+            
+            if candidate_found {
+                self.stats.timestamp_parse_failed += 1;
+                Ok(None)
+            } else {
+                self.fallback_timestamp(full_line)
+            }
+        } else {
+            // Use the configured format
+            let timestamp_regex = self.timestamp_regex.as_ref().unwrap();
+            let timestamp_format = self.timestamp_format.as_ref().unwrap();
+            
+            if let Some(captures) = find(timestamp_regex) {
+                if let Some(ts_str) = captures.name("timestamp").or_else(|| captures.get(1)) {
+                    // A line the regex matched but whose timestamp doesn't parse is skipped and
+                    // counted, as in auto-detection, rather than ending the run
+                    let Ok(parsed) = parse_timestamp(ts_str.as_str(), timestamp_format) else {
+                        self.stats.timestamp_parse_failed += 1;
+                        return Ok(None);
+                    };
+                    let fields = self
+                        .timestamp_field_names
+                        .iter()
+                        .filter_map(|name| Some((name.clone(), captures.name(name)?.as_str().to_string())))
+                        .collect();
+                    
+                    return Ok(Some((self.resolve(parsed), fields)));
+                }
+            }
+            
+            self.fallback_timestamp(full_line)
+        }
+    }
+    
+    /// Timestamp of a line without one of its own, from the file name and offset markers;
+    /// counts the line as lacking a timestamp when there is no base time
+    fn fallback_timestamp(&mut self, line: &str) -> Result<Option<(NaiveDateTime, BTreeMap<String, String>)>> {
+        match self.filename_timestamps.as_mut().and_then(|fallback| fallback.timestamp(line)) {
+            Some(timestamp) => Ok(Some((timestamp, BTreeMap::new()))),
+            None => {
+                self.stats.no_timestamp += 1;
+                Ok(None)
+            }
+        }
+    }
+    
+    /// Turn a parsed timestamp into a full date and time.
+    ///
+    /// Time-only timestamps are placed on the day that keeps them closest to the
+    /// previous one, so `23:59:59` followed by `00:00:01` is two seconds later on
+    /// the next day, while small backwards jitter between threads stays on the same day.
+    fn resolve(&mut self, parsed: ParsedTimestamp) -> NaiveDateTime {
+        let time = match parsed {
+            ParsedTimestamp::DateTime(timestamp) => return self.clamp_leap_second(timestamp),
+            ParsedTimestamp::TimeOfDay(time) => self.clamp_leap_second(time),
+        };
+        
+        let timestamp = match self.last_time_of_day {
+            None => NaiveDate::default().and_time(time),
+            Some(last) => {
+                let candidate = last.date().and_time(time);
+                let half_day = Duration::hours(12);
+                if last - candidate > half_day {
+                    candidate + Duration::days(1)
+                } else if candidate - last > half_day {
+                    candidate - Duration::days(1)
+                } else {
+                    candidate
+                }
+            }
+        };
+        
+        self.last_time_of_day = Some(timestamp);
+        timestamp
+    }
+    
+    /// Clamp a leap second (`23:59:60.250`, which chrono keeps as second 59 with an
+    /// extra second of nanoseconds) to the end of second 59, counting it.
+    ///
+    /// Left alone, leap seconds produce negative or skewed intervals to the next
+    /// minute; clamped, ordering is kept and the error is under a second.
+    fn clamp_leap_second<T: Timelike>(&mut self, time: T) -> T {
+        if time.nanosecond() < 1_000_000_000 {
+            return time;
+        }
+        
+        self.stats.leap_seconds += 1;
+        time.with_nanosecond(999_999_999).unwrap_or(time)
+    }
+}
+
+/// Matches of one input, found as the iterator advances (see `LogParser::matches`); it ends
+/// at the end of the input, after the first error, or when the stop flag is raised
+pub struct Matches<'a, R> {
+    parser: &'a mut LogParser,
+    /// Lines read ahead to rank the formats, parsed before the rest
+    sample: std::vec::IntoIter<String>,
+    lines: std::io::Lines<R>,
+    /// Matches of the last line not yet returned
+    found: std::vec::IntoIter<LogMatch>,
+    /// The input ended or failed to read, so no more lines are read
+    finished: bool,
+}
+
+impl<R: BufRead> Iterator for Matches<'_, R> {
+    type Item = Result<LogMatch>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(log_match) = self.found.next() {
+                return Some(Ok(log_match));
+            }
+            if self.finished || self.parser.should_stop() {
+                return None;
+            }
+            
+            let mut found = Vec::new();
+            let line = match self.sample.next() {
+                Some(line) => line,
+                None => match self.lines.next() {
+                    Some(Ok(line)) => line,
+                    Some(Err(e)) => {
+                        self.finished = true;
+                        return Some(Err(LogLineError::io("Failed to read line from log", e)));
+                    }
+                    None => {
+                        // The end, after the last multiline entry
+                        self.finished = true;
+                        if let Err(e) = self.parser.finish_input(&mut found) {
+                            return Some(Err(e));
+                        }
+                        self.found = found.into_iter();
+                        continue;
+                    }
+                },
+            };
+            if let Err(e) = self.parser.feed_line(&line, &mut found) {
+                self.finished = true;
+                return Some(Err(e));
+            }
+            self.found = found.into_iter();
+        }
+    }
+}
+
+/// A date and time, or a date alone (taken as midnight)
+fn parse_base_time(text: &str, format: &str) -> chrono::ParseResult<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text, format).or_else(|e| {
+        NaiveDate::parse_from_str(text, format)
+            .map(|date| date.and_time(NaiveTime::MIN))
+            .map_err(|_| e)
+    })
+}
+
+/// An offset marker: a bare number of seconds (as in dmesg's `[   12.345678]`) or a
+/// duration such as `250ms`
+fn parse_offset(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    match text.parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() => Some(Duration::microseconds((seconds * 1_000_000.0).round() as i64)),
+        Ok(_) => None,
+        Err(_) => parse_duration(text).ok(),
+    }
+}
+
+/// Text of the field at `path` (e.g. `["meta", "ts"]`): strings as they are, numbers and
+/// other values as JSON; array elements are addressed by index
+fn json_field(value: &serde_json::Value, path: &[String]) -> Option<String> {
+    let field = path.iter().try_fold(value, |value, key| match value {
+        serde_json::Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => value.get(key),
+    })?;
+    match field {
+        serde_json::Value::String(text) => Some(text.clone()),
+        serde_json::Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// The correlation key of a line: the `correlation` group of `regex`, or else group 1
+fn correlation_key(regex: Option<&Regex>, line: &str) -> Option<String> {
+    let captures = regex?.captures(line)?;
+    let key = captures.name("correlation").or_else(|| captures.get(1))?;
+    Some(key.as_str().to_string())
+}
+
+/// Named capture groups of a regex to record as match fields, skipping those in `skip`.
+///
+/// `pattern`, `source`, `line`, and `raw_line` are rejected since they would clash with the
+/// `from_pattern`/`from_source`/`from_line`/`from_raw_line` output columns.
+fn field_names(regex: &Regex, skip: &[&str], what: &str) -> Result<Vec<String>> {
+    let names: Vec<String> = regex
+        .capture_names()
+        .flatten()
+        .filter(|name| !skip.contains(name))
+        .map(str::to_string)
+        .collect();
+    
+    if let Some(name) = names.iter().find(|n| ["pattern", "source", "line", "raw_line"].contains(&n.as_str())) {
+        return Err(LogLineError::config(format!(
+            "Capture group name '{}' in {} is reserved (it would clash with from_{}/to_{} in the output)",
+            name, what, name, name
+        )));
+    }
+    
+    Ok(names)
+}
+
+/// A timestamp as parsed from the log, before any day is assigned to time-only values
+enum ParsedTimestamp {
+    DateTime(NaiveDateTime),
+    TimeOfDay(NaiveTime),
+}
+
+/// Parse a timestamp, treating formats without any date field as a time of day
+fn parse_timestamp(text: &str, format: &str) -> chrono::ParseResult<ParsedTimestamp> {
+    if is_time_only_format(format) {
+        NaiveTime::parse_from_str(text, format).map(ParsedTimestamp::TimeOfDay)
+    } else {
+        NaiveDateTime::parse_from_str(text, format).map(ParsedTimestamp::DateTime)
+    }
+}
+
+/// Whether a chrono format string has no specifier that carries date information
+fn is_time_only_format(format: &str) -> bool {
+    const DATE_SPECIFIERS: &[char] = &[
+        'Y', 'C', 'y', 'G', 'g', 'm', 'b', 'B', 'h', 'd', 'e', 'a', 'A', 'w', 'u', 'U', 'W', 'V',
+        'j', 'D', 'x', 'F', 'v', 's', 'c', '+',
+    ];
+    
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        // Skip padding and width modifiers such as %-d, %_m, %3f
+        let spec = chars.by_ref().find(|c| !matches!(c, '-' | '_' | '0'..='9' | '.' | ':'));
+        if spec.is_some_and(|spec| DATE_SPECIFIERS.contains(&spec)) {
+            return false;
+        }
+    }
+    
+    true
+}
+
+/// Byte length of the noise at the start of a line that precedes a timestamp:
+/// a UTF-8 byte order mark, whitespace, and ANSI escape sequences
+fn leading_noise_len(line: &str) -> usize {
+    let bytes = line.as_bytes();
+    let mut offset = 0;
+    
+    loop {
+        let rest = &line[offset..];
+        if let Some(stripped) = rest.strip_prefix('\u{feff}') {
+            offset = line.len() - stripped.len();
+        } else if rest.starts_with(char::is_whitespace) {
+            offset = line.len() - rest.trim_start().len();
+        } else if let Some(len) = ansi_sequence_len(&bytes[offset..]) {
+            offset += len;
+        } else {
+            return offset;
+        }
+    }
+}
+
+/// Offsets splitting a file into `chunks` byte ranges that each start at the beginning of a
+/// line (the first is 0, the last is the file length)
+fn chunk_bounds(path: &Path, chunks: usize) -> std::io::Result<Vec<u64>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let len = reader.get_ref().metadata()?.len();
+    let mut bounds = vec![0];
+    let mut skipped = Vec::new();
+    
+    for i in 1..chunks as u64 {
+        let target = len * i / chunks as u64;
+        if target <= *bounds.last().unwrap_or(&0) {
+            continue;
+        }
+        // Move forward to just after the next newline
+        reader.seek(SeekFrom::Start(target - 1))?;
+        skipped.clear();
+        let read = reader.read_until(b'\n', &mut skipped)? as u64;
+        let bound = (target - 1 + read).min(len);
+        if bound > *bounds.last().unwrap_or(&0) && bound < len {
+            bounds.push(bound);
+        }
+    }
+    
+    bounds.push(len);
+    Ok(bounds)
+}
+
+/// Expand glob patterns such as `logs/app-*.log` into the matching files (sorted by name).
+///
+/// Paths without glob characters, or that exist as written, are kept unchanged; a pattern
+/// that matches nothing is an error rather than silently analyzing fewer files.
+pub fn expand_log_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    
+    for path in paths {
+        let pattern = path.to_string_lossy();
+        if !pattern.contains(['*', '?', '[']) || path.exists() {
+            files.push(path.clone());
+            continue;
+        }
+        
+        let entries = glob::glob(&pattern)
+            .map_err(|e| LogLineError::config_caused_by(format!("Invalid log file pattern: {}", pattern), e))?;
+        let before = files.len();
+        for entry in entries {
+            let file = entry.map_err(|e| {
+                let message = format!("Failed to read {:?} while expanding {}", e.path(), pattern);
+                LogLineError::io(message, e.into())
+            })?;
+            if file.is_file() {
+                files.push(file);
+            }
+        }
+        
+        if files.len() == before {
+            return Err(LogLineError::config(format!("No log files match pattern: {}", pattern)));
+        }
+    }
+    
+    Ok(files)
+}
+
+/// Remove all ANSI escape sequences (colors, cursor movement, titles) from a line
+pub fn strip_ansi_codes(line: &str) -> Cow<'_, str> {
+    if !line.contains('\x1b') {
+        return Cow::Borrowed(line);
+    }
+    
+    let bytes = line.as_bytes();
+    let mut output = String::with_capacity(line.len());
+    let mut start = 0;
+    let mut i = 0;
+    
+    while i < bytes.len() {
+        match ansi_sequence_len(&bytes[i..]) {
+            Some(len) => {
+                output.push_str(&line[start..i]);
+                i += len;
+                start = i;
+            }
+            None => i += 1,
+        }
+    }
+    output.push_str(&line[start..]);
+    
+    Cow::Owned(output)
+}
+
+/// Length of the ANSI escape sequence at the start of `bytes`, if there is one
+fn ansi_sequence_len(bytes: &[u8]) -> Option<usize> {
+    if bytes.first() != Some(&0x1b) {
+        return None;
+    }
+    
+    match bytes.get(1)? {
+        // CSI: ESC [ parameters/intermediates, terminated by a byte in 0x40..=0x7e
+        b'[' => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map(|end| end + 3),
+        // OSC: ESC ] ... terminated by BEL or ESC \
+        b']' => {
+            let body = &bytes[2..];
+            body.iter().enumerate().find_map(|(i, &b)| match b {
+                0x07 => Some(i + 3),
+                0x1b if body.get(i + 1) == Some(&b'\\') => Some(i + 4),
+                _ => None,
+            })
+        }
+        // Two-byte escape such as ESC ( or ESC =
+        b if (0x20..=0x7e).contains(b) => Some(2),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MessagePattern;
+    use std::io::Cursor;
+
+    fn parser(config: Config) -> LogParser {
+        LogParser::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_skip_reason_counters() {
+        let mut config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        config.exclude_patterns = vec!["DEBUG".to_string()];
+        config.max_line_length = Some(60);
+
+        let log = "\
+2025-11-13 10:00:00 start
+2025-11-13 10:00:01 DEBUG start
+no timestamp here
+2025-11-13 10:00:02 unrelated
+2025-11-13 10:00:03 end with a very long message that goes past the limit
+2025-11-13 10:00:04 end
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+        let stats = parser.stats();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(stats.lines_read, 6);
+        assert_eq!(stats.matches, 2);
+        assert_eq!(stats.excluded, 1);
+        assert_eq!(stats.no_timestamp, 1);
+        assert_eq!(stats.no_pattern_matched, 1);
+        assert_eq!(stats.too_long, 1);
+        assert_eq!(stats.skipped(), 4);
+    }
+
+    #[test]
+    fn test_leading_noise_len() {
+        assert_eq!(leading_noise_len("2025-11-13 10:00:00 x"), 0);
+        assert_eq!(leading_noise_len("\u{feff}  2025"), 5);
+        assert_eq!(leading_noise_len("\x1b[32m\x1b[1m 2025"), 10);
+        assert_eq!(leading_noise_len("\x1b]0;title\x07 2025"), 11);
+    }
+
+    #[test]
+    fn test_anchored_timestamp_ignores_dates_in_message() {
+        let mut config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        config.anchor_timestamp = true;
+
+        let log = "\
+\u{feff}2025-11-13 10:00:00 start
+worker-3 end of report for 2025-11-13 10:00:05
+\x1b[32m2025-11-13 10:00:02\x1b[0m end
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(parser.stats().no_timestamp, 1);
+        assert_eq!(matches[1].timestamp.to_string(), "2025-11-13 10:00:02");
+    }
+
+    #[test]
+    fn test_strip_ansi_codes() {
+        assert_eq!(strip_ansi_codes("plain"), "plain");
+        assert_eq!(
+            strip_ansi_codes("\x1b[2m2025-11-13 10:00:00\x1b[0m \x1b[1;31mRequest\x1b[0m done"),
+            "2025-11-13 10:00:00 Request done"
+        );
+        assert_eq!(strip_ansi_codes("dangling \x1b["), "dangling \x1b[");
+        assert_eq!(strip_ansi_codes("\x1bé"), "\x1bé");
+    }
+
+    #[test]
+    fn test_detected_format_prefers_most_hits() {
+        let config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        let log = "\
+2025-11-13T10:00:00 start
+[13/Nov/2025:10:00:01 +0000] end
+[13/Nov/2025:10:00:02 +0000] start
+";
+        let mut parser = parser(config);
+        parser.parse_reader(Cursor::new(log)).unwrap();
+
+        assert_eq!(parser.detected_format().unwrap().name, "Apache/Nginx common log format");
+    }
+
+    #[test]
+    fn test_format_locks_after_consecutive_parses() {
+        let mut log: String = (0..FORMAT_LOCK_LINES)
+            .map(|i| format!("2025-11-13 10:00:{:02} start\n", i % 60))
+            .collect();
+        log.push_str("1699876800 end\n");
+
+        let mut config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        let mut locking = parser(config.clone());
+        let matches = locking.parse_reader(Cursor::new(&log)).unwrap();
+        assert_eq!(matches.len(), FORMAT_LOCK_LINES);
+        assert_eq!(locking.stats().no_timestamp, 1);
+        assert!(locking.format_detection_report().unwrap().contains("Locked onto Common log format"));
+
+        config.mixed_timestamp_formats = true;
+        let mut mixed = parser(config);
+        let matches = mixed.parse_reader(Cursor::new(&log)).unwrap();
+        assert_eq!(matches.len(), FORMAT_LOCK_LINES + 1);
+        assert!(!mixed.format_detection_report().unwrap().contains("Locked"));
+    }
+
+    #[test]
+    fn test_explain_line_lists_each_matching_format() {
+        let parser = parser(Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap());
+        let candidates = parser.explain_line("2025-13-45 10:00:00 start");
+        let common = candidates
+            .iter()
+            .find(|c| c.format == "Common log format (YYYY-MM-DD HH:MM:SS)")
+            .unwrap();
+        let time_of_day = candidates.iter().find(|c| c.format == "Time of day (HH:MM:SS)").unwrap();
+
+        assert_eq!(common.text, "2025-13-45 10:00:00");
+        assert!(common.parsed.is_err());
+        assert_eq!(time_of_day.parsed, Ok("10:00:00".to_string()));
+        assert!(parser.explain_line("no timestamp here").is_empty());
+    }
+
+    #[test]
+    fn test_label_capture_names_matches() {
+        let config = Config::for_auto_detection(vec![
+            r"> Task (?P<label>:\S+)".into(),
+            "BUILD SUCCESSFUL".into(),
+        ])
+        .unwrap();
+        let log = "\
+2025-11-13 10:00:00 > Task :app:compileKotlin
+2025-11-13 10:00:05 > Task :app:test
+2025-11-13 10:00:09 BUILD SUCCESSFUL in 9s
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+        let names: Vec<&str> = matches.iter().map(|m| m.pattern.as_str()).collect();
+
+        assert_eq!(names, vec![":app:compileKotlin", ":app:test", "BUILD SUCCESSFUL"]);
+    }
+
+    #[test]
+    fn test_first_pattern_in_config_order_wins() {
+        let config = Config::for_auto_detection(vec![
+            "request".into(),
+            r"request (?P<label>\w+) done".into(),
+            r"(?P<status>\d{3}) sent".into(),
+        ])
+        .unwrap();
+        let log = "\
+2025-11-13 10:00:00 request abc done
+2025-11-13 10:00:01 response 200 sent
+2025-11-13 10:00:02 idle
+";
+        let mut single_pass = parser(config.clone());
+        let mut one_by_one = parser(config);
+        one_by_one.enable_pattern_profiling();
+
+        for parser in [&mut single_pass, &mut one_by_one] {
+            let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+            let names: Vec<&str> = matches.iter().map(|m| m.pattern.as_str()).collect();
+            assert_eq!(names, vec!["request", r"(?P<status>\d{3}) sent"]);
+            assert_eq!(matches[1].fields.get("status").map(String::as_str), Some("200"));
+            assert_eq!(parser.stats().no_pattern_matched, 1);
+        }
+    }
+
+    #[test]
+    fn test_named_patterns() {
+        let yaml = r#"
+timestamp_regex: '^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2})'
+timestamp_format: '%Y-%m-%d %H:%M:%S'
+message_patterns:
+  - { name: DB connect, regex: 'Connecting to database.*' }
+  - 'Query (done|failed)'
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.message_patterns[0], MessagePattern::named("DB connect", "Connecting to database.*"));
+        // Named patterns round-trip as maps, the others as plain regexes
+        let dumped = serde_yaml::to_string(&config).unwrap();
+        assert!(dumped.contains("- name: DB connect\n  regex: Connecting to database.*\n- Query (done|failed)\n"), "{}", dumped);
+
+        let log = "\
+2025-11-13 10:00:00 Connecting to database db1
+2025-11-13 10:00:02 Query done
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+        let names: Vec<&str> = matches.iter().map(|m| m.pattern.as_str()).collect();
+        assert_eq!(names, vec!["DB connect", "Query (done|failed)"]);
+
+        assert_eq!(MessagePattern::parse("DB connect=Connecting to .*"), MessagePattern::named("DB connect", "Connecting to .*"));
+        assert_eq!(MessagePattern::parse("=status=5\\d\\d"), MessagePattern::from("status=5\\d\\d"));
+        assert_eq!(MessagePattern::parse("Query done"), MessagePattern::from("Query done"));
+    }
+
+    #[test]
+    fn test_named_captures_become_fields() {
+        let config = Config::for_auto_detection(vec![
+            r"GET (?P<path>\S+)".into(),
+            r"status=(?P<status>\d+)(?: bytes=(?P<bytes>\d+))?".into(),
+        ])
+        .unwrap();
+        let log = "\
+2025-11-13 10:00:00 GET /api/orders
+2025-11-13 10:00:01 status=200
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        assert_eq!(matches[0].fields.get("path").map(String::as_str), Some("/api/orders"));
+        assert_eq!(matches[1].fields.get("status").map(String::as_str), Some("200"));
+        assert!(!matches[1].fields.contains_key("bytes"));
+    }
+
+    #[test]
+    fn test_timestamp_regex_fields() {
+        let config = Config {
+            timestamp_regex: r"^(?P<host>\S+) \[(?P<pid>\d+)\] (?P<timestamp>\S+ \S+)".to_string(),
+            timestamp_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            message_patterns: vec!["start".into(), r"end (?P<pid>\w+)".into()],
+            ..Default::default()
+        };
+        let log = "\
+web-1 [4242] 2025-11-13 10:00:00 start
+web-1 [4242] 2025-11-13 10:00:03 end override
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        assert_eq!(matches[0].timestamp.to_string(), "2025-11-13 10:00:00");
+        assert_eq!(matches[0].fields.get("host").map(String::as_str), Some("web-1"));
+        assert_eq!(matches[0].fields.get("pid").map(String::as_str), Some("4242"));
+        // A pattern's own capture wins over the timestamp regex's field of the same name
+        assert_eq!(matches[1].fields.get("pid").map(String::as_str), Some("override"));
+    }
+
+    #[test]
+    fn test_json_input() {
+        let mut config = Config::for_auto_detection(vec!["^start".into(), "^end".into()]).unwrap();
+        config.input_format = InputFormat::Json;
+        config.timestamp_field = Some("meta.ts".to_string());
+        config.message_field = Some("events.0".to_string());
+        config.validate().unwrap();
+        let log = r#"{"meta": {"ts": "2025-11-13 10:00:00.250"}, "events": ["start 2025-01-01 00:00:00"]}
+{"meta": {"ts": "2025-11-13 10:00:00.500"}, "events": []}
+{"meta": {"ts": "2025-11-13 10:00:01.000"}, "events": ["end"]}
+{"meta": {}, "events": ["end"]}
+{"meta": {"ts": "2025-11-13 10:00:02.000"}, "events": ["end"
+"#;
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        let stamps: Vec<String> = matches.iter().map(|m| m.timestamp.to_string()).collect();
+        assert_eq!(stamps, vec!["2025-11-13 10:00:00.250", "2025-11-13 10:00:01"]);
+        let stats = parser.stats();
+        assert_eq!((stats.no_pattern_matched, stats.no_timestamp, stats.invalid_json), (1, 1, 1));
+    }
+
+    #[test]
+    fn test_correlation_keys() {
+        let mut config = Config::for_auto_detection(vec![
+            "start".into(),
+            r"end of (?P<correlation>\w+)".into(),
+        ])
+        .unwrap();
+        config.correlation_regex = Some(r"req=(\w+)".to_string());
+        let log = "\
+2025-11-13 10:00:00 start req=a1
+2025-11-13 10:00:01 start
+2025-11-13 10:00:02 end of b2 req=a1
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        let keys: Vec<Option<&str>> = matches.iter().map(|m| m.correlation.as_deref()).collect();
+        assert_eq!(keys, vec![Some("a1"), None, Some("b2")]);
+        assert!(matches[2].fields.is_empty());
+
+        let mut config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        config.correlation_regex = Some(r"req=\w+".to_string());
+        assert!(LogParser::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_embedded_events_each_become_a_match() {
+        let mut config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        config.embedded_event_regex =
+            Some(r"(?P<label>queued|started|done)=(?P<timestamp>\d{2}:\d{2}:\d{2}\.\d{3})(?:/(?P<worker>\w+))?".to_string());
+        config.embedded_timestamp_format = Some("%H:%M:%S%.3f".to_string());
+        config.correlation_regex = Some(r"job=(\w+)".to_string());
+        let log = "\
+2025-11-13 10:00:00 job=7 queued=10:00:00.100 started=10:00:00.350/w2 done=10:00:01.200
+2025-11-13 10:00:02 end
+";
+        let mut parser = parser(config);
+        parser.record_source_lines(false);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        let events: Vec<(&str, String)> = matches
+            .iter()
+            .map(|m| (m.pattern.as_str(), m.timestamp.format("%Y-%m-%d %H:%M:%S%.3f").to_string()))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("queued", "2025-11-13 10:00:00.100".to_string()),
+                ("started", "2025-11-13 10:00:00.350".to_string()),
+                ("done", "2025-11-13 10:00:01.200".to_string()),
+                ("end", "2025-11-13 10:00:02.000".to_string()),
+            ]
+        );
+        assert!(matches[..3].iter().all(|m| m.correlation.as_deref() == Some("7") && m.line_number == Some(1)));
+        assert_eq!(matches[1].fields.get("worker").map(String::as_str), Some("w2"));
+        assert_eq!(parser.stats().matches, 4);
+
+        let mut config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        config.embedded_event_regex = Some(r"(?P<label>\w+)=(?P<ts>\S+)".to_string());
+        config.embedded_timestamp_format = Some("%H:%M:%S".to_string());
+        assert!(LogParser::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_ranking_prefers_consistent_precise_format() {
+        let config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        let log = "\
+2025-11-13 10:00:00.100 start
+2025-11-13 10:00:01.350 end
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        // First-match order would pick the plain common log format and drop the milliseconds
+        assert_eq!(matches[1].timestamp - matches[0].timestamp, Duration::milliseconds(1250));
+        assert_eq!(parser.detected_format().unwrap().name, "Common log format with milliseconds");
+        assert!(parser.format_detection_report().unwrap().contains("100.0%  Common log format with milliseconds  <- chosen"));
+    }
+
+    #[test]
+    fn test_prefer_format_overrides_ranking() {
+        let mut config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        config.prefer_format = Some("Common log format (YYYY-MM-DD HH:MM:SS)".to_string());
+        let log = "\
+2025-11-13 10:00:00.100 start
+2025-11-13 10:00:01.350 end
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        assert_eq!(matches[1].timestamp - matches[0].timestamp, Duration::seconds(1));
+    }
+
+    #[test]
+    fn test_leap_seconds_are_clamped() {
+        let config = Config {
+            timestamp_regex: r"^(\S+ \S+)".to_string(),
+            timestamp_format: "%Y-%m-%d %H:%M:%S%.f".to_string(),
+            message_patterns: vec!["tick".into()],
+            ..Default::default()
+        };
+        let log = "\
+2016-12-31 23:59:59.500 tick
+2016-12-31 23:59:60.250 tick
+2017-01-01 00:00:00.100 tick
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        assert_eq!(matches.len(), 3);
+        assert_eq!(parser.stats().leap_seconds, 1);
+        assert!(matches[1].timestamp > matches[0].timestamp);
+        // Unclamped, chrono puts 23:59:60.250 after 00:00:00.100 in duration terms
+        assert!(matches[2].timestamp - matches[1].timestamp > Duration::zero());
+        assert!(parser.stats().format_summary().contains("Leap seconds clamped:"));
+    }
+
+    #[test]
+    fn test_time_only_format_detection() {
+        assert!(is_time_only_format("%H:%M:%S%.3f"));
+        assert!(is_time_only_format("%I:%M:%S %p"));
+        assert!(!is_time_only_format("%Y-%m-%d %H:%M:%S"));
+        assert!(!is_time_only_format("%-d/%b %H:%M"));
+        assert!(!is_time_only_format("%s"));
+    }
+
+    #[test]
+    fn test_time_only_timestamps_roll_over_midnight() {
+        let config = Config {
+            timestamp_regex: r"^(\d{2}:\d{2}:\d{2}\.\d{3})".to_string(),
+            timestamp_format: "%H:%M:%S%.3f".to_string(),
+            message_patterns: vec!["tick".into()],
+            ..Default::default()
+        };
+        let log = "\
+23:59:58.000 tick
+23:59:59.900 tick
+23:59:59.800 tick
+00:00:00.250 tick
+23:59:59.950 tick
+06:00:00.000 tick
+17:00:00.000 tick
+00:00:01.000 tick
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+        let stamps: Vec<String> = matches.iter().map(|m| m.timestamp.to_string()).collect();
+
+        assert_eq!(
+            stamps,
+            vec![
+                "1970-01-01 23:59:58",
+                "1970-01-01 23:59:59.900",
+                "1970-01-01 23:59:59.800",
+                "1970-01-02 00:00:00.250",
+                "1970-01-01 23:59:59.950",
+                "1970-01-02 06:00:00",
+                "1970-01-02 17:00:00",
+                "1970-01-03 00:00:01",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_time_in_message_is_not_a_timestamp() {
+        let config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        let log = "\
+2025-11-13 10:00:00 start
+worker reported elapsed 00:00:05 for job end
+2025-11-13 10:00:02 end
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+        let stamps: Vec<String> = matches.iter().map(|m| m.timestamp.to_string()).collect();
+
+        assert_eq!(stamps, vec!["2025-11-13 10:00:00", "2025-11-13 10:00:02"]);
+        assert_eq!(parser.stats().no_timestamp, 1);
+        assert!(parser.format_detection_report().unwrap().contains("Common log format (YYYY-MM-DD HH:MM:SS)  <- chosen"));
+    }
+
+    #[test]
+    fn test_errors_are_typed() {
+        let config = Config {
+            timestamp_regex: r"^(\S+)".to_string(),
+            timestamp_format: "%Y-%m-%d".to_string(),
+            message_patterns: vec!["start".into(), "(unclosed".into()],
+            ..Default::default()
+        };
+        assert!(matches!(LogParser::new(&config), Err(LogLineError::Regex { .. })));
+
+        let config = Config {
+            message_patterns: vec!["start".into(), "end".into()],
+            ..config
+        };
+        let config = Config {
+            embedded_event_regex: Some(r"(?P<label>start)=(?P<timestamp>\S+)".to_string()),
+            embedded_timestamp_format: Some("%H:%M".to_string()),
+            ..config
+        };
+        let result = parser(config).parse_reader(Cursor::new("2025-11-13 start=yesterday\n"));
+        assert!(matches!(result, Err(LogLineError::TimestampParse { ref text, .. }) if text == "yesterday"));
+    }
+
+    #[test]
+    fn test_unparsable_configured_timestamps_are_counted() {
+        let config = Config {
+            timestamp_regex: r"^(\S+ \S+)".to_string(),
+            timestamp_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            message_patterns: vec!["start".into(), "end".into()],
+            ..Default::default()
+        };
+        let log = "\
+2025-11-13 10:00:00 start
+2025-11-13 25:61:00 end
+2025-11-13 10:00:02 end
+";
+        let mut parser = parser(config);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[1].timestamp.format("%H:%M:%S").to_string(), "10:00:02");
+        assert_eq!(parser.stats().timestamp_parse_failed, 1);
+        assert_eq!(parser.stats().skipped(), 1);
+    }
+
+    #[test]
+    fn test_expand_log_files() {
+        let dir = std::env::temp_dir().join(format!("ll-glob-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["app-2.log", "app-1.log", "other.txt"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        let files = expand_log_files(&[dir.join("app-*.log"), dir.join("other.txt")]).unwrap();
+        let names: Vec<_> = files.iter().map(|f| f.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(names, vec!["app-1.log", "app-2.log", "other.txt"]);
+        assert!(expand_log_files(&[dir.join("missing-*.log")]).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_timestamps_from_file_name_and_offsets() {
+        let dir = std::env::temp_dir().join(format!("ll-filename-ts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dump = dir.join("console-20251113-100000.log");
+        std::fs::write(&dump, "[ 0.5] boot\nno marker: mount\n[ 2.25] 2025-11-13 11:00:00 ready\n[3] ready\n").unwrap();
+        let other = dir.join("console.log");
+        std::fs::write(&other, "[ 1.0] boot\n").unwrap();
+
+        let mut parser = parser(Config {
+            timestamp_regex: r"(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2})".to_string(),
+            timestamp_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            message_patterns: vec!["boot".into(), "mount".into(), "ready".into()],
+            filename_timestamp_regex: Some(r"console-(\d{8}-\d{6})".to_string()),
+            filename_timestamp_format: Some("%Y%m%d-%H%M%S".to_string()),
+            offset_regex: Some(r"^\[\s*(?P<offset>[\d.]+)\]".to_string()),
+            ..Default::default()
+        });
+        let times: Vec<String> = parser
+            .parse_file(&dump)
+            .unwrap()
+            .iter()
+            .map(|m| m.timestamp.format("%H:%M:%S%.3f").to_string())
+            .collect();
+        // A line's own timestamp wins; lines without a marker keep the previous offset
+        assert_eq!(times, vec!["10:00:00.500", "10:00:00.500", "11:00:00.000", "10:00:03.000"]);
+
+        // Without a base time in the name the fallback doesn't apply
+        assert!(parser.parse_file(&other).unwrap().is_empty());
+        assert_eq!(parser.stats().no_timestamp, 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_follow_reports_matches_and_completes_partial_lines() {
+        let config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        let log = "\
+header without a timestamp
+2025-11-13 10:00:00.100 start
+2025-11-13 10:00:01.350 end";
+        let mut parser = parser(config);
+        let mut matches = Vec::new();
+        parser.follow(Cursor::new(log), None, |m| matches.push(m)).unwrap();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[1].timestamp - matches[0].timestamp, Duration::milliseconds(1250));
+        assert_eq!(parser.stats().lines_read, 3);
+    }
+
+    #[test]
+    fn test_multiline_entries_are_matched_whole() {
+        let log = "\
+2025-11-13 10:00:00.100 request started
+2025-11-13 10:00:00.900 request failed: NullPointerException
+    at com.example.Checkout.pay(Checkout.java:42)
+    at com.example.Checkout.run(Checkout.java:17)
+2025-11-13 10:00:01.000 request retried
+Caused by: timeout
+2025-11-13 10:00:02.000 done";
+        let mut config = Config::for_auto_detection(vec![
+            MessagePattern::named("start", "request started"),
+            MessagePattern::named("checkout failure", r"(?s)failed.*Checkout\.pay"),
+            MessagePattern::named("timeout", "Caused by: timeout"),
+        ])
+        .unwrap();
+        config.multiline = true;
+        let mut parser = parser(config.clone());
+        parser.record_source_lines(true);
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        let names: Vec<&str> = matches.iter().map(|m| m.pattern.as_str()).collect();
+        assert_eq!(names, ["start", "checkout failure", "timeout"]);
+        assert_eq!(matches[1].line_number, Some(2));
+        assert_eq!(matches[1].line.as_deref().map(|line| line.lines().count()), Some(3));
+        assert_eq!(matches[2].timestamp.to_string(), "2025-11-13 10:00:01");
+        assert_eq!((parser.stats().lines_read, parser.stats().continuation_lines), (7, 3));
+
+        // Only indented lines continue an entry, so the unindented one stands alone
+        config.continuation_regex = Some(r"^\s".to_string());
+        let mut indented = LogParser::new(&config).unwrap();
+        let names: Vec<String> = indented
+            .parse_reader(Cursor::new(log))
+            .unwrap()
+            .into_iter()
+            .map(|m| m.pattern)
+            .collect();
+        assert_eq!(names, ["start", "checkout failure"]);
+        assert_eq!(indented.stats().no_timestamp, 1);
+    }
+
+    #[test]
+    fn test_follow_completes_multiline_entry_when_caught_up() {
+        let mut config = Config::for_auto_detection(vec!["start".into(), "(?s)end.*detail".into()]).unwrap();
+        config.multiline = true;
+        let log = "2025-11-13 10:00:00.100 start\n2025-11-13 10:00:01.350 end\n  detail\n";
+        let mut parser = parser(config);
+        let mut matches = Vec::new();
+        parser.follow(Cursor::new(log), None, |m| matches.push(m)).unwrap();
+        assert_eq!(matches.len(), 2);
+    }
+
+    #[test]
+    fn test_follow_reloads_patterns_between_lines() {
+        let config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        let reloaded = Config::for_auto_detection(vec!["begin".into(), "end".into()]).unwrap();
+        let log = "\
+2025-11-13 10:00:00 start
+2025-11-13 10:00:01 begin
+2025-11-13 10:00:02 end
+2025-11-13 10:00:03 start";
+        let (sender, updates) = std::sync::mpsc::channel();
+        let mut parser = parser(config);
+        let mut events = Vec::new();
+        parser
+            .follow_reloading(Cursor::new(log), None, Some(&updates), |event| {
+                if events.is_empty() {
+                    // Edited while the first match is being handled
+                    sender.send(reloaded.clone()).unwrap();
+                }
+                events.push(match event {
+                    FollowEvent::Match(m) => m.pattern,
+                    FollowEvent::Reloaded(_) => "reloaded".to_string(),
+                });
+            })
+            .unwrap();
+
+        assert_eq!(events, vec!["start", "reloaded", "begin", "end"]);
+        assert_eq!(parser.stats().lines_read, 4);
+    }
+
+    #[test]
+    fn test_stop_flag_returns_partial_results() {
+        let config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        let flag = Arc::new(AtomicBool::new(true));
+        let mut parser = parser(config);
+        parser.set_stop_flag(flag);
+        let matches = parser.parse_reader(Cursor::new("2025-11-13 10:00:00 start\n")).unwrap();
+
+        assert!(matches.is_empty());
+        assert!(parser.stats().interrupted);
+        assert!(parser.stats().format_summary().ends_with("Interrupted: results are partial"));
+    }
+
+    #[test]
+    fn test_pattern_profile_counts_attempts() {
+        let config = Config {
+            timestamp_regex: r"^(\S+ \S+)".to_string(),
+            timestamp_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            message_patterns: vec!["start".into(), r"end (?P<code>\d+)".into()],
+            exclude_patterns: vec!["DEBUG".to_string()],
+            ..Default::default()
+        };
+        let log = "\
+2025-11-13 10:00:00 start
+2025-11-13 10:00:01 DEBUG end 1
+2025-11-13 10:00:02 end 200
+";
+        let mut parser = parser(config);
+        parser.enable_pattern_profiling();
+        parser.parse_reader(Cursor::new(log)).unwrap();
+
+        let profiles: Vec<(usize, usize)> = parser.pattern_regexes.iter().map(|m| (m.profile.tried, m.profile.hits)).collect();
+        assert_eq!(profiles, vec![(2, 1), (1, 1)]);
+        assert_eq!((parser.exclude_regexes[0].1.tried, parser.exclude_regexes[0].1.hits), (3, 1));
+        assert!(parser.format_pattern_profile().unwrap().contains("(exclude) DEBUG"));
+    }
+
+    #[test]
+    fn test_time_window_drops_matches_outside_it() {
+        let log = "\
+2025-11-13 09:59:59 start
+2025-11-13 10:00:00 start
+2025-11-13 10:30:00 end
+2025-11-13 11:00:00 end
+";
+        let mut parser = parser(Config {
+            timestamp_regex: r"^(\S+ \S+)".to_string(),
+            timestamp_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            message_patterns: vec!["start".into(), "end".into()],
+            ..Default::default()
+        });
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        parser.set_time_window(Some(at("2025-11-13 10:00:00")), Some(at("2025-11-13 11:00:00")));
+
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+        let times: Vec<NaiveDateTime> = matches.iter().map(|m| m.timestamp).collect();
+        assert_eq!(times, vec![at("2025-11-13 10:00:00"), at("2025-11-13 10:30:00")]);
+        assert_eq!((parser.stats().matches, parser.stats().outside_time_window), (2, 2));
+        assert!(parser.stats().format_summary().contains("Outside time window:"));
+    }
+
+    #[test]
+    fn test_matches_are_read_lazily() {
+        let log = "2025-11-13 10:00:00 start\n2025-11-13 10:00:01 noise\n2025-11-13 10:00:02 end\n2025-11-13 10:00:03 start\n";
+        let mut parser = parser(Config {
+            timestamp_regex: r"^(\S+ \S+)".to_string(),
+            timestamp_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            message_patterns: vec!["start".into(), "end".into()],
+            ..Default::default()
+        });
+
+        let mut matches = parser.matches(Cursor::new(log)).unwrap();
+        assert_eq!(matches.next().unwrap().unwrap().pattern, "start");
+        assert_eq!(matches.next().unwrap().unwrap().pattern, "end");
+        drop(matches);
+        // Nothing after the second match was read yet
+        assert_eq!(parser.stats().lines_read, 3);
+
+        let collected = parser.parse_reader(Cursor::new(log)).unwrap();
+        assert_eq!(collected.len(), 3);
+    }
+
+    #[test]
+    fn test_chunked_parse_matches_sequential_parse() {
+        let path = std::env::temp_dir().join(format!("ll-chunks-{}.log", std::process::id()));
+        let mut log = String::new();
+        for i in 0..2000 {
+            let event = ["start", "noise", "end"][i % 3];
+            log.push_str(&format!("2025-11-13 10:{:02}:{:02}.{:03} {}\n", i / 600, i / 10 % 60, i % 1000, event));
+        }
+        std::fs::write(&path, &log).unwrap();
+
+        let config = Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap();
+        let mut sequential = parser(config.clone());
+        sequential.record_source_lines(true);
+        let expected = sequential.parse_reader(Cursor::new(log.as_str())).unwrap();
+        assert_eq!((expected[1].line_number, expected[1].line.as_deref()), (Some(3), Some("2025-11-13 10:00:00.002 end")));
+
+        let mut chunked = parser(config);
+        chunked.record_source_lines(true);
+        chunked.min_chunk_bytes = 4096;
+        let matches = chunked.parse_file_chunked(&path, 7).unwrap();
+
+        // Line numbers count from the start of the file, not of each chunk
+        let key = |m: &LogMatch| (m.pattern.clone(), m.timestamp, m.line_number, m.line.clone());
+        assert_eq!(matches.iter().map(key).collect::<Vec<_>>(), expected.iter().map(key).collect::<Vec<_>>());
+        assert_eq!(chunked.stats().lines_read, 2000);
+        assert_eq!(chunked.stats().no_pattern_matched, sequential.stats().no_pattern_matched);
+        assert_eq!(chunked.detected_format().unwrap().name, sequential.detected_format().unwrap().name);
+
+        // Fewer threads than chunks still yields the chunks in file order
+        let mut two_threads = parser(Config::for_auto_detection(vec!["start".into(), "end".into()]).unwrap());
+        two_threads.record_source_lines(true);
+        two_threads.min_chunk_bytes = 4096;
+        two_threads.set_threads(2);
+        let matches = two_threads.parse_file_chunked(&path, 7).unwrap();
+        assert_eq!(matches.iter().map(key).collect::<Vec<_>>(), expected.iter().map(key).collect::<Vec<_>>());
+
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -0,0 +1,99 @@
+/// Lowering the CPU and I/O priority of a run (`--nice`, `--io-priority`), so a heavy analysis
+/// on a shared host yields to the service whose logs it reads
+use anyhow::Result;
+
+/// Linux I/O scheduling class and level (see ioprio_set(2))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoPriority {
+    /// Disk time only when no other process wants it
+    Idle,
+    /// Normal scheduling at a level from 0 (highest) to 7 (lowest)
+    BestEffort(u8),
+}
+
+impl IoPriority {
+    /// `idle`, or `best-effort` with an optional level (`best-effort:7`, default 7)
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (class, level) = match spec.split_once(':') {
+            Some((class, level)) => (class, Some(level)),
+            None => (spec, None),
+        };
+        match (class.to_lowercase().as_str(), level) {
+            ("idle", None) => Ok(IoPriority::Idle),
+            ("best-effort", None) => Ok(IoPriority::BestEffort(7)),
+            ("best-effort", Some(level)) => match level.parse::<u8>() {
+                Ok(level @ 0..=7) => Ok(IoPriority::BestEffort(level)),
+                _ => anyhow::bail!("Invalid I/O priority level '{}': expected 0 to 7", level),
+            },
+            _ => anyhow::bail!("Invalid I/O priority '{}'. Valid options: idle, best-effort[:0-7]", spec),
+        }
+    }
+}
+
+/// Raise the process's niceness to `nice` (never lowering it) and set its I/O priority.
+///
+/// Both apply to the calling thread and every thread it starts afterwards, so this runs
+/// before the parsing pool and the other worker threads are created.
+pub fn apply(nice: Option<i32>, io_priority: Option<IoPriority>) -> Result<()> {
+    if let Some(nice) = nice {
+        set_nice(nice)?;
+    }
+    if let Some(io_priority) = io_priority {
+        set_io_priority(io_priority)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_nice(nice: i32) -> Result<()> {
+    // A niceness already at or above the requested one is left alone: lowering it again
+    // would need privileges, and would undo what whoever started the run asked for
+    let current = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+    if nice <= current {
+        return Ok(());
+    }
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+        anyhow::bail!("Failed to set --nice {}: {}", nice, std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_nice(_nice: i32) -> Result<()> {
+    anyhow::bail!("--nice is only supported on Unix")
+}
+
+#[cfg(target_os = "linux")]
+fn set_io_priority(priority: IoPriority) -> Result<()> {
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+    let (class, level) = match priority {
+        IoPriority::BestEffort(level) => (2, level as libc::c_int),
+        IoPriority::Idle => (3, 0),
+    };
+    let ioprio = (class << IOPRIO_CLASS_SHIFT) | level;
+    if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ioprio) } != 0 {
+        anyhow::bail!("Failed to set the I/O priority: {}", std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_io_priority(_priority: IoPriority) -> Result<()> {
+    anyhow::bail!("--io-priority is only supported on Linux")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_priority_specs() {
+        assert_eq!(IoPriority::parse("idle").unwrap(), IoPriority::Idle);
+        assert_eq!(IoPriority::parse("best-effort").unwrap(), IoPriority::BestEffort(7));
+        assert_eq!(IoPriority::parse("Best-Effort:4").unwrap(), IoPriority::BestEffort(4));
+        assert!(IoPriority::parse("best-effort:8").is_err());
+        assert!(IoPriority::parse("idle:1").is_err());
+        assert!(IoPriority::parse("realtime").is_err());
+    }
+}
@@ -0,0 +1,467 @@
+/// Analysis profiles: ready-made timestamp and pattern settings for well-known tools, built in
+/// or shared as YAML files in a profiles directory or at a URL
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use log_time_analyzer::config::{Config, MessagePattern, PairRule};
+
+/// A named, ready-to-use configuration for a specific kind of log
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// Name used with `--profile`
+    pub name: &'static str,
+    /// What kind of log the profile is for
+    pub description: &'static str,
+    /// Regular expression to extract the timestamp (with a capture group);
+    /// `None` auto-detects it, for tools whose lines are stamped by the CI runner
+    pub timestamp_regex: Option<&'static str>,
+    /// Chrono format string for parsing the timestamp
+    pub timestamp_format: Option<&'static str>,
+    /// Message patterns in priority order, each a regex or `NAME=REGEX` as with `-p`; a
+    /// `label` group names the match
+    pub message_patterns: &'static [&'static str],
+    /// Remove ANSI color codes before matching
+    pub strip_ansi: bool,
+    /// Start/end pairs to measure instead of consecutive matches
+    pub pairs: &'static [(&'static str, &'static str)],
+    /// Regex extracting the key pairs are matched on, e.g. a unit name
+    pub correlation_regex: Option<&'static str>,
+}
+
+impl Profile {
+    /// Build the configuration this profile stands for
+    pub fn to_config(&self) -> Config {
+        Config {
+            timestamp_regex: self.timestamp_regex.unwrap_or_default().to_string(),
+            timestamp_format: self.timestamp_format.unwrap_or_default().to_string(),
+            message_patterns: self.message_patterns.iter().map(|&p| MessagePattern::parse(p)).collect(),
+            strip_ansi: self.strip_ansi,
+            pairs: self
+                .pairs
+                .iter()
+                .map(|&(start, end)| PairRule { start: start.to_string(), end: end.to_string() })
+                .collect(),
+            correlation_regex: self.correlation_regex.map(str::to_string),
+            is_auto_detect: self.timestamp_regex.is_none(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Get all built-in profiles
+pub fn get_profiles() -> Vec<Profile> {
+    vec![
+        // MSBuild with -clp:ShowTimestamp, dotnet build and dotnet test
+        Profile {
+            name: "dotnet",
+            description: "MSBuild / dotnet build and test output with HH:MM:SS.fff timestamps",
+            timestamp_regex: Some(r"^\s*(\d{2}:\d{2}:\d{2}(?:\.\d+)?)\b"),
+            timestamp_format: Some("%H:%M:%S%.f"),
+            message_patterns: &[
+                r"Build started",
+                // "MyLib -> /src/MyLib/bin/Debug/net8.0/MyLib.dll" ends a project's build
+                r"\b(?P<label>[\w.-]+) -> \S.*\.(?:dll|exe)\b",
+                r"Starting test execution",
+                // "Passed!  - Failed: 0, Passed: 12, ... - MyLib.Tests.dll (net8.0)"
+                r"(?:Passed|Failed)!\s+-.* - (?P<label>[\w.-]+\.dll)",
+                r"(?P<label>Build succeeded|Build FAILED)",
+                r"Time Elapsed",
+            ],
+            strip_ansi: false,
+            pairs: &[],
+            correlation_regex: None,
+        },
+        // Gradle console output, stamped by the CI runner or `ts`
+        Profile {
+            name: "gradle",
+            description: "Gradle build output, one step per task",
+            timestamp_regex: None,
+            timestamp_format: None,
+            message_patterns: &[
+                r"> Task (?P<label>:\S+)",
+                r"(?P<label>BUILD (?:SUCCESSFUL|FAILED))",
+            ],
+            strip_ansi: false,
+            pairs: &[],
+            correlation_regex: None,
+        },
+        // Maven console output, stamped by the CI runner or simplelogger's showDateTime
+        Profile {
+            name: "maven",
+            description: "Maven build output, one step per module and plugin goal",
+            timestamp_regex: None,
+            timestamp_format: None,
+            message_patterns: &[
+                // "[INFO] Building my-app 1.0-SNAPSHOT  [2/5]" (but not "Building jar: ...")
+                r"\[INFO\] Building (?P<label>[\w.-]+ [\w.-]+)(?:\s+\[\d+/\d+\])?\s*$",
+                // "[INFO] --- compiler:3.11.0:compile (default-compile) @ my-app ---"
+                r"\[INFO\] --- (?P<label>.+?) ---",
+                r"\[INFO\] (?P<label>BUILD (?:SUCCESS|FAILURE))",
+            ],
+            strip_ansi: false,
+            pairs: &[],
+            correlation_regex: None,
+        },
+        // GitHub Actions raw job logs: every line starts with a 7-digit-fraction UTC stamp
+        Profile {
+            name: "github-actions",
+            description: "GitHub Actions raw job logs, one step per ##[group] marker",
+            timestamp_regex: Some(r"^\x{feff}?(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?)Z"),
+            timestamp_format: Some("%Y-%m-%dT%H:%M:%S%.f"),
+            message_patterns: &[
+                r"##\[group\](?P<label>.+)",
+                r"##\[(?P<label>error)\]",
+                r"Cleaning up orphan processes",
+            ],
+            strip_ansi: true,
+            pairs: &[],
+            correlation_regex: None,
+        },
+        // Jenkins console output with the Timestamper plugin; the prefix is either
+        // `[2025-11-13T10:00:00.123Z]` or `[10:00:00]`, both covered by auto-detection
+        Profile {
+            name: "jenkins",
+            description: "Jenkins pipeline console output with Timestamper prefixes, one step per stage",
+            timestamp_regex: None,
+            timestamp_format: None,
+            message_patterns: &[
+                // "[Pipeline] { (Build)" opens the "Build" stage
+                r"\[Pipeline\] \{ \((?P<label>[^)]+)\)",
+                r"(?P<label>Finished: [A-Z]+)",
+            ],
+            strip_ansi: true,
+            pairs: &[],
+            correlation_regex: None,
+        },
+        // systemd's journal as `journalctl -b -o short-precise` (or `short-iso-precise`)
+        // prints it: each unit from its "Starting" line to its "Started" line, or "Finished"
+        // for oneshot units, paired per unit. Both end lines share one pattern and one pair, so
+        // each start is closed once, by whichever of them comes
+        Profile {
+            name: "systemd",
+            description: "systemd journal, startup time of every unit from Starting to Started",
+            timestamp_regex: None,
+            timestamp_format: None,
+            message_patterns: &[
+                // "systemd[1]: Starting nginx.service - A high performance web server..."
+                r"systemd\[\d+\]: (?P<label>Starting) (?P<unit>.+?)\.\.\.$",
+                // "systemd[1]: Started nginx.service - A high performance web server."
+                r"Started|Finished=systemd\[\d+\]: (?:Started|Finished) (?P<unit>.+?)\.$",
+            ],
+            strip_ansi: false,
+            pairs: &[("Starting", "Started|Finished")],
+            correlation_regex: Some(r"systemd\[\d+\]: (?:Starting|Started|Finished) (?P<correlation>.+?)\.{1,3}$"),
+        },
+    ]
+}
+
+/// Look up a built-in profile by name
+pub fn find_profile(name: &str) -> Result<Profile> {
+    get_profiles()
+        .into_iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| anyhow::anyhow!("Unknown profile '{}'", name))
+}
+
+/// The directory of one's own and a team's profiles: `$XDG_CONFIG_HOME/log-line/profiles`,
+/// or else `~/.config/log-line/profiles` (`%APPDATA%\log-line\profiles` on Windows)
+pub fn profile_dir() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(config_home.join("log-line").join("profiles"))
+}
+
+/// The part of a profile file only used to list it; the rest is a config file's settings
+#[derive(Deserialize)]
+struct ProfileHeader {
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// Parse a profile file: a config file whose timestamp settings may be left out to
+/// auto-detect them, like those of the built-in profiles, plus an optional `description`
+pub fn parse_profile(yaml: &str) -> Result<Config> {
+    let mut config: Config = serde_yaml::from_str(yaml).context("Failed to parse the profile's YAML")?;
+    config.is_auto_detect = config.timestamp_regex.is_empty()
+        && config.timestamp_format.is_empty()
+        && config.filename_timestamp_regex.is_none()
+        && config.embedded_event_regex.is_none();
+    Ok(config)
+}
+
+/// Look up a profile by name: `<name>.yaml` or `<name>.yml` in `dir`, which takes precedence
+/// so a team can adjust a built-in profile under its own name, or else a built-in profile
+pub fn load_profile(name: &str, dir: Option<&Path>) -> Result<Config> {
+    // The name becomes a file name, so it can't leave the directory
+    let valid_name = !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid_name {
+        anyhow::bail!("Invalid profile name '{}': use letters, digits, '-', '_', and '.'", name);
+    }
+    if let Some(dir) = dir {
+        for extension in ["yaml", "yml"] {
+            let path = dir.join(format!("{}.{}", name, extension));
+            if path.is_file() {
+                let yaml = std::fs::read_to_string(&path).with_context(|| format!("Failed to read profile {:?}", path))?;
+                return parse_profile(&yaml).with_context(|| format!("Invalid profile {:?}", path));
+            }
+        }
+    }
+    match find_profile(name) {
+        Ok(profile) => Ok(profile.to_config()),
+        Err(_) => {
+            let available: Vec<String> = list_profiles(dir)
+                .into_iter()
+                .map(|(name, description)| format!("  {:<14} {}", name, description))
+                .collect();
+            anyhow::bail!("Unknown profile '{}'. Available profiles:\n{}", name, available.join("\n"))
+        }
+    }
+}
+
+/// Names and descriptions of the profiles in `dir`, in name order, then of the built-in ones
+pub fn list_profiles(dir: Option<&Path>) -> Vec<(String, String)> {
+    let mut local: Vec<(String, String)> = dir
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if !matches!(path.extension()?.to_str()?, "yaml" | "yml") {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_string();
+            let description = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|yaml| serde_yaml::from_str::<ProfileHeader>(&yaml).ok())
+                .and_then(|header| header.description)
+                .unwrap_or_else(|| format!("(from {})", path.display()));
+            Some((name, description))
+        })
+        .collect();
+    local.sort();
+    local.extend(get_profiles().into_iter().map(|p| (p.name.to_string(), p.description.to_string())));
+    local
+}
+
+/// Largest profile file `fetch_profile` reads; a profile is a few dozen lines, so anything
+/// bigger is the wrong URL
+const MAX_PROFILE_BYTES: u64 = 1024 * 1024;
+
+/// Download a profile file shared at `url`, e.g. a versioned file in a team's repository.
+/// Only HTTPS is accepted, since the profile decides what is read and run from the log.
+pub fn fetch_profile(url: &str) -> Result<Config> {
+    if !url.starts_with("https://") {
+        anyhow::bail!("--profile-url needs an https:// URL, got '{}'", url);
+    }
+    let response = ureq::get(url).call().with_context(|| format!("Failed to fetch profile {}", url))?;
+    let yaml = read_profile(response.into_reader()).with_context(|| format!("Failed to read profile {}", url))?;
+    parse_profile(&yaml).with_context(|| format!("Invalid profile {}", url))
+}
+
+/// The text of a downloaded profile, up to `MAX_PROFILE_BYTES`
+fn read_profile(reader: impl Read) -> Result<String> {
+    let mut bytes = Vec::new();
+    reader.take(MAX_PROFILE_BYTES + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_PROFILE_BYTES {
+        anyhow::bail!("The profile is larger than {} KiB", MAX_PROFILE_BYTES / 1024);
+    }
+    String::from_utf8(bytes).context("The profile is not UTF-8 text")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log_time_analyzer::analyzer::Analyzer;
+    use log_time_analyzer::parser::LogParser;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_all_profiles_compile() {
+        for profile in get_profiles() {
+            let config = profile.to_config();
+            assert!(LogParser::new(&config).is_ok(), "Profile does not compile: {}", profile.name);
+        }
+    }
+
+    #[test]
+    fn test_dotnet_profile() {
+        let config = find_profile("dotnet").unwrap().to_config();
+        let log = "\
+10:00:00.100 Build started 11/13/2025 10:00:00.
+10:00:04.250   MyLib -> /src/MyLib/bin/Debug/net8.0/MyLib.dll
+10:00:07.900   MyLib.Tests -> /src/MyLib.Tests/bin/Debug/net8.0/MyLib.Tests.dll
+10:00:08.000 Starting test execution, please wait...
+10:00:11.500 Passed!  - Failed:     0, Passed:    12, Skipped:     0, Total:    12, Duration: 3 s - MyLib.Tests.dll (net8.0)
+";
+        let mut parser = LogParser::new(&config).unwrap();
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+        let names: Vec<&str> = matches.iter().map(|m| m.pattern.as_str()).collect();
+
+        assert_eq!(
+            names,
+            vec!["Build started", "MyLib", "MyLib.Tests", "Starting test execution", "MyLib.Tests.dll"]
+        );
+        assert!(find_profile("nope").is_err());
+    }
+
+    #[test]
+    fn test_profiles_directory_comes_before_built_ins() {
+        let dir = std::env::temp_dir().join(format!("ll-profiles-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("nginx.yaml"),
+            "description: nginx access log, one request per line\nmessage_patterns: ['GET /login', 'POST /login']\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("gradle.yml"), "timestamp_regex: '^(\\d+)'\ntimestamp_format: '%s'\nmessage_patterns: [a, b]\n").unwrap();
+
+        let nginx = load_profile("nginx", Some(&dir)).unwrap();
+        assert!(nginx.is_auto_detect);
+        assert_eq!(nginx.message_patterns[1].regex, "POST /login");
+        assert!(!load_profile("gradle", Some(&dir)).unwrap().is_auto_detect);
+        assert!(load_profile("maven", Some(&dir)).unwrap().is_auto_detect);
+
+        let error = load_profile("apache", Some(&dir)).unwrap_err().to_string();
+        assert!(error.contains("nginx          nginx access log"), "{}", error);
+        assert!(load_profile("../nginx", Some(&dir)).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_profile_without_timestamp_settings_auto_detects() {
+        let config = parse_profile("message_patterns: ['Compiling', 'Finished']\n").unwrap();
+        assert!(config.is_auto_detect);
+        assert!(config.validate().is_ok());
+        let log = "2025-11-13 10:00:00 Compiling app\n2025-11-13 10:00:05 Finished dev profile\n";
+        let matches = LogParser::new(&config).unwrap().parse_reader(Cursor::new(log)).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!((matches[1].timestamp - matches[0].timestamp).num_seconds(), 5);
+
+        let stamped = "filename_timestamp_regex: '(\\d{8})'\nmessage_patterns: [a, b]\n";
+        assert!(!parse_profile(stamped).unwrap().is_auto_detect);
+        assert!(parse_profile("message_patterns: a: b").is_err());
+    }
+
+    #[test]
+    fn test_profile_urls_need_https_and_a_small_body() {
+        let error = fetch_profile("http://example.com/nginx.yaml").unwrap_err().to_string();
+        assert!(error.contains("needs an https:// URL"), "{}", error);
+        assert!(fetch_profile("file:///etc/passwd").is_err());
+
+        assert_eq!(read_profile(Cursor::new("message_patterns: [a, b]\n")).unwrap(), "message_patterns: [a, b]\n");
+        let huge = "#".repeat(MAX_PROFILE_BYTES as usize + 1);
+        let error = read_profile(Cursor::new(huge)).unwrap_err().to_string();
+        assert_eq!(error, "The profile is larger than 1024 KiB");
+        assert!(read_profile(Cursor::new(vec![0xff, 0xfe])).is_err());
+    }
+
+    #[test]
+    fn test_github_actions_profile_keeps_sub_microsecond_fraction() {
+        let config = find_profile("github-actions").unwrap().to_config();
+        let log = "\
+\u{feff}2025-11-13T10:00:00.1234567Z ##[group]Run actions/checkout@v4
+2025-11-13T10:00:00.2000000Z with:
+2025-11-13T10:00:00.3000000Z ##[endgroup]
+2025-11-13T10:00:02.5000001Z ##[group]\x1b[36;1mRun cargo build\x1b[0m
+";
+        let mut parser = LogParser::new(&config).unwrap();
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].pattern, "Run actions/checkout@v4");
+        assert_eq!(matches[1].pattern, "Run cargo build");
+        let elapsed = matches[1].timestamp - matches[0].timestamp;
+        assert_eq!(elapsed.num_nanoseconds(), Some(2_376_543_400));
+    }
+
+    #[test]
+    fn test_jenkins_profile_timestamper_variants() {
+        let config = find_profile("jenkins").unwrap().to_config();
+        for log in [
+            "\
+[2025-11-13T10:00:00.000Z] [Pipeline] { (Checkout)
+[2025-11-13T10:00:01.000Z] [Pipeline] sh
+[2025-11-13T10:00:04.500Z] [Pipeline] { (Build)
+[2025-11-13T10:01:00.000Z] Finished: SUCCESS
+",
+            "\
+[23:59:58] [Pipeline] { (Checkout)
+[23:59:59] [Pipeline] sh
+[00:00:01] [Pipeline] { (Build)
+[00:01:00] Finished: SUCCESS
+",
+        ] {
+            let mut parser = LogParser::new(&config).unwrap();
+            let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+            let names: Vec<&str> = matches.iter().map(|m| m.pattern.as_str()).collect();
+
+            assert_eq!(names, vec!["Checkout", "Build", "Finished: SUCCESS"]);
+            assert!(matches[1].timestamp > matches[0].timestamp);
+        }
+    }
+
+    #[test]
+    fn test_systemd_profile_pairs_units() {
+        let config = find_profile("systemd").unwrap().to_config();
+        let log = "\
+Nov 13 10:00:00.100000 host systemd[1]: Starting nginx.service - A high performance web server...
+Nov 13 10:00:00.150000 host systemd[1]: Starting systemd-tmpfiles-setup.service - Create Volatile Files and Directories...
+Nov 13 10:00:00.400000 host systemd[1]: Finished systemd-tmpfiles-setup.service - Create Volatile Files and Directories.
+Nov 13 10:00:01.000000 host systemd[1]: Started Session 1 of User root.
+Nov 13 10:00:01.350000 host systemd[1]: Started nginx.service - A high performance web server.
+Nov 13 10:00:01.400000 host systemd[1]: Reached target multi-user.target - Multi-User System.
+Nov 13 10:00:02.000000 host systemd[1]: Starting systemd-tmpfiles-setup.service - Create Volatile Files and Directories...
+Nov 13 10:00:02.100000 host systemd[1]: Started systemd-tmpfiles-setup.service - Create Volatile Files and Directories.
+";
+        let mut parser = LogParser::new(&config).unwrap();
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+        assert_eq!(matches.len(), 7);
+        let mut intervals = Analyzer::analyze_with(matches, &config);
+        intervals.iter_mut().for_each(|interval| interval.group_by("unit"));
+        let units: Vec<(&str, &str, i64)> = intervals
+            .iter()
+            .map(|i| (i.group.as_deref().unwrap(), i.to_pattern.as_str(), i.duration.num_milliseconds()))
+            .collect();
+
+        assert_eq!(
+            units,
+            [
+                ("systemd-tmpfiles-setup.service - Create Volatile Files and Directories", "Started|Finished", 250),
+                ("nginx.service - A high performance web server", "Started|Finished", 1250),
+                // A restart pairs with its own start, not one left over from the first run
+                ("systemd-tmpfiles-setup.service - Create Volatile Files and Directories", "Started|Finished", 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_maven_profile_pairs_goals() {
+        let config = find_profile("maven").unwrap().to_config();
+        let log = "\
+2025-11-13T10:00:00.000Z [INFO] Building my-app 1.0-SNAPSHOT                  [2/5]
+2025-11-13T10:00:01.000Z [INFO] --- compiler:3.11.0:compile (default-compile) @ my-app ---
+2025-11-13T10:00:06.000Z [INFO] Building jar: /src/my-app/target/my-app-1.0-SNAPSHOT.jar
+2025-11-13T10:00:07.000Z [INFO] BUILD SUCCESS
+";
+        let mut parser = LogParser::new(&config).unwrap();
+        let matches = parser.parse_reader(Cursor::new(log)).unwrap();
+        let names: Vec<&str> = matches.iter().map(|m| m.pattern.as_str()).collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "my-app 1.0-SNAPSHOT",
+                "compiler:3.11.0:compile (default-compile) @ my-app",
+                "BUILD SUCCESS",
+            ]
+        );
+    }
+}
@@ -0,0 +1,230 @@
+/// Machine-readable progress of a run (`--progress-fd`): the parser counts lines, bytes, and
+/// matches as it reads, and a reporter thread writes them as JSON lines to a separate file
+/// descriptor, so a wrapper can show a progress bar without parsing the messages on stderr
+use serde::Serialize;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::error::{LogLineError, Result};
+
+/// How often a progress event is written while the run goes on
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Counters shared between the parser (and its chunk workers) and the reporter
+#[derive(Debug, Default)]
+pub struct Progress {
+    lines_read: AtomicU64,
+    bytes_read: AtomicU64,
+    matches: AtomicU64,
+    files_started: AtomicU64,
+}
+
+/// The counters at one point in time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Counters {
+    pub lines_read: u64,
+    /// Bytes of the lines read, line breaks included; for a compressed file these are the
+    /// decompressed bytes, so they can run past its size on disk
+    pub bytes_read: u64,
+    pub matches: u64,
+    /// Number of the file being read, from 1 (0 for stdin)
+    pub file: u64,
+}
+
+impl Progress {
+    /// Count one line of `bytes` bytes (without its line break) that gave `matches` matches
+    pub(crate) fn record_line(&self, bytes: usize, matches: usize) {
+        self.lines_read.fetch_add(1, Ordering::Relaxed);
+        self.bytes_read.fetch_add(bytes as u64 + 1, Ordering::Relaxed);
+        self.matches.fetch_add(matches as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn start_file(&self) {
+        self.files_started.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn counters(&self) -> Counters {
+        Counters {
+            lines_read: self.lines_read.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            matches: self.matches.load(Ordering::Relaxed),
+            file: self.files_started.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// One line written to the progress descriptor
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// Written once before reading: the number of input files and their total size on disk
+    /// (neither for stdin)
+    Start { files: usize, total_bytes: Option<u64> },
+    /// Written every `HEARTBEAT_INTERVAL`, also when nothing new was read (e.g. while
+    /// following an idle log), so a wrapper can tell a quiet run from a hung one
+    Progress {
+        elapsed_ms: u64,
+        #[serde(flatten)]
+        counters: Counters,
+    },
+    /// Written once when the run ends, successfully or not
+    Done {
+        elapsed_ms: u64,
+        interrupted: bool,
+        #[serde(flatten)]
+        counters: Counters,
+    },
+}
+
+/// Writes the progress events of a run from a thread of its own; the `done` event is written
+/// when the reporter is dropped, so it ends every run, whichever way it returns
+pub struct ProgressReporter {
+    progress: Arc<Progress>,
+    out: Arc<Mutex<Box<dyn Write + Send>>>,
+    started: Instant,
+    interrupted: Arc<AtomicBool>,
+    stop: Option<Sender<()>>,
+    heartbeat: Option<JoinHandle<()>>,
+}
+
+impl ProgressReporter {
+    /// Write the `start` event to `out`, then a `progress` event every `interval` until dropped
+    pub fn start(
+        out: Box<dyn Write + Send>,
+        progress: Arc<Progress>,
+        start: ProgressEvent,
+        interval: Duration,
+        interrupted: Arc<AtomicBool>,
+    ) -> Self {
+        let out = Arc::new(Mutex::new(out));
+        let started = Instant::now();
+        write_event(&out, &start);
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let heartbeat = {
+            let (out, progress) = (out.clone(), progress.clone());
+            thread::spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let event = ProgressEvent::Progress {
+                        elapsed_ms: started.elapsed().as_millis() as u64,
+                        counters: progress.counters(),
+                    };
+                    write_event(&out, &event);
+                }
+            })
+        };
+
+        ProgressReporter {
+            progress,
+            out,
+            started,
+            interrupted,
+            stop: Some(stop),
+            heartbeat: Some(heartbeat),
+        }
+    }
+}
+
+impl Drop for ProgressReporter {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(heartbeat) = self.heartbeat.take() {
+            let _ = heartbeat.join();
+        }
+        let event = ProgressEvent::Done {
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            interrupted: self.interrupted.load(Ordering::SeqCst),
+            counters: self.progress.counters(),
+        };
+        write_event(&self.out, &event);
+    }
+}
+
+/// Write one event as a JSON line. Failures are ignored: a wrapper that stopped reading the
+/// descriptor should not fail the run itself.
+fn write_event(out: &Mutex<Box<dyn Write + Send>>, event: &ProgressEvent) {
+    if let (Ok(mut out), Ok(line)) = (out.lock(), serde_json::to_string(event)) {
+        let _ = writeln!(out, "{}", line).and_then(|_| out.flush());
+    }
+}
+
+/// Take over an inherited file descriptor (e.g. `3` from `ll --progress-fd 3 3>progress.log`)
+/// for writing the progress events
+#[cfg(unix)]
+pub fn open_fd(fd: i32) -> Result<std::fs::File> {
+    use std::os::unix::io::FromRawFd;
+
+    if fd == 0 || fd == 1 {
+        return Err(LogLineError::config(format!("--progress-fd {}: the progress events would mix with the input or the results", fd)));
+    }
+    // Checked first, since a `File` must own an open descriptor
+    if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        return Err(LogLineError::io(format!("--progress-fd {}", fd), std::io::Error::last_os_error()));
+    }
+    Ok(unsafe { std::fs::File::from_raw_fd(fd) })
+}
+
+#[cfg(not(unix))]
+pub fn open_fd(fd: i32) -> Result<std::fs::File> {
+    Err(LogLineError::config(format!("--progress-fd {}: file descriptors are not supported on this platform", fd)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A writer whose output the test can still read after handing it to the reporter
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_events_start_with_start_and_end_with_done() {
+        let output = Shared::default();
+        let progress = Arc::new(Progress::default());
+        let start = ProgressEvent::Start {
+            files: 2,
+            total_bytes: Some(100),
+        };
+        let reporter = ProgressReporter::start(
+            Box::new(output.clone()),
+            progress.clone(),
+            start,
+            Duration::from_millis(5),
+            Arc::new(AtomicBool::new(false)),
+        );
+        progress.start_file();
+        progress.record_line(9, 1);
+        progress.record_line(19, 0);
+        thread::sleep(Duration::from_millis(30));
+        drop(reporter);
+
+        let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<serde_json::Value> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(events[0], serde_json::json!({"event": "start", "files": 2, "total_bytes": 100}));
+        assert!(events.len() > 2, "expected progress events between start and done: {}", text);
+        assert!(events[1..events.len() - 1].iter().all(|event| event["event"] == "progress"));
+
+        let done = events.last().unwrap();
+        assert_eq!(done["event"], "done");
+        assert_eq!(done["interrupted"], false);
+        assert_eq!(done["lines_read"], 2);
+        assert_eq!(done["bytes_read"], 30);
+        assert_eq!(done["matches"], 1);
+        assert_eq!(done["file"], 1);
+    }
+}
@@ -0,0 +1,105 @@
+/// Prometheus text exposition format (`-f prometheus`): the statistics of every transition as
+/// a summary metric, for the node_exporter textfile collector or a Pushgateway
+use crate::stats::TransitionStats;
+
+/// Quantiles reported for every transition
+const QUANTILES: [f64; 4] = [0.5, 0.9, 0.95, 0.99];
+
+/// Escape a label value: backslashes, double quotes, and line feeds
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// `{from="...",to="..."}` of a transition, with its group when grouped and `extra` last
+fn labels(stats: &TransitionStats, extra: Option<(&str, String)>) -> String {
+    let mut labels = vec![
+        format!("from=\"{}\"", escape_label(&stats.from_pattern)),
+        format!("to=\"{}\"", escape_label(&stats.to_pattern)),
+    ];
+    if let Some(group) = &stats.group {
+        labels.push(format!("group=\"{}\"", escape_label(group)));
+    }
+    if let Some((name, value)) = extra {
+        labels.push(format!("{}=\"{}\"", name, value));
+    }
+    format!("{{{}}}", labels.join(","))
+}
+
+/// Seconds of a duration in milliseconds, rounded to the microsecond
+fn seconds(ms: f64) -> f64 {
+    (ms * 1000.0).round() / 1_000_000.0
+}
+
+/// Metrics of every transition with intervals: a `logline_interval_seconds` summary
+/// (quantiles, sum, and count) and gauges of the shortest and longest interval
+pub fn format_metrics(stats: &[TransitionStats]) -> String {
+    let stats: Vec<&TransitionStats> = stats.iter().filter(|s| s.count > 0).collect();
+    let mut lines = vec![
+        "# HELP logline_interval_seconds Time between matched log messages per transition.".to_string(),
+        "# TYPE logline_interval_seconds summary".to_string(),
+    ];
+    for s in &stats {
+        for quantile in QUANTILES {
+            lines.push(format!(
+                "logline_interval_seconds{} {}",
+                labels(s, Some(("quantile", quantile.to_string()))),
+                seconds(s.percentile(quantile * 100.0))
+            ));
+        }
+        lines.push(format!(
+            "logline_interval_seconds_sum{} {}",
+            labels(s, None),
+            seconds(s.sorted_ms.iter().sum::<i64>() as f64)
+        ));
+        lines.push(format!("logline_interval_seconds_count{} {}", labels(s, None), s.count));
+    }
+
+    for (name, help) in [("min", "Shortest"), ("max", "Longest")] {
+        lines.push(format!(
+            "# HELP logline_interval_{}_seconds {} time between matched log messages per transition.",
+            name, help
+        ));
+        lines.push(format!("# TYPE logline_interval_{}_seconds gauge", name));
+        for s in &stats {
+            let ms = if name == "min" { s.min_ms } else { s.max_ms };
+            lines.push(format!("logline_interval_{}_seconds{} {}", name, labels(s, None), seconds(ms as f64)));
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
+    use crate::analyzer::Interval;
+    use crate::stats;
+    use chrono::Duration;
+
+    #[test]
+    fn test_summary_per_transition() {
+        let intervals: Vec<Interval> = [100, 200, 300, 400]
+            .iter()
+            .map(|&ms| Interval::new("GET \"/users\"", "done", Duration::milliseconds(ms)))
+            .collect();
+        let metrics = format_metrics(&stats::compute(&intervals, None));
+        let lines: Vec<&str> = metrics.lines().filter(|line| !line.starts_with('#')).collect();
+
+        assert_eq!(
+            lines,
+            [
+                r#"logline_interval_seconds{from="GET \"/users\"",to="done",quantile="0.5"} 0.25"#,
+                r#"logline_interval_seconds{from="GET \"/users\"",to="done",quantile="0.9"} 0.37"#,
+                r#"logline_interval_seconds{from="GET \"/users\"",to="done",quantile="0.95"} 0.385"#,
+                r#"logline_interval_seconds{from="GET \"/users\"",to="done",quantile="0.99"} 0.397"#,
+                r#"logline_interval_seconds_sum{from="GET \"/users\"",to="done"} 1"#,
+                r#"logline_interval_seconds_count{from="GET \"/users\"",to="done"} 4"#,
+                r#"logline_interval_min_seconds{from="GET \"/users\"",to="done"} 0.1"#,
+                r#"logline_interval_max_seconds{from="GET \"/users\"",to="done"} 0.4"#,
+            ]
+        );
+        assert!(metrics.contains("# TYPE logline_interval_seconds summary\n"));
+        assert!(metrics.contains("# TYPE logline_interval_max_seconds gauge\n"));
+    }
+}
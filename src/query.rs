@@ -0,0 +1,272 @@
+/// Ad-hoc SQL over the matches and intervals of a run, in an in-memory SQLite database (`query`)
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::Connection;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use log_time_analyzer::analyzer::Interval;
+use log_time_analyzer::csv::CsvDialect;
+use log_time_analyzer::output::{OutputFormat, OutputFormatter};
+use log_time_analyzer::parser::LogMatch;
+
+/// Timestamps are stored as text SQLite's date and time functions read, e.g.
+/// `2025-11-13 10:00:00.105`
+pub(crate) const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// Columns of a query result and its rows
+#[derive(Debug, PartialEq)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// A database with a `matches` table of every match and an `intervals` table of every
+/// interval; captured fields and derived values get a column each
+pub fn load(matches: &[LogMatch], intervals: &[Interval]) -> Result<Connection> {
+    let mut db = Connection::open_in_memory().context("Failed to open the query database")?;
+    let tx = db.transaction()?;
+
+    let fields: BTreeSet<&str> = matches.iter().flat_map(|m| m.fields.keys().map(String::as_str)).collect();
+    let mut columns: Vec<String> =
+        ["pattern", "timestamp", "source", "line", "correlation"].map(String::from).to_vec();
+    // A capture named like a built-in column is left out
+    let fields: Vec<String> = fields.into_iter().map(String::from).filter(|name| !columns.contains(name)).collect();
+    columns.extend(fields);
+    let fields: Vec<&str> = columns[5..].iter().map(String::as_str).collect();
+    insert(&tx, "matches", &columns, matches.iter().map(|m| {
+        let mut row = vec![
+            SqlValue::Text(m.pattern.clone()),
+            timestamp(Some(m.timestamp)),
+            text(&m.source),
+            integer(m.line_number),
+            text(&m.correlation),
+        ];
+        row.extend(fields.iter().map(|name| text(&m.fields.get(*name).cloned())));
+        row
+    }))?;
+
+    let mut columns: Vec<String> = [
+        "from_pattern", "to_pattern", "duration_ms", "start", "end", "from_line", "to_line", "correlation", "group",
+        "from_source", "to_source", "tags",
+    ]
+    .map(String::from)
+    .to_vec();
+    let fixed = columns.len();
+    let captured: BTreeSet<String> = intervals
+        .iter()
+        .flat_map(|i| {
+            let from = i.from_fields.keys().map(|name| format!("from_{}", name));
+            from.chain(i.to_fields.keys().map(|name| format!("to_{}", name)))
+        })
+        .collect();
+    let derived: BTreeSet<&str> = intervals.iter().flat_map(|i| i.derived.keys().map(String::as_str)).collect();
+    // A derived value or capture named like a built-in column is left out
+    columns.extend(captured.into_iter().filter(|name| !columns.contains(name)).collect::<Vec<_>>());
+    let captures = columns.len() - fixed;
+    columns.extend(derived.iter().map(|name| name.to_string()).filter(|name| !columns.contains(name)).collect::<Vec<_>>());
+    let extra: Vec<String> = columns[fixed..].to_vec();
+    insert(&tx, "intervals", &columns, intervals.iter().map(|i| {
+        let mut row = vec![
+            SqlValue::Text(i.from_pattern.clone()),
+            SqlValue::Text(i.to_pattern.clone()),
+            SqlValue::Integer(i.duration.num_milliseconds()),
+            timestamp(i.start),
+            timestamp(i.end),
+            integer(i.from_line),
+            integer(i.to_line),
+            text(&i.correlation),
+            text(&i.group),
+            text(&i.from_source),
+            text(&i.to_source),
+            SqlValue::Text(i.tags.join(",")),
+        ];
+        for (n, name) in extra.iter().enumerate() {
+            let value = if n < captures {
+                match name.split_once('_') {
+                    Some(("from", field)) => text(&i.from_fields.get(field).cloned()),
+                    Some((_, field)) => text(&i.to_fields.get(field).cloned()),
+                    None => SqlValue::Null,
+                }
+            } else {
+                i.derived.get(name).map_or(SqlValue::Null, |&value| SqlValue::Real(value))
+            };
+            row.push(value);
+        }
+        row
+    }))?;
+
+    tx.commit()?;
+    Ok(db)
+}
+
+fn insert(db: &Connection, table: &str, columns: &[String], rows: impl Iterator<Item = Vec<SqlValue>>) -> Result<()> {
+    let names: Vec<String> = columns.iter().map(|name| identifier(name)).collect();
+    db.execute(&format!("CREATE TABLE {} ({})", table, names.join(", ")), [])?;
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let mut statement = db.prepare(&format!("INSERT INTO {} VALUES ({})", table, placeholders))?;
+    for row in rows {
+        statement.execute(rusqlite::params_from_iter(row))?;
+    }
+    Ok(())
+}
+
+fn identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+pub(crate) fn text(value: &Option<String>) -> SqlValue {
+    value.clone().map_or(SqlValue::Null, SqlValue::Text)
+}
+
+pub(crate) fn integer(value: Option<usize>) -> SqlValue {
+    value.map_or(SqlValue::Null, |value| SqlValue::Integer(value as i64))
+}
+
+pub(crate) fn timestamp(value: Option<NaiveDateTime>) -> SqlValue {
+    value.map_or(SqlValue::Null, |value| SqlValue::Text(value.format(TIMESTAMP_FORMAT).to_string()))
+}
+
+/// Run one SQL statement and collect its result
+pub fn run(db: &Connection, sql: &str) -> Result<QueryResult> {
+    let mut statement = db.prepare(sql).context("Invalid query")?;
+    let columns: Vec<String> = statement.column_names().into_iter().map(String::from).collect();
+    let mut rows = Vec::new();
+    let mut results = statement.query([]).context("Query failed")?;
+    while let Some(row) = results.next().context("Query failed")? {
+        let values = (0..columns.len())
+            .map(|i| {
+                Ok(match row.get_ref(i)? {
+                    ValueRef::Null => Value::Null,
+                    ValueRef::Integer(n) => Value::from(n),
+                    ValueRef::Real(x) => Value::from(x),
+                    ValueRef::Text(text) => Value::from(String::from_utf8_lossy(text).into_owned()),
+                    ValueRef::Blob(bytes) => Value::from(format!("<{} bytes>", bytes.len())),
+                })
+            })
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.push(values);
+    }
+    Ok(QueryResult { columns, rows })
+}
+
+/// A result row as a JSON object with its keys in column order
+struct JsonRow<'a>(&'a [String], &'a [Value]);
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (column, value) in self.0.iter().zip(self.1) {
+            map.serialize_entry(column, value)?;
+        }
+        map.end()
+    }
+}
+
+impl QueryResult {
+    /// The rows as JSON objects (an array, or one per line for jsonl), CSV or TSV with a header, or a table (markdown or aligned
+    /// columns) for any other format
+    pub fn format(&self, format: OutputFormat, dialect: CsvDialect) -> String {
+        let cell = |value: &Value| match value {
+            Value::Null => String::new(),
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        match format {
+            OutputFormat::Json => {
+                let objects: Vec<JsonRow> = self.rows.iter().map(|row| JsonRow(&self.columns, row)).collect();
+                serde_json::to_string_pretty(&objects).unwrap_or_else(|_| "[]".to_string())
+            }
+            OutputFormat::Jsonl => self
+                .rows
+                .iter()
+                .filter_map(|row| serde_json::to_string(&JsonRow(&self.columns, row)).ok())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            OutputFormat::Csv => {
+                let names: Vec<&str> = self.columns.iter().map(String::as_str).collect();
+                let mut lines = vec![dialect.header(&names)];
+                lines.extend(self.rows.iter().map(|row| {
+                    let fields: Vec<(String, bool)> = row.iter().map(|value| (cell(value), value.is_string())).collect();
+                    dialect.record(&fields)
+                }));
+                lines.join("\n")
+            }
+            OutputFormat::Tsv => {
+                let mut lines = vec![self.columns.join("\t")];
+                lines.extend(self.rows.iter().map(|row| {
+                    row.iter().map(|value| cell(value).replace('\t', "    ").replace('\n', " ")).collect::<Vec<_>>().join("\t")
+                }));
+                lines.join("\n")
+            }
+            _ => {
+                let titles: Vec<&str> = self.columns.iter().map(String::as_str).collect();
+                let cells: Vec<Vec<String>> = self.rows.iter().map(|row| row.iter().map(cell).collect()).collect();
+                // A column is right-aligned when it holds numbers only
+                let numeric = |col: usize| {
+                    self.rows.iter().any(|row| row[col].is_number())
+                        && self.rows.iter().all(|row| row[col].is_number() || row[col].is_null())
+                };
+                OutputFormatter::format_grid(&titles, &cells, numeric, format)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+    use chrono::Duration;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_sql_over_matches_and_intervals() {
+        let ts = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.3f").unwrap();
+        let matches = vec![LogMatch {
+            pattern: "start".to_string(),
+            timestamp: ts("2025-11-13 10:00:00.105"),
+            fields: BTreeMap::from([("endpoint".to_string(), "GET /users".to_string())]),
+            ..Default::default()
+        }];
+        let interval = |ms: i64, endpoint: &str| Interval {
+            from_fields: BTreeMap::from([("endpoint".to_string(), endpoint.to_string())]),
+            derived: BTreeMap::from([("per_item".to_string(), ms as f64 / 4.0)]),
+            ..Interval::new("start", "end", Duration::milliseconds(ms))
+        };
+        let intervals = vec![interval(120, "GET /users"), interval(900, "GET /orders"), interval(300, "GET /users")];
+
+        let db = load(&matches, &intervals).unwrap();
+        let result = run(
+            &db,
+            "SELECT from_endpoint AS endpoint, count(*) AS n, max(duration_ms) AS max_ms, sum(per_item) AS items
+             FROM intervals GROUP BY from_endpoint ORDER BY max_ms DESC",
+        )
+        .unwrap();
+        assert_eq!(result.columns, vec!["endpoint", "n", "max_ms", "items"]);
+        assert_eq!(
+            result.format(OutputFormat::Table, CsvDialect::default()),
+            "| endpoint    | n | max_ms | items |\n|-------------|---|--------|-------|\n\
+             | GET /orders | 1 |    900 | 225.0 |\n| GET /users  | 2 |    300 | 105.0 |"
+        );
+        assert_eq!(
+            result.format(OutputFormat::Csv, CsvDialect::default()),
+            "endpoint,n,max_ms,items\n\"GET /orders\",1,900,225.0\n\"GET /users\",2,300,105.0"
+        );
+
+        assert!(result.format(OutputFormat::Json, CsvDialect::default()).starts_with("[\n  {\n    \"endpoint\": \"GET /orders\",\n    \"n\": 1,"));
+        assert_eq!(
+            result.format(OutputFormat::Jsonl, CsvDialect::default()).lines().next(),
+            Some(r#"{"endpoint":"GET /orders","n":1,"max_ms":900,"items":225.0}"#)
+        );
+
+        let result = run(&db, "SELECT pattern, timestamp, endpoint, line FROM matches").unwrap();
+        assert_eq!(
+            result.rows[0],
+            vec![Value::from("start"), Value::from("2025-11-13 10:00:00.105"), Value::from("GET /users"), Value::Null]
+        );
+        assert!(run(&db, "SELECT nope FROM intervals").is_err());
+    }
+}
@@ -0,0 +1,332 @@
+/// Per-day trend reports over a directory of dated or rotated logs (`report` subcommand)
+use anyhow::{Context, Result};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use log_time_analyzer::analyzer::Analyzer;
+use log_time_analyzer::config::Config;
+use log_time_analyzer::locale::Locale;
+use log_time_analyzer::parser::{LogMatch, LogParser};
+use log_time_analyzer::stats::{self, human_ms, TransitionStats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, ()> {
+        match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Statistics of every transition seen on one day
+#[derive(Debug)]
+pub struct DayReport {
+    pub day: NaiveDate,
+    pub stats: Vec<TransitionStats>,
+}
+
+/// Per-day statistics for all log files of a directory over a period
+#[derive(Debug)]
+pub struct Report {
+    pub dir: PathBuf,
+    /// Only matches at or after this time are included
+    pub since: NaiveDateTime,
+    pub files: usize,
+    /// Days with at least one match, oldest first
+    pub days: Vec<DayReport>,
+}
+
+/// Analyze every log file under `dir` that was written to during the last `period`, one
+/// day at a time (intervals spanning midnight are not measured)
+pub fn build(parser: &mut LogParser, config: &Config, dir: &Path, period: Duration) -> Result<Report> {
+    let now = Local::now();
+    let since = now.naive_local() - period;
+
+    // Files last modified before the period can't contain any of its lines
+    let files = find_log_files(dir, SystemTime::from(now - period))?;
+    if files.is_empty() {
+        anyhow::bail!("No log files in {:?} were modified in the reporting period", dir);
+    }
+
+    let matches = parser.parse_files(&files).context("Failed to parse log files")?;
+
+    let mut by_day: BTreeMap<NaiveDate, Vec<LogMatch>> = BTreeMap::new();
+    for log_match in matches.into_iter().filter(|m| m.timestamp >= since) {
+        by_day.entry(log_match.timestamp.date()).or_default().push(log_match);
+    }
+
+    let mut days = Vec::new();
+    for (day, matches) in by_day {
+        let intervals = Analyzer::apply_min_duration(Analyzer::analyze_with(matches, config), config)?;
+        days.push(DayReport {
+            day,
+            stats: stats::compute(&intervals, None),
+        });
+    }
+
+    Ok(Report {
+        dir: dir.to_path_buf(),
+        since,
+        files: files.len(),
+        days,
+    })
+}
+
+/// Regular files under `dir`, recursively, modified at or after `cutoff`, in path order.
+/// Hidden files and directories are skipped.
+pub fn find_log_files(dir: &Path, cutoff: SystemTime) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir).with_context(|| format!("Failed to read directory {:?}", dir))?;
+        for entry in entries {
+            let entry = entry.with_context(|| format!("Failed to read directory {:?}", dir))?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let metadata = entry.metadata().with_context(|| format!("Failed to read {:?}", entry.path()))?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() && metadata.modified().is_ok_and(|modified| modified >= cutoff) {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// One row of a transition's trend table
+struct TrendRow {
+    day: NaiveDate,
+    /// `None` on days the transition didn't occur
+    stats: Option<TrendStats>,
+    /// Change of the p95 from the previous day the transition occurred, in percent
+    p95_change: Option<f64>,
+}
+
+struct TrendStats {
+    count: usize,
+    mean_ms: f64,
+    p95_ms: f64,
+    max_ms: i64,
+}
+
+impl Report {
+    /// The report, with its counts, durations, and changes in `locale`'s conventions if given
+    pub fn format(&self, format: ReportFormat, locale: Option<Locale>) -> String {
+        match format {
+            ReportFormat::Markdown => self.format_markdown(locale),
+            ReportFormat::Html => self.format_html(locale),
+        }
+    }
+
+    fn title(&self) -> String {
+        format!("Log time report: {}", self.dir.display())
+    }
+
+    fn period(&self) -> String {
+        format!(
+            "Since {}: {} files, {} days with matches",
+            self.since.format("%Y-%m-%d %H:%M"),
+            self.files,
+            self.days.len()
+        )
+    }
+
+    /// Transitions in order of first appearance, with one row per reported day
+    fn trends(&self) -> Vec<((String, String), Vec<TrendRow>)> {
+        let mut transitions: Vec<(String, String)> = Vec::new();
+        for day in &self.days {
+            for s in &day.stats {
+                let key = (s.from_pattern.clone(), s.to_pattern.clone());
+                if !transitions.contains(&key) {
+                    transitions.push(key);
+                }
+            }
+        }
+
+        transitions
+            .into_iter()
+            .map(|key| {
+                let mut previous_p95 = None;
+                let rows = self
+                    .days
+                    .iter()
+                    .map(|day| {
+                        let stats = day
+                            .stats
+                            .iter()
+                            .find(|s| s.from_pattern == key.0 && s.to_pattern == key.1 && s.count > 0)
+                            .map(|s| TrendStats {
+                                count: s.count,
+                                mean_ms: s.mean_ms,
+                                p95_ms: s.percentile(95.0),
+                                max_ms: s.max_ms,
+                            });
+                        let p95_change = stats.as_ref().and_then(|s| {
+                            let change = previous_p95
+                                .filter(|&previous: &f64| previous > 0.0)
+                                .map(|previous| (s.p95_ms - previous) / previous * 100.0);
+                            previous_p95 = Some(s.p95_ms);
+                            change
+                        });
+                        TrendRow { day: day.day, stats, p95_change }
+                    })
+                    .collect();
+                (key, rows)
+            })
+            .collect()
+    }
+
+    fn format_markdown(&self, locale: Option<Locale>) -> String {
+        let mut output = format!("# {}\n\n{}\n", self.title(), self.period());
+
+        for ((from, to), rows) in self.trends() {
+            output.push_str(&format!("\n## {} -> {}\n\n", from, to));
+            output.push_str("| Day | Count | Mean | P95 | Max | P95 vs previous day |\n");
+            output.push_str("|-----|------:|-----:|----:|----:|--------------------:|\n");
+            for row in rows {
+                let cells = row_cells(&row, locale);
+                output.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+        }
+
+        output.trim_end().to_string()
+    }
+
+    fn format_html(&self, locale: Option<Locale>) -> String {
+        let title = escape_html(&self.title());
+        let mut output = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+             <style>table {{ border-collapse: collapse; }} th, td {{ border: 1px solid #ccc; padding: 4px 8px; }} \
+             td {{ text-align: right; }} td:first-child {{ text-align: left; }}</style>\n\
+             </head>\n<body>\n<h1>{}</h1>\n<p>{}</p>\n",
+            title,
+            title,
+            escape_html(&self.period())
+        );
+
+        for ((from, to), rows) in self.trends() {
+            output.push_str(&format!("<h2>{} -&gt; {}</h2>\n<table>\n", escape_html(&from), escape_html(&to)));
+            output.push_str(
+                "<tr><th>Day</th><th>Count</th><th>Mean</th><th>P95</th><th>Max</th><th>P95 vs previous day</th></tr>\n",
+            );
+            for row in rows {
+                let cells: Vec<String> = row_cells(&row, locale)
+                    .iter()
+                    .map(|cell| format!("<td>{}</td>", escape_html(cell)))
+                    .collect();
+                output.push_str(&format!("<tr>{}</tr>\n", cells.concat()));
+            }
+            output.push_str("</table>\n");
+        }
+
+        output.push_str("</body>\n</html>");
+        output
+    }
+}
+
+/// Day, count, mean, p95, max, and p95 change of a trend row
+fn row_cells(row: &TrendRow, locale: Option<Locale>) -> Vec<String> {
+    let day = row.day.to_string();
+    match (&row.stats, locale) {
+        (Some(s), None) => vec![
+            day,
+            s.count.to_string(),
+            human_ms(s.mean_ms),
+            human_ms(s.p95_ms),
+            human_ms(s.max_ms as f64),
+            row.p95_change.map(|change| format!("{:+.1}%", change)).unwrap_or_default(),
+        ],
+        (Some(s), Some(locale)) => vec![
+            day,
+            locale.integer(s.count as i64),
+            locale.ms(s.mean_ms),
+            locale.ms(s.p95_ms),
+            locale.ms(s.max_ms as f64),
+            row.p95_change.map(|change| locale.percent(change)).unwrap_or_default(),
+        ],
+        (None, _) => vec![day, "0".to_string(), String::new(), String::new(), String::new(), String::new()],
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+    use chrono::NaiveDateTime;
+
+    fn stats_for(day: &str, durations_ms: &[i64]) -> DayReport {
+        let intervals: Vec<_> = durations_ms
+            .iter()
+            .map(|&ms| log_time_analyzer::analyzer::Interval::new("start", "end", Duration::milliseconds(ms)))
+            .collect();
+        DayReport {
+            day: day.parse().unwrap(),
+            stats: stats::compute(&intervals, None),
+        }
+    }
+
+    #[test]
+    fn test_markdown_trend_report() {
+        let report = Report {
+            dir: PathBuf::from("/var/log/app"),
+            since: NaiveDateTime::parse_from_str("2025-11-10 00:00", "%Y-%m-%d %H:%M").unwrap(),
+            files: 3,
+            days: vec![
+                stats_for("2025-11-10", &[100, 200]),
+                DayReport { day: "2025-11-11".parse().unwrap(), stats: Vec::new() },
+                stats_for("2025-11-12", &[300]),
+            ],
+        };
+
+        let markdown = report.format(ReportFormat::Markdown, None);
+        assert!(markdown.starts_with("# Log time report: /var/log/app\n\nSince 2025-11-10 00:00: 3 files, 3 days with matches"));
+        assert!(markdown.contains("## start -> end"));
+        assert!(markdown.contains("| 2025-11-10 | 2 | 150ms | 195ms | 200ms |  |"));
+        assert!(markdown.contains("| 2025-11-11 | 0 |  |  |  |  |"));
+        assert!(markdown.contains("| 2025-11-12 | 1 | 300ms | 300ms | 300ms | +53.8% |"));
+
+        let html = report.format(ReportFormat::Html, None);
+        assert!(html.contains("<h2>start -&gt; end</h2>"));
+        assert!(html.contains("<td>+53.8%</td>"));
+
+        let german = report.format(ReportFormat::Html, Locale::parse("de"));
+        assert!(german.contains("<td>+53,8\u{202F}%</td>"));
+    }
+
+    #[test]
+    fn test_find_log_files_skips_hidden_and_old_files() {
+        let dir = std::env::temp_dir().join(format!("ll-report-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("2025-11-12")).unwrap();
+        fs::write(dir.join("app.log"), "").unwrap();
+        fs::write(dir.join("2025-11-12/app.log.1"), "").unwrap();
+        fs::write(dir.join(".hidden.log"), "").unwrap();
+
+        let all = find_log_files(&dir, SystemTime::UNIX_EPOCH).unwrap();
+        let future = find_log_files(&dir, SystemTime::now() + std::time::Duration::from_secs(3600)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(all, vec![dir.join("2025-11-12/app.log.1"), dir.join("app.log")]);
+        assert!(future.is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+/// Descriptions of the machine-readable outputs for downstream tools (`--print-schema`)
+use serde_json::{json, Map, Value};
+
+/// Version of the machine-readable output layout. It goes up when a field is renamed or
+/// removed or changes type; new optional fields don't change it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Output whose layout `--print-schema` describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    /// `-f json`: an array of objects, or a page object with `--page-size`
+    Json,
+    /// One object per line, as `-f jsonl` prints, and `-f json` in follow mode or with `--append`
+    Jsonl,
+    /// `-f csv` and `-f tsv` columns
+    Csv,
+}
+
+impl std::str::FromStr for SchemaFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(SchemaFormat::Json),
+            "jsonl" | "ndjson" => Ok(SchemaFormat::Jsonl),
+            "csv" | "tsv" => Ok(SchemaFormat::Csv),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A field of an output record, in output order
+struct Field {
+    name: &'static str,
+    /// JSON Schema type of the value in JSON; CSV cells are always text
+    kind: &'static str,
+    /// Always present (otherwise only with the setting named in the description)
+    required: bool,
+    description: &'static str,
+}
+
+const fn field(name: &'static str, kind: &'static str, required: bool, description: &'static str) -> Field {
+    Field {
+        name,
+        kind,
+        required,
+        description,
+    }
+}
+
+const INTERVAL_FIELDS: [Field; 19] = [
+    field("run_id", "string", false, "ID of the run that appended the row (--append with --run-id)"),
+    field("from_pattern", "string", true, "Name of the pattern (or captured label) the interval starts at"),
+    field("to_pattern", "string", true, "Name of the pattern (or captured label) the interval ends at"),
+    field("duration_ms", "integer", true, "Duration in whole milliseconds; negative when timestamps go backwards"),
+    field("duration_human", "string", true, "Duration for display, e.g. \"1s 250ms\""),
+    field("start", "string", false, "Timestamp of the starting match, ISO 8601 without a zone (--include-source)"),
+    field("end", "string", false, "Timestamp of the ending match, ISO 8601 without a zone (--include-source)"),
+    field("from_line", "integer", false, "Line number of the starting match in its input, from 1 (--include-source)"),
+    field("to_line", "integer", false, "Line number of the ending match in its input, from 1 (--include-source)"),
+    field("from_raw_line", "string", false, "The starting line as read (--raw-lines)"),
+    field("to_raw_line", "string", false, "The ending line as read (--raw-lines)"),
+    field("correlation", "string", false, "Correlation key shared by both matches (correlation_regex)"),
+    field("group", "string", false, "Value of the --group-by capture on the starting (or else ending) match"),
+    field("from_source", "string", false, "File or host of the starting match, with several inputs"),
+    field("to_source", "string", false, "File or host of the ending match, with several inputs"),
+    field("cross_source", "boolean", false, "Whether the two matches come from different sources"),
+    field("from_<name>", "string", false, "Named capture <name> of the starting match, one field per capture"),
+    field("to_<name>", "string", false, "Named capture <name> of the ending match, one field per capture"),
+    field("<derived>", "number", false, "Value of each configured derived expression, by its name"),
+];
+
+/// Tags come after the dynamic fields
+const TAGS_FIELD: Field = field("tags", "array", false, "Tags of the matching tags rules; comma-separated in CSV");
+
+const STATS_FIELDS: [Field; 11] = [
+    field("group", "string", false, "Value of the --group-by capture the row's intervals share"),
+    field("from_pattern", "string", true, "Pattern the transition starts at"),
+    field("to_pattern", "string", true, "Pattern the transition ends at"),
+    field("count", "integer", true, "Number of intervals in the statistics (after --trim)"),
+    field("min_ms", "integer", true, "Shortest duration in milliseconds"),
+    field("max_ms", "integer", true, "Longest duration in milliseconds"),
+    field("mean_ms", "number", true, "Mean duration in milliseconds"),
+    field("median_ms", "number", true, "Median duration in milliseconds"),
+    field("p95_ms", "number", true, "95th percentile in milliseconds"),
+    field("p99_ms", "number", true, "99th percentile in milliseconds"),
+    field("stddev_ms", "number", true, "Population standard deviation in milliseconds"),
+];
+
+impl Field {
+    /// Placeholders such as `from_<name>` stand for a family of fields
+    fn is_dynamic(&self) -> bool {
+        self.name.contains('<')
+    }
+
+    fn json_schema(&self) -> Value {
+        let mut schema = json!({ "type": self.kind, "description": self.description });
+        if self.kind == "array" {
+            schema["items"] = json!({ "type": "string" });
+        }
+        schema
+    }
+}
+
+/// The schema (JSON formats) or column dictionary (CSV) of the interval listing, or of the
+/// `--stats` rows with `stats`, as pretty-printed JSON
+pub fn print_schema(format: SchemaFormat, stats: bool) -> String {
+    let fields: Vec<&Field> = if stats {
+        STATS_FIELDS.iter().collect()
+    } else {
+        INTERVAL_FIELDS.iter().chain([&TAGS_FIELD]).collect()
+    };
+    let name = if stats { "transition-stats" } else { "intervals" };
+
+    let document = match format {
+        SchemaFormat::Csv => column_dictionary(name, &fields),
+        SchemaFormat::Jsonl => {
+            let mut schema = record_schema(&fields);
+            schema.insert("$schema".to_string(), json!(JSON_SCHEMA_DIALECT));
+            schema.insert("$id".to_string(), json!(format!("log-time-analyzer/{}.jsonl/v{}", name, SCHEMA_VERSION)));
+            schema.insert("x-schema-version".to_string(), json!(SCHEMA_VERSION));
+            Value::Object(schema)
+        }
+        SchemaFormat::Json => {
+            let array = json!({ "type": "array", "items": { "$ref": "#/$defs/record" } });
+            // Only the interval listing can be paginated
+            let root = if stats {
+                array
+            } else {
+                json!({ "oneOf": [array, page_schema()] })
+            };
+            let mut schema = json!({
+                "$schema": JSON_SCHEMA_DIALECT,
+                "$id": format!("log-time-analyzer/{}.json/v{}", name, SCHEMA_VERSION),
+                "x-schema-version": SCHEMA_VERSION,
+                "$defs": { "record": record_schema(&fields) },
+            });
+            if let (Some(schema), Value::Object(root)) = (schema.as_object_mut(), root) {
+                schema.extend(root);
+            }
+            schema
+        }
+    };
+
+    serde_json::to_string_pretty(&document).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// JSON Schema of one record; dynamic fields are covered by `additionalProperties`
+fn record_schema(fields: &[&Field]) -> Map<String, Value> {
+    let properties: Map<String, Value> = fields
+        .iter()
+        .filter(|f| !f.is_dynamic())
+        .map(|f| (f.name.to_string(), f.json_schema()))
+        .collect();
+    let required: Vec<&str> = fields.iter().filter(|f| f.required).map(|f| f.name).collect();
+    let dynamic: Vec<String> = fields
+        .iter()
+        .filter(|f| f.is_dynamic())
+        .map(|f| format!("{} ({})", f.name, f.description))
+        .collect();
+
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), json!("object"));
+    schema.insert("properties".to_string(), Value::Object(properties));
+    schema.insert("required".to_string(), json!(required));
+    if dynamic.is_empty() {
+        schema.insert("additionalProperties".to_string(), json!(false));
+    } else {
+        schema.insert(
+            "additionalProperties".to_string(),
+            json!({ "type": ["string", "number"], "description": dynamic.join("; ") }),
+        );
+    }
+    schema
+}
+
+/// The page object printed with `--page-size`
+fn page_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "schema_version": { "type": "integer", "const": SCHEMA_VERSION },
+            "page": { "type": "integer", "minimum": 1 },
+            "page_size": { "type": "integer", "minimum": 1 },
+            "total_pages": { "type": "integer", "minimum": 1 },
+            "total_intervals": { "type": "integer", "minimum": 0 },
+            "next_page": { "type": ["integer", "null"], "description": "Null on the last page" },
+            "intervals": { "type": "array", "items": { "$ref": "#/$defs/record" } },
+        },
+        "required": ["schema_version", "page", "page_size", "total_pages", "total_intervals", "next_page", "intervals"],
+    })
+}
+
+/// Columns in header order; optional columns only appear when some row has a value
+fn column_dictionary(name: &str, fields: &[&Field]) -> Value {
+    let columns: Vec<Value> = fields
+        .iter()
+        .map(|f| {
+            json!({
+                "name": f.name,
+                "type": if f.kind == "array" { "string" } else { f.kind },
+                "always_present": f.required,
+                "description": f.description,
+            })
+        })
+        .collect();
+    json!({
+        "format": "csv",
+        "output": name,
+        "schema_version": SCHEMA_VERSION,
+        "description": "Columns in header order; TSV has the same columns. Optional columns are only \
+                        present when some row has a value, and empty cells mean no value.",
+        "columns": columns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
+    use crate::csv::CsvDialect;
+    use crate::analyzer::Interval;
+    use crate::output::{OutputFormat, OutputFormatter};
+    use chrono::{Duration, NaiveDate};
+
+    fn interval_with_every_field() -> Interval {
+        let start = NaiveDate::from_ymd_opt(2025, 11, 13).unwrap().and_hms_opt(10, 0, 0).unwrap();
+        let mut interval = Interval {
+            from_source: Some("a.log".to_string()),
+            to_source: Some("a.log".to_string()),
+            correlation: Some("req-1".to_string()),
+            group: Some("GET /users".to_string()),
+            tags: vec!["slow".to_string()],
+            from_line: Some(1),
+            to_line: Some(2),
+            from_raw_line: Some("line 1".to_string()),
+            to_raw_line: Some("line 2".to_string()),
+            ..Interval::new("A", "B", Duration::seconds(1)).starting_at(start)
+        };
+        interval.from_fields.insert("user".to_string(), "u".to_string());
+        interval.to_fields.insert("status".to_string(), "200".to_string());
+        interval.derived.insert("per_item".to_string(), 0.5);
+        interval
+    }
+
+    const DYNAMIC: [&str; 3] = ["from_user", "to_status", "per_item"];
+
+    #[test]
+    fn test_json_schema_describes_every_field() {
+        let output = OutputFormatter::format_appendable(&[interval_with_every_field()], OutputFormat::Json, Some("r1"), CsvDialect::default());
+        let record: Value = serde_json::from_str(&output).unwrap();
+        let mut keys: Vec<&str> = record.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.retain(|key| !DYNAMIC.contains(key));
+
+        let schema: Value = serde_json::from_str(&print_schema(SchemaFormat::Json, false)).unwrap();
+        let mut properties: Vec<&str> =
+            schema["$defs"]["record"]["properties"].as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        properties.sort_unstable();
+        assert_eq!(keys, properties);
+        assert_eq!(schema["x-schema-version"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_csv_dictionary_matches_header() {
+        let output = OutputFormatter::format_appendable(&[interval_with_every_field()], OutputFormat::Csv, Some("r1"), CsvDialect::default());
+        let header: Vec<&str> = output.lines().next().unwrap().split(',').filter(|c| !DYNAMIC.contains(c)).collect();
+
+        let dictionary: Value = serde_json::from_str(&print_schema(SchemaFormat::Csv, false)).unwrap();
+        let columns: Vec<&str> = dictionary["columns"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["name"].as_str().unwrap())
+            .filter(|name| !name.contains('<'))
+            .collect();
+        assert_eq!(header, columns);
+    }
+}
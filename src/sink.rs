@@ -0,0 +1,348 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use log_time_analyzer::analyzer::Interval;
+use log_time_analyzer::output::OutputFormat;
+
+/// Where a formatted result is delivered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SinkTarget {
+    /// Print to standard output
+    Stdout,
+    /// Write (overwrite) a file, or add to it with `--append`
+    File(PathBuf),
+    /// Send over a TCP connection (`host:port`)
+    Tcp(String),
+    /// Send over a Unix domain socket
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// Pipe into a shell command's stdin
+    Exec(String),
+}
+
+/// Stretch of time whose intervals `--split-output-by` writes to files of their own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitWindow {
+    Day,
+    Hour,
+}
+
+impl std::str::FromStr for SplitWindow {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, ()> {
+        match s.to_lowercase().as_str() {
+            "day" => Ok(SplitWindow::Day),
+            "hour" => Ok(SplitWindow::Hour),
+            _ => Err(()),
+        }
+    }
+}
+
+impl SplitWindow {
+    /// Name of the window `timestamp` falls in, as used in file names: `2025-11-13`, or
+    /// `2025-11-13T10` for an hour
+    pub fn label(self, timestamp: NaiveDateTime) -> String {
+        match self {
+            SplitWindow::Day => timestamp.format("%Y-%m-%d").to_string(),
+            SplitWindow::Hour => timestamp.format("%Y-%m-%dT%H").to_string(),
+        }
+    }
+
+    /// Intervals grouped by the window of their start, windows in chronological order and
+    /// intervals in their original order; intervals without a start come last, as `undated`
+    pub fn split(self, intervals: &[Interval]) -> Vec<(String, Vec<Interval>)> {
+        let mut windows: BTreeMap<Option<String>, Vec<Interval>> = BTreeMap::new();
+        for interval in intervals {
+            let label = interval.start.map(|start| self.label(start));
+            windows.entry(label).or_default().push(interval.clone());
+        }
+        let undated = windows.remove(&None);
+        windows
+            .into_iter()
+            .map(|(label, intervals)| (label.unwrap_or_default(), intervals))
+            .chain(undated.map(|intervals| ("undated".to_string(), intervals)))
+            .collect()
+    }
+}
+
+/// One output destination with its own format, e.g. `format=json,path=out.json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputSink {
+    pub format: OutputFormat,
+    pub target: SinkTarget,
+}
+
+impl OutputSink {
+    /// Parse a sink spec of comma-separated `key=value` pairs.
+    ///
+    /// Keys: `format` (defaults to `default_format`) plus at most one destination:
+    /// `path=<file>`, `socket=<host:port>` or `socket=unix:<path>`, `exec=<command>`.
+    /// With no destination the sink writes to stdout. `exec` must come last since
+    /// its command may itself contain commas.
+    pub fn parse(spec: &str, default_format: OutputFormat) -> Result<Self> {
+        let mut format = default_format;
+        let mut target = None;
+        let mut rest = spec.trim();
+
+        while !rest.is_empty() {
+            let (key, after_key) = rest
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid output spec '{}': expected key=value", spec))?;
+            let key = key.trim();
+
+            let value = if key == "exec" {
+                rest = "";
+                after_key
+            } else {
+                let (value, remaining) = after_key.split_once(',').unwrap_or((after_key, ""));
+                rest = remaining.trim_start();
+                value
+            };
+
+            let new_target = match key {
+                "format" => {
+                    format = value.parse::<OutputFormat>().map_err(|_| {
+                        anyhow::anyhow!("Invalid output format '{}' in output spec '{}'", value, spec)
+                    })?;
+                    None
+                }
+                "path" => Some(SinkTarget::File(PathBuf::from(value))),
+                "socket" => Some(parse_socket(value)?),
+                "exec" => Some(SinkTarget::Exec(value.to_string())),
+                "stdout" => Some(SinkTarget::Stdout),
+                _ => anyhow::bail!(
+                    "Unknown key '{}' in output spec '{}'. Valid keys: format, path, socket, exec",
+                    key,
+                    spec
+                ),
+            };
+
+            if let Some(new_target) = new_target {
+                if target.is_some() {
+                    anyhow::bail!("Output spec '{}' has more than one destination", spec);
+                }
+                target = Some(new_target);
+            }
+        }
+
+        Ok(OutputSink {
+            format,
+            target: target.unwrap_or(SinkTarget::Stdout),
+        })
+    }
+
+    /// This sink writing to the file of one `--split-output-by` window instead, the window's
+    /// label added to the file name: `out.json` becomes `out-2025-11-13.json`
+    pub fn for_window(&self, label: &str) -> OutputSink {
+        let target = match &self.target {
+            SinkTarget::File(path) => {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                let name = match path.extension() {
+                    Some(extension) => format!("{}-{}.{}", stem, label, extension.to_string_lossy()),
+                    None => format!("{}-{}", stem, label),
+                };
+                SinkTarget::File(path.with_file_name(name))
+            }
+            target => target.clone(),
+        };
+        OutputSink {
+            format: self.format,
+            target,
+        }
+    }
+
+    /// Deliver already-formatted content to this sink
+    pub fn write(&self, content: &str) -> Result<()> {
+        match &self.target {
+            SinkTarget::Stdout => {
+                println!("{}", content);
+            }
+            SinkTarget::File(path) => {
+                fs::write(path, format!("{}\n", content))
+                    .with_context(|| format!("Failed to write output file: {:?}", path))?;
+            }
+            SinkTarget::Tcp(address) => {
+                let mut stream = TcpStream::connect(address)
+                    .with_context(|| format!("Failed to connect to output socket {}", address))?;
+                stream.write_all(content.as_bytes())?;
+                stream.write_all(b"\n")?;
+            }
+            #[cfg(unix)]
+            SinkTarget::Unix(path) => {
+                let mut stream = std::os::unix::net::UnixStream::connect(path)
+                    .with_context(|| format!("Failed to connect to output socket {:?}", path))?;
+                stream.write_all(content.as_bytes())?;
+                stream.write_all(b"\n")?;
+            }
+            SinkTarget::Exec(command) => {
+                let mut child = shell(command)
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .with_context(|| format!("Failed to run output command: {}", command))?;
+
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(content.as_bytes())?;
+                    stdin.write_all(b"\n")?;
+                }
+
+                let status = child.wait()?;
+                if !status.success() {
+                    anyhow::bail!("Output command '{}' exited with {}", command, status);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add rows from `OutputFormatter::format_appendable` to the end of this sink's file, so
+    /// repeated runs accumulate one dataset. A CSV header is only written to a new or empty
+    /// file; an existing file's header must match, or its rows would be misread.
+    pub fn append(&self, content: &str) -> Result<()> {
+        let SinkTarget::File(path) = &self.target else {
+            anyhow::bail!("--append only writes to files (path=...)");
+        };
+
+        let mut content = content;
+        if self.format == OutputFormat::Csv {
+            let (header, rows) = content.split_once('\n').unwrap_or((content, ""));
+            if let Some(existing) = first_line(path)? {
+                if existing != header {
+                    anyhow::bail!(
+                        "Cannot append to {:?}: its columns ({}) differ from this run's ({})",
+                        path,
+                        existing,
+                        header
+                    );
+                }
+                content = rows;
+            }
+        }
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open output file for appending: {:?}", path))?;
+        file.write_all(format!("{}\n", content).as_bytes())
+            .with_context(|| format!("Failed to append to output file: {:?}", path))
+    }
+}
+
+/// First line of a file, or `None` when the file doesn't exist or is empty
+fn first_line(path: &Path) -> Result<Option<String>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read output file: {:?}", path)),
+    };
+    let mut line = String::new();
+    BufReader::new(file)
+        .read_line(&mut line)
+        .with_context(|| format!("Failed to read output file: {:?}", path))?;
+    let line = line.trim_end_matches(['\n', '\r']);
+    Ok((!line.is_empty()).then(|| line.to_string()))
+}
+
+fn parse_socket(value: &str) -> Result<SinkTarget> {
+    if let Some(path) = value.strip_prefix("unix:") {
+        #[cfg(unix)]
+        return Ok(SinkTarget::Unix(PathBuf::from(path)));
+        #[cfg(not(unix))]
+        anyhow::bail!("Unix sockets are not supported on this platform: {}", path);
+    }
+
+    Ok(SinkTarget::Tcp(value.to_string()))
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(not(unix))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+
+    #[test]
+    fn test_parse_sink_specs() {
+        let sink = OutputSink::parse("format=json,path=out.json", OutputFormat::Human).unwrap();
+        assert_eq!(sink.format, OutputFormat::Json);
+        assert_eq!(sink.target, SinkTarget::File(PathBuf::from("out.json")));
+
+        let sink = OutputSink::parse("format=table", OutputFormat::Human).unwrap();
+        assert_eq!(sink.target, SinkTarget::Stdout);
+
+        let sink = OutputSink::parse("socket=localhost:9000", OutputFormat::Csv).unwrap();
+        assert_eq!(sink.format, OutputFormat::Csv);
+        assert_eq!(sink.target, SinkTarget::Tcp("localhost:9000".to_string()));
+
+        let sink = OutputSink::parse("format=csv,exec=cut -d, -f1,3", OutputFormat::Human).unwrap();
+        assert_eq!(sink.target, SinkTarget::Exec("cut -d, -f1,3".to_string()));
+    }
+
+    #[test]
+    fn test_append_writes_csv_header_once() {
+        let path = std::env::temp_dir().join(format!("ll-append-test-{}.csv", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let sink = OutputSink::parse(&format!("format=csv,path={}", path.display()), OutputFormat::Human).unwrap();
+
+        sink.append("run_id,from_pattern\n\"r1\",\"a\"").unwrap();
+        sink.append("run_id,from_pattern\n\"r2\",\"b\"").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "run_id,from_pattern\n\"r1\",\"a\"\n\"r2\",\"b\"\n");
+
+        assert!(sink.append("from_pattern\n\"c\"").is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_split_into_window_files() {
+        use chrono::{Duration, NaiveDate};
+
+        let at = |hour: u32, minute: u32| {
+            Interval::new("A", "B", Duration::seconds(1))
+                .starting_at(NaiveDate::from_ymd_opt(2025, 11, 13).unwrap().and_hms_opt(hour, minute, 0).unwrap())
+        };
+        let intervals = vec![at(10, 5), at(9, 59), at(10, 30), Interval::default()];
+
+        let labels = |windows: Vec<(String, Vec<Interval>)>| {
+            windows.into_iter().map(|(label, intervals)| (label, intervals.len())).collect::<Vec<_>>()
+        };
+        assert_eq!(labels(SplitWindow::Day.split(&intervals)), vec![("2025-11-13".to_string(), 3), ("undated".to_string(), 1)]);
+        assert_eq!(
+            labels(SplitWindow::Hour.split(&intervals)),
+            vec![("2025-11-13T09".to_string(), 1), ("2025-11-13T10".to_string(), 2), ("undated".to_string(), 1)]
+        );
+
+        let sink = OutputSink::parse("format=json,path=out/run.json", OutputFormat::Human).unwrap();
+        assert_eq!(sink.for_window("2025-11-13").target, SinkTarget::File(PathBuf::from("out/run-2025-11-13.json")));
+    }
+
+    #[test]
+    fn test_parse_sink_errors() {
+        assert!(OutputSink::parse("format=xml", OutputFormat::Human).is_err());
+        assert!(OutputSink::parse("path=a,path=b", OutputFormat::Human).is_err());
+        assert!(OutputSink::parse("color=red", OutputFormat::Human).is_err());
+        assert!(OutputSink::parse("json", OutputFormat::Human).is_err());
+    }
+}
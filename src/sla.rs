@@ -0,0 +1,192 @@
+/// Per-transition service levels from the config's `expected_max` durations (`sla`)
+use anyhow::Result;
+use chrono::Duration;
+use std::collections::HashMap;
+
+use log_time_analyzer::analyzer::{format_duration, Interval};
+use log_time_analyzer::config::Config;
+use log_time_analyzer::duration::parse_duration;
+
+/// One transition's intervals checked against its `expected_max`
+#[derive(Debug)]
+pub struct SlaResult {
+    pub from_pattern: String,
+    pub to_pattern: String,
+    pub expected_max: Duration,
+    pub count: usize,
+    /// Longest interval of the transition, if it had any
+    pub max: Option<Duration>,
+    /// Indexes (into the analyzed intervals) of intervals longer than `expected_max`
+    pub violations: Vec<usize>,
+}
+
+impl SlaResult {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Check every interval of the transitions with an `expected_max`, in config order
+pub fn evaluate(intervals: &[Interval], config: &Config) -> Result<Vec<SlaResult>> {
+    let mut results = Vec::new();
+    let mut index = HashMap::new();
+    for rule in &config.transitions {
+        let Some(limit) = &rule.expected_max else {
+            continue;
+        };
+        let key = (rule.from.as_str(), rule.to.as_str());
+        // A later rule for the same transition wins, as for the other per-transition settings
+        let result = SlaResult {
+            from_pattern: rule.from.clone(),
+            to_pattern: rule.to.clone(),
+            expected_max: parse_duration(limit)?,
+            count: 0,
+            max: None,
+            violations: Vec::new(),
+        };
+        match index.get(&key) {
+            Some(&i) => results[i] = result,
+            None => {
+                index.insert(key, results.len());
+                results.push(result);
+            }
+        }
+    }
+    if results.is_empty() {
+        anyhow::bail!("No transition in the config has an expected_max; add one under `transitions`");
+    }
+
+    for (i, interval) in intervals.iter().enumerate() {
+        let Some(&r) = index.get(&(interval.from_pattern.as_str(), interval.to_pattern.as_str())) else {
+            continue;
+        };
+        let result = &mut results[r];
+        result.count += 1;
+        result.max = result.max.max(Some(interval.duration));
+        if interval.duration > result.expected_max {
+            result.violations.push(i);
+        }
+    }
+
+    Ok(results)
+}
+
+/// Pass/fail line per transition, every violation in log order, and a summary
+pub fn format_report(results: &[SlaResult], intervals: &[Interval]) -> String {
+    let mut output = String::new();
+
+    for result in results {
+        let detail = match result.max {
+            None => "no intervals".to_string(),
+            Some(max) if result.passed() => format!("{} intervals, max {}", result.count, format_duration(&max)),
+            Some(max) => format!(
+                "{} of {} intervals over, max {}",
+                result.violations.len(),
+                result.count,
+                format_duration(&max)
+            ),
+        };
+        output.push_str(&format!(
+            "{}  {} -> {}: expected max {} ({})\n",
+            if result.passed() { "PASS" } else { "FAIL" },
+            result.from_pattern,
+            result.to_pattern,
+            format_duration(&result.expected_max),
+            detail
+        ));
+    }
+
+    let mut violations: Vec<(usize, &SlaResult)> =
+        results.iter().flat_map(|result| result.violations.iter().map(move |&i| (i, result))).collect();
+    violations.sort_by_key(|&(i, _)| i);
+    if !violations.is_empty() {
+        output.push_str("\nViolations:\n");
+    }
+    for (i, result) in violations {
+        let interval = &intervals[i];
+        output.push_str(&format!(
+            "  {} -> {}: {} (over by {})",
+            interval.from_pattern,
+            interval.to_pattern,
+            format_duration(&interval.duration),
+            format_duration(&(interval.duration - result.expected_max))
+        ));
+        if let Some(start) = interval.start {
+            output.push_str(&format!(" at {}", start.format("%Y-%m-%d %H:%M:%S%.3f")));
+        }
+        if let Some(line) = interval.from_line {
+            output.push_str(&format!(", line {}", line));
+        }
+        output.push('\n');
+    }
+
+    let failed = results.iter().filter(|result| !result.passed()).count();
+    output.push_str(&format!(
+        "\n{} transitions, {} passed, {} failed",
+        results.len(),
+        results.len() - failed,
+        failed
+    ));
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+    use log_time_analyzer::config::TransitionRule;
+
+    fn interval(from: &str, to: &str, ms: i64) -> Interval {
+        Interval::new(from, to, Duration::milliseconds(ms))
+    }
+
+    #[test]
+    fn test_violations_fail_their_transition() {
+        let rule = |from: &str, to: &str, max: &str| TransitionRule {
+            from: from.to_string(),
+            to: to.to_string(),
+            expected_max: Some(max.to_string()),
+            ..Default::default()
+        };
+        let config = Config {
+            transitions: vec![rule("extract", "load", "5s"), rule("load", "done", "1s"), rule("done", "report", "1s")],
+            ..Default::default()
+        };
+        let intervals = vec![
+            interval("extract", "load", 4000),
+            interval("load", "done", 900),
+            interval("extract", "load", 7000),
+            interval("load", "cleanup", 60000),
+        ];
+
+        let results = evaluate(&intervals, &config).unwrap();
+        assert_eq!(results.iter().map(SlaResult::passed).collect::<Vec<_>>(), vec![false, true, true]);
+        assert_eq!((results[0].count, results[0].violations.clone()), (2, vec![2]));
+
+        let report = format_report(&results, &intervals);
+        assert!(report.contains("FAIL  extract -> load: expected max 5s 0ms (1 of 2 intervals over, max 7s 0ms)"), "{}", report);
+        assert!(report.contains("extract -> load: 7s 0ms (over by 2s 0ms)"), "{}", report);
+        assert!(report.contains("PASS  done -> report: expected max 1s 0ms (no intervals)"), "{}", report);
+        assert!(report.ends_with("3 transitions, 2 passed, 1 failed"));
+
+        assert!(evaluate(&intervals, &Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_expected_max_is_an_error() {
+        let config = Config {
+            transitions: vec![TransitionRule {
+                from: "extract".to_string(),
+                to: "load".to_string(),
+                expected_max: Some("soon".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(evaluate(&[interval("extract", "load", 10)], &config).is_err());
+
+        let error = evaluate(&[], &Config::default()).unwrap_err();
+        assert!(error.to_string().contains("No transition in the config has an expected_max"), "{}", error);
+    }
+}
@@ -0,0 +1,342 @@
+/// Latency SLO compliance and error-budget burn rate per transition (`--slo`)
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDateTime};
+use std::collections::HashMap;
+
+use log_time_analyzer::analyzer::{format_duration, Interval};
+use log_time_analyzer::duration::parse_duration;
+use log_time_analyzer::stats::{self, human_ms};
+
+/// Span of the most recent intervals checked for a fast burn
+const FAST_BURN_WINDOW_HOURS: i64 = 1;
+
+/// Share of the whole window's error budget that may burn within the fast-burn window before
+/// it warrants a page (2% per hour is 14.4x for a 30-day window)
+const FAST_BURN_BUDGET_SHARE: f64 = 0.02;
+
+/// A latency objective such as `p99 < 500ms over 30d`: that percentage of intervals must be
+/// faster than the threshold, measured over the window
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slo {
+    pub text: String,
+    /// Target share of good intervals, in percent (the `99` of `p99`)
+    pub percentile: f64,
+    pub threshold: Duration,
+    /// With `<=`, an interval exactly at the threshold is still good
+    pub inclusive: bool,
+    pub window: Duration,
+}
+
+/// Compliance of one transition with the objective
+#[derive(Debug)]
+pub struct SloResult {
+    pub from_pattern: String,
+    pub to_pattern: String,
+    pub count: usize,
+    /// Intervals at or over the threshold
+    pub bad: usize,
+    /// Actual duration at the objective's percentile
+    pub percentile_ms: f64,
+    /// How many times faster than sustainable the error budget burns (1.0 spends exactly
+    /// the budget over the window)
+    pub burn_rate: f64,
+    /// Burn rate over the last hour of the log, when the log spans more than that
+    pub recent_burn_rate: Option<f64>,
+}
+
+/// Compliance of every transition plus the stretch of log it was measured over
+#[derive(Debug)]
+pub struct SloReport {
+    pub slo: Slo,
+    /// Earliest interval start and latest interval end, when intervals carry timestamps
+    pub observed: Option<(NaiveDateTime, NaiveDateTime)>,
+    pub results: Vec<SloResult>,
+}
+
+impl Slo {
+    /// Parse `p<N> < <duration> over <window>` (`<=` is accepted too)
+    pub fn parse(text: &str) -> Result<Self> {
+        parse_slo(text).with_context(|| format!("Invalid SLO '{}', expected e.g. 'p99 < 500ms over 30d'", text))
+    }
+
+    /// Share of intervals allowed to miss the threshold
+    pub fn error_budget(&self) -> f64 {
+        1.0 - self.percentile / 100.0
+    }
+
+    /// Burn rate at which the fast-burn window spends its share of the budget
+    pub fn fast_burn_threshold(&self) -> f64 {
+        FAST_BURN_BUDGET_SHARE * self.window.num_seconds() as f64 / (FAST_BURN_WINDOW_HOURS * 3600) as f64
+    }
+
+    fn is_bad(&self, interval: &Interval) -> bool {
+        if self.inclusive {
+            interval.duration > self.threshold
+        } else {
+            interval.duration >= self.threshold
+        }
+    }
+
+    fn burn_rate<'a>(&self, intervals: impl Iterator<Item = &'a Interval>) -> Option<f64> {
+        let (total, bad) = intervals.fold((0usize, 0usize), |(total, bad), interval| {
+            (total + 1, bad + self.is_bad(interval) as usize)
+        });
+        (total > 0).then(|| bad as f64 / total as f64 / self.error_budget())
+    }
+
+    /// Measure every transition (in order of first appearance) against the objective
+    pub fn evaluate(&self, intervals: &[Interval]) -> SloReport {
+        let observed = intervals
+            .iter()
+            .filter_map(|interval| interval.start.map(|start| (start, start + interval.duration.max(Duration::zero()))))
+            .reduce(|(first, last), (start, end)| (first.min(start), last.max(end)));
+        let recent_since = observed
+            .filter(|(first, last)| *last - *first > Duration::hours(FAST_BURN_WINDOW_HOURS))
+            .map(|(_, last)| last - Duration::hours(FAST_BURN_WINDOW_HOURS));
+
+        let mut order: Vec<(&str, &str)> = Vec::new();
+        let mut groups: HashMap<(&str, &str), Vec<&Interval>> = HashMap::new();
+        for interval in intervals {
+            let key = (interval.from_pattern.as_str(), interval.to_pattern.as_str());
+            groups
+                .entry(key)
+                .or_insert_with(|| {
+                    order.push(key);
+                    Vec::new()
+                })
+                .push(interval);
+        }
+
+        let results = order
+            .into_iter()
+            .map(|key| {
+                let group = &groups[&key];
+                let mut sorted_ms: Vec<i64> = group.iter().map(|i| i.duration.num_milliseconds()).collect();
+                sorted_ms.sort_unstable();
+
+                let recent_burn_rate = recent_since.and_then(|since| {
+                    self.burn_rate(group.iter().copied().filter(|i| i.start.is_some_and(|start| start >= since)))
+                });
+
+                SloResult {
+                    from_pattern: key.0.to_string(),
+                    to_pattern: key.1.to_string(),
+                    count: group.len(),
+                    bad: group.iter().filter(|i| self.is_bad(i)).count(),
+                    percentile_ms: stats::percentile(&sorted_ms, self.percentile),
+                    burn_rate: self.burn_rate(group.iter().copied()).unwrap_or(0.0),
+                    recent_burn_rate,
+                }
+            })
+            .collect();
+
+        SloReport {
+            slo: self.clone(),
+            observed,
+            results,
+        }
+    }
+}
+
+impl SloReport {
+    /// Length of log the intervals were measured over
+    fn observed_span(&self) -> Option<Duration> {
+        self.observed.map(|(first, last)| last - first)
+    }
+
+    /// Share of the whole window's budget spent during the observed span, assuming traffic
+    /// is spread evenly over the window
+    fn budget_used(&self, result: &SloResult) -> Option<f64> {
+        self.observed_span()
+            .map(|span| result.burn_rate * span.num_milliseconds() as f64 / self.slo.window.num_milliseconds() as f64)
+    }
+
+    /// Time left until the window's budget is spent, if the burn rate holds
+    fn time_to_exhaustion(&self, result: &SloResult) -> Option<Duration> {
+        if result.burn_rate <= 0.0 {
+            return None;
+        }
+        let remaining = (1.0 - self.budget_used(result).unwrap_or(0.0)).max(0.0);
+        let ms = remaining * self.slo.window.num_milliseconds() as f64 / result.burn_rate;
+        Some(Duration::milliseconds(ms as i64))
+    }
+
+    /// Plain-text compliance report
+    pub fn format(&self) -> String {
+        let slo = &self.slo;
+        let mut output = format!(
+            "SLO: {} (error budget {}% of intervals)\n",
+            slo.text,
+            format_percent(slo.error_budget() * 100.0)
+        );
+        if let Some((first, last)) = self.observed {
+            output.push_str(&format!(
+                "Observed {} of log, {} to {}\n",
+                format_duration(&(last - first)),
+                first.format("%Y-%m-%d %H:%M:%S"),
+                last.format("%Y-%m-%d %H:%M:%S")
+            ));
+        }
+
+        for result in &self.results {
+            output.push_str(&format!(
+                "\n{} -> {}: {} intervals, {} over {} ({}%), p{} {}\n",
+                result.from_pattern,
+                result.to_pattern,
+                result.count,
+                result.bad,
+                format_duration(&slo.threshold),
+                format_percent(result.bad as f64 / result.count as f64 * 100.0),
+                slo.percentile,
+                human_ms(result.percentile_ms)
+            ));
+            output.push_str(&format!(
+                "  compliance {}% (target {}%), burn rate {:.2}x",
+                format_percent(100.0 - result.bad as f64 / result.count as f64 * 100.0),
+                slo.percentile,
+                result.burn_rate
+            ));
+            if let Some(recent) = result.recent_burn_rate {
+                output.push_str(&format!(", {:.2}x in the last hour", recent));
+            }
+            output.push('\n');
+            if let Some(used) = self.budget_used(result) {
+                output.push_str(&format!("  budget used {}% of the window's", format_percent(used * 100.0)));
+                match self.time_to_exhaustion(result) {
+                    Some(left) if left <= Duration::zero() => output.push_str("; exhausted\n"),
+                    Some(left) => output.push_str(&format!("; exhausted in {} at this rate\n", format_duration(&left))),
+                    None => output.push('\n'),
+                }
+            }
+        }
+
+        output.trim_end().to_string()
+    }
+
+    /// Warnings for transitions burning their budget too fast: a fast burn over the last hour
+    /// (or the whole log, if shorter), and any sustained burn above 1x
+    pub fn warnings(&self) -> Vec<String> {
+        let fast_threshold = self.slo.fast_burn_threshold();
+        let mut warnings = Vec::new();
+
+        for result in &self.results {
+            let transition = format!("{} -> {}", result.from_pattern, result.to_pattern);
+            let (recent, over) = match result.recent_burn_rate {
+                Some(rate) => (rate, "the last hour"),
+                None => (result.burn_rate, "the observed log"),
+            };
+
+            if recent >= fast_threshold {
+                warnings.push(format!(
+                    "{} is burning its error budget fast: {:.2}x over {} (fast-burn threshold {:.1}x)",
+                    transition, recent, over, fast_threshold
+                ));
+            } else if result.burn_rate > 1.0 {
+                let left = match self.time_to_exhaustion(result) {
+                    Some(left) if left > Duration::zero() => format!("; it runs out in {}", format_duration(&left)),
+                    _ => "; it is exhausted".to_string(),
+                };
+                warnings.push(format!(
+                    "{} is burning its error budget {:.2}x faster than sustainable{}",
+                    transition, result.burn_rate, left
+                ));
+            }
+        }
+
+        warnings
+    }
+}
+
+fn parse_slo(text: &str) -> Result<Slo> {
+    let (target, window) = text
+        .split_once(" over ")
+        .ok_or_else(|| anyhow::anyhow!("Missing the window ('over <duration>')"))?;
+    let window = parse_duration(window)?;
+    if window <= Duration::zero() {
+        anyhow::bail!("The window must be longer than zero");
+    }
+
+    let (metric, threshold, inclusive) = match target.split_once("<=") {
+        Some((metric, threshold)) => (metric, threshold, true),
+        None => {
+            let (metric, threshold) = target
+                .split_once('<')
+                .ok_or_else(|| anyhow::anyhow!("Expected a target such as 'p99 < 500ms'"))?;
+            (metric, threshold, false)
+        }
+    };
+
+    let percentile = metric
+        .trim()
+        .strip_prefix('p')
+        .and_then(|p| p.parse::<f64>().ok())
+        .filter(|p| *p > 0.0 && *p < 100.0)
+        .ok_or_else(|| anyhow::anyhow!("Expected a percentile between p0 and p100 (exclusive), got '{}'", metric.trim()))?;
+
+    let threshold = parse_duration(threshold)?;
+
+    Ok(Slo {
+        text: text.trim().to_string(),
+        percentile,
+        threshold,
+        inclusive,
+        window,
+    })
+}
+
+/// Percentage with up to two decimals, without trailing zeros
+fn format_percent(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_parse_slo() {
+        let slo = Slo::parse("p99 < 500ms over 30d").unwrap();
+        assert_eq!(slo.percentile, 99.0);
+        assert_eq!(slo.threshold, Duration::milliseconds(500));
+        assert!(!slo.inclusive);
+        assert_eq!(slo.window, Duration::days(30));
+        assert!((slo.error_budget() - 0.01).abs() < 1e-9);
+        assert!((slo.fast_burn_threshold() - 14.4).abs() < 1e-9);
+
+        assert!(Slo::parse("p99.9<=1s over 7d").unwrap().inclusive);
+        for text in ["p99 < 500ms", "p100 < 1s over 30d", "max < 1s over 30d", "p99 > 1s over 30d", "p99 < 1s over 0s"] {
+            assert!(Slo::parse(text).is_err(), "{} should not parse", text);
+        }
+    }
+
+    #[test]
+    fn test_burn_rate() {
+        let base = NaiveDate::from_ymd_opt(2025, 11, 13).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        // One interval a minute for two hours; every tenth is slow, all of them in the last hour
+        let intervals: Vec<Interval> = (0..120)
+            .map(|minute| {
+                let ms = if minute >= 60 && minute % 10 == 0 { 900 } else { 100 };
+                Interval::new("A", "B", Duration::milliseconds(ms)).starting_at(base + Duration::minutes(minute))
+            })
+            .collect();
+
+        let report = Slo::parse("p99 < 500ms over 30d").unwrap().evaluate(&intervals);
+        let result = &report.results[0];
+        assert_eq!((result.count, result.bad), (120, 6));
+        // 5% bad against a 1% budget
+        assert!((result.burn_rate - 5.0).abs() < 1e-9);
+        assert!((result.recent_burn_rate.unwrap() - 10.0).abs() < 1e-9);
+
+        let warnings = report.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("5.00x faster than sustainable"), "{}", warnings[0]);
+
+        let report = Slo::parse("p99 < 500ms over 1d").unwrap().evaluate(&intervals);
+        assert!(report.warnings()[0].contains("fast: 10.00x over the last hour"));
+
+        assert!(Slo::parse("p90 <= 900ms over 30d").unwrap().evaluate(&intervals).warnings().is_empty());
+    }
+}
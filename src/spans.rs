@@ -0,0 +1,280 @@
+/// Distributed tracing exports of the intervals, Zipkin v2 JSON (`-f zipkin`) and OTLP JSON
+/// (`-f otlp`): the intervals of each correlation ID make up a trace, with a root span over
+/// all of them and a child span per interval, to browse log-based traces in Zipkin, Jaeger,
+/// or Tempo
+use chrono::{Duration, NaiveDateTime};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::analyzer::Interval;
+
+/// Service the spans are reported under, unless they come from a named source
+const SERVICE_NAME: &str = "log-line";
+
+/// OTLP `SPAN_KIND_INTERNAL`: the work of one step, neither a request nor a response
+const OTLP_KIND_INTERNAL: u8 = 1;
+
+/// One span of a trace, shared by both exports
+#[derive(Debug)]
+struct Span<'a> {
+    /// 32 hex digits
+    trace_id: String,
+    /// 16 hex digits
+    id: String,
+    parent_id: Option<String>,
+    name: String,
+    start: NaiveDateTime,
+    duration: Duration,
+    service: &'a str,
+    tags: BTreeMap<String, String>,
+}
+
+/// FNV-1a over `parts` separated by NUL bytes; IDs derived from the log rather than random
+/// ones, so exporting the same log again gives the same traces
+fn hash(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (index, part) in parts.iter().enumerate() {
+        let separator: &[u8] = if index == 0 { &[] } else { &[0] };
+        for &byte in separator.iter().chain(part.as_bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    // An ID of all zeros is invalid in both formats
+    hash.max(1)
+}
+
+/// The attributes of an interval: its patterns, correlation ID, group, line numbers, named
+/// captures, derived values, and tags
+fn tags(interval: &Interval) -> BTreeMap<String, String> {
+    let mut tags = BTreeMap::from([
+        ("from".to_string(), interval.from_pattern.clone()),
+        ("to".to_string(), interval.to_pattern.clone()),
+    ]);
+    let optional = [
+        ("correlation", interval.correlation.clone()),
+        ("group", interval.group.clone()),
+        ("from.line", interval.from_line.map(|line| line.to_string())),
+        ("to.line", interval.to_line.map(|line| line.to_string())),
+        ("from.source", interval.from_source.clone()),
+        ("to.source", interval.to_source.clone()),
+    ];
+    tags.extend(optional.into_iter().filter_map(|(key, value)| Some((key.to_string(), value?))));
+    tags.extend(interval.from_fields.iter().map(|(name, value)| (format!("from.{}", name), value.clone())));
+    tags.extend(interval.to_fields.iter().map(|(name, value)| (format!("to.{}", name), value.clone())));
+    tags.extend(interval.derived.iter().map(|(name, value)| (format!("derived.{}", name), value.to_string())));
+    if !interval.tags.is_empty() {
+        tags.insert("tags".to_string(), interval.tags.join(","));
+    }
+    tags
+}
+
+/// The spans of every trace: one trace per correlation ID, or else per source file, or else a
+/// single trace, like the tracks of the chrome-trace format. Intervals without a start
+/// timestamp have no place on a timeline and are left out.
+fn spans(intervals: &[Interval]) -> Vec<Span<'_>> {
+    let mut order: Vec<&str> = Vec::new();
+    let mut traces: BTreeMap<&str, Vec<&Interval>> = BTreeMap::new();
+    for interval in intervals.iter().filter(|i| i.start.is_some()) {
+        let key = interval
+            .correlation
+            .as_deref()
+            .or(interval.from_source.as_deref())
+            .unwrap_or("intervals");
+        traces
+            .entry(key)
+            .or_insert_with(|| {
+                order.push(key);
+                Vec::new()
+            })
+            .push(interval);
+    }
+
+    let mut spans = Vec::new();
+    for key in order {
+        let steps = &traces[key];
+        // Out-of-order timestamps give negative durations; those spans start at the earlier end
+        let extent = |interval: &Interval| {
+            let start = interval.start.unwrap_or_default();
+            if interval.duration < Duration::zero() {
+                (start + interval.duration, -interval.duration)
+            } else {
+                (start, interval.duration)
+            }
+        };
+        let start = steps.iter().map(|i| extent(i).0).min().unwrap_or_default();
+        let end = steps.iter().map(|i| extent(i).0 + extent(i).1).max().unwrap_or_default();
+        let origin = start.format("%Y-%m-%dT%H:%M:%S%.f").to_string();
+        let trace_id = format!("{:016x}{:016x}", hash(&["trace", key, &origin]), hash(&["trace", &origin, key]));
+        let root_id = format!("{:016x}", hash(&["span", key, &origin]));
+        let service = steps[0].from_source.as_deref().unwrap_or(SERVICE_NAME);
+
+        spans.push(Span {
+            trace_id: trace_id.clone(),
+            id: root_id.clone(),
+            parent_id: None,
+            name: key.to_string(),
+            start,
+            duration: end - start,
+            service,
+            tags: BTreeMap::from([("intervals".to_string(), steps.len().to_string())]),
+        });
+        for (index, interval) in steps.iter().enumerate() {
+            let (start, duration) = extent(interval);
+            spans.push(Span {
+                trace_id: trace_id.clone(),
+                id: format!("{:016x}", hash(&["span", key, &origin, &index.to_string()])),
+                parent_id: Some(root_id.clone()),
+                name: format!("{} -> {}", interval.from_pattern, interval.to_pattern),
+                start,
+                duration,
+                service: interval.from_source.as_deref().unwrap_or(SERVICE_NAME),
+                tags: tags(interval),
+            });
+        }
+    }
+    spans
+}
+
+/// Microseconds since the Unix epoch; log timestamps carry no zone and are taken as UTC
+fn epoch_micros(timestamp: NaiveDateTime) -> i64 {
+    timestamp.and_utc().timestamp_micros()
+}
+
+fn epoch_nanos(timestamp: NaiveDateTime) -> String {
+    timestamp.and_utc().timestamp_nanos_opt().unwrap_or_default().to_string()
+}
+
+/// Zipkin v2 JSON, a list of spans to POST to `/api/v2/spans`
+pub fn format_zipkin(intervals: &[Interval]) -> String {
+    let spans: Vec<Value> = spans(intervals)
+        .into_iter()
+        .map(|span| {
+            let mut value = json!({
+                "traceId": span.trace_id,
+                "id": span.id,
+                "name": span.name,
+                "timestamp": epoch_micros(span.start),
+                // Zipkin wants at least a microsecond
+                "duration": span.duration.num_microseconds().unwrap_or(i64::MAX).max(1),
+                "localEndpoint": { "serviceName": span.service },
+                "tags": span.tags,
+            });
+            if let Some(parent_id) = span.parent_id {
+                value["parentId"] = json!(parent_id);
+            }
+            value
+        })
+        .collect();
+    serde_json::to_string_pretty(&spans).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// OTLP JSON, a trace export request to POST to an OpenTelemetry collector's `/v1/traces`,
+/// with a resource per service
+pub fn format_otlp(intervals: &[Interval]) -> String {
+    let mut services: Vec<&str> = Vec::new();
+    let mut by_service: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for span in spans(intervals) {
+        let attributes: Vec<Value> = span
+            .tags
+            .iter()
+            .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+            .collect();
+        let mut value = json!({
+            "traceId": span.trace_id,
+            "spanId": span.id,
+            "name": span.name,
+            "kind": OTLP_KIND_INTERNAL,
+            "startTimeUnixNano": epoch_nanos(span.start),
+            "endTimeUnixNano": epoch_nanos(span.start + span.duration),
+            "attributes": attributes,
+        });
+        if let Some(parent_id) = span.parent_id {
+            value["parentSpanId"] = json!(parent_id);
+        }
+        if !by_service.contains_key(span.service) {
+            services.push(span.service);
+        }
+        by_service.entry(span.service).or_default().push(value);
+    }
+
+    let resource_spans: Vec<Value> = services
+        .into_iter()
+        .map(|service| {
+            json!({
+                "resource": {
+                    "attributes": [{ "key": "service.name", "value": { "stringValue": service } }],
+                },
+                "scopeSpans": [{
+                    "scope": { "name": SERVICE_NAME },
+                    "spans": by_service.remove(service).unwrap_or_default(),
+                }],
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&json!({ "resourceSpans": resource_spans })).unwrap_or_else(|_| "{}".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
+
+    fn interval(key: Option<&str>, from: &str, to: &str, start_ms: i64, ms: i64) -> Interval {
+        let origin = NaiveDateTime::parse_from_str("2025-11-13 10:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        Interval {
+            correlation: key.map(str::to_string),
+            ..Interval::new(from, to, Duration::milliseconds(ms)).starting_at(origin + Duration::milliseconds(start_ms))
+        }
+    }
+
+    #[test]
+    fn test_zipkin_traces_per_correlation_id() {
+        let intervals = [
+            interval(Some("req-1"), "received", "queried", 0, 20),
+            interval(Some("req-2"), "received", "queried", 5, 900),
+            interval(Some("req-1"), "queried", "sent", 20, 100),
+            Interval { start: None, ..interval(Some("req-3"), "received", "sent", 0, 10) },
+        ];
+        let spans: Vec<Value> = serde_json::from_str(&format_zipkin(&intervals)).unwrap();
+        assert_eq!(spans.len(), 5, "a root and a child per interval, without the untimed one");
+
+        let root = &spans[0];
+        assert_eq!(root["name"], "req-1");
+        assert_eq!(root["timestamp"], 1763028000000000_i64);
+        assert_eq!(root["duration"], 120_000);
+        assert!(root.get("parentId").is_none());
+        assert_eq!(root["traceId"].as_str().unwrap().len(), 32);
+
+        let child = &spans[2];
+        assert_eq!(child["name"], "queried -> sent");
+        assert_eq!(child["parentId"], root["id"]);
+        assert_eq!(child["traceId"], root["traceId"]);
+        assert_eq!(child["timestamp"], 1763028000020000_i64);
+        assert_eq!(child["tags"]["correlation"], "req-1");
+        assert_eq!(child["localEndpoint"]["serviceName"], "log-line");
+        assert_ne!(spans[1]["id"], child["id"]);
+        assert_ne!(spans[3]["traceId"], root["traceId"]);
+
+        // The same log gives the same IDs
+        assert_eq!(format_zipkin(&intervals), format_zipkin(&intervals));
+    }
+
+    #[test]
+    fn test_otlp_resource_per_source() {
+        let mut intervals = [interval(None, "start", "end", 0, 250), interval(None, "start", "end", 500, 0)];
+        intervals[1].from_source = Some("worker.log".to_string());
+        let request: Value = serde_json::from_str(&format_otlp(&intervals)).unwrap();
+
+        let resources = request["resourceSpans"].as_array().unwrap();
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[1]["resource"]["attributes"][0]["value"]["stringValue"], "worker.log");
+        let spans = &resources[0]["scopeSpans"][0]["spans"];
+        assert_eq!(spans[0]["name"], "intervals");
+        assert_eq!(spans[1]["startTimeUnixNano"], "1763028000000000000");
+        assert_eq!(spans[1]["endTimeUnixNano"], "1763028000250000000");
+        assert_eq!(spans[1]["kind"], 1);
+        assert_eq!(spans[1]["parentSpanId"], spans[0]["spanId"]);
+        assert!(spans[1]["attributes"].as_array().unwrap().contains(&json!({ "key": "to", "value": { "stringValue": "end" } })));
+    }
+}
@@ -0,0 +1,348 @@
+/// Aggregate statistics over intervals, grouped by transition
+use crate::error::{LogLineError, Result};
+use std::collections::HashMap;
+
+use crate::analyzer::{format_duration, Interval};
+use crate::config::ReportedDuration;
+
+/// Aggregate duration statistics for one (from, to) transition, or one group of it
+#[derive(Debug, Clone)]
+pub struct TransitionStats {
+    pub from_pattern: String,
+    pub to_pattern: String,
+    /// Captured value the intervals were grouped by (`--group-by`), if any
+    pub group: Option<String>,
+    /// Number of intervals included in the statistics (after trimming)
+    pub count: usize,
+    pub min_ms: i64,
+    pub max_ms: i64,
+    pub mean_ms: f64,
+    /// Population standard deviation
+    pub stddev_ms: f64,
+    /// Included durations in ascending order, for percentiles
+    pub sorted_ms: Vec<i64>,
+    /// Indexes (into the analyzed intervals) of intervals excluded by trimming
+    pub trimmed: Vec<usize>,
+}
+
+impl TransitionStats {
+    /// Duration at the given percentile (0-100), interpolating between neighbors
+    pub fn percentile(&self, p: f64) -> f64 {
+        percentile(&self.sorted_ms, p)
+    }
+
+    /// `from -> to`, followed by the group in parentheses when grouped
+    pub fn transition(&self) -> String {
+        match &self.group {
+            Some(group) => format!("{} -> {} ({})", self.from_pattern, self.to_pattern, group),
+            None => format!("{} -> {}", self.from_pattern, self.to_pattern),
+        }
+    }
+}
+
+/// Parse a trim amount such as `1%` or `2.5` (percent from each end)
+pub fn parse_trim(input: &str) -> Result<f64> {
+    let value: f64 = input
+        .trim()
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| LogLineError::config(format!("Invalid trim percentage '{}'", input)))?;
+
+    if !(0.0..50.0).contains(&value) {
+        return Err(LogLineError::config(format!("Trim percentage must be at least 0% and below 50%, got '{}'", input)));
+    }
+
+    Ok(value)
+}
+
+/// Group intervals by transition and group (in order of first appearance) and compute
+/// statistics.
+///
+/// With `trim_percent`, that percentage of intervals is dropped from each end of every
+/// transition's distribution before computing the statistics; the dropped intervals are
+/// recorded in `trimmed` so they can still be listed.
+pub fn compute(intervals: &[Interval], trim_percent: Option<f64>) -> Vec<TransitionStats> {
+    let mut order: Vec<(&str, &str, Option<&str>)> = Vec::new();
+    let mut groups: HashMap<(&str, &str, Option<&str>), Vec<usize>> = HashMap::new();
+
+    for (idx, interval) in intervals.iter().enumerate() {
+        let key = (interval.from_pattern.as_str(), interval.to_pattern.as_str(), interval.group.as_deref());
+        groups
+            .entry(key)
+            .or_insert_with(|| {
+                order.push(key);
+                Vec::new()
+            })
+            .push(idx);
+    }
+
+    order
+        .into_iter()
+        .map(|key| {
+            let mut indexes = groups.remove(&key).unwrap_or_default();
+            indexes.sort_by_key(|&idx| intervals[idx].duration);
+
+            let trim_each = trim_percent
+                .map(|p| (indexes.len() as f64 * p / 100.0).floor() as usize)
+                .unwrap_or(0);
+
+            let mut trimmed: Vec<usize> = indexes[..trim_each]
+                .iter()
+                .chain(&indexes[indexes.len() - trim_each..])
+                .copied()
+                .collect();
+            trimmed.sort_unstable();
+
+            let sorted_ms: Vec<i64> = indexes[trim_each..indexes.len() - trim_each]
+                .iter()
+                .map(|&idx| intervals[idx].duration.num_milliseconds())
+                .collect();
+
+            let count = sorted_ms.len();
+            let mean_ms = if count > 0 {
+                sorted_ms.iter().sum::<i64>() as f64 / count as f64
+            } else {
+                0.0
+            };
+            let stddev_ms = if count > 0 {
+                let variance = sorted_ms
+                    .iter()
+                    .map(|&ms| (ms as f64 - mean_ms).powi(2))
+                    .sum::<f64>()
+                    / count as f64;
+                variance.sqrt()
+            } else {
+                0.0
+            };
+
+            TransitionStats {
+                from_pattern: key.0.to_string(),
+                to_pattern: key.1.to_string(),
+                group: key.2.map(str::to_string),
+                count,
+                min_ms: sorted_ms.first().copied().unwrap_or(0),
+                max_ms: sorted_ms.last().copied().unwrap_or(0),
+                mean_ms,
+                stddev_ms,
+                sorted_ms,
+                trimmed,
+            }
+        })
+        .collect()
+}
+
+/// Percentile (0-100) of ascending values using linear interpolation; 0 when empty
+pub fn percentile(sorted: &[i64], p: f64) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        1 => sorted[0] as f64,
+        n => {
+            let rank = (p / 100.0).clamp(0.0, 1.0) * (n - 1) as f64;
+            let lower = rank.floor() as usize;
+            let upper = rank.ceil() as usize;
+            let weight = rank - lower as f64;
+            sorted[lower] as f64 * (1.0 - weight) + sorted[upper] as f64 * weight
+        }
+    }
+}
+
+/// Human-readable form of a fractional number of milliseconds
+pub fn human_ms(ms: f64) -> String {
+    format_duration(&chrono::Duration::microseconds((ms * 1000.0).round() as i64))
+}
+
+/// Plain-text statistics section for the run summary, listing trimmed intervals separately
+pub fn format_summary(stats: &[TransitionStats], intervals: &[Interval], trim_percent: Option<f64>) -> String {
+    let mut output = match trim_percent {
+        Some(p) => format!("Transition statistics (trimmed {}% from each end):\n", p),
+        None => "Transition statistics:\n".to_string(),
+    };
+
+    for s in stats {
+        output.push_str(&format!(
+            "  {}: count {}, min {}, mean {}, max {}",
+            s.transition(),
+            s.count,
+            human_ms(s.min_ms as f64),
+            human_ms(s.mean_ms),
+            human_ms(s.max_ms as f64)
+        ));
+        if !s.trimmed.is_empty() {
+            output.push_str(&format!(" ({} trimmed)", s.trimmed.len()));
+        }
+        output.push('\n');
+    }
+
+    let mut trimmed: Vec<usize> = stats.iter().flat_map(|s| s.trimmed.iter().copied()).collect();
+    if !trimmed.is_empty() {
+        trimmed.sort_unstable();
+        output.push_str("Trimmed intervals:\n");
+        for idx in trimmed {
+            output.push_str(&format!("  {}\n", intervals[idx].format()));
+        }
+    }
+
+    output.trim_end().to_string()
+}
+
+/// Observed, reported, and unaccounted milliseconds of one interval
+type ReportedSample = (f64, f64, f64);
+
+/// Observed against server-reported durations per transition (see `ReportedDuration`), for
+/// the run summary; `None` when no interval carries a reported duration
+pub fn format_reported_comparison(intervals: &[Interval], field: &str) -> Option<String> {
+    let [reported_name, unaccounted_name] = ReportedDuration::VALUES;
+    let mut order: Vec<(&str, &str)> = Vec::new();
+    // Per transition: the number of intervals, and the samples of those reporting
+    let mut groups: HashMap<(&str, &str), (usize, Vec<ReportedSample>)> = HashMap::new();
+    for interval in intervals {
+        let key = (interval.from_pattern.as_str(), interval.to_pattern.as_str());
+        let (count, reporting) = groups.entry(key).or_insert_with(|| {
+            order.push(key);
+            (0, Vec::new())
+        });
+        *count += 1;
+        if let (Some(&reported), Some(&unaccounted)) =
+            (interval.derived.get(reported_name), interval.derived.get(unaccounted_name))
+        {
+            reporting.push((interval.duration.num_milliseconds() as f64, reported, unaccounted));
+        }
+    }
+    if groups.values().all(|(_, reporting)| reporting.is_empty()) {
+        return None;
+    }
+    
+    let mut output = format!("Reported ({}) vs observed durations:\n", field);
+    for key in order {
+        let (count, reporting) = &groups[&key];
+        output.push_str(&format!("  {} -> {}: ", key.0, key.1));
+        if reporting.is_empty() {
+            output.push_str(&format!("none of {} intervals report a duration\n", count));
+            continue;
+        }
+        let mean = |value: fn(&ReportedSample) -> f64| reporting.iter().map(value).sum::<f64>() / reporting.len() as f64;
+        let max_unaccounted = reporting.iter().map(|r| r.2).fold(f64::MIN, f64::max);
+        output.push_str(&format!(
+            "{} of {} intervals, observed mean {}, reported mean {}, unaccounted mean {} (max {})\n",
+            reporting.len(),
+            count,
+            human_ms(mean(|r| r.0)),
+            human_ms(mean(|r| r.1)),
+            human_ms(mean(|r| r.2)),
+            human_ms(max_unaccounted)
+        ));
+    }
+    
+    Some(output.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
+    use chrono::Duration;
+
+    fn interval(from: &str, to: &str, ms: i64) -> Interval {
+        Interval::new(from, to, Duration::milliseconds(ms))
+    }
+
+    #[test]
+    fn test_compute_groups_by_transition() {
+        let intervals = vec![
+            interval("A", "B", 100),
+            interval("B", "A", 10),
+            interval("A", "B", 300),
+        ];
+
+        let stats = compute(&intervals, None);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].from_pattern, "A");
+        assert_eq!(stats[0].count, 2);
+        assert_eq!(stats[0].mean_ms, 200.0);
+        assert_eq!(stats[0].max_ms, 300);
+        assert_eq!(stats[1].count, 1);
+    }
+
+    #[test]
+    fn test_compute_splits_transitions_by_group() {
+        let grouped = |ms: i64, endpoint: &str| {
+            let mut interval = interval("request", "response", ms);
+            interval.from_fields.insert("endpoint".to_string(), endpoint.to_string());
+            interval.group_by("endpoint");
+            interval
+        };
+        let intervals = vec![grouped(100, "/users"), grouped(900, "/orders"), grouped(300, "/users")];
+
+        let stats = compute(&intervals, None);
+        assert_eq!(stats.len(), 2);
+        assert_eq!((stats[0].transition(), stats[0].count, stats[0].mean_ms), ("request -> response (/users)".to_string(), 2, 200.0));
+        assert_eq!((stats[1].group.as_deref(), stats[1].count), (Some("/orders"), 1));
+    }
+
+    #[test]
+    fn test_trim_excludes_both_ends() {
+        let mut intervals: Vec<Interval> = (1..=20).map(|ms| interval("A", "B", ms)).collect();
+        intervals.push(interval("A", "B", 60_000));
+
+        let stats = compute(&intervals, Some(5.0));
+        assert_eq!(stats[0].count, 19);
+        assert_eq!(stats[0].min_ms, 2);
+        assert_eq!(stats[0].max_ms, 20);
+        assert_eq!(stats[0].trimmed, vec![0, 20]);
+    }
+
+    #[test]
+    fn test_percentile_and_stddev() {
+        assert_eq!(percentile(&[10, 20, 30, 40], 50.0), 25.0);
+        assert_eq!(percentile(&[10, 20, 30, 40], 100.0), 40.0);
+        assert_eq!(percentile(&[], 95.0), 0.0);
+
+        let intervals: Vec<Interval> = [2, 4, 4, 4, 5, 5, 7, 9].iter().map(|&ms| interval("A", "B", ms)).collect();
+        let stats = compute(&intervals, None);
+        assert_eq!(stats[0].stddev_ms, 2.0);
+        assert_eq!(stats[0].percentile(50.0), 4.5);
+    }
+
+    #[test]
+    fn test_reported_comparison() {
+        use crate::analyzer::Analyzer;
+        use crate::config::Config;
+
+        let mut intervals: Vec<Interval> = [(1000, "0.4"), (2000, "1.2"), (3000, "")]
+            .iter()
+            .map(|&(ms, taken)| {
+                let mut interval = interval("GET /a", "GET /b", ms);
+                if !taken.is_empty() {
+                    interval.to_fields.insert("time_taken".to_string(), taken.to_string());
+                }
+                interval
+            })
+            .collect();
+        let config = Config {
+            reported_duration: Some(ReportedDuration::parse("time_taken:s")),
+            ..Default::default()
+        };
+        Analyzer::derive_values(&mut intervals, &config);
+
+        assert_eq!(intervals[1].derived["reported_ms"], 1200.0);
+        assert_eq!(intervals[1].derived["unaccounted_ms"], 800.0);
+        assert!(intervals[2].derived.is_empty());
+        assert_eq!(
+            format_reported_comparison(&intervals, "time_taken").unwrap(),
+            "Reported (time_taken) vs observed durations:\n  GET /a -> GET /b: 2 of 3 intervals, observed mean \
+             1s 500ms, reported mean 800ms, unaccounted mean 700ms (max 800ms)"
+        );
+        assert!(format_reported_comparison(&intervals[2..], "time_taken").is_none());
+        let bad_unit = Config {
+            reported_duration: Some(ReportedDuration::parse("time_taken:min")),
+            ..Default::default()
+        };
+        assert!(bad_unit.derived_values().is_err());
+    }
+
+    #[test]
+    fn test_parse_trim() {
+        assert_eq!(parse_trim("1%").unwrap(), 1.0);
+        assert!(parse_trim("50%").is_err());
+    }
+}
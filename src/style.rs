@@ -0,0 +1,162 @@
+/// Terminal colors of durations in the human, table, and waterfall formats: green below a
+/// warning threshold, yellow from it, and red from a slow threshold (`--color`,
+/// `--color-thresholds`), and the terminal's width
+use crate::error::{LogLineError, Result};
+
+use crate::duration::parse_duration;
+
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether output is colored: `auto` colors a terminal unless `NO_COLOR` is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, ()> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ColorChoice {
+    /// Whether to color output written to a terminal (`is_terminal`) or elsewhere; `auto`
+    /// follows https://no-color.org, where a non-empty `NO_COLOR` turns colors off
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => is_terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+        }
+    }
+}
+
+/// Durations (ms) from which a duration is drawn yellow and red
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DurationColors {
+    pub warn_ms: f64,
+    pub slow_ms: f64,
+}
+
+impl Default for DurationColors {
+    fn default() -> Self {
+        DurationColors {
+            warn_ms: 1_000.0,
+            slow_ms: 5_000.0,
+        }
+    }
+}
+
+impl DurationColors {
+    /// Parse `<warn>,<slow>`, e.g. `500ms,2s`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (warn, slow) = spec
+            .split_once(',')
+            .ok_or_else(|| LogLineError::config(format!("Expected two durations such as '500ms,2s', got '{}'", spec)))?;
+        let ms = |text: &str| -> Result<f64> {
+            let duration = parse_duration(text)?;
+            Ok(duration.num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0)
+        };
+        let colors = DurationColors {
+            warn_ms: ms(warn)?,
+            slow_ms: ms(slow)?,
+        };
+        if colors.slow_ms < colors.warn_ms {
+            return Err(LogLineError::config(format!("The slow threshold must not be below the warning threshold, got '{}'", spec)));
+        }
+        Ok(colors)
+    }
+
+    /// `text` in the color of a `ms` long duration
+    pub fn paint(&self, ms: f64, text: &str) -> String {
+        let color = if ms >= self.slow_ms {
+            RED
+        } else if ms >= self.warn_ms {
+            YELLOW
+        } else {
+            GREEN
+        };
+        format!("{}{}{}", color, text, RESET)
+    }
+}
+
+/// Paint `text` for a `ms` long duration when `colors` are on, or return it unchanged
+pub fn paint(colors: Option<DurationColors>, ms: f64, text: &str) -> String {
+    colors.map_or_else(|| text.to_string(), |colors| colors.paint(ms, text))
+}
+
+/// Number of characters `text` takes up on a terminal, leaving out color escape codes
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip `ESC [ ... m`
+            for c in chars.by_ref() {
+                if c == 'm' {
+                    break;
+                }
+            }
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Columns of the terminal: `COLUMNS` when set, otherwise the size of the terminal on
+/// stdout; `None` when neither is known (e.g. output is piped)
+pub fn terminal_width() -> Option<usize> {
+    if let Some(columns) = std::env::var("COLUMNS").ok().and_then(|value| value.parse().ok()).filter(|&c: &usize| c > 0) {
+        return Some(columns);
+    }
+    #[cfg(unix)]
+    {
+        let mut size = libc::winsize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+        if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 {
+            return Some(size.ws_col.into());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thresholds() {
+        let colors = DurationColors::parse("500ms,2s").unwrap();
+        assert_eq!(colors, DurationColors { warn_ms: 500.0, slow_ms: 2_000.0 });
+        assert_eq!(colors.paint(499.0, "fast"), "\x1b[32mfast\x1b[0m");
+        assert_eq!(colors.paint(500.0, "warn"), "\x1b[33mwarn\x1b[0m");
+        assert_eq!(colors.paint(2_000.0, "slow"), "\x1b[31mslow\x1b[0m");
+        assert_eq!(paint(None, 2_000.0, "plain"), "plain");
+        assert_eq!(visible_width(&colors.paint(2_000.0, "2s 0ms")), 6);
+
+        assert!(DurationColors::parse("2s").is_err());
+        assert!(DurationColors::parse("2s,500ms").is_err());
+        assert!(DurationColors::parse("fast,slow").is_err());
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert_eq!("ALWAYS".parse(), Ok(ColorChoice::Always));
+        assert_eq!("sometimes".parse::<ColorChoice>(), Err(()));
+        assert!(ColorChoice::Always.enabled(false));
+        assert!(!ColorChoice::Never.enabled(true));
+        assert!(!ColorChoice::Auto.enabled(false));
+    }
+}
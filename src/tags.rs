@@ -0,0 +1,272 @@
+/// Conditions on intervals, e.g. `duration > 1s`: those of the `tags` rules that classify
+/// intervals, and the `--where` filters
+use regex::Regex;
+
+use crate::analyzer::Interval;
+use crate::duration::parse_duration;
+use crate::error::{LogLineError, Result};
+use crate::expr::{Expr, Variable};
+
+/// A parsed condition: clauses joined by `and` (or `&&`), all of which must hold, and
+/// alternatives of those joined by `or` (or `||`), one of which must hold.
+///
+/// Each clause is one of
+///
+/// - `<text> matches /<regex>/`, where `<text>` is `from` or `to` (the pattern of either
+///   endpoint), `correlation`, or a named capture `from.<name>`, `to.<name>`, or
+///   `extracted.<name>`; `~` is short for `matches` and `!~` holds when the regex doesn't
+///   match, and the regex may also be quoted as `"<regex>"`
+/// - `<expr> <op> <expr>` with `<op>` one of `< <= > >= == !=` and expressions as in derived
+///   values; `duration` stands for `duration_ms`, and a side may be a duration such as `1s`
+///
+/// A clause whose field is missing or not a number doesn't hold.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    alternatives: Vec<Vec<Clause>>,
+}
+
+#[derive(Debug, Clone)]
+enum Clause {
+    /// The field's value matches the regex, or doesn't when negated
+    Matches(TextField, Regex, bool),
+    Compare(Expr, Comparison, Expr),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TextField {
+    FromPattern,
+    ToPattern,
+    Correlation,
+    From(String),
+    To(String),
+    Extracted(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Condition {
+    /// Parse a condition; `derived` lists the derived values it may refer to by name
+    pub fn parse(text: &str, derived: &[&str]) -> Result<Condition> {
+        let alternatives = split_outside_quotes(text, &[" or ", "||"])
+            .into_iter()
+            .map(|alternative| {
+                split_outside_quotes(alternative, &[" and ", "&&"])
+                    .into_iter()
+                    .map(|clause| parse_clause(clause.trim(), derived))
+                    .collect()
+            })
+            .collect::<std::result::Result<_, String>>()
+            .map_err(|msg| LogLineError::config(format!("Invalid condition '{}': {}", text, msg)))?;
+        Ok(Condition { alternatives })
+    }
+
+    pub fn matches(&self, interval: &Interval) -> bool {
+        self.alternatives
+            .iter()
+            .any(|clauses| clauses.iter().all(|clause| clause.matches(interval)))
+    }
+}
+
+impl Clause {
+    fn matches(&self, interval: &Interval) -> bool {
+        match self {
+            Clause::Matches(field, regex, negated) => field
+                .value(interval)
+                .is_some_and(|value| regex.is_match(value) != *negated),
+            Clause::Compare(left, comparison, right) => match (left.eval(interval), right.eval(interval)) {
+                (Some(left), Some(right)) => comparison.holds(left, right),
+                _ => false,
+            },
+        }
+    }
+}
+
+impl TextField {
+    fn parse(name: &str) -> std::result::Result<TextField, String> {
+        let field = |prefix: &str| name.strip_prefix(prefix).filter(|rest| !rest.is_empty()).map(str::to_string);
+        if let Some(field) = field("from.") {
+            Ok(TextField::From(field))
+        } else if let Some(field) = field("to.") {
+            Ok(TextField::To(field))
+        } else if let Some(field) = field("extracted.") {
+            Ok(TextField::Extracted(field))
+        } else {
+            match name {
+                "from" => Ok(TextField::FromPattern),
+                "to" => Ok(TextField::ToPattern),
+                "correlation" => Ok(TextField::Correlation),
+                _ => Err(format!(
+                    "unknown field '{}' (use from, to, correlation, from.<field>, to.<field>, or extracted.<field>)",
+                    name
+                )),
+            }
+        }
+    }
+
+    fn value<'a>(&self, interval: &'a Interval) -> Option<&'a str> {
+        match self {
+            TextField::FromPattern => Some(&interval.from_pattern),
+            TextField::ToPattern => Some(&interval.to_pattern),
+            TextField::Correlation => interval.correlation.as_deref(),
+            TextField::From(name) => interval.from_fields.get(name).map(String::as_str),
+            TextField::To(name) => interval.to_fields.get(name).map(String::as_str),
+            TextField::Extracted(name) => interval
+                .to_fields
+                .get(name)
+                .or_else(|| interval.from_fields.get(name))
+                .map(String::as_str),
+        }
+    }
+}
+
+impl Comparison {
+    fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+        }
+    }
+}
+
+/// Split `text` at each of the separators, except within double quotes
+fn split_outside_quotes<'a>(text: &'a str, separators: &[&str]) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted) = (0, false);
+    let mut chars = text.char_indices();
+    while let Some((at, c)) = chars.next() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if !quoted {
+            if let Some(separator) = separators.iter().find(|separator| text[at..].starts_with(**separator)) {
+                parts.push(&text[start..at]);
+                start = at + separator.len();
+                // Skip the rest of the separator
+                for _ in 1..separator.chars().count() {
+                    chars.next();
+                }
+            }
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+fn parse_clause(clause: &str, derived: &[&str]) -> std::result::Result<Clause, String> {
+    // Field names never contain `~`, so the first one is the operator
+    let matches = clause
+        .split_once(" matches ")
+        .map(|(field, pattern)| (field, pattern, false))
+        .or_else(|| {
+            let (field, pattern) = clause.split_once('~')?;
+            Some(match field.strip_suffix('!') {
+                Some(field) => (field, pattern, true),
+                None => (field, pattern, false),
+            })
+        });
+    if let Some((field, pattern, negated)) = matches {
+        let pattern = pattern.trim();
+        let pattern = ['/', '"']
+            .iter()
+            .find_map(|&quote| pattern.strip_prefix(quote).and_then(|inner| inner.strip_suffix(quote)))
+            .unwrap_or(pattern);
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid regex '{}': {}", pattern, e))?;
+        return Ok(Clause::Matches(TextField::parse(field.trim())?, regex, negated));
+    }
+
+    // Two-character operators first, so `>=` isn't split as `>`
+    const OPERATORS: [(&str, Comparison); 6] = [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+    let (left, comparison, right) = OPERATORS
+        .iter()
+        .find_map(|&(op, comparison)| clause.split_once(op).map(|(left, right)| (left, comparison, right)))
+        .ok_or_else(|| format!("'{}' needs 'matches', '~', '!~', or a comparison (< <= > >= == !=)", clause))?;
+    Ok(Clause::Compare(operand(left, derived)?, comparison, operand(right, derived)?))
+}
+
+/// One side of a comparison: `duration`, a duration literal such as `250ms`, or an expression
+fn operand(text: &str, derived: &[&str]) -> std::result::Result<Expr, String> {
+    let text = text.trim();
+    if text == "duration" {
+        return Ok(Expr::Variable(Variable::DurationMs));
+    }
+    Expr::parse(text, derived).or_else(|e| {
+        parse_duration(text)
+            .ok()
+            .and_then(|duration| duration.num_microseconds())
+            .map(|us| Expr::Number(us as f64 / 1000.0))
+            .ok_or_else(|| e.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::fixtures::IntervalFixture;
+    use chrono::Duration;
+
+    fn interval() -> Interval {
+        let mut interval = Interval {
+            correlation: Some("req-42".to_string()),
+            ..Interval::new("checkout started", "payment done", Duration::milliseconds(1500))
+        };
+        interval.to_fields.insert("items".to_string(), "3".to_string());
+        interval.derived.insert("per_item".to_string(), 500.0);
+        interval
+    }
+
+    fn holds(text: &str) -> bool {
+        Condition::parse(text, &["per_item"]).unwrap().matches(&interval())
+    }
+
+    #[test]
+    fn test_conditions() {
+        assert!(holds("duration > 1s"));
+        assert!(holds("duration >= 1500"));
+        assert!(!holds("duration < 1s"));
+        assert!(holds("from matches /checkout/"));
+        assert!(!holds("to matches /^checkout/"));
+        assert!(holds("correlation matches req-\\d+"));
+        assert!(holds("extracted.items == 3 and per_item <= 500"));
+        assert!(!holds("duration > 1s and extracted.items != 3"));
+        assert!(holds("duration_ms / extracted.items == per_item"));
+        assert!(!holds("from.items > 0"));
+        assert!(!holds("from.items matches /./"));
+    }
+
+    #[test]
+    fn test_where_syntax() {
+        assert!(holds("duration_ms > 100 && to ~ \"done\""));
+        assert!(!holds("duration_ms > 100 && to !~ \"done\""));
+        assert!(holds("from ~ /^checkout/ && correlation !~ /^job-/"));
+        assert!(holds("duration < 1s || extracted.items == 3"));
+        assert!(!holds("duration < 1s or extracted.items == 4"));
+        assert!(!holds("to ~ \"payment and refund\""), "quoted separators don't split");
+        assert!(holds("to ~ \"(payment and refund)|done\""));
+        assert!(!holds("to.missing !~ /x/"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for text in ["", "duration", "duration > ", "duration > soon", "status matches /x/", "from matches /(/", "to ~ \"(\"", "duration > 1s &&", "|| from ~ /x/"] {
+            assert!(Condition::parse(text, &[]).is_err(), "{} should not parse", text);
+        }
+    }
+}
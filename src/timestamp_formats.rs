@@ -1,6 +1,8 @@
 /// Built-in timestamp format definitions for automatic detection
 use serde::{Deserialize, Serialize};
 
+use crate::error::{LogLineError, Result};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimestampFormat {
     /// Name of the format
@@ -135,13 +137,46 @@ pub fn get_builtin_formats() -> Vec<TimestampFormat> {
             format: "%Y%m%d_%H%M%S%.3f",
             example: "20251113_100000.123",
         },
+        // Time of day only (build tools, test runners). Kept last so any format
+        // with a date wins; day rollovers are inferred by the parser.
+        TimestampFormat {
+            name: "Time of day (HH:MM:SS)",
+            regex: r"\b(\d{2}:\d{2}:\d{2}(?:\.\d+)?)\b",
+            format: "%H:%M:%S%.f",
+            example: "10:00:00.123",
+        },
     ]
 }
 
+/// Look up a built-in format by name (case-insensitive), or by a fragment of its name
+/// that only one format contains
+pub fn find_builtin_format(name: &str) -> Result<TimestampFormat> {
+    let formats = get_builtin_formats();
+    let wanted = name.to_lowercase();
+    
+    if let Some(format) = formats.iter().find(|f| f.name.to_lowercase() == wanted) {
+        return Ok(format.clone());
+    }
+    
+    let partial: Vec<&TimestampFormat> = formats
+        .iter()
+        .filter(|f| f.name.to_lowercase().contains(&wanted))
+        .collect();
+    match partial.as_slice() {
+        [format] => Ok((*format).clone()),
+        [] => Err(LogLineError::config(format!("Unknown timestamp format '{}'", name))),
+        several => Err(LogLineError::config(format!(
+            "Timestamp format '{}' is ambiguous, it matches: {}",
+            name,
+            several.iter().map(|f| f.name).collect::<Vec<_>>().join(", ")
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::NaiveDateTime;
+    use chrono::{NaiveDateTime, NaiveTime};
     use regex::Regex;
 
     #[test]
@@ -175,5 +210,21 @@ mod tests {
         let parsed = NaiveDateTime::parse_from_str(ts_str, format.format);
         assert!(parsed.is_ok());
     }
+
+    #[test]
+    fn test_time_of_day_format_is_last() {
+        let format = get_builtin_formats().pop().unwrap();
+        assert_eq!(format.name, "Time of day (HH:MM:SS)");
+
+        let regex = Regex::new(format.regex).unwrap();
+        for (line, expected) in [
+            ("10:00:00.123 Build started", "10:00:00.123"),
+            ("[23:59:59] Step 3/7 : RUN make", "23:59:59"),
+        ] {
+            let ts_str = regex.captures(line).unwrap().get(1).unwrap().as_str();
+            let parsed = NaiveTime::parse_from_str(ts_str, format.format).unwrap();
+            assert_eq!(parsed.to_string(), expected);
+        }
+    }
 }
 
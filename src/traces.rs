@@ -0,0 +1,192 @@
+/// The slowest complete traces of a correlated run (`--slowest-traces`): the intervals sharing
+/// a correlation key form one trace, listed with the duration of each of its steps
+use anyhow::Result;
+use chrono::Duration;
+use std::collections::HashMap;
+
+use log_time_analyzer::analyzer::{format_duration, Interval};
+use log_time_analyzer::config::Config;
+
+/// The intervals of one correlation key, in the order they start
+#[derive(Debug)]
+pub struct Trace<'a> {
+    pub key: &'a str,
+    pub steps: Vec<&'a Interval>,
+    /// From the start of the first step to the end of the last, or the sum of the steps when
+    /// they have no timestamps
+    pub duration: Duration,
+}
+
+/// The traces of a run, slowest first
+#[derive(Debug)]
+pub struct Traces<'a> {
+    pub complete: Vec<Trace<'a>>,
+    /// Traces that don't run from the first message pattern to the last
+    pub incomplete: usize,
+}
+
+/// The patterns a complete trace starts and ends at: the first and last message patterns.
+///
+/// Pair mode has none, since every interval is a finished start/end pair, and neither do
+/// patterns whose matches are named by a `label` capture, seen as no interval starting at
+/// the first pattern or ending at the last.
+fn bounds<'c>(config: &'c Config, intervals: &[Interval]) -> Option<(&'c str, &'c str)> {
+    if !config.pairs.is_empty() || config.message_patterns.len() < 2 {
+        return None;
+    }
+    let first = config.message_patterns.first()?.label();
+    let last = config.message_patterns.last()?.label();
+    let seen = intervals.iter().any(|i| i.from_pattern == first) && intervals.iter().any(|i| i.to_pattern == last);
+    seen.then_some((first, last))
+}
+
+/// Group the intervals into traces by correlation key, keeping those that run from the first
+/// message pattern to the last, slowest first (in order of first appearance when tied)
+pub fn collect<'a>(intervals: &'a [Interval], config: &Config) -> Result<Traces<'a>> {
+    let mut order = Vec::new();
+    let mut by_key: HashMap<&str, Vec<&Interval>> = HashMap::new();
+    for interval in intervals {
+        let Some(key) = interval.correlation.as_deref() else {
+            continue;
+        };
+        by_key
+            .entry(key)
+            .or_insert_with(|| {
+                order.push(key);
+                Vec::new()
+            })
+            .push(interval);
+    }
+    if order.is_empty() {
+        anyhow::bail!(
+            "--slowest-traces needs intervals with a correlation key: set correlation_regex \
+             (--correlation-regex) or capture a `correlation` group in the message patterns"
+        );
+    }
+
+    let bounds = bounds(config, intervals);
+    let mut traces = Traces { complete: Vec::new(), incomplete: 0 };
+    for key in order {
+        let Some(mut steps) = by_key.remove(key) else {
+            continue;
+        };
+        // Intervals are listed as they complete, so an outer step may come after its inner ones
+        steps.sort_by_key(|step| step.start);
+        let complete = match bounds {
+            Some((first, last)) => {
+                steps.first().is_some_and(|step| step.from_pattern == first)
+                    && steps.last().is_some_and(|step| step.to_pattern == last)
+            }
+            None => true,
+        };
+        if !complete {
+            traces.incomplete += 1;
+            continue;
+        }
+
+        let start = steps.iter().filter_map(|step| step.start).min();
+        let end = steps.iter().filter_map(|step| step.end).max();
+        let duration = match (start, end) {
+            (Some(start), Some(end)) => end - start,
+            _ => steps.iter().map(|step| step.duration).fold(Duration::zero(), |total, d| total + d),
+        };
+        traces.complete.push(Trace { key, steps, duration });
+    }
+    traces.complete.sort_by_key(|trace| std::cmp::Reverse(trace.duration));
+    Ok(traces)
+}
+
+/// The `limit` slowest traces with their start and line, each followed by its steps
+pub fn format_report(traces: &Traces, limit: usize) -> String {
+    let shown = limit.min(traces.complete.len());
+    let mut output = format!("Slowest {} of {} complete traces", shown, traces.complete.len());
+    if traces.incomplete > 0 {
+        output.push_str(&format!(" ({} incomplete skipped)", traces.incomplete));
+    }
+    output.push('\n');
+    if traces.complete.is_empty() {
+        output.push_str("No trace runs from the first message pattern to the last");
+        return output;
+    }
+
+    for (rank, trace) in traces.complete.iter().take(limit).enumerate() {
+        output.push_str(&format!("\n{}. {}: {}", rank + 1, trace.key, format_duration(&trace.duration)));
+        let first = trace.steps[0];
+        if let Some(start) = first.start {
+            output.push_str(&format!(" at {}", start.format("%Y-%m-%d %H:%M:%S%.3f")));
+        }
+        if let Some(line) = first.from_line {
+            output.push_str(&format!(", line {}", line));
+        }
+        if let Some(source) = &first.from_source {
+            output.push_str(&format!(" [{}]", source));
+        }
+        let steps: Vec<String> = trace
+            .steps
+            .iter()
+            .map(|step| format!("{} -> {} {}", step.from_pattern, step.to_pattern, format_duration(&step.duration)))
+            .collect();
+        output.push_str(&format!("\n   {}", steps.join(", ")));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::IntervalFixture;
+    use chrono::NaiveDateTime;
+    use log_time_analyzer::config::PairRule;
+
+    fn step(key: &str, from: &str, to: &str, start_ms: i64, ms: i64) -> Interval {
+        let origin = NaiveDateTime::parse_from_str("2025-11-13 10:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        Interval {
+            correlation: Some(key.to_string()),
+            ..Interval::new(from, to, Duration::milliseconds(ms)).starting_at(origin + Duration::milliseconds(start_ms))
+        }
+    }
+
+    #[test]
+    fn test_slowest_complete_traces() {
+        let mut config = Config {
+            message_patterns: ["received", "queried", "sent"].map(Into::into).to_vec(),
+            ..Default::default()
+        };
+        let intervals = [
+            step("req-1", "received", "queried", 0, 20),
+            step("req-2", "received", "queried", 5, 900),
+            step("req-1", "queried", "sent", 20, 100),
+            step("req-3", "received", "queried", 10, 50),
+            step("req-2", "queried", "sent", 905, 300),
+            step("req-4", "queried", "sent", 0, 5000),
+        ];
+
+        let traces = collect(&intervals, &config).unwrap();
+        let keys: Vec<&str> = traces.complete.iter().map(|trace| trace.key).collect();
+        assert_eq!(keys, ["req-2", "req-1"], "req-3 never finished and req-4's start was cut off");
+        assert_eq!(traces.incomplete, 2);
+        assert_eq!(traces.complete[0].duration, Duration::milliseconds(1200));
+
+        let report = format_report(&traces, 1);
+        assert_eq!(
+            report,
+            "Slowest 1 of 2 complete traces (2 incomplete skipped)\n\n\
+             1. req-2: 1s 200ms at 2025-11-13 10:00:00.005\n   \
+             received -> queried 900ms, queried -> sent 300ms"
+        );
+
+        // Pairs are complete on their own
+        config.pairs = vec![PairRule { start: "received".into(), end: "sent".into() }];
+        assert_eq!(collect(&intervals, &config).unwrap().complete.len(), 4);
+    }
+
+    #[test]
+    fn test_traces_need_correlation_keys() {
+        let intervals = [Interval { correlation: None, ..step("req-1", "received", "sent", 0, 20) }];
+        let error = collect(&intervals, &Config::default()).unwrap_err();
+        assert!(error.to_string().contains("correlation_regex"), "{}", error);
+
+        let traces = Traces { complete: Vec::new(), incomplete: 3 };
+        assert!(format_report(&traces, 5).ends_with("No trace runs from the first message pattern to the last"));
+    }
+}